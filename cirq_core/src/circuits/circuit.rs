@@ -0,0 +1,1148 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::{Add, Bound, Mul, Range, RangeBounds};
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::circuits::moment::Moment;
+use crate::devices::Device;
+use crate::linalg;
+use crate::ops::channels::KrausChannel;
+use crate::ops::gate_operation::GateOperation;
+use crate::ops::measure::MeasurementGate;
+use crate::ops::qubit_order::{DefaultQubitOrder, QubitOrder};
+use crate::ops::raw_types::{Operation, QId, QIdHasher};
+use crate::ops::wait::TimeUnit;
+use crate::study::ParamResolver;
+
+/// Determines where `Circuit::insert_with_strategy` places each operation at the end of the circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InsertStrategy {
+    /// The first operation starts a new moment, and the rest are added to the last moment when their qubits are free there.
+    NewThenInline,
+    /// Each operation goes in the earliest moment after the last moment that acts on its qubits.
+    Earliest,
+    /// Each operation goes in a new moment of its own.
+    New,
+    /// Each operation is added to the last moment when its qubits are free there, and starts a new moment otherwise.
+    Inline,
+}
+
+/// A problem found by `Circuit::validate`.
+#[derive(Clone, Debug)]
+pub enum CircuitError {
+    /// Two operations of a moment act on the same qubit.
+    QubitCollision { moment_index: usize, qubit: Box<dyn QId> },
+    /// A qubit appears with different dimensions in different operations.
+    DimensionMismatch { qubit: Box<dyn QId>, expected: u64, found: u64 },
+    /// A gate is applied to the wrong number of qubits, or to qubits of the wrong dimensions.
+    InvalidGateArgs { moment_index: usize, gate: String },
+    /// A channel's parameters are out of range, so its Kraus operators don't satisfy Σ K†K = I.
+    InvalidParameters { moment_index: usize, gate: String },
+}
+
+impl fmt::Display for CircuitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitError::QubitCollision { moment_index, qubit } => {
+                write!(f, "Moment {} has more than one operation on qubit {}.", moment_index, qubit)
+            }
+            CircuitError::DimensionMismatch { qubit, expected, found } => {
+                write!(f, "Qubit {} has dimension {} but also appears with dimension {}.", qubit, expected, found)
+            }
+            CircuitError::InvalidGateArgs { moment_index, gate } => {
+                write!(f, "Gate {} in moment {} can't be applied to its qubits.", gate, moment_index)
+            }
+            CircuitError::InvalidParameters { moment_index, gate } => {
+                write!(f, "Gate {} in moment {} has parameters out of range.", gate, moment_index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CircuitError {}
+
+/// A quantum circuit, made up of a sequence of moments.
+#[derive(Clone, Default)]
+pub struct Circuit {
+    moments: Vec<Moment>,
+}
+
+impl Circuit {
+    /// Creates an empty circuit.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_moments(moments: Vec<Moment>) -> Self {
+        Self {
+            moments
+        }
+    }
+
+    /// Creates a circuit from the operations, placing each one as `append` does.
+    pub fn from_ops(ops: impl IntoIterator<Item = Box<dyn Operation>>) -> Self {
+        let mut circuit = Self::new();
+        // Appending can't fail, since the chosen moment never acts on the operation's qubits.
+        circuit.append(ops.into_iter().collect()).unwrap();
+        circuit
+    }
+
+    /// Returns the moments of the circuit.
+    pub fn moments(&self) -> &[Moment] {
+        &self.moments
+    }
+
+    /**
+     * Appends the operations to the circuit. Each operation is placed in the earliest
+     * moment after the last moment that acts on any of its qubits, creating new moments
+     * at the end of the circuit as needed.
+     */
+    pub fn append(&mut self, ops: Vec<Box<dyn Operation>>) -> Result<(), Error> {
+        self.insert_with_strategy(ops, InsertStrategy::Earliest)
+    }
+
+    /// Adds the operations to the end of the circuit, placing each one as the strategy describes.
+    pub fn insert_with_strategy(&mut self, ops: Vec<Box<dyn Operation>>, strategy: InsertStrategy) -> Result<(), Error> {
+        for (i, op) in ops.into_iter().enumerate() {
+            let qubits = op.qubits();
+            let last_is_free = self.moments
+                .last()
+                .is_some_and(|moment| !qubits.iter().any(|q| moment.operates_on(q.as_ref())));
+
+            let index = match strategy {
+                InsertStrategy::Earliest => self.moments
+                    .iter()
+                    .rposition(|moment| qubits.iter().any(|q| moment.operates_on(q.as_ref())))
+                    .map_or(0, |i| i + 1),
+                InsertStrategy::New => self.moments.len(),
+                InsertStrategy::NewThenInline if i == 0 => self.moments.len(),
+                InsertStrategy::Inline | InsertStrategy::NewThenInline if last_is_free => self.moments.len() - 1,
+                InsertStrategy::Inline | InsertStrategy::NewThenInline => self.moments.len(),
+            };
+
+            if index == self.moments.len() {
+                self.moments.push(Moment::default());
+            }
+            self.moments[index] = self.moments[index].with_operation(op)?;
+        }
+        Ok(())
+    }
+
+    /**
+     * Inserts the operation at the given moment index. The operation is added to the
+     * moment at that index if its qubits are free there, otherwise a new moment holding
+     * the operation is inserted before it.
+     */
+    pub fn insert(&mut self, moment_index: usize, op: Box<dyn Operation>) -> Result<(), Error> {
+        if moment_index > self.moments.len() {
+            return Err(anyhow!(format!(
+                "Moment index {} is out of range for a circuit with {} moments.",
+                moment_index,
+                self.moments.len()
+            )));
+        }
+
+        match self.moments.get(moment_index) {
+            Some(moment) if !op.qubits().iter().any(|q| moment.operates_on(q.as_ref())) => {
+                self.moments[moment_index] = moment.with_operation(op)?;
+            }
+            _ => self.moments.insert(moment_index, Moment::new(vec![op])?),
+        }
+        Ok(())
+    }
+
+    /**
+     * Returns the circuit made of the moments in the range. The range is clipped to the circuit's moments,
+     * and a range that starts after its end gives an empty circuit.
+     */
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> Circuit {
+        let len = self.moments.len();
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => end.saturating_add(1),
+            Bound::Excluded(end) => *end,
+            Bound::Unbounded => len,
+        }
+        .min(len);
+
+        Circuit::from_moments(self.moments[start.min(end)..end].to_vec())
+    }
+
+    /// Returns the indices of the moments that act on any of the qubits, in increasing order.
+    pub fn moments_operating_on(&self, qubits: &[&dyn QId]) -> Vec<usize> {
+        self.moments
+            .iter()
+            .enumerate()
+            .filter(|(_, moment)| qubits.iter().any(|q| moment.operates_on(*q)))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Returns the index of the last moment before `end_moment_index` that acts on any of the qubits.
+    pub fn prev_moment_operating_on(&self, qubits: &[Box<dyn QId>], end_moment_index: usize) -> Option<usize> {
+        let end = end_moment_index.min(self.moments.len());
+        self.moments[..end].iter().rposition(|moment| qubits.iter().any(|q| moment.operates_on(q.as_ref())))
+    }
+
+    /// Returns the index of the first moment from `start_moment_index` onwards that acts on any of the qubits.
+    pub fn next_moment_operating_on(&self, qubits: &[Box<dyn QId>], start_moment_index: usize) -> Option<usize> {
+        self.moments
+            .iter()
+            .enumerate()
+            .skip(start_moment_index)
+            .find(|(_, moment)| qubits.iter().any(|q| moment.operates_on(q.as_ref())))
+            .map(|(i, _)| i)
+    }
+
+    /**
+     * Returns the circuit with every qubit replaced by its image under `f`.
+     * If `f` maps two qubits of a moment to the same qubit, the moment is split so that
+     * the operations keep their order. Fails if an operation can't act on its new qubits.
+     */
+    pub fn transform_qubits(&self, f: impl Fn(&dyn QId) -> Box<dyn QId>) -> Result<Circuit, Error> {
+        let mut moments = vec![];
+        for moment in &self.moments {
+            let ops = moment
+                .iter()
+                .map(|op| op.with_qubits(op.qubits().iter().map(|q| f(q.as_ref())).collect()))
+                .collect::<Result<Vec<_>, _>>()?;
+            if ops.is_empty() {
+                moments.push(Moment::default());
+                continue;
+            }
+
+            let mut part = Circuit::new();
+            part.append(ops)?;
+            moments.extend(part.moments);
+        }
+        Ok(Circuit::from_moments(moments))
+    }
+
+    /**
+     * Returns the circuit moved to device qubits by the mapping. Fails unless the mapping covers
+     * every qubit of the circuit, maps them to distinct qubits and keeps their dimensions.
+     */
+    pub fn with_device_qubits(&self, mapping: &HashMap<QIdHasher, Box<dyn QId>>) -> Result<Circuit, Error> {
+        let mut images = HashSet::new();
+        for q in self.all_qubits() {
+            let image = mapping
+                .get(&QIdHasher::new(q.clone()))
+                .ok_or_else(|| anyhow!(format!("The qubit mapping doesn't cover {}.", q)))?;
+            if image.dimension() != q.dimension() {
+                return Err(anyhow!(format!("Can't map {} to {}, which has a different dimension.", q, image)));
+            }
+            if !images.insert(QIdHasher::new(image.clone())) {
+                return Err(anyhow!(format!("The qubit mapping isn't injective: {} is used more than once.", image)));
+            }
+        }
+
+        self.transform_qubits(|q| mapping[&QIdHasher::new(q.dyn_clone())].clone())
+    }
+
+    /// Removes every operation that touches one of the qubits from the moments in the range.
+    pub fn clear_operations_touching(&mut self, qubits: &[&dyn QId], moment_indices: Range<usize>) {
+        let end = moment_indices.end.min(self.moments.len());
+        for moment in &mut self.moments[moment_indices.start.min(end)..end] {
+            *moment = moment.without_operations(qubits);
+        }
+    }
+
+    /**
+     * Writes the operations into the moments from `start` to `end`, keeping their order on each qubit:
+     * every operation goes in the earliest free moment after the previous operations on its qubits.
+     * New moments are inserted before `end` when the range runs out of room.
+     * Returns the end of the range after the insertions.
+     */
+    pub fn insert_into_range(&mut self, ops: Vec<Box<dyn Operation>>, start: usize, end: usize) -> Result<usize, Error> {
+        if start > end || end > self.moments.len() {
+            return Err(anyhow!(format!(
+                "Moment range {}..{} is invalid for a circuit with {} moments.",
+                start,
+                end,
+                self.moments.len()
+            )));
+        }
+
+        let mut end = end;
+        let mut placed: Vec<(Box<dyn QId>, usize)> = vec![];
+        for op in ops {
+            let qubits = op.qubits();
+            let mut index = placed
+                .iter()
+                .filter(|(q, _)| qubits.iter().any(|other| other.eq_qid(q.as_ref())))
+                .map(|(_, index)| index + 1)
+                .max()
+                .unwrap_or(start);
+            while index < end && qubits.iter().any(|q| self.moments[index].operates_on(q.as_ref())) {
+                index += 1;
+            }
+            if index == end {
+                self.moments.insert(end, Moment::default());
+                end += 1;
+            }
+
+            self.moments[index] = self.moments[index].with_operation(op)?;
+            placed.extend(qubits.into_iter().map(|q| (q, index)));
+        }
+        Ok(end)
+    }
+
+    /**
+     * Checks that the circuit is consistent: no moment acts on a qubit more than once, every qubit has the same
+     * dimension wherever it appears, every gate accepts its qubits, and every channel has valid parameters.
+     * Returns all of the problems found.
+     */
+    pub fn validate(&self) -> Result<(), Vec<CircuitError>> {
+        let mut errors = vec![];
+        let mut dimensions: HashMap<String, u64> = HashMap::new();
+
+        for (moment_index, moment) in self.moments.iter().enumerate() {
+            let mut seen: Vec<Box<dyn QId>> = vec![];
+            for op in moment {
+                for qubit in op.qubits() {
+                    if seen.iter().any(|other| other.eq_qid(qubit.as_ref())) {
+                        errors.push(CircuitError::QubitCollision { moment_index, qubit: qubit.clone() });
+                    }
+                    let expected = *dimensions.entry(qubit.comparison_key()).or_insert_with(|| qubit.dimension());
+                    if expected != qubit.dimension() {
+                        let found = qubit.dimension();
+                        errors.push(CircuitError::DimensionMismatch { qubit: qubit.clone(), expected, found });
+                    }
+                    seen.push(qubit);
+                }
+
+                let gate = match op.gate() {
+                    Some(gate) => gate,
+                    None => continue,
+                };
+                if gate.validate_args(op.qubits()).is_err() {
+                    errors.push(CircuitError::InvalidGateArgs { moment_index, gate: gate.to_string() });
+                }
+                if !gate.has_unitary() && gate.kraus().is_some_and(|kraus| KrausChannel::new(kraus).is_err()) {
+                    errors.push(CircuitError::InvalidParameters { moment_index, gate: gate.to_string() });
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Returns the distinct qubits acted upon by the circuit, in the default qubit order.
+    pub fn all_qubits(&self) -> Vec<Box<dyn QId>> {
+        let qubits = self.distinct_qubits();
+        // The default order never fails.
+        DefaultQubitOrder.sorted(qubits.clone()).unwrap_or(qubits)
+    }
+
+    /**
+     * Returns the distinct qubits acted upon by the circuit, sorted by the given order.
+     * Fails if the order can't sort them, such as an explicit order that leaves out one of the qubits.
+     */
+    pub fn all_qubits_with_order(&self, order: &dyn QubitOrder) -> Result<Vec<Box<dyn QId>>, Error> {
+        order.sorted(self.distinct_qubits())
+    }
+
+    /// Returns the distinct qubits of the circuit, in the order they first appear.
+    fn distinct_qubits(&self) -> Vec<Box<dyn QId>> {
+        let mut qubits: Vec<Box<dyn QId>> = vec![];
+        for q in self.moments.iter().flat_map(|moment| moment.qubits()) {
+            if !qubits.iter().any(|other| other.eq_qid(q.as_ref())) {
+                qubits.push(q);
+            }
+        }
+        qubits
+    }
+
+    /// Returns the number of moments in the circuit, including empty ones.
+    pub fn num_moments(&self) -> usize {
+        self.moments.len()
+    }
+
+    /// Returns the number of non-empty moments in the circuit.
+    pub fn depth(&self) -> usize {
+        self.moments.iter().filter(|moment| !moment.is_empty()).count()
+    }
+
+    /// Determines if the circuit measures any of its qubits.
+    pub fn has_measurements(&self) -> bool {
+        self.all_operations().any(|op| measurement_gate(op).is_some())
+    }
+
+    /**
+     * Determines if every measurement is terminal, meaning no later moment acts on any of the
+     * measured qubits. Measurements in the last moment are always terminal.
+     */
+    pub fn are_all_measurements_terminal(&self) -> bool {
+        self.moments.iter().enumerate().all(|(i, moment)| {
+            moment
+                .iter()
+                .filter(|op| measurement_gate(op.as_ref()).is_some())
+                .all(|op| self.next_moment_operating_on(&op.qubits(), i + 1).is_none())
+        })
+    }
+
+    /**
+     * Determines if the operations of the moment at the index commute with each other, which holds when
+     * they act on disjoint qubits. This is always true for moments built through `Moment`'s constructors.
+     * Returns false if there is no moment at the index.
+     */
+    pub fn commuting_operations_in_moment(&self, moment_index: usize) -> bool {
+        self.moments.get(moment_index).is_some_and(|moment| {
+            let qubits = moment.qubits();
+            qubits.iter().enumerate().all(|(i, q)| !qubits[..i].iter().any(|other| other.eq_qid(q.as_ref())))
+        })
+    }
+
+    /**
+     * Determines if the adjacent moments at the indices can be swapped without changing the circuit's unitary.
+     * Moments on disjoint qubits can always be swapped. Otherwise both moments must be unitary, and
+     * applying them in either order must give the same unitary. Returns false if the moments aren't adjacent.
+     */
+    pub fn can_reorder_moments(&self, i: usize, j: usize) -> bool {
+        let (first, second) = match (self.moments.get(i.min(j)), self.moments.get(i.max(j))) {
+            (Some(first), Some(second)) if i.abs_diff(j) == 1 => (first, second),
+            _ => return false,
+        };
+        if !first.qubits().iter().any(|q| second.operates_on(q.as_ref())) {
+            return true;
+        }
+
+        let forward = Circuit::from_moments(vec![first.clone(), second.clone()]).unitary();
+        let backward = Circuit::from_moments(vec![second.clone(), first.clone()]).unitary();
+        match (forward, backward) {
+            (Ok(forward), Ok(backward)) => linalg::allclose(&forward, &backward, 1e-8),
+            _ => false,
+        }
+    }
+
+    /// Returns the time it takes to run the circuit on the device in nanoseconds, with the moments run one after another.
+    pub fn total_duration(&self, device: &dyn Device) -> f64 {
+        self.moments.iter().map(|moment| moment.duration_on(device, TimeUnit::Nanoseconds)).sum()
+    }
+
+    /// Returns an iterator over the operations of the circuit, moment by moment.
+    pub fn all_operations(&self) -> impl Iterator<Item = &dyn Operation> {
+        self.moments.iter().flat_map(|moment| moment.iter().map(|op| op.as_ref()))
+    }
+
+    /// Returns a circuit with the moments of `other` placed after the moments of this circuit.
+    pub fn concat(&self, other: &Circuit) -> Circuit {
+        let mut moments = self.moments.clone();
+        moments.extend(other.moments.iter().cloned());
+        Circuit::from_moments(moments)
+    }
+
+    /**
+     * Combines two circuits moment by moment, so that moment `i` of the result holds the
+     * operations of moment `i` of both circuits. Fails if two combined moments share a qubit.
+     */
+    pub fn zip(&self, other: &Circuit) -> Result<Circuit, Error> {
+        let num_moments = self.num_moments().max(other.num_moments());
+        let mut moments = Vec::with_capacity(num_moments);
+
+        for i in 0..num_moments {
+            let mut moment = self.moments.get(i).cloned().unwrap_or_default();
+            for op in other.moments.get(i).into_iter().flatten() {
+                moment = moment.with_operation(op.clone())?;
+            }
+            moments.push(moment);
+        }
+
+        Ok(Circuit::from_moments(moments))
+    }
+
+    /**
+     * Returns the circuit with the symbols of its gates replaced by the values the resolver gives them.
+     * Resolved operations keep their tags, and operations without a gate are kept as they are.
+     */
+    pub fn resolve_parameters(&self, resolver: &ParamResolver) -> Result<Circuit, Error> {
+        let mut moments = Vec::with_capacity(self.moments.len());
+
+        for moment in &self.moments {
+            let mut ops: Vec<Box<dyn Operation>> = vec![];
+            for op in moment {
+                match op.gate() {
+                    Some(gate) => {
+                        let resolved = GateOperation::new(gate.resolve_parameters(resolver)?, op.qubits());
+                        ops.push(with_tags_of(op.as_ref(), resolved));
+                    }
+                    None => ops.push(op.clone()),
+                }
+            }
+            moments.push(Moment::new(ops)?);
+        }
+
+        Ok(Circuit::from_moments(moments))
+    }
+
+    /**
+     * Returns the inverse of the circuit: the moments in reverse order, with every operation
+     * replaced by the inverse of its gate, with the same tags. Fails if an operation has no gate or the gate
+     * has no inverse.
+     */
+    pub fn inverse(&self) -> Result<Circuit, Error> {
+        let mut moments = Vec::with_capacity(self.moments.len());
+
+        for moment in self.moments.iter().rev() {
+            let mut ops: Vec<Box<dyn Operation>> = vec![];
+            for op in moment {
+                let gate = op.gate().ok_or_else(|| anyhow!("Can't invert an operation without a gate."))?;
+                ops.push(with_tags_of(op.as_ref(), GateOperation::new(gate.inverse()?, op.qubits())));
+            }
+            moments.push(Moment::new(ops)?);
+        }
+
+        Ok(Circuit::from_moments(moments))
+    }
+
+    /**
+     * Returns the unitary matrix of the circuit, with qubits in the default qubit order and
+     * the first qubit as the most significant bit. Fails if any operation is not unitary,
+     * which includes measurements.
+     */
+    pub fn unitary(&self) -> Result<Vec<Vec<Complex<f64>>>, Error> {
+        let qubits = self.all_qubits();
+        if let Some(q) = qubits.iter().find(|q| q.dimension() != 2) {
+            return Err(anyhow!(format!("Can't compute the unitary of a circuit on qid {} with dimension {}.", q, q.dimension())));
+        }
+
+        let num_qubits = qubits.len();
+        let mut columns = linalg::eye(1 << num_qubits);
+        for op in self.all_operations() {
+            let unitary = op.unitary().ok_or_else(|| anyhow!("Can't compute the unitary of a circuit with non-unitary operations."))?;
+            let targets: Vec<usize> = op.qubits()
+                .iter()
+                .filter_map(|q| qubits.iter().position(|other| other.eq_qid(q.as_ref())))
+                .collect();
+
+            columns = columns
+                .iter()
+                .map(|column| linalg::targeted_left_multiply(&unitary, &targets, column, num_qubits))
+                .collect();
+        }
+
+        // Each column is the image of a basis state, so the unitary is their transpose.
+        Ok(linalg::transpose(&columns))
+    }
+
+    /// Returns the circuit repeated `count` times.
+    pub fn repeat(&self, count: usize) -> Circuit {
+        self * count
+    }
+}
+
+/// Boxes `new_op`, tagged with the tags of `op` if it has any.
+fn with_tags_of(op: &dyn Operation, new_op: GateOperation) -> Box<dyn Operation> {
+    let tags = op.tags();
+    if tags.is_empty() {
+        Box::new(new_op)
+    } else {
+        Box::new(new_op.with_tags(tags))
+    }
+}
+
+/// Returns the measurement gate of the operation, or None if it isn't a measurement.
+fn measurement_gate(op: &dyn Operation) -> Option<MeasurementGate> {
+    op.gate().and_then(|gate| gate.as_any().downcast_ref::<MeasurementGate>().cloned())
+}
+
+/**
+ * Splits the circuit into its unitary part, which keeps every moment with the measurements
+ * removed, and its measurement gates in the order they appear. The split only preserves the
+ * behavior of the circuit if all of its measurements are terminal.
+ */
+pub fn circuit_components(circuit: &Circuit) -> (Circuit, Vec<MeasurementGate>) {
+    let mut measurements = vec![];
+    let moments = circuit.moments
+        .iter()
+        .map(|moment| {
+            let mut unitary = Moment::default();
+            for op in moment {
+                match measurement_gate(op.as_ref()) {
+                    Some(gate) => measurements.push(gate),
+                    // The operations of a moment are disjoint, so adding them back can't fail.
+                    None => unitary = unitary.with_operation(op.clone()).unwrap(),
+                }
+            }
+            unitary
+        })
+        .collect();
+
+    (Circuit::from_moments(moments), measurements)
+}
+
+impl Add for &Circuit {
+    type Output = Circuit;
+
+    fn add(self, other: &Circuit) -> Circuit {
+        self.concat(other)
+    }
+}
+
+impl Add for Circuit {
+    type Output = Circuit;
+
+    fn add(self, other: Circuit) -> Circuit {
+        self.concat(&other)
+    }
+}
+
+impl Mul<usize> for &Circuit {
+    type Output = Circuit;
+
+    fn mul(self, repetitions: usize) -> Circuit {
+        let moments = (0..repetitions).flat_map(|_| self.moments.iter().cloned()).collect();
+        Circuit::from_moments(moments)
+    }
+}
+
+impl Mul<usize> for Circuit {
+    type Output = Circuit;
+
+    fn mul(self, repetitions: usize) -> Circuit {
+        &self * repetitions
+    }
+}
+
+/**
+ * A circuit together with the moments each qubit appears in, so that the timeline of a qubit
+ * can be looked up without scanning the circuit. The index is kept up to date as operations are appended.
+ */
+#[derive(Clone, Default)]
+pub struct MomentIndex {
+    circuit: Circuit,
+    moments_by_qubit: HashMap<QIdHasher, Vec<usize>>,
+}
+
+impl MomentIndex {
+    pub fn new(circuit: Circuit) -> Self {
+        let mut moments_by_qubit: HashMap<QIdHasher, Vec<usize>> = HashMap::new();
+        for (i, moment) in circuit.moments.iter().enumerate() {
+            for q in moment.qubits() {
+                moments_by_qubit.entry(QIdHasher::new(q)).or_default().push(i);
+            }
+        }
+
+        Self {
+            circuit,
+            moments_by_qubit,
+        }
+    }
+
+    pub fn circuit(&self) -> &Circuit {
+        &self.circuit
+    }
+
+    pub fn into_circuit(self) -> Circuit {
+        self.circuit
+    }
+
+    /// Appends the operations to the circuit as `Circuit::append` does, and records the moments they are placed in.
+    pub fn append(&mut self, ops: Vec<Box<dyn Operation>>) -> Result<(), Error> {
+        for op in ops {
+            let qubits = op.qubits();
+            let index = qubits
+                .iter()
+                .filter_map(|q| self.moments_for_qubit(q.as_ref()).last())
+                .max()
+                .map_or(0, |i| i + 1);
+
+            // The moment at the index doesn't act on the operation's qubits, so no moments are shifted.
+            self.circuit.insert(index, op)?;
+            for q in qubits {
+                self.moments_by_qubit.entry(QIdHasher::new(q)).or_default().push(index);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the indices of the moments that act on the qubit, in increasing order.
+    pub fn moments_for_qubit(&self, q: &dyn QId) -> &[usize] {
+        self.moments_by_qubit.get(&QIdHasher::new(q.dyn_clone())).map_or(&[], |indices| indices.as_slice())
+    }
+
+    /// Returns the operations that act on the qubit, in the order of their moments.
+    pub fn operations_for_qubit(&self, q: &dyn QId) -> Vec<&dyn Operation> {
+        self.moments_for_qubit(q)
+            .iter()
+            .filter_map(|i| self.circuit.moments[*i].iter().find(|op| op.qubits().iter().any(|other| other.eq_qid(q))))
+            .map(|op| op.as_ref())
+            .collect()
+    }
+}
+
+/**
+ * Creates a circuit from the given operations with `Circuit::from_ops`.
+ * Each operation is boxed, so it can be a `GateOperation` or any other operation.
+ */
+#[macro_export]
+macro_rules! circuit {
+    ($($op:expr),* $(,)?) => {
+        $crate::circuits::circuit::Circuit::from_ops(
+            vec![$(Box::new($op) as Box<dyn $crate::ops::raw_types::Operation>),*],
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::devices::{DeviceDurationMap, UnconstrainedDevice};
+    use crate::gate_on;
+    use crate::ops::channels::DepolarizingChannel;
+    use crate::ops::common_gates::{CNotGate, Rz, CNOT, CZ, H, S, S_DAG};
+    use crate::ops::gate_operation::GateOperation;
+    use crate::ops::measure::M;
+    use crate::ops::pauli_gates::{Pauli, X};
+    use crate::ops::qubit_order::ExplicitQubitOrder;
+    use crate::ops::qubits::LineQubit;
+    use crate::ops::raw_traits::gates_equal;
+    use crate::study::{Symbol, SymbolicRotation};
+    use crate::utils::extra_traits::Hashable;
+
+    use super::*;
+
+    fn op(result: Result<GateOperation, Error>) -> Box<dyn Operation> {
+        Box::new(result.unwrap())
+    }
+
+    #[test]
+    fn append_packs_into_earliest_moment() {
+        let q = LineQubit::range(3);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            op(gate_on!(H, q[0].clone())),
+            op(gate_on!(H, q[1].clone())),
+            op(gate_on!(CNOT, q[0].clone(), q[1].clone())),
+            op(gate_on!(X, q[2].clone())),
+        ]).unwrap();
+
+        assert_eq!(circuit.num_moments(), 2);
+        assert_eq!(circuit.moments()[0].len(), 3);
+        assert_eq!(circuit.moments()[1].len(), 1);
+        assert_eq!(circuit.all_operations().count(), 4);
+        assert_eq!(circuit.all_qubits().len(), 3);
+    }
+
+    #[test]
+    fn insert_strategies() {
+        let q = LineQubit::range(2);
+        let ops = || vec![
+            op(gate_on!(H, q[0].clone())),
+            op(gate_on!(X, q[1].clone())),
+            op(gate_on!(X, q[0].clone())),
+            op(gate_on!(H, q[1].clone())),
+        ];
+        let lengths = |strategy| {
+            let mut circuit = Circuit::new();
+            circuit.append(vec![op(gate_on!(H, q[1].clone()))]).unwrap();
+            circuit.insert_with_strategy(ops(), strategy).unwrap();
+            circuit.moments().iter().map(|moment| moment.len()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(lengths(InsertStrategy::Earliest), vec![2, 2, 1]);
+        assert_eq!(lengths(InsertStrategy::New), vec![1, 1, 1, 1, 1]);
+        assert_eq!(lengths(InsertStrategy::Inline), vec![2, 2, 1]);
+        assert_eq!(lengths(InsertStrategy::NewThenInline), vec![1, 2, 2]);
+    }
+
+    #[test]
+    fn terminal_measurements_and_components() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![op(gate_on!(H, q[0].clone())), Box::new(M("a", &[&q[1]]))]).unwrap();
+        assert!(circuit.has_measurements());
+        assert!(circuit.are_all_measurements_terminal());
+        assert!(!Circuit::new().has_measurements());
+
+        circuit.append(vec![op(gate_on!(CNOT, q[0].clone(), q[1].clone())), Box::new(M("b", &[&q[0], &q[1]]))]).unwrap();
+        assert!(!circuit.are_all_measurements_terminal());
+
+        let (unitary, measurements) = circuit_components(&circuit);
+        assert_eq!(unitary.num_moments(), circuit.num_moments());
+        assert!(!unitary.has_measurements());
+        assert_eq!(unitary.all_operations().count(), 2);
+        assert_eq!(measurements, vec![MeasurementGate::new("a", 1), MeasurementGate::new("b", 2)]);
+    }
+
+    #[test]
+    fn moment_index_tracks_qubit_timelines() {
+        let q = LineQubit::range(3);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![op(gate_on!(H, q[0].clone())), op(gate_on!(CNOT, q[0].clone(), q[1].clone()))]).unwrap();
+
+        let mut index = MomentIndex::new(circuit);
+        assert_eq!(index.moments_for_qubit(&q[0]), &[0, 1]);
+        assert_eq!(index.moments_for_qubit(&q[1]), &[1]);
+        assert!(index.moments_for_qubit(&q[2]).is_empty());
+
+        index.append(vec![op(gate_on!(X, q[2].clone())), op(gate_on!(CNOT, q[1].clone(), q[2].clone())), op(gate_on!(S, q[0].clone()))]).unwrap();
+        assert_eq!(index.moments_for_qubit(&q[2]), &[0, 2]);
+        assert_eq!(index.moments_for_qubit(&q[1]), &[1, 2]);
+        assert_eq!(index.moments_for_qubit(&q[0]), &[0, 1, 2]);
+
+        let ops = index.operations_for_qubit(&q[2]);
+        assert_eq!(ops.len(), 2);
+        assert!(gates_equal(ops[1], op(gate_on!(CNOT, q[1].clone(), q[2].clone())).as_ref()));
+
+        let circuit = index.into_circuit();
+        assert_eq!(circuit.num_moments(), 3);
+        assert_eq!(circuit.moments()[0].len(), 2);
+    }
+
+    #[test]
+    fn moments_commute_when_their_unitaries_do() {
+        let q = LineQubit::range(3);
+        let circuit = Circuit::from_moments(vec![
+            Moment::new(vec![op(gate_on!(CZ, q[0].clone(), q[1].clone()))]).unwrap(),
+            Moment::new(vec![op(gate_on!(S, q[0].clone())), op(gate_on!(X, q[2].clone()))]).unwrap(),
+            Moment::new(vec![op(gate_on!(H, q[0].clone()))]).unwrap(),
+            Moment::new(vec![Box::new(M("m", &[&q[0]]))]).unwrap(),
+            Moment::new(vec![op(gate_on!(H, q[1].clone()))]).unwrap(),
+        ]);
+
+        assert!(circuit.commuting_operations_in_moment(1));
+        assert!(!circuit.commuting_operations_in_moment(5));
+
+        assert!(circuit.can_reorder_moments(0, 1));
+        assert!(circuit.can_reorder_moments(1, 0));
+        assert!(!circuit.can_reorder_moments(1, 2));
+        assert!(!circuit.can_reorder_moments(2, 3));
+        assert!(circuit.can_reorder_moments(3, 4));
+        assert!(!circuit.can_reorder_moments(0, 2));
+        assert!(!circuit.can_reorder_moments(4, 5));
+    }
+
+    #[test]
+    fn from_ops_and_circuit_macro_pack_operations() {
+        let q = LineQubit::range(2);
+        let circuit = circuit![
+            gate_on!(H, q[0].clone()).unwrap(),
+            gate_on!(X, q[1].clone()).unwrap(),
+            gate_on!(CNOT, q[0].clone(), q[1].clone()).unwrap(),
+        ];
+        assert_eq!(circuit.num_moments(), 2);
+        assert_eq!(circuit.moments()[0].len(), 2);
+
+        let ops = ["a", "b"].iter().map(|_| op(gate_on!(H, q[0].clone())));
+        assert_eq!(Circuit::from_ops(ops).num_moments(), 2);
+        assert_eq!(circuit![].num_moments(), 0);
+    }
+
+    #[test]
+    fn slices_and_moments_on_qubits() {
+        let q = LineQubit::range(3);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            op(gate_on!(H, q[0].clone())),
+            op(gate_on!(CNOT, q[0].clone(), q[1].clone())),
+            op(gate_on!(S, q[1].clone())),
+        ]).unwrap();
+
+        assert_eq!(circuit.slice(1..).num_moments(), 2);
+        assert_eq!(circuit.slice(..=1).all_operations().count(), 2);
+        assert_eq!(circuit.slice(1..10).num_moments(), 2);
+        assert_eq!(circuit.slice(3..).num_moments(), 0);
+        assert_eq!(circuit.slice(..=usize::MAX).num_moments(), 3);
+        assert_eq!(circuit.slice(5..).num_moments(), 0);
+        assert_eq!(circuit.slice((Bound::Included(2), Bound::Excluded(1))).num_moments(), 0);
+        assert_eq!(circuit.slice((Bound::Excluded(usize::MAX), Bound::Unbounded)).num_moments(), 0);
+
+        assert_eq!(circuit.moments_operating_on(&[&q[1]]), vec![1, 2]);
+        assert_eq!(circuit.moments_operating_on(&[&q[0], &q[1]]), vec![0, 1, 2]);
+        assert!(circuit.moments_operating_on(&[&q[2]]).is_empty());
+
+        let first: Vec<Box<dyn QId>> = vec![Box::new(q[0].clone())];
+        assert_eq!(circuit.prev_moment_operating_on(&first, 2), Some(1));
+        assert_eq!(circuit.prev_moment_operating_on(&first, 1), Some(0));
+        assert_eq!(circuit.prev_moment_operating_on(&first, 0), None);
+        assert_eq!(circuit.prev_moment_operating_on(&first, 10), Some(1));
+        assert_eq!(circuit.next_moment_operating_on(&first, 1), Some(1));
+        assert_eq!(circuit.next_moment_operating_on(&[Box::new(q[1].clone())], 0), Some(1));
+        assert_eq!(circuit.next_moment_operating_on(&first, 2), None);
+        assert_eq!(circuit.next_moment_operating_on(&[Box::new(q[2].clone())], 0), None);
+    }
+
+    #[test]
+    fn qubits_are_transformed() {
+        let q = LineQubit::range(4);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![op(gate_on!(H, q[0].clone())), op(gate_on!(CNOT, q[0].clone(), q[1].clone()))]).unwrap();
+
+        let shifted = circuit.transform_qubits(|qubit| {
+            let line = qubit.as_any().downcast_ref::<LineQubit>().unwrap();
+            Box::new(LineQubit::new(line.x + 2))
+        }).unwrap();
+        assert!(gates_equal(shifted.moments()[1].operations()[0].as_ref(), &gate_on!(CNOT, q[2].clone(), q[3].clone()).unwrap()));
+
+        // Merging the qubits of a moment splits it.
+        let mut parallel = Circuit::new();
+        parallel.append(vec![op(gate_on!(H, q[0].clone())), op(gate_on!(S, q[1].clone()))]).unwrap();
+        let merged = parallel.transform_qubits(|_| Box::new(q[3].clone())).unwrap();
+        assert_eq!(merged.num_moments(), 2);
+
+        let mapping: HashMap<QIdHasher, Box<dyn QId>> = vec![
+            (QIdHasher::new(Box::new(q[0].clone())), Box::new(q[3].clone()) as Box<dyn QId>),
+            (QIdHasher::new(Box::new(q[1].clone())), Box::new(q[2].clone())),
+        ].into_iter().collect();
+        let moved = circuit.with_device_qubits(&mapping).unwrap();
+        assert!(moved.all_qubits()[1].eq_qid(&q[3]));
+
+        let mut partial = mapping.clone();
+        partial.remove(&QIdHasher::new(Box::new(q[1].clone())));
+        assert!(circuit.with_device_qubits(&partial).is_err());
+        partial.insert(QIdHasher::new(Box::new(q[1].clone())), Box::new(q[3].clone()));
+        assert!(circuit.with_device_qubits(&partial).is_err());
+    }
+
+    #[test]
+    fn insert_into_range_keeps_qubit_order() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            op(gate_on!(H, q[0].clone())),
+            op(gate_on!(X, q[1].clone())),
+            op(gate_on!(CNOT, q[0].clone(), q[1].clone())),
+        ]).unwrap();
+
+        circuit.clear_operations_touching(&[&q[0]], 0..1);
+        assert_eq!(circuit.moments()[0].len(), 1);
+
+        let end = circuit.insert_into_range(vec![op(gate_on!(S, q[0].clone())), op(gate_on!(S_DAG, q[0].clone()))], 0, 1).unwrap();
+        assert_eq!(end, 2);
+        assert_eq!(circuit.num_moments(), 3);
+        assert!(gates_equal(circuit.moments()[1].operations()[0].as_ref(), &gate_on!(S_DAG, q[0].clone()).unwrap()));
+        assert_eq!(circuit.moments()[2].len(), 1);
+        assert!(circuit.insert_into_range(vec![], 2, 4).is_err());
+    }
+
+    #[test]
+    fn insert_creates_moment_on_conflict() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![op(gate_on!(H, q[0].clone()))]).unwrap();
+
+        circuit.insert(0, op(gate_on!(X, q[1].clone()))).unwrap();
+        assert_eq!(circuit.num_moments(), 1);
+
+        circuit.insert(0, op(gate_on!(X, q[0].clone()))).unwrap();
+        assert_eq!(circuit.num_moments(), 2);
+        assert_eq!(circuit.moments()[0].len(), 1);
+
+        assert!(circuit.insert(5, op(gate_on!(X, q[0].clone()))).is_err());
+    }
+
+    #[test]
+    fn all_qubits_are_sorted_deterministically() {
+        let q = LineQubit::range(11);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            op(gate_on!(H, q[10].clone())),
+            op(gate_on!(CNOT, q[2].clone(), q[1].clone())),
+            op(gate_on!(X, q[10].clone())),
+        ]).unwrap();
+
+        let qubits = circuit.all_qubits();
+        assert_eq!(qubits.len(), 3);
+        assert!(qubits[0].eq_qid(&q[1]) && qubits[2].eq_qid(&q[10]));
+
+        let order = ExplicitQubitOrder::new(vec![Box::new(q[10].clone()), Box::new(q[2].clone()), Box::new(q[1].clone())]);
+        let qubits = circuit.all_qubits_with_order(&order).unwrap();
+        assert!(qubits[0].eq_qid(&q[10]) && qubits[2].eq_qid(&q[1]));
+        assert!(circuit.all_qubits_with_order(&ExplicitQubitOrder::new(vec![])).is_err());
+    }
+
+    #[test]
+    fn depth_ignores_empty_moments() {
+        let q = LineQubit::new(0);
+        let circuit = Circuit::from_moments(vec![
+            Moment::default(),
+            Moment::new(vec![op(gate_on!(X, q))]).unwrap(),
+        ]);
+        assert_eq!(circuit.num_moments(), 2);
+        assert_eq!(circuit.depth(), 1);
+    }
+
+    #[test]
+    fn total_duration_sums_moments() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            op(gate_on!(H, q[0].clone())),
+            op(gate_on!(X, q[1].clone())),
+            op(gate_on!(CNOT, q[0].clone(), q[1].clone())),
+        ]).unwrap();
+
+        let device = UnconstrainedDevice::new(DeviceDurationMap::new(Some(Duration::from_nanos(20))).with_duration::<CNotGate>(Duration::from_nanos(300)));
+        assert_eq!(circuit.total_duration(&device), 320.0);
+        assert_eq!(circuit.total_duration(&UnconstrainedDevice::default()), 0.0);
+    }
+
+    #[test]
+    fn concat_and_repeat() {
+        let q = LineQubit::range(2);
+        let mut a = Circuit::new();
+        a.append(vec![op(gate_on!(H, q[0].clone()))]).unwrap();
+        let mut b = Circuit::new();
+        b.append(vec![op(gate_on!(X, q[1].clone()))]).unwrap();
+
+        let sum = &a + &b;
+        assert_eq!(sum.num_moments(), 2);
+        assert!(sum.moments()[1].operates_on(&q[1]));
+        assert_eq!((&a * 3).num_moments(), 3);
+        assert_eq!((a + Circuit::new()).num_moments(), 1);
+    }
+
+    #[test]
+    fn zip_merges_moments() {
+        let q = LineQubit::range(2);
+        let mut a = Circuit::new();
+        a.append(vec![op(gate_on!(H, q[0].clone())), op(gate_on!(X, q[0].clone()))]).unwrap();
+        let mut b = Circuit::new();
+        b.append(vec![op(gate_on!(X, q[1].clone()))]).unwrap();
+
+        let zipped = a.zip(&b).unwrap();
+        assert_eq!(zipped.num_moments(), 2);
+        assert_eq!(zipped.moments()[0].len(), 2);
+        assert!(a.zip(&a).is_err());
+    }
+
+    #[test]
+    fn inverse_reverses_and_inverts() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![op(gate_on!(S, q[0].clone())), op(gate_on!(CNOT, q[0].clone(), q[1].clone()))]).unwrap();
+
+        let inverse = circuit.inverse().unwrap();
+        assert_eq!(inverse.num_moments(), 2);
+        assert!(gates_equal(inverse.moments()[0].operations()[0].as_ref(), &gate_on!(CNOT, q[0].clone(), q[1].clone()).unwrap()));
+        assert!(gates_equal(inverse.moments()[1].operations()[0].as_ref(), &gate_on!(S_DAG, q[0].clone()).unwrap()));
+        assert_eq!(circuit.repeat(2).num_moments(), 4);
+    }
+
+    #[derive(Clone)]
+    struct Tag(u64);
+
+    impl Hashable for Tag {
+        fn hash(&self) -> u64 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn inverse_keeps_tags() {
+        let q = LineQubit::new(0);
+        let tagged = gate_on!(S, q.clone()).unwrap().with_tags(vec![Box::new(Tag(1)), Box::new(Tag(2))]);
+        let circuit = Circuit::from_ops(vec![Box::new(tagged) as Box<dyn Operation>, op(gate_on!(H, q.clone()))]);
+
+        let inverse = circuit.inverse().unwrap();
+        let ops: Vec<&dyn Operation> = inverse.all_operations().collect();
+        assert!(ops[0].tags().is_empty());
+        assert!(ops[1].is_tagged_with(&Tag(1)) && ops[1].is_tagged_with(&Tag(2)));
+        assert!(gates_equal(ops[1].untagged().as_ref(), &gate_on!(S_DAG, q).unwrap()));
+    }
+
+    #[test]
+    fn resolve_parameters_keeps_tags() {
+        let q = LineQubit::new(0);
+        let rotation = SymbolicRotation::new(Pauli::Z, Symbol::new("phi")).unwrap();
+        let tagged = gate_on!(rotation, q.clone()).unwrap().with_tags(vec![Box::new(Tag(3))]);
+        let circuit = Circuit::from_ops(vec![Box::new(tagged) as Box<dyn Operation>, op(gate_on!(H, q.clone()))]);
+
+        let resolved = circuit.resolve_parameters(&ParamResolver::new(&[("phi", 0.5)])).unwrap();
+        let ops: Vec<&dyn Operation> = resolved.all_operations().collect();
+        assert!(ops[0].is_tagged_with(&Tag(3)));
+        assert!(gates_equal(ops[0].untagged().as_ref(), &gate_on!(Rz::new(0.5), q).unwrap()));
+        assert!(ops[1].tags().is_empty());
+    }
+
+    /// A qid whose dimension isn't part of its comparison key, to build circuits with inconsistent dimensions.
+    #[derive(Clone)]
+    struct Resonator(u64);
+
+    impl QId for Resonator {
+        fn comparison_key(&self) -> String {
+            "resonator".to_string()
+        }
+
+        fn dimension(&self) -> u64 {
+            self.0
+        }
+
+        fn validate_dimension(&self, _dimension: u64) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    #[test]
+    fn validate_reports_every_error() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![op(gate_on!(H, q[0].clone())), op(gate_on!(CNOT, q[0].clone(), q[1].clone()))]).unwrap();
+        assert!(circuit.validate().is_ok());
+
+        let mut invalid = Circuit::new();
+        invalid.append(vec![
+            Box::new(GateOperation::new(Box::new(CNOT), vec![Box::new(q[0].clone()), Box::new(q[0].clone())])),
+            Box::new(GateOperation::new(Box::new(H), vec![Box::new(q[1].clone()), Box::new(LineQubit::new(2))])),
+            Box::new(GateOperation::new(Box::new(DepolarizingChannel { p: 2.0 }), vec![Box::new(LineQubit::new(3))])),
+            Box::new(GateOperation::new(Box::new(H), vec![Box::new(Resonator(2))])),
+        ]).unwrap();
+        invalid.append(vec![Box::new(GateOperation::new(Box::new(H), vec![Box::new(Resonator(3))]))]).unwrap();
+
+        let errors = invalid.validate().unwrap_err();
+        assert_eq!(errors.len(), 5);
+        assert!(matches!(&errors[0], CircuitError::QubitCollision { moment_index: 0, qubit } if qubit.eq_qid(&q[0])));
+        assert!(matches!(&errors[1], CircuitError::InvalidGateArgs { moment_index: 0, .. }));
+        assert!(matches!(&errors[2], CircuitError::InvalidParameters { moment_index: 0, .. }));
+        assert!(matches!(&errors[3], CircuitError::DimensionMismatch { expected: 2, found: 3, .. }));
+        assert!(matches!(&errors[4], CircuitError::InvalidGateArgs { moment_index: 1, .. }));
+        assert_eq!(errors[4].to_string(), format!("Gate {} in moment 1 can't be applied to its qubits.", H));
+    }
+
+    #[test]
+    fn h_then_cnot_prepares_bell_state() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![op(gate_on!(H, q[0].clone())), op(gate_on!(CNOT, q[0].clone(), q[1].clone()))]).unwrap();
+
+        let unitary = circuit.unitary().unwrap();
+        let r = Complex::new(std::f64::consts::FRAC_1_SQRT_2, 0.0);
+        let zero = Complex::new(0.0, 0.0);
+        let expected = vec![
+            vec![r, zero, r, zero],
+            vec![zero, r, zero, r],
+            vec![zero, r, zero, -r],
+            vec![r, zero, -r, zero],
+        ];
+        assert!(linalg::allclose(&unitary, &expected, 1e-10), "{:?}", unitary);
+    }
+
+    #[test]
+    fn unitary_respects_qubit_order() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![op(gate_on!(CNOT, q[1].clone(), q[0].clone()))]).unwrap();
+
+        // With q0 as the most significant bit, |01⟩ and |11⟩ are swapped.
+        let unitary = circuit.unitary().unwrap();
+        assert_eq!(unitary[3][1], Complex::new(1.0, 0.0));
+        assert_eq!(unitary[1][3], Complex::new(1.0, 0.0));
+        assert_eq!(unitary[2][2], Complex::new(1.0, 0.0));
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+
+use crate::circuits::circuit::Circuit;
+use crate::ops::raw_types::{Operation, QId};
+
+/**
+ * The operations of a circuit as a directed acyclic graph. There is an edge from one operation to another
+ * when the second is the next operation to act on one of the first one's qubits, so every path
+ * through the graph follows the data on the qubits.
+ * Nodes are numbered in the order the operations appear in the circuit, moment by moment.
+ */
+#[derive(Clone, Default)]
+pub struct CircuitDag {
+    nodes: Vec<Box<dyn Operation>>,
+    successors: Vec<Vec<usize>>,
+    predecessors: Vec<Vec<usize>>,
+}
+
+impl CircuitDag {
+    pub fn from_circuit(circuit: &Circuit) -> Self {
+        let mut dag = Self::default();
+        // The last node that acted on each qubit seen so far.
+        let mut frontier: Vec<(Box<dyn QId>, usize)> = vec![];
+
+        for op in circuit.moments().iter().flat_map(|moment| moment.iter()) {
+            let node = dag.nodes.len();
+            let mut predecessors = vec![];
+            for q in op.qubits() {
+                match frontier.iter_mut().find(|(other, _)| other.eq_qid(q.as_ref())) {
+                    Some(entry) => {
+                        if !predecessors.contains(&entry.1) {
+                            predecessors.push(entry.1);
+                        }
+                        entry.1 = node;
+                    }
+                    None => frontier.push((q, node)),
+                }
+            }
+
+            for predecessor in &predecessors {
+                dag.successors[*predecessor].push(node);
+            }
+            dag.nodes.push(op.clone());
+            dag.successors.push(vec![]);
+            dag.predecessors.push(predecessors);
+        }
+
+        dag
+    }
+
+    /// Returns the operations of the graph, indexed by node.
+    pub fn nodes(&self) -> &[Box<dyn Operation>] {
+        &self.nodes
+    }
+
+    /// Returns the nodes with an edge into the given node.
+    pub fn predecessors(&self, node: usize) -> &[usize] {
+        &self.predecessors[node]
+    }
+
+    /// Returns the nodes with an edge from the given node.
+    pub fn successors(&self, node: usize) -> &[usize] {
+        &self.successors[node]
+    }
+
+    /**
+     * Returns the number of operations on the longest path through the graph. This is the depth of
+     * the circuit once every operation is moved as early as its dependencies allow.
+     */
+    pub fn critical_path_length(&self) -> usize {
+        let mut lengths = vec![0; self.nodes.len()];
+        // Nodes are numbered in circuit order, so every predecessor comes before its successors.
+        for node in 0..self.nodes.len() {
+            lengths[node] = self.predecessors[node].iter().map(|p| lengths[*p]).max().unwrap_or(0) + 1;
+        }
+        lengths.into_iter().max().unwrap_or(0)
+    }
+
+    /**
+     * Returns the operations in an order where each one comes after all of its predecessors.
+     * Among the operations that are ready at the same time, the one that appears first in the circuit goes first.
+     */
+    pub fn topological_sort(&self) -> Vec<&dyn Operation> {
+        let mut remaining: Vec<usize> = self.predecessors.iter().map(|p| p.len()).collect();
+        let mut ready: VecDeque<usize> = (0..self.nodes.len()).filter(|node| remaining[*node] == 0).collect();
+        let mut order = Vec::with_capacity(self.nodes.len());
+
+        while let Some(node) = ready.pop_front() {
+            order.push(self.nodes[node].as_ref());
+            for successor in &self.successors[node] {
+                remaining[*successor] -= 1;
+                if remaining[*successor] == 0 {
+                    ready.push_back(*successor);
+                }
+            }
+        }
+
+        order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Error;
+
+    use crate::gate_on;
+    use crate::ops::common_gates::{CNOT, H};
+    use crate::ops::gate_operation::GateOperation;
+    use crate::ops::pauli_gates::{X, Z};
+    use crate::ops::qubits::LineQubit;
+    use crate::ops::raw_traits::gates_equal;
+
+    use super::*;
+
+    fn op(result: Result<GateOperation, Error>) -> Box<dyn Operation> {
+        Box::new(result.unwrap())
+    }
+
+    #[test]
+    fn dag_follows_qubit_dependencies() {
+        let q = LineQubit::range(3);
+        let circuit = Circuit::from_moments(vec![
+            Default::default(),
+            Circuit::from_ops(vec![op(gate_on!(H, q[0].clone())), op(gate_on!(X, q[2].clone()))]).moments()[0].clone(),
+            Circuit::from_ops(vec![op(gate_on!(CNOT, q[0].clone(), q[1].clone()))]).moments()[0].clone(),
+            Circuit::from_ops(vec![op(gate_on!(Z, q[2].clone()))]).moments()[0].clone(),
+        ]);
+
+        let dag = CircuitDag::from_circuit(&circuit);
+        assert_eq!(dag.nodes().len(), 4);
+        assert_eq!(dag.predecessors(2), &[0]);
+        assert_eq!(dag.successors(1), &[3]);
+        assert!(dag.predecessors(1).is_empty());
+
+        // The empty moment and the late Z don't add to the critical path.
+        assert_eq!(circuit.num_moments(), 4);
+        assert_eq!(dag.critical_path_length(), 2);
+    }
+
+    #[test]
+    fn topological_sort_respects_dependencies() {
+        let q = LineQubit::range(2);
+        let circuit = Circuit::from_ops(vec![
+            op(gate_on!(H, q[0].clone())),
+            op(gate_on!(CNOT, q[0].clone(), q[1].clone())),
+            op(gate_on!(X, q[1].clone())),
+            op(gate_on!(Z, q[0].clone())),
+        ]);
+
+        let dag = CircuitDag::from_circuit(&circuit);
+        let order = dag.topological_sort();
+        assert_eq!(order.len(), 4);
+        assert!(gates_equal(order[0], op(gate_on!(H, q[0].clone())).as_ref()));
+        assert!(gates_equal(order[1], op(gate_on!(CNOT, q[0].clone(), q[1].clone())).as_ref()));
+        assert_eq!(dag.critical_path_length(), circuit.depth());
+
+        assert_eq!(CircuitDag::from_circuit(&Circuit::new()).critical_path_length(), 0);
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::circuits::circuit::Circuit;
+use crate::linalg;
+use crate::ops::raw_traits::{EqValue, EqualityValue, HasUnitary};
+use crate::ops::raw_types::{Operation, QId, QIdHasher, QIdShape, TaggedOperation};
+use crate::utils::extra_traits::Hashable;
+
+/**
+ * An operation that applies a shared circuit, with its qubits renamed by `qubit_map` and repeated
+ * `repetitions` times. Qubits of the circuit that aren't in the map keep their names.
+ * Copies of the operation share the circuit, so it can be reused as a template without copying it.
+ */
+#[derive(Clone)]
+pub struct CircuitOperation {
+    pub circuit: Arc<Circuit>,
+    pub qubit_map: HashMap<QIdHasher, Box<dyn QId>>,
+    pub repetitions: usize,
+}
+
+impl CircuitOperation {
+    /// Creates an operation that applies the circuit once, on its own qubits.
+    pub fn new(circuit: Circuit) -> Self {
+        Self {
+            circuit: Arc::new(circuit),
+            qubit_map: HashMap::new(),
+            repetitions: 1,
+        }
+    }
+
+    /// Returns the qubit that the qubit of the circuit is applied to.
+    fn mapped(&self, qubit: &dyn QId) -> Box<dyn QId> {
+        self.qubit_map
+            .get(&QIdHasher::new(qubit.dyn_clone()))
+            .cloned()
+            .unwrap_or_else(|| qubit.dyn_clone())
+    }
+
+    /**
+     * Returns the operation with the qubit mapping applied after the current one.
+     * The mapping renames the qubits that the operation acts on, not the qubits of the circuit.
+     * Fails if the mapping sends two of the operation's qubits to the same qubit.
+     */
+    pub fn with_qubit_mapping(&self, mapping: &HashMap<QIdHasher, Box<dyn QId>>) -> Result<Self, Error> {
+        let qubit_map: HashMap<QIdHasher, Box<dyn QId>> = self.circuit
+            .all_qubits()
+            .into_iter()
+            .map(|q| {
+                let mapped = self.mapped(q.as_ref());
+                let remapped = mapping.get(&QIdHasher::new(mapped.clone())).cloned().unwrap_or(mapped);
+                (QIdHasher::new(q), remapped)
+            })
+            .collect();
+        check_injective(qubit_map.values())?;
+
+        Ok(Self {
+            circuit: self.circuit.clone(),
+            qubit_map,
+            repetitions: self.repetitions,
+        })
+    }
+
+    /// Returns the operation repeated `n` more times, so that it applies the circuit `n * repetitions` times.
+    pub fn repeated(&self, n: usize) -> Self {
+        Self {
+            circuit: self.circuit.clone(),
+            qubit_map: self.qubit_map.clone(),
+            repetitions: self.repetitions * n,
+        }
+    }
+
+    /**
+     * Returns the operations of the circuit on the mapped qubits, once for every repetition.
+     * Fails if an operation can't act on its mapped qubits.
+     */
+    pub fn decompose(&self) -> Result<Vec<Box<dyn Operation>>, Error> {
+        let once = self.circuit
+            .all_operations()
+            .map(|op| op.with_qubits(op.qubits().iter().map(|q| self.mapped(q.as_ref())).collect()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((0..self.repetitions).flat_map(|_| once.iter().cloned()).collect())
+    }
+
+    /// Returns a circuit of the decomposed operations.
+    pub fn to_circuit(&self) -> Result<Circuit, Error> {
+        let mut circuit = Circuit::new();
+        circuit.append(self.decompose()?)?;
+        Ok(circuit)
+    }
+}
+
+/// Fails if a qubit appears more than once, since the circuit's qubits must stay distinct.
+fn check_injective<'a>(qubits: impl Iterator<Item = &'a Box<dyn QId>>) -> Result<(), Error> {
+    let mut seen: Vec<&Box<dyn QId>> = vec![];
+    for q in qubits {
+        if seen.iter().any(|other| other.eq_qid(q.as_ref())) {
+            return Err(anyhow!(format!("More than one qubit of the circuit is mapped to qubit {}.", q)));
+        }
+        seen.push(q);
+    }
+    Ok(())
+}
+
+impl QIdShape for CircuitOperation {
+    fn qid_shape(&self) -> Vec<u64> {
+        self.circuit.all_qubits().iter().map(|q| q.dimension()).collect()
+    }
+}
+
+impl HasUnitary for CircuitOperation {
+    /// Returns the unitary of the repeated circuit, on the qubits in the order of `qubits`.
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        let once = self.circuit.unitary().ok()?;
+        Some((0..self.repetitions).fold(linalg::eye(once.len()), |unitary, _| linalg::dot(&once, &unitary)))
+    }
+}
+
+impl EqualityValue for CircuitOperation {
+    fn equality_value(&self) -> EqValue {
+        let operations = self.decompose().unwrap_or_default().iter().map(|op| op.equality_value()).collect();
+        EqValue::Tuple(vec![EqValue::String("CircuitOperation".to_string()), EqValue::Tuple(operations)])
+    }
+}
+
+impl Operation for CircuitOperation {
+    /// Returns the mapped qubits of the circuit, in the default qubit order of the circuit's own qubits.
+    fn qubits(&self) -> Vec<Box<dyn QId>> {
+        self.circuit.all_qubits().iter().map(|q| self.mapped(q.as_ref())).collect()
+    }
+
+    fn with_qubits(&self, new_qubits: Vec<Box<dyn QId>>) -> Result<Box<dyn Operation>, Error> {
+        let qubits = self.circuit.all_qubits();
+        if new_qubits.len() != qubits.len() {
+            return Err(anyhow!(format!("The circuit acts on {} qubits, but {} were given.", qubits.len(), new_qubits.len())));
+        }
+        if let Some((q, new_q)) = qubits.iter().zip(&new_qubits).find(|(q, new_q)| q.dimension() != new_q.dimension()) {
+            return Err(anyhow!(format!("Qubit {} can't be replaced by {}, which has a different dimension.", q, new_q)));
+        }
+
+        check_injective(new_qubits.iter())?;
+
+        let qubit_map = qubits.into_iter().map(QIdHasher::new).zip(new_qubits).collect();
+        Ok(Box::new(Self {
+            circuit: self.circuit.clone(),
+            qubit_map,
+            repetitions: self.repetitions,
+        }))
+    }
+
+    fn tags(&self) -> Vec<Box<dyn Hashable>> {
+        vec![]
+    }
+
+    fn untagged(&self) -> Box<dyn Operation> {
+        Box::new(self.clone())
+    }
+
+    fn with_tags(&self, new_tags: Vec<Box<dyn Hashable>>) -> TaggedOperation {
+        TaggedOperation::new(Box::new(self.clone()), new_tags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gate_on;
+    use crate::ops::common_gates::{CNOT, H, S};
+    use crate::ops::qubits::LineQubit;
+    use crate::ops::raw_traits::gates_equal;
+
+    use super::*;
+
+    fn bell_circuit() -> Circuit {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            Box::new(gate_on!(H, q[0].clone()).unwrap()),
+            Box::new(gate_on!(CNOT, q[0].clone(), q[1].clone()).unwrap()),
+        ]).unwrap();
+        circuit
+    }
+
+    #[test]
+    fn decomposition_follows_the_qubit_map() {
+        let q = LineQubit::range(4);
+        let template = CircuitOperation::new(bell_circuit());
+        let mapping: HashMap<QIdHasher, Box<dyn QId>> = vec![
+            (QIdHasher::new(Box::new(q[0].clone())), Box::new(q[2].clone()) as Box<dyn QId>),
+            (QIdHasher::new(Box::new(q[1].clone())), Box::new(q[3].clone())),
+        ].into_iter().collect();
+
+        let moved = template.with_qubit_mapping(&mapping).unwrap();
+        assert!(Arc::ptr_eq(&moved.circuit, &template.circuit));
+        let ops = moved.decompose().unwrap();
+        assert_eq!(ops.len(), 2);
+        assert!(gates_equal(ops[1].as_ref(), &gate_on!(CNOT, q[2].clone(), q[3].clone()).unwrap()));
+        assert!(moved.qubits()[0].eq_qid(&q[2]));
+
+        let swapped = moved.with_qubits(vec![Box::new(q[1].clone()), Box::new(q[0].clone())]).unwrap();
+        assert!(moved.with_qubits(vec![Box::new(q[1].clone())]).is_err());
+        assert!(swapped.qubits()[0].eq_qid(&q[1]));
+        assert!(!gates_equal(swapped.as_ref(), &moved));
+
+        // Mapping both qubits of the circuit onto one qubit is rejected.
+        let merging: HashMap<QIdHasher, Box<dyn QId>> = vec![(QIdHasher::new(Box::new(q[2].clone())), Box::new(q[3].clone()) as Box<dyn QId>)]
+            .into_iter()
+            .collect();
+        assert!(moved.with_qubit_mapping(&merging).is_err());
+        assert!(moved.with_qubits(vec![Box::new(q[1].clone()), Box::new(q[1].clone())]).is_err());
+    }
+
+    #[test]
+    fn circuit_operations_can_be_shared_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<CircuitOperation>();
+    }
+
+    #[test]
+    fn repetitions_repeat_the_circuit() {
+        let q = LineQubit::new(0);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![Box::new(gate_on!(S, q.clone()).unwrap())]).unwrap();
+
+        let twice = CircuitOperation::new(circuit).repeated(2);
+        assert_eq!(twice.decompose().unwrap().len(), 2);
+        assert_eq!(twice.repeated(3).repetitions, 6);
+        // S² = Z.
+        let z = twice.unitary().unwrap();
+        assert!((z[1][1] - Complex::new(-1.0, 0.0)).norm() < 1e-10);
+
+        // Circuit operations can be nested in circuits.
+        let mut outer = Circuit::new();
+        outer.append(vec![Box::new(twice.clone()), Box::new(twice.clone())]).unwrap();
+        assert_eq!(outer.num_moments(), 2);
+        assert!(linalg::allclose(&outer.unitary().unwrap(), &twice.repeated(2).to_circuit().unwrap().unitary().unwrap(), 1e-10));
+    }
+}
@@ -0,0 +1,314 @@
+use std::fmt;
+
+use anyhow::Error;
+
+use crate::circuits::circuit::Circuit;
+use crate::ops::qubit_order::{DefaultQubitOrder, QubitOrder};
+use crate::ops::raw_types::{Operation, QId};
+
+/// Options for rendering a circuit as a text diagram.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DiagramArgs {
+    /// Draws qubits as vertical wires and moments as rows, instead of horizontal wires and columns.
+    pub transpose: bool,
+    /// Draws wires with box-drawing characters. Otherwise only ASCII characters are used.
+    pub use_unicode_characters: bool,
+}
+
+impl Default for DiagramArgs {
+    fn default() -> Self {
+        Self {
+            transpose: false,
+            use_unicode_characters: true,
+        }
+    }
+}
+
+/// A column of the diagram: the symbol on each qubit's wire, and the ranges of wires joined by a connecting line.
+struct Column {
+    symbols: Vec<Option<String>>,
+    connections: Vec<(usize, usize)>,
+}
+
+impl Column {
+    fn new(num_qubits: usize) -> Self {
+        Self {
+            symbols: vec![None; num_qubits],
+            connections: vec![],
+        }
+    }
+
+    fn is_free(&self, low: usize, high: usize) -> bool {
+        self.symbols[low..=high].iter().all(|symbol| symbol.is_none())
+            && self.connections.iter().all(|&(l, h)| h < low || l > high)
+    }
+
+    fn is_connected(&self, low: usize, high: usize) -> bool {
+        self.connections.iter().any(|&(l, h)| l <= low && high <= h)
+    }
+
+    fn is_crossed(&self, i: usize) -> bool {
+        self.connections.iter().any(|&(l, h)| l < i && i < h)
+    }
+
+    fn width(&self) -> usize {
+        self.symbols.iter().flatten().map(|symbol| symbol.chars().count()).max().unwrap_or(1)
+    }
+}
+
+struct Characters {
+    horizontal: char,
+    vertical: char,
+    cross: char,
+}
+
+impl Circuit {
+    /// Renders the circuit as a text diagram, with the qubits in the given order.
+    pub fn to_text_diagram(&self, qubit_order: &dyn QubitOrder) -> Result<String, Error> {
+        self.to_text_diagram_with_args(qubit_order, &DiagramArgs::default())
+    }
+
+    /// Renders the circuit as a text diagram, with the qubits in the given order and the given options.
+    pub fn to_text_diagram_with_args(&self, qubit_order: &dyn QubitOrder, args: &DiagramArgs) -> Result<String, Error> {
+        let qubits = qubit_order.sorted(self.all_qubits())?;
+        let columns = self.diagram_columns(&qubits, args);
+        let characters = if args.use_unicode_characters {
+            Characters { horizontal: '─', vertical: '│', cross: '┼' }
+        } else {
+            Characters { horizontal: '-', vertical: '|', cross: '+' }
+        };
+
+        let lines = if args.transpose {
+            render_transposed(&qubits, &columns, &characters)
+        } else {
+            render(&qubits, &columns, &characters)
+        };
+
+        Ok(lines.iter().map(|line| line.trim_end()).collect::<Vec<_>>().join("\n"))
+    }
+
+    /// Splits each moment into columns, so that operations drawn in the same column don't overlap.
+    fn diagram_columns(&self, qubits: &[Box<dyn QId>], args: &DiagramArgs) -> Vec<Column> {
+        let mut columns = vec![];
+
+        for moment in self.moments() {
+            let mut moment_columns: Vec<Column> = vec![];
+
+            for op in moment {
+                let indices: Vec<usize> = op.qubits()
+                    .iter()
+                    .filter_map(|q| qubits.iter().position(|other| other.eq_qid(q.as_ref())))
+                    .collect();
+                let (low, high) = match (indices.iter().min(), indices.iter().max()) {
+                    (Some(&low), Some(&high)) => (low, high),
+                    _ => continue,
+                };
+
+                let column = match moment_columns.iter().position(|column| column.is_free(low, high)) {
+                    Some(i) => &mut moment_columns[i],
+                    None => {
+                        moment_columns.push(Column::new(qubits.len()));
+                        moment_columns.last_mut().unwrap()
+                    }
+                };
+
+                for (i, symbol) in indices.iter().zip(operation_symbols(op.as_ref(), args)) {
+                    column.symbols[*i] = Some(symbol);
+                }
+                if low < high {
+                    column.connections.push((low, high));
+                }
+            }
+
+            if moment_columns.is_empty() {
+                moment_columns.push(Column::new(qubits.len()));
+            }
+            columns.extend(moment_columns);
+        }
+
+        columns
+    }
+}
+
+impl fmt::Display for Circuit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let diagram = self.to_text_diagram(&DefaultQubitOrder).map_err(|_| fmt::Error)?;
+        write!(f, "{}", diagram)
+    }
+}
+
+fn operation_symbols(op: &dyn Operation, args: &DiagramArgs) -> Vec<String> {
+    let symbols = match op.gate() {
+        Some(gate) => gate.wire_symbols(),
+        None => vec!["?".to_string(); op.qubits().len()],
+    };
+
+    if args.use_unicode_characters {
+        symbols
+    } else {
+        symbols.iter().map(|symbol| symbol.replace('×', "x").replace('π', "pi")).collect()
+    }
+}
+
+fn pad(text: &str, width: usize, fill: char) -> String {
+    let mut padded = text.to_string();
+    padded.extend(std::iter::repeat_n(fill, width.saturating_sub(text.chars().count())));
+    padded
+}
+
+/// Draws each qubit as a horizontal wire, with a blank line between wires for connections.
+fn render(qubits: &[Box<dyn QId>], columns: &[Column], characters: &Characters) -> Vec<String> {
+    let labels: Vec<String> = qubits.iter().map(|q| format!("{}: ", q)).collect();
+    let label_width = labels.iter().map(|label| label.chars().count()).max().unwrap_or(0);
+    let wire = |width: usize| pad("", width, characters.horizontal);
+    let mut lines = vec![];
+
+    for (i, label) in labels.iter().enumerate() {
+        let mut line = pad(label, label_width, ' ');
+        for column in columns {
+            line.push_str(&wire(3));
+            let symbol = match &column.symbols[i] {
+                Some(symbol) => symbol.clone(),
+                None if column.is_crossed(i) => characters.cross.to_string(),
+                None => String::new(),
+            };
+            line.push_str(&pad(&symbol, column.width(), characters.horizontal));
+        }
+        line.push_str(&wire(3));
+        lines.push(line);
+
+        if i + 1 < qubits.len() {
+            let mut spacer = pad("", label_width, ' ');
+            for column in columns {
+                spacer.push_str("   ");
+                let connector = if column.is_connected(i, i + 1) { characters.vertical } else { ' ' };
+                spacer.push_str(&pad(&connector.to_string(), column.width(), ' '));
+            }
+            lines.push(spacer);
+        }
+    }
+
+    lines
+}
+
+/// Draws each qubit as a vertical wire, with moments going down the page.
+fn render_transposed(qubits: &[Box<dyn QId>], columns: &[Column], characters: &Characters) -> Vec<String> {
+    let labels: Vec<String> = qubits.iter().map(|q| q.to_string()).collect();
+    let widths: Vec<usize> = labels
+        .iter()
+        .enumerate()
+        .map(|(i, label)| {
+            columns
+                .iter()
+                .filter_map(|column| column.symbols[i].as_ref())
+                .map(|symbol| symbol.chars().count())
+                .chain(std::iter::once(label.chars().count()))
+                .max()
+                .unwrap_or(1)
+        })
+        .collect();
+
+    let wires = || {
+        widths.iter().map(|width| pad(&characters.vertical.to_string(), *width, ' ')).collect::<Vec<_>>().join(" ")
+    };
+    let mut lines = vec![
+        labels.iter().zip(widths.iter()).map(|(label, width)| pad(label, *width, ' ')).collect::<Vec<_>>().join(" "),
+        wires(),
+    ];
+
+    for column in columns {
+        let mut line = String::new();
+        for (i, width) in widths.iter().enumerate() {
+            let symbol = match &column.symbols[i] {
+                Some(symbol) => symbol.clone(),
+                None if column.is_crossed(i) => characters.cross.to_string(),
+                None => characters.vertical.to_string(),
+            };
+            let connected = i + 1 < widths.len() && column.is_connected(i, i + 1);
+            let fill = if connected { characters.horizontal } else { ' ' };
+
+            line.push_str(&pad(&symbol, *width, fill));
+            if i + 1 < widths.len() {
+                line.push(fill);
+            }
+        }
+        lines.push(line);
+        lines.push(wires());
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gate_on;
+    use crate::ops::common_gates::{CNOT, H, SWAP};
+    use crate::ops::gate_operation::GateOperation;
+    use crate::ops::pauli_gates::X;
+    use crate::ops::qubits::LineQubit;
+
+    use super::*;
+
+    fn op(result: Result<GateOperation, Error>) -> Box<dyn Operation> {
+        Box::new(result.unwrap())
+    }
+
+    fn bell_circuit() -> Circuit {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![op(gate_on!(H, q[0].clone())), op(gate_on!(CNOT, q[0].clone(), q[1].clone()))]).unwrap();
+        circuit
+    }
+
+    #[test]
+    fn diagram_draws_connected_gates() {
+        let expected = [
+            "q0: ───H───@───",
+            "           │",
+            "q1: ───────X───",
+        ].join("\n");
+        assert_eq!(bell_circuit().to_text_diagram(&DefaultQubitOrder).unwrap(), expected);
+        assert_eq!(bell_circuit().to_string(), expected);
+    }
+
+    #[test]
+    fn ascii_diagram() {
+        let args = DiagramArgs { transpose: false, use_unicode_characters: false };
+        let expected = [
+            "q0: ---H---@---",
+            "           |",
+            "q1: -------X---",
+        ].join("\n");
+        assert_eq!(bell_circuit().to_text_diagram_with_args(&DefaultQubitOrder, &args).unwrap(), expected);
+    }
+
+    #[test]
+    fn transposed_diagram() {
+        let args = DiagramArgs { transpose: true, use_unicode_characters: true };
+        let expected = [
+            "q0 q1",
+            "│  │",
+            "H  │",
+            "│  │",
+            "@──X",
+            "│  │",
+        ].join("\n");
+        assert_eq!(bell_circuit().to_text_diagram_with_args(&DefaultQubitOrder, &args).unwrap(), expected);
+    }
+
+    #[test]
+    fn overlapping_operations_are_split_into_columns() {
+        let q = LineQubit::range(3);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![op(gate_on!(SWAP, q[0].clone(), q[2].clone())), op(gate_on!(X, q[1].clone()))]).unwrap();
+
+        let expected = [
+            "q0: ───×───────",
+            "       │",
+            "q1: ───┼───X───",
+            "       │",
+            "q2: ───×───────",
+        ].join("\n");
+        assert_eq!(circuit.to_text_diagram(&DefaultQubitOrder).unwrap(), expected);
+    }
+}
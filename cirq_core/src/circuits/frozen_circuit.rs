@@ -0,0 +1,116 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::circuits::circuit::Circuit;
+
+/**
+ * An immutable circuit that can be hashed, so it can be used as a `HashMap` key.
+ * Two frozen circuits are equal if their moments hold equal operations, in any order within each moment.
+ * The canonical form used for comparisons and the hash are computed once, when the circuit is frozen.
+ */
+#[derive(Clone)]
+pub struct FrozenCircuit {
+    circuit: Circuit,
+    canonical_form: Vec<Vec<String>>,
+    hash: u64,
+}
+
+impl FrozenCircuit {
+    pub fn from_circuit(circuit: Circuit) -> Self {
+        let canonical_form: Vec<Vec<String>> = circuit
+            .moments()
+            .iter()
+            .map(|moment| {
+                let mut ops: Vec<String> = moment.iter().map(|op| format!("{:?}", op.equality_value())).collect();
+                ops.sort();
+                ops
+            })
+            .collect();
+
+        let mut hasher = DefaultHasher::new();
+        canonical_form.hash(&mut hasher);
+
+        Self {
+            circuit,
+            canonical_form,
+            hash: hasher.finish(),
+        }
+    }
+
+    /// Returns a mutable copy of the circuit.
+    pub fn to_circuit(&self) -> Circuit {
+        self.circuit.clone()
+    }
+
+    pub fn circuit(&self) -> &Circuit {
+        &self.circuit
+    }
+}
+
+impl From<Circuit> for FrozenCircuit {
+    fn from(circuit: Circuit) -> Self {
+        Self::from_circuit(circuit)
+    }
+}
+
+impl PartialEq for FrozenCircuit {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.canonical_form == other.canonical_form
+    }
+}
+
+impl Eq for FrozenCircuit {}
+
+impl Hash for FrozenCircuit {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use anyhow::Error;
+
+    use crate::gate_on;
+    use crate::ops::common_gates::{CNOT, H};
+    use crate::ops::gate_operation::GateOperation;
+    use crate::ops::pauli_gates::X;
+    use crate::ops::qubits::LineQubit;
+    use crate::ops::raw_types::Operation;
+
+    use super::*;
+
+    fn op(result: Result<GateOperation, Error>) -> Box<dyn Operation> {
+        Box::new(result.unwrap())
+    }
+
+    #[test]
+    fn frozen_circuits_compare_by_operations() {
+        let q = LineQubit::range(2);
+        let first = Circuit::from_ops(vec![op(gate_on!(H, q[0].clone())), op(gate_on!(X, q[1].clone()))]);
+        let reordered = Circuit::from_ops(vec![op(gate_on!(X, q[1].clone())), op(gate_on!(H, q[0].clone()))]);
+        let other = Circuit::from_ops(vec![op(gate_on!(CNOT, q[0].clone(), q[1].clone()))]);
+
+        assert!(FrozenCircuit::from(first.clone()) == FrozenCircuit::from(reordered));
+        assert!(FrozenCircuit::from(first.clone()) != FrozenCircuit::from(other.clone()));
+
+        let mut cache = HashMap::new();
+        cache.insert(FrozenCircuit::from_circuit(first.clone()), 1);
+        cache.insert(FrozenCircuit::from_circuit(other), 2);
+        assert_eq!(cache[&FrozenCircuit::from_circuit(first.clone())], 1);
+
+        let thawed = FrozenCircuit::from_circuit(first).to_circuit();
+        assert_eq!(thawed.num_moments(), 1);
+        assert_eq!(thawed.all_operations().count(), 2);
+    }
+
+    #[test]
+    fn empty_moments_are_significant() {
+        let q = LineQubit::new(0);
+        let circuit = Circuit::from_ops(vec![op(gate_on!(H, q))]);
+        let padded = &circuit + &Circuit::from_moments(vec![Default::default()]);
+        assert!(FrozenCircuit::from(circuit) != FrozenCircuit::from(padded));
+    }
+}
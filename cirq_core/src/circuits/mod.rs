@@ -0,0 +1,11 @@
+pub mod circuit;
+pub mod circuit_dag;
+pub mod circuit_operation;
+pub mod diagram;
+pub mod frozen_circuit;
+pub mod moment;
+pub mod openqasm3_output;
+pub mod qasm_input;
+pub mod qasm_output;
+pub mod quil_output;
+pub mod svg;
\ No newline at end of file
@@ -0,0 +1,216 @@
+use std::slice;
+use std::time::Duration;
+
+use anyhow::Error;
+
+use crate::devices::{Device, UnconstrainedDevice};
+use crate::ops::raw_types::{Operation, QId};
+use crate::ops::wait::{TimeUnit, WaitGate};
+
+/**
+ * A time-slice of operations within a circuit.
+ * The operations in a moment act on disjoint sets of qubits, so they can be
+ * thought of as happening at the same time.
+ */
+#[derive(Clone, Default)]
+pub struct Moment {
+    operations: Vec<Box<dyn Operation>>,
+}
+
+impl Moment {
+    /// Creates a moment from the given operations. Fails if two operations share a qubit.
+    pub fn new(operations: Vec<Box<dyn Operation>>) -> Result<Self, anyhow::Error> {
+        let mut moment = Self::default();
+        for op in operations {
+            moment = moment.with_operation(op)?;
+        }
+        Ok(moment)
+    }
+
+    /// Returns the operations in the moment.
+    pub fn operations(&self) -> &[Box<dyn Operation>] {
+        &self.operations
+    }
+
+    /// Returns an iterator over the operations in the moment.
+    pub fn iter(&self) -> slice::Iter<'_, Box<dyn Operation>> {
+        self.operations.iter()
+    }
+
+    /// Returns the qubits acted upon by the moment, in the order of the operations.
+    pub fn qubits(&self) -> Vec<Box<dyn QId>> {
+        self.operations.iter().flat_map(|op| op.qubits()).collect()
+    }
+
+    /// Determines if the moment has an operation that acts on the given qubit.
+    pub fn operates_on(&self, qubit: &dyn QId) -> bool {
+        self.operations.iter().any(|op| op.qubits().iter().any(|q| q.eq_qid(qubit)))
+    }
+
+    /// Returns a copy of the moment with the given operation added. Fails if the operation's qubits are in use.
+    pub fn with_operation(&self, op: Box<dyn Operation>) -> Result<Moment, Error> {
+        if let Some(qubit) = op.qubits().iter().find(|q| self.operates_on(q.as_ref())) {
+            return Err(anyhow!(format!("Overlapping operations in moment on qubit {}.", qubit)));
+        }
+
+        let mut moment = self.clone();
+        moment.operations.push(op);
+        Ok(moment)
+    }
+
+    /// Same as `with_operation`.
+    pub fn with(&self, op: Box<dyn Operation>) -> Result<Moment, Error> {
+        self.with_operation(op)
+    }
+
+    /// Returns a copy of the moment with all of the given operations added. Fails if any of their qubits are in use.
+    pub fn with_operations(&self, ops: Vec<Box<dyn Operation>>) -> Result<Moment, Error> {
+        ops.into_iter().try_fold(self.clone(), |moment, op| moment.with_operation(op))
+    }
+
+    /// Returns a copy of the moment with every operation that touches one of the given qubits removed.
+    pub fn without_operations(&self, qubits: &[&dyn QId]) -> Moment {
+        let operations = self.operations
+            .iter()
+            .filter(|op| !op.qubits().iter().any(|q| qubits.iter().any(|other| q.eq_qid(*other))))
+            .cloned()
+            .collect();
+
+        Self {
+            operations
+        }
+    }
+
+    /// Same as `without_operations`.
+    pub fn without(&self, qubits: &[&dyn QId]) -> Moment {
+        self.without_operations(qubits)
+    }
+
+    /// Returns the number of operations in the moment.
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Determines if the moment has no operations.
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Returns how long the moment takes when only waits have a duration.
+    pub fn duration(&self, unit: TimeUnit) -> f64 {
+        self.duration_on(&UnconstrainedDevice::default(), unit)
+    }
+
+    /**
+     * Returns how long the moment takes on the device, which is the duration of its longest operation.
+     * Waits take their own duration, and operations the device has no duration for take no time.
+     */
+    pub fn duration_on(&self, device: &dyn Device, unit: TimeUnit) -> f64 {
+        let longest = self.operations
+            .iter()
+            .filter_map(|op| {
+                let gate = op.gate()?;
+                if let Some(wait) = gate.as_any().downcast_ref::<WaitGate>() {
+                    return Some(wait.duration());
+                }
+                let qubits = op.qubits();
+                let qubits: Vec<&dyn QId> = qubits.iter().map(|q| q.as_ref()).collect();
+                device.gate_duration(gate.as_ref(), &qubits)
+            })
+            .max()
+            .unwrap_or(Duration::ZERO);
+        unit.convert(longest)
+    }
+}
+
+impl<'a> IntoIterator for &'a Moment {
+    type Item = &'a Box<dyn Operation>;
+    type IntoIter = slice::Iter<'a, Box<dyn Operation>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for Moment {
+    type Item = Box<dyn Operation>;
+    type IntoIter = std::vec::IntoIter<Box<dyn Operation>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.operations.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::devices::DeviceDurationMap;
+    use crate::gate_on;
+    use crate::ops::common_gates::{CZGate, CZ, H};
+    use crate::ops::gate_operation::GateOperation;
+    use crate::ops::pauli_gates::X;
+    use crate::ops::qubits::LineQubit;
+    use crate::ops::wait::wait;
+
+    use super::*;
+
+    fn op(result: Result<GateOperation, Error>) -> Box<dyn Operation> {
+        Box::new(result.unwrap())
+    }
+
+    #[test]
+    fn moment_rejects_overlapping_operations() {
+        let q = LineQubit::range(3);
+        assert!(Moment::new(vec![op(gate_on!(CZ, q[0].clone(), q[1].clone())), op(gate_on!(X, q[2].clone()))]).is_ok());
+        assert!(Moment::new(vec![op(gate_on!(CZ, q[0].clone(), q[1].clone())), op(gate_on!(X, q[1].clone()))]).is_err());
+    }
+
+    #[test]
+    fn moment_with_and_without() {
+        let q = LineQubit::range(3);
+        let moment = Moment::new(vec![op(gate_on!(H, q[0].clone()))]).unwrap();
+        let moment = moment.with_operation(op(gate_on!(CZ, q[1].clone(), q[2].clone()))).unwrap();
+
+        assert_eq!(moment.len(), 2);
+        assert!(moment.operates_on(&q[2]));
+        assert!(moment.with_operation(op(gate_on!(X, q[0].clone()))).is_err());
+
+        let moment = moment.without_operations(&[&q[2]]);
+        assert_eq!(moment.len(), 1);
+        assert!(!moment.operates_on(&q[1]));
+        assert_eq!(moment.qubits(), vec![Box::new(q[0].clone()) as Box<dyn QId>]);
+        assert_eq!(moment.iter().count(), 1);
+
+        let moment = moment.with(op(gate_on!(X, q[1].clone()))).unwrap();
+        assert_eq!(moment.len(), 2);
+        assert_eq!(moment.without(&[&q[0], &q[1]]).len(), 0);
+    }
+
+    #[test]
+    fn moment_with_operations() {
+        let q = LineQubit::range(3);
+        let moment = Moment::new(vec![op(gate_on!(H, q[0].clone()))]).unwrap();
+
+        let extended = moment.with_operations(vec![op(gate_on!(X, q[1].clone())), op(gate_on!(X, q[2].clone()))]).unwrap();
+        assert_eq!(extended.len(), 3);
+        assert_eq!(moment.len(), 1);
+
+        assert!(moment.with_operations(vec![op(gate_on!(X, q[1].clone())), op(gate_on!(X, q[1].clone()))]).is_err());
+    }
+
+    #[test]
+    fn moment_takes_as_long_as_its_longest_operation() {
+        let q = LineQubit::range(3);
+        let moment = Moment::new(vec![
+            op(gate_on!(H, q[0].clone())),
+            op(gate_on!(CZ, q[1].clone(), q[2].clone())),
+        ]).unwrap();
+        assert_eq!(moment.duration(TimeUnit::Nanoseconds), 0.0);
+
+        let device = UnconstrainedDevice::new(DeviceDurationMap::new(Some(Duration::from_nanos(20))).with_duration::<CZGate>(Duration::from_nanos(50)));
+        assert_eq!(moment.duration_on(&device, TimeUnit::Nanoseconds), 50.0);
+
+        let moment = moment.with_operation(Box::new(wait(2500.0, &[&LineQubit::new(3)]).unwrap())).unwrap();
+        assert_eq!(moment.duration(TimeUnit::Microseconds), 2.5);
+        assert_eq!(moment.duration_on(&device, TimeUnit::Microseconds), 2.5);
+    }
+}
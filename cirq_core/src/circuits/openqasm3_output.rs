@@ -0,0 +1,293 @@
+use std::f64::consts::PI;
+
+use anyhow::Error;
+
+use crate::circuits::circuit::Circuit;
+use crate::circuits::qasm_output::{classical_registers, register_for, ClassicalRegister};
+use crate::linalg::decompositions::{kak_decomposition, kak_to_operations, zyz_decomposition};
+use crate::ops::common_gates::{
+    CNotGate, CZGate, HGate, IdentityGate, Rx, Ry, Rz, SGate, SGateDag, SwapGate, TGate, TGateDag, ZPowGate,
+};
+use crate::ops::controlled_gate::ControlledGate;
+use crate::ops::measure::MeasurementGate;
+use crate::ops::pauli_gates::{XGate, YGate, ZGate};
+use crate::ops::qubits::LineQubit;
+use crate::ops::raw_types::{Gate, Operation, QId};
+use crate::ops::reset::ResetChannel;
+use crate::ops::three_qubit_gates::{FredkinGate, ToffoliGate};
+use crate::ops::wait::WaitGate;
+
+impl Circuit {
+    /**
+     * Converts the circuit to an OpenQASM 3.0 program on a single register `q`, with qubits in the
+     * default qubit order, and a `bit` register for each measurement key. Single-qubit gates without a
+     * `stdgates.inc` equivalent become `U` gates, while larger gates get a `gate` definition from their
+     * decomposition, or from their KAK decomposition for two-qubit unitaries. Gates that can't be
+     * expressed this way produce an error.
+     * OpenQASM 3 has no statement that applies a matrix, so custom gates are written out this way
+     * rather than as a `unitary` application, which other tools couldn't parse.
+     */
+    pub fn to_openqasm3(&self) -> Result<String, Error> {
+        let qubits = self.all_qubits();
+        if let Some(q) = qubits.iter().find(|q| q.dimension() != 2) {
+            return Err(anyhow!(format!("OpenQASM 3 export only supports qubits, but {} has dimension {}.", q, q.dimension())));
+        }
+        let names: Vec<String> = (0..qubits.len()).map(|i| format!("q[{}]", i)).collect();
+
+        let registers = classical_registers(self)?;
+
+        let mut definitions = GateDefinitions::default();
+        let mut instructions = vec![];
+        for op in self.all_operations() {
+            operation_to_openqasm3(op, &qubits, &names, &registers, &mut definitions, &mut instructions)?;
+        }
+
+        let mut lines = vec!["OPENQASM 3.0;".to_string(), "include \"stdgates.inc\";".to_string(), String::new()];
+        if !definitions.lines.is_empty() {
+            lines.extend(definitions.lines);
+            lines.push(String::new());
+        }
+        lines.push(format!("qubit[{}] q;", qubits.len()));
+        lines.extend(registers.iter().map(|register| format!("bit[{}] {};", register.size, register.name)));
+        lines.push(String::new());
+        lines.extend(instructions);
+
+        Ok(lines.join("\n") + "\n")
+    }
+}
+
+/// The `gate` definitions of a program, keyed by the gate they define.
+#[derive(Default)]
+struct GateDefinitions {
+    names: Vec<(String, String)>,
+    lines: Vec<String>,
+}
+
+fn operation_to_openqasm3(
+    op: &dyn Operation,
+    qubits: &[Box<dyn QId>],
+    names: &[String],
+    registers: &[ClassicalRegister],
+    definitions: &mut GateDefinitions,
+    lines: &mut Vec<String>,
+) -> Result<(), Error> {
+    let gate = op.gate().ok_or_else(|| anyhow!("Only gate operations can be converted to OpenQASM 3."))?;
+
+    let arguments: Vec<String> = op.qubits()
+        .iter()
+        .map(|q| qubits.iter().position(|other| other.eq_qid(q.as_ref())).map(|i| names[i].clone()))
+        .collect::<Option<_>>()
+        .ok_or_else(|| anyhow!("Operation acts on a qubit outside of the circuit."))?;
+
+    if let Some(measurement) = gate.as_any().downcast_ref::<MeasurementGate>() {
+        for (i, argument) in arguments.iter().enumerate() {
+            lines.push(format!("{}[{}] = measure {};", register_for(registers, &measurement.key), i, argument));
+        }
+        return Ok(());
+    }
+
+    if let Some(wait) = gate.as_any().downcast_ref::<WaitGate>() {
+        lines.push(format!("delay[{}ns] {};", wait.duration_nanos, arguments.join(", ")));
+        return Ok(());
+    }
+
+    if let Some(instruction) = gate_to_openqasm3(gate.as_ref()) {
+        lines.push(format!("{} {};", instruction, arguments.join(", ")));
+        return Ok(());
+    }
+
+    // Single-qubit unitaries are U(θ, φ, λ) = Rz(φ) Ry(θ) Rz(λ), up to global phase.
+    if arguments.len() == 1 && gate.has_unitary() {
+        let (_, z0, y, z1) = zyz_decomposition(gate.as_ref())?;
+        lines.push(format!("U({}, {}, {}) {};", angle(y), angle(z0), angle(z1), arguments[0]));
+        return Ok(());
+    }
+
+    let name = define_gate(gate.as_ref(), arguments.len(), registers, definitions)?;
+    lines.push(format!("{} {};", name, arguments.join(", ")));
+    Ok(())
+}
+
+/// Returns the name of the `gate` definition for the gate, defining it if it is new.
+fn define_gate(
+    gate: &dyn Gate,
+    num_qubits: usize,
+    registers: &[ClassicalRegister],
+    definitions: &mut GateDefinitions,
+) -> Result<String, Error> {
+    let key = format!("{:?}", gate.equality_value());
+    if let Some((_, name)) = definitions.names.iter().find(|(other, _)| *other == key) {
+        return Ok(name.clone());
+    }
+
+    let qubits: Vec<Box<dyn QId>> = LineQubit::range(num_qubits as i64)
+        .into_iter()
+        .map(|q| Box::new(q) as Box<dyn QId>)
+        .collect();
+    let body = match gate.as_decompose().and_then(|decompose| decompose.decompose_once(qubits.clone())) {
+        Some(body) => body,
+        None if num_qubits == 2 && gate.has_unitary() => {
+            kak_to_operations(&kak_decomposition(gate)?, qubits[0].clone(), qubits[1].clone())
+        }
+        None => return Err(anyhow!(format!("Gate {} has no OpenQASM 3 equivalent and can't be decomposed.", gate))),
+    };
+
+    let parameters: Vec<String> = (0..num_qubits).map(|i| format!("a{}", i)).collect();
+    let mut body_lines = vec![];
+    for op in body {
+        operation_to_openqasm3(op.as_ref(), &qubits, &parameters, registers, definitions, &mut body_lines)?;
+    }
+
+    // Definitions used by the body were added while it was converted, so they come first.
+    let name = format!("gate_{}", definitions.names.len());
+    definitions.lines.push(format!("gate {} {} {{", name, parameters.join(", ")));
+    definitions.lines.extend(body_lines.into_iter().map(|line| format!("    {}", line)));
+    definitions.lines.push("}".to_string());
+    definitions.names.push((key, name.clone()));
+    Ok(name)
+}
+
+/// Formats an angle as a multiple of π when it is a simple fraction of it, and as a number otherwise.
+fn angle(theta: f64) -> String {
+    let ratio = theta / PI;
+    for denominator in [1, 2, 4, 8] {
+        let numerator = ratio * denominator as f64;
+        if (numerator - numerator.round()).abs() > 1e-10 {
+            continue;
+        }
+        return match (numerator.round() as i64, denominator) {
+            (0, _) => "0".to_string(),
+            (1, 1) => "pi".to_string(),
+            (-1, 1) => "-pi".to_string(),
+            (n, 1) => format!("{}*pi", n),
+            (1, d) => format!("pi/{}", d),
+            (-1, d) => format!("-pi/{}", d),
+            (n, d) => format!("{}*pi/{}", n, d),
+        };
+    }
+    theta.to_string()
+}
+
+/// Returns the `stdgates.inc` instruction for the gate, or None if there isn't one.
+fn gate_to_openqasm3(gate: &dyn Gate) -> Option<String> {
+    let any = gate.as_any();
+    let instruction = if any.is::<HGate>() {
+        "h".to_string()
+    } else if any.is::<XGate>() {
+        "x".to_string()
+    } else if any.is::<YGate>() {
+        "y".to_string()
+    } else if any.is::<ZGate>() {
+        "z".to_string()
+    } else if any.is::<SGate>() {
+        "s".to_string()
+    } else if any.is::<SGateDag>() {
+        "sdg".to_string()
+    } else if any.is::<TGate>() {
+        "t".to_string()
+    } else if any.is::<TGateDag>() {
+        "tdg".to_string()
+    } else if any.is::<CNotGate>() {
+        "cx".to_string()
+    } else if any.is::<CZGate>() {
+        "cz".to_string()
+    } else if any.is::<SwapGate>() {
+        "swap".to_string()
+    } else if any.is::<ToffoliGate>() {
+        "ccx".to_string()
+    } else if any.is::<FredkinGate>() {
+        "cswap".to_string()
+    } else if any.is::<ResetChannel>() {
+        "reset".to_string()
+    } else if let Some(gate) = any.downcast_ref::<IdentityGate>() {
+        if gate.num_qubits != 1 {
+            return None;
+        }
+        "id".to_string()
+    } else if let Some(gate) = any.downcast_ref::<Rx>() {
+        format!("rx({})", angle(gate.theta))
+    } else if let Some(gate) = any.downcast_ref::<Ry>() {
+        format!("ry({})", angle(gate.theta))
+    } else if let Some(gate) = any.downcast_ref::<Rz>() {
+        format!("rz({})", angle(gate.theta))
+    } else if let Some(gate) = any.downcast_ref::<ZPowGate>() {
+        format!("p({})", angle(PI * gate.exponent))
+    } else if let Some(gate) = any.downcast_ref::<ControlledGate>() {
+        // Controlled standard gates use the `ctrl` modifier.
+        let sub_instruction = gate_to_openqasm3(gate.sub_gate.as_ref())?;
+        if gate.num_controls == 1 {
+            format!("ctrl @ {}", sub_instruction)
+        } else {
+            format!("ctrl({}) @ {}", gate.num_controls, sub_instruction)
+        }
+    } else {
+        return None;
+    };
+
+    Some(instruction)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gate_on;
+    use crate::ops::channels::DepolarizingChannel;
+    use crate::ops::common_gates::{CNOT, H, ISWAP};
+    use crate::ops::gate_operation::GateOperation;
+    use crate::ops::matrix_gates::MatrixGate;
+    use crate::ops::measure::M;
+    use crate::ops::pauli_gates::X;
+    use crate::ops::raw_traits::HasUnitary;
+
+    use super::*;
+
+    fn op(result: Result<GateOperation, Error>) -> Box<dyn Operation> {
+        Box::new(result.unwrap())
+    }
+
+    #[test]
+    fn openqasm3_for_measured_bell_circuit() {
+        let q = LineQubit::range(3);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            op(gate_on!(H, q[0].clone())),
+            op(gate_on!(CNOT, q[0].clone(), q[1].clone())),
+            op(gate_on!(Rz::new(PI / 2.0), q[1].clone())),
+            op(gate_on!(ControlledGate::new(Box::new(ZPowGate::new(0.25)), 2), q[0].clone(), q[1].clone(), q[2].clone())),
+            op(gate_on!(WaitGate::new(100.0, 1).unwrap(), q[2].clone())),
+            op(gate_on!(ResetChannel, q[2].clone())),
+            Box::new(M("q0,q1", &[&q[0], &q[1]])),
+        ]).unwrap();
+
+        let expected = "OPENQASM 3.0;\ninclude \"stdgates.inc\";\n\nqubit[3] q;\nbit[2] m_q0_q1;\n\n\
+            h q[0];\ncx q[0], q[1];\nrz(pi/2) q[1];\nctrl(2) @ p(pi/4) q[0], q[1], q[2];\ndelay[100ns] q[2];\n\
+            m_q0_q1[0] = measure q[0];\nm_q0_q1[1] = measure q[1];\nreset q[2];\n";
+        assert_eq!(circuit.to_openqasm3().unwrap(), expected);
+
+        let mut colliding = Circuit::new();
+        colliding.append(vec![Box::new(M("a,b", &[&q[0]])), Box::new(M("a_b", &[&q[1]]))]).unwrap();
+        let program = colliding.to_openqasm3().unwrap();
+        assert!(program.contains("bit[1] m_a_b;\nbit[1] m_a_b_2;\n") && program.contains("m_a_b_2[0] = measure q[1];"));
+    }
+
+    #[test]
+    fn custom_gates_are_defined_from_their_decompositions() {
+        let q = LineQubit::range(2);
+        let root = ISWAP.pow(0.5).unwrap();
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            op(gate_on!(MatrixGate::new(X.unitary().unwrap(), vec![2]).unwrap(), q[0].clone())),
+            op(root.on(vec![Box::new(q[0].clone()), Box::new(q[1].clone())])),
+            op(root.on(vec![Box::new(q[1].clone()), Box::new(q[0].clone())])),
+        ]).unwrap();
+
+        let qasm = circuit.to_openqasm3().unwrap();
+        assert!(qasm.contains("\ngate gate_0 a0, a1 {\n"));
+        assert_eq!(qasm.matches("gate gate_").count(), 1);
+        assert!(qasm.contains("\nU(pi, "));
+        assert!(qasm.ends_with("gate_0 q[0], q[1];\ngate_0 q[1], q[0];\n"));
+
+        let mut noisy = Circuit::new();
+        noisy.append(vec![op(gate_on!(DepolarizingChannel::new(0.1).unwrap(), q[0].clone()))]).unwrap();
+        assert!(noisy.to_openqasm3().is_err());
+    }
+}
@@ -0,0 +1,688 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use anyhow::Error;
+
+use crate::circuits::circuit::Circuit;
+use crate::ops::common_gates::{Rx, Ry, Rz, ZPowGate, CNOT, CZ, H, I, S, S_DAG, SWAP, T, T_DAG};
+use crate::ops::measure::measure;
+use crate::ops::pauli_gates::{X, Y, Z};
+use crate::ops::qubits::NamedQubit;
+use crate::ops::raw_types::{Gate, Operation, QId};
+use crate::ops::reset::R;
+use crate::ops::three_qubit_gates::{FREDKIN, TOFFOLI};
+
+impl Circuit {
+    /**
+     * Parses an OpenQASM 2.0 program into a circuit.
+     * Qubit `i` of register `q` becomes `NamedQubit("q_i")`, matching Cirq's QASM parser.
+     * Custom `gate` definitions are expanded into the standard gates they are built from, and
+     * measuring into bit `i` of register `c` uses the measurement key `c_i`.
+     */
+    pub fn from_qasm(qasm: &str) -> Result<Circuit, Error> {
+        let mut parser = Parser::new(tokenize(qasm)?);
+        let operations = parser.parse_program()?;
+
+        let mut circuit = Circuit::new();
+        circuit.append(operations)?;
+        Ok(circuit)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Identifier(String),
+    Number(f64),
+    Text(String),
+    Symbol(char),
+    Arrow,
+}
+
+fn tokenize(qasm: &str) -> Result<Vec<Token>, Error> {
+    let chars: Vec<char> = qasm.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Identifier(chars[start..i].iter().collect()));
+        } else if c.is_ascii_digit() || c == '.' {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                i += 1;
+                if i < chars.len() && (chars[i] == '+' || chars[i] == '-') {
+                    i += 1;
+                }
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let number = text.parse().map_err(|_| anyhow!(format!("Invalid number {} in QASM.", text)))?;
+            tokens.push(Token::Number(number));
+        } else if c == '"' {
+            i += 1;
+            while i < chars.len() && chars[i] != '"' {
+                i += 1;
+            }
+            if i == chars.len() {
+                return Err(anyhow!("Unterminated string in QASM."));
+            }
+            tokens.push(Token::Text(chars[start + 1..i].iter().collect()));
+            i += 1;
+        } else if c == '-' && chars.get(i + 1) == Some(&'>') {
+            tokens.push(Token::Arrow);
+            i += 2;
+        } else if "()[]{},;+-*/^".contains(c) {
+            tokens.push(Token::Symbol(c));
+            i += 1;
+        } else {
+            return Err(anyhow!(format!("Unexpected character '{}' in QASM.", c)));
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// A parameter expression, kept unevaluated inside gate definitions.
+#[derive(Clone, Debug)]
+enum Expr {
+    Number(f64),
+    Parameter(String),
+    Negate(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+    Function(String, Box<Expr>),
+}
+
+impl Expr {
+    fn evaluate(&self, parameters: &HashMap<String, f64>) -> Result<f64, Error> {
+        match self {
+            Expr::Number(value) => Ok(*value),
+            Expr::Parameter(name) if name == "pi" => Ok(PI),
+            Expr::Parameter(name) => parameters
+                .get(name)
+                .copied()
+                .ok_or_else(|| anyhow!(format!("Undefined parameter {} in QASM.", name))),
+            Expr::Negate(expr) => Ok(-expr.evaluate(parameters)?),
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.evaluate(parameters)?, rhs.evaluate(parameters)?);
+                Ok(match op {
+                    '+' => lhs + rhs,
+                    '-' => lhs - rhs,
+                    '*' => lhs * rhs,
+                    '/' => lhs / rhs,
+                    _ => lhs.powf(rhs),
+                })
+            }
+            Expr::Function(name, expr) => {
+                let value = expr.evaluate(parameters)?;
+                match name.as_str() {
+                    "sin" => Ok(value.sin()),
+                    "cos" => Ok(value.cos()),
+                    "tan" => Ok(value.tan()),
+                    "exp" => Ok(value.exp()),
+                    "ln" => Ok(value.ln()),
+                    "sqrt" => Ok(value.sqrt()),
+                    _ => Err(anyhow!(format!("Unsupported QASM function {}.", name))),
+                }
+            }
+        }
+    }
+}
+
+/// A gate application inside a custom gate definition.
+#[derive(Clone, Debug)]
+struct GateCall {
+    name: String,
+    parameters: Vec<Expr>,
+    arguments: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+struct GateDefinition {
+    parameters: Vec<String>,
+    qubits: Vec<String>,
+    body: Vec<GateCall>,
+}
+
+/// A top-level gate argument: a single qubit, or a whole register the gate is broadcast over.
+enum Argument {
+    Qubit(Box<dyn QId>),
+    Register(Vec<Box<dyn QId>>),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    qregs: HashMap<String, usize>,
+    cregs: HashMap<String, usize>,
+    gates: HashMap<String, GateDefinition>,
+}
+
+impl Parser {
+    fn new(tokens: Vec<Token>) -> Self {
+        Self {
+            tokens,
+            position: 0,
+            qregs: HashMap::new(),
+            cregs: HashMap::new(),
+            gates: HashMap::new(),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<Token, Error> {
+        let token = self.peek().cloned().ok_or_else(|| anyhow!("Unexpected end of QASM."))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn is_symbol(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Symbol(c))
+    }
+
+    fn expect_symbol(&mut self, c: char) -> Result<(), Error> {
+        match self.next()? {
+            Token::Symbol(s) if s == c => Ok(()),
+            token => Err(anyhow!(format!("Expected '{}' in QASM but got {:?}.", c, token))),
+        }
+    }
+
+    fn expect_identifier(&mut self) -> Result<String, Error> {
+        match self.next()? {
+            Token::Identifier(name) => Ok(name),
+            token => Err(anyhow!(format!("Expected an identifier in QASM but got {:?}.", token))),
+        }
+    }
+
+    fn expect_size(&mut self) -> Result<usize, Error> {
+        match self.next()? {
+            Token::Number(n) if n >= 0.0 && n.fract() == 0.0 => Ok(n as usize),
+            token => Err(anyhow!(format!("Expected a non-negative integer in QASM but got {:?}.", token))),
+        }
+    }
+
+    fn parse_program(&mut self) -> Result<Vec<Box<dyn Operation>>, Error> {
+        if self.expect_identifier()? != "OPENQASM" {
+            return Err(anyhow!("Missing OPENQASM header."));
+        }
+        match self.next()? {
+            Token::Number(2.0) => {}
+            token => return Err(anyhow!(format!("Unsupported OPENQASM version {:?}.", token))),
+        }
+        self.expect_symbol(';')?;
+
+        let mut operations = vec![];
+        while self.peek().is_some() {
+            let keyword = self.expect_identifier()?;
+            match keyword.as_str() {
+                "include" => {
+                    self.next()?;
+                    self.expect_symbol(';')?;
+                }
+                "qreg" | "creg" => {
+                    let name = self.expect_identifier()?;
+                    self.expect_symbol('[')?;
+                    let size = self.expect_size()?;
+                    self.expect_symbol(']')?;
+                    self.expect_symbol(';')?;
+
+                    let registers = if keyword == "qreg" { &mut self.qregs } else { &mut self.cregs };
+                    if registers.insert(name.clone(), size).is_some() {
+                        return Err(anyhow!(format!("Register {} is already defined.", name)));
+                    }
+                }
+                "gate" => self.parse_gate_definition()?,
+                "barrier" => {
+                    while !self.is_symbol(';') {
+                        self.next()?;
+                    }
+                    self.next()?;
+                }
+                "measure" => self.parse_measurement(&mut operations)?,
+                "opaque" | "if" => {
+                    return Err(anyhow!(format!("Unsupported QASM instruction {}.", keyword)));
+                }
+                _ => {
+                    let parameters = self.parse_parameters()?
+                        .iter()
+                        .map(|expr| expr.evaluate(&HashMap::new()))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let arguments = self.parse_arguments()?;
+                    self.broadcast(&keyword, &parameters, arguments, &mut operations)?;
+                }
+            }
+        }
+
+        Ok(operations)
+    }
+
+    fn parse_gate_definition(&mut self) -> Result<(), Error> {
+        let name = self.expect_identifier()?;
+        if self.gates.contains_key(&name) || BUILTIN_GATES.contains(&name.as_str()) {
+            return Err(anyhow!(format!("Gate {} is already defined.", name)));
+        }
+        let mut parameters = vec![];
+        if self.is_symbol('(') {
+            self.next()?;
+            while !self.is_symbol(')') {
+                parameters.push(self.expect_identifier()?);
+                if !self.is_symbol(')') {
+                    self.expect_symbol(',')?;
+                }
+            }
+            self.next()?;
+        }
+
+        let mut qubits = vec![self.expect_identifier()?];
+        while self.is_symbol(',') {
+            self.next()?;
+            qubits.push(self.expect_identifier()?);
+        }
+
+        self.expect_symbol('{')?;
+        let mut body = vec![];
+        while !self.is_symbol('}') {
+            let gate = self.expect_identifier()?;
+            if gate == "barrier" {
+                while !self.is_symbol(';') {
+                    self.next()?;
+                }
+                self.next()?;
+                continue;
+            }
+
+            let call_parameters = self.parse_parameters()?;
+            let mut arguments = vec![self.expect_identifier()?];
+            while self.is_symbol(',') {
+                self.next()?;
+                arguments.push(self.expect_identifier()?);
+            }
+            self.expect_symbol(';')?;
+
+            // Only gates defined earlier can be called, which also rules out recursive definitions.
+            if !self.gates.contains_key(&gate) && !BUILTIN_GATES.contains(&gate.as_str()) {
+                return Err(anyhow!(format!("Undefined gate {} in definition of gate {}.", gate, name)));
+            }
+            if let Some(argument) = arguments.iter().find(|argument| !qubits.contains(argument)) {
+                return Err(anyhow!(format!("Undefined qubit {} in definition of gate {}.", argument, name)));
+            }
+            body.push(GateCall {
+                name: gate,
+                parameters: call_parameters,
+                arguments,
+            });
+        }
+        self.next()?;
+
+        self.gates.insert(name, GateDefinition {
+            parameters,
+            qubits,
+            body,
+        });
+        Ok(())
+    }
+
+    fn parse_parameters(&mut self) -> Result<Vec<Expr>, Error> {
+        let mut parameters = vec![];
+        if self.is_symbol('(') {
+            self.next()?;
+            while !self.is_symbol(')') {
+                parameters.push(self.parse_expression()?);
+                if !self.is_symbol(')') {
+                    self.expect_symbol(',')?;
+                }
+            }
+            self.next()?;
+        }
+        Ok(parameters)
+    }
+
+    fn parse_arguments(&mut self) -> Result<Vec<Argument>, Error> {
+        let mut arguments = vec![self.parse_argument()?];
+        while self.is_symbol(',') {
+            self.next()?;
+            arguments.push(self.parse_argument()?);
+        }
+        self.expect_symbol(';')?;
+        Ok(arguments)
+    }
+
+    fn parse_argument(&mut self) -> Result<Argument, Error> {
+        let (register, index, size) = self.parse_register_reference(true)?;
+        Ok(match index {
+            Some(index) => Argument::Qubit(register_qubit(&register, index)),
+            None => Argument::Register((0..size).map(|i| register_qubit(&register, i)).collect()),
+        })
+    }
+
+    /// Parses `register` or `register[index]`, returning the register name, the index and the register size.
+    fn parse_register_reference(&mut self, quantum: bool) -> Result<(String, Option<usize>, usize), Error> {
+        let register = self.expect_identifier()?;
+        let registers = if quantum { &self.qregs } else { &self.cregs };
+        let size = *registers.get(&register).ok_or_else(|| {
+            let kind = if quantum { "quantum" } else { "classical" };
+            anyhow!(format!("Undefined {} register {}.", kind, register))
+        })?;
+
+        if !self.is_symbol('[') {
+            return Ok((register, None, size));
+        }
+        self.next()?;
+        let index = self.expect_size()?;
+        self.expect_symbol(']')?;
+        if index >= size {
+            return Err(anyhow!(format!("Index {} is out of range for register {} of size {}.", index, register, size)));
+        }
+        Ok((register, Some(index), size))
+    }
+
+    /// Parses `qubits -> bits;`, measuring each qubit under the key `creg_index` as Cirq does.
+    fn parse_measurement(&mut self, operations: &mut Vec<Box<dyn Operation>>) -> Result<(), Error> {
+        let (qreg, qubit_index, qreg_size) = self.parse_register_reference(true)?;
+        match self.next()? {
+            Token::Arrow => {}
+            token => return Err(anyhow!(format!("Expected '->' in QASM measurement but got {:?}.", token))),
+        }
+        let (creg, bit_index, creg_size) = self.parse_register_reference(false)?;
+        self.expect_symbol(';')?;
+
+        let pairs = match (qubit_index, bit_index) {
+            (Some(q), Some(b)) => vec![(q, b)],
+            (None, None) if qreg_size == creg_size => (0..qreg_size).map(|i| (i, i)).collect(),
+            _ => return Err(anyhow!(format!("Can't measure {} into {}, since their sizes differ.", qreg, creg))),
+        };
+        for (q, b) in pairs {
+            let qubit = register_qubit(&qreg, q);
+            operations.push(Box::new(measure(&format!("{}_{}", creg, b), &[qubit.as_ref()])));
+        }
+        Ok(())
+    }
+
+    fn parse_expression(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.parse_term()?;
+        while self.is_symbol('+') || self.is_symbol('-') {
+            let op = if self.is_symbol('+') { '+' } else { '-' };
+            self.next()?;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.parse_term()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_term(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.parse_power()?;
+        while self.is_symbol('*') || self.is_symbol('/') {
+            let op = if self.is_symbol('*') { '*' } else { '/' };
+            self.next()?;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.parse_power()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_power(&mut self) -> Result<Expr, Error> {
+        let base = self.parse_unary()?;
+        if self.is_symbol('^') {
+            self.next()?;
+            return Ok(Expr::Binary('^', Box::new(base), Box::new(self.parse_power()?)));
+        }
+        Ok(base)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, Error> {
+        if self.is_symbol('-') {
+            self.next()?;
+            return Ok(Expr::Negate(Box::new(self.parse_unary()?)));
+        }
+
+        match self.next()? {
+            Token::Number(value) => Ok(Expr::Number(value)),
+            Token::Identifier(name) if self.is_symbol('(') => {
+                self.next()?;
+                let argument = self.parse_expression()?;
+                self.expect_symbol(')')?;
+                Ok(Expr::Function(name, Box::new(argument)))
+            }
+            Token::Identifier(name) => Ok(Expr::Parameter(name)),
+            Token::Symbol('(') => {
+                let expr = self.parse_expression()?;
+                self.expect_symbol(')')?;
+                Ok(expr)
+            }
+            token => Err(anyhow!(format!("Unexpected {:?} in QASM expression.", token))),
+        }
+    }
+
+    /// Applies the gate once, or once per index when some arguments are whole registers.
+    fn broadcast(
+        &self,
+        name: &str,
+        parameters: &[f64],
+        arguments: Vec<Argument>,
+        operations: &mut Vec<Box<dyn Operation>>,
+    ) -> Result<(), Error> {
+        let sizes: Vec<usize> = arguments
+            .iter()
+            .filter_map(|argument| match argument {
+                Argument::Register(qubits) => Some(qubits.len()),
+                Argument::Qubit(_) => None,
+            })
+            .collect();
+        if sizes.windows(2).any(|pair| pair[0] != pair[1]) {
+            return Err(anyhow!(format!("Registers of different sizes passed to gate {}.", name)));
+        }
+
+        for i in 0..sizes.first().copied().unwrap_or(1) {
+            let qubits = arguments
+                .iter()
+                .map(|argument| match argument {
+                    Argument::Qubit(qubit) => qubit.clone(),
+                    Argument::Register(qubits) => qubits[i].clone(),
+                })
+                .collect();
+            self.apply(name, parameters, qubits, operations)?;
+        }
+        Ok(())
+    }
+
+    fn apply(
+        &self,
+        name: &str,
+        parameters: &[f64],
+        qubits: Vec<Box<dyn QId>>,
+        operations: &mut Vec<Box<dyn Operation>>,
+    ) -> Result<(), Error> {
+        for (i, q) in qubits.iter().enumerate() {
+            if qubits[..i].iter().any(|other| other.eq_qid(q.as_ref())) {
+                return Err(anyhow!(format!("Qubit {} is passed to gate {} more than once.", q, name)));
+            }
+        }
+
+        if let Some(definition) = self.gates.get(name) {
+            check_parameter_count(name, parameters, definition.parameters.len())?;
+            if qubits.len() != definition.qubits.len() {
+                return Err(anyhow!(format!("Gate {} expects {} qubits but got {}.", name, definition.qubits.len(), qubits.len())));
+            }
+
+            let scope: HashMap<String, f64> = definition.parameters.iter().cloned().zip(parameters.iter().copied()).collect();
+            for call in &definition.body {
+                let call_parameters = call.parameters
+                    .iter()
+                    .map(|expr| expr.evaluate(&scope))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let call_qubits = call.arguments
+                    .iter()
+                    .map(|argument| {
+                        let position = definition.qubits.iter().position(|q| q == argument).unwrap_or_default();
+                        qubits[position].clone()
+                    })
+                    .collect();
+                self.apply(&call.name, &call_parameters, call_qubits, operations)?;
+            }
+            return Ok(());
+        }
+
+        let gate: Box<dyn Gate> = match name {
+            "rx" | "ry" | "rz" | "u1" => {
+                check_parameter_count(name, parameters, 1)?;
+                match name {
+                    "rx" => Box::new(Rx::new(parameters[0])),
+                    "ry" => Box::new(Ry::new(parameters[0])),
+                    "rz" => Box::new(Rz::new(parameters[0])),
+                    _ => Box::new(ZPowGate::new(parameters[0] / PI)),
+                }
+            }
+            _ => {
+                check_parameter_count(name, parameters, 0)?;
+                match name {
+                    "id" => Box::new(I),
+                    "h" => Box::new(H),
+                    "x" => Box::new(X),
+                    "y" => Box::new(Y),
+                    "z" => Box::new(Z),
+                    "s" => Box::new(S),
+                    "sdg" => Box::new(S_DAG),
+                    "t" => Box::new(T),
+                    "tdg" => Box::new(T_DAG),
+                    "cx" | "CX" => Box::new(CNOT),
+                    "cz" => Box::new(CZ),
+                    "swap" => Box::new(SWAP),
+                    "ccx" => Box::new(TOFFOLI),
+                    "cswap" => Box::new(FREDKIN),
+                    "reset" => Box::new(R),
+                    _ => return Err(anyhow!(format!("Unsupported QASM gate {}.", name))),
+                }
+            }
+        };
+
+        operations.push(Box::new(gate.on(qubits)?));
+        Ok(())
+    }
+}
+
+/// The gates that `Parser::apply` maps to gates of this crate.
+const BUILTIN_GATES: [&str; 20] = [
+    "rx", "ry", "rz", "u1", "id", "h", "x", "y", "z", "s", "sdg", "t", "tdg", "cx", "CX", "cz", "swap", "ccx", "cswap", "reset",
+];
+
+fn check_parameter_count(name: &str, parameters: &[f64], expected: usize) -> Result<(), Error> {
+    if parameters.len() != expected {
+        return Err(anyhow!(format!("Gate {} expects {} parameters but got {}.", name, expected, parameters.len())));
+    }
+    Ok(())
+}
+
+fn register_qubit(register: &str, index: usize) -> Box<dyn QId> {
+    Box::new(NamedQubit::new(&format!("{}_{}", register, index)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gate_on;
+    use crate::ops::raw_traits::gates_equal;
+
+    use super::*;
+
+    #[test]
+    fn parses_standard_gates() {
+        let circuit = Circuit::from_qasm(r#"
+            OPENQASM 2.0;
+            include "qelib1.inc";
+            // A Bell pair, then a rotation.
+            qreg q[2];
+            creg c[2];
+            h q[0];
+            cx q[0],q[1];
+            rz(pi/2) q[1];
+        "#).unwrap();
+
+        let (q0, q1) = (NamedQubit::new("q_0"), NamedQubit::new("q_1"));
+        let expected = [
+            gate_on!(H, q0.clone()).unwrap(),
+            gate_on!(CNOT, q0, q1.clone()).unwrap(),
+            gate_on!(Rz::new(PI / 2.0), q1).unwrap(),
+        ];
+        assert_eq!(circuit.num_moments(), 3);
+        assert_eq!(circuit.all_operations().count(), 3);
+        for (op, expected) in circuit.all_operations().zip(expected.iter()) {
+            assert!(gates_equal(op, expected));
+        }
+    }
+
+    #[test]
+    fn broadcasts_over_registers_and_expands_gate_definitions() {
+        let circuit = Circuit::from_qasm("
+            OPENQASM 2.0;
+            qreg a[2];
+            qreg b[2];
+            gate bell x, y { h x; cx x, y; }
+            gate twice(theta) x { rx(theta) x; rx(-theta * 1) x; }
+            bell a, b;
+            twice(0.3) a[0];
+        ").unwrap();
+
+        // Two Bell pairs and two rotations.
+        assert_eq!(circuit.all_operations().count(), 6);
+        assert!(circuit.unitary().is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_programs() {
+        assert!(Circuit::from_qasm("qreg q[1];").is_err());
+        assert!(Circuit::from_qasm("OPENQASM 2.0; qreg q[1]; u3(0, 0, 0) q[0];").is_err());
+        assert!(Circuit::from_qasm("OPENQASM 2.0; qreg q[1]; h q[1];").is_err());
+        assert!(Circuit::from_qasm("OPENQASM 2.0; qreg q[2]; cx q[0], q[0];").is_err());
+        assert!(Circuit::from_qasm("OPENQASM 2.0; qreg q[2]; creg c[1]; measure q -> c;").is_err());
+        assert!(Circuit::from_qasm("OPENQASM 2.0; qreg q[1]; measure q[0] -> c[0];").is_err());
+        assert!(Circuit::from_qasm("OPENQASM 2.0; qreg q[1]; rx q[0];").is_err());
+    }
+
+    #[test]
+    fn rejects_recursive_gate_definitions() {
+        assert!(Circuit::from_qasm("OPENQASM 2.0; gate g a { g a; } qreg q[1]; g q[0];").is_err());
+        assert!(Circuit::from_qasm("OPENQASM 2.0; gate f a { g a; } gate g a { f a; } qreg q[1]; g q[0];").is_err());
+        assert!(Circuit::from_qasm("OPENQASM 2.0; gate f a { h a; } gate h a { f a; } qreg q[1]; h q[0];").is_err());
+        assert!(Circuit::from_qasm("OPENQASM 2.0; gate f a { x a; } gate f a { f a; } qreg q[1]; f q[0];").is_err());
+        assert!(Circuit::from_qasm("OPENQASM 2.0; gate f a { h a; } gate g a { f a; x a; } qreg q[1]; g q[0];").is_ok());
+    }
+
+    #[test]
+    fn parses_measurements() {
+        let circuit = Circuit::from_qasm("
+            OPENQASM 2.0;
+            qreg q[2];
+            creg c[2];
+            measure q[1] -> c[0];
+            measure q -> c;
+        ").unwrap();
+
+        let ops: Vec<&dyn Operation> = circuit.all_operations().collect();
+        assert_eq!(ops.len(), 3);
+        assert!(gates_equal(ops[0], &measure("c_0", &[&NamedQubit::new("q_1")])));
+        assert!(gates_equal(ops[2], &measure("c_1", &[&NamedQubit::new("q_1")])));
+    }
+
+    #[test]
+    fn parses_resets() {
+        let circuit = Circuit::from_qasm("OPENQASM 2.0; qreg q[2]; reset q;").unwrap();
+        let ops: Vec<&dyn Operation> = circuit.all_operations().collect();
+        assert_eq!(ops.len(), 2);
+        assert_eq!(circuit.to_qasm().unwrap().lines().last(), Some("reset q[1];"));
+    }
+}
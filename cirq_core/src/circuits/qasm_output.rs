@@ -0,0 +1,242 @@
+use std::f64::consts::PI;
+
+use anyhow::Error;
+
+use crate::circuits::circuit::Circuit;
+use crate::ops::common_gates::{
+    CNotGate, CZGate, HGate, IdentityGate, Rx, Ry, Rz, SGate, SGateDag, SwapGate, TGate, TGateDag, ZPowGate,
+};
+use crate::ops::measure::MeasurementGate;
+use crate::ops::pauli_gates::{XGate, YGate, ZGate};
+use crate::ops::raw_types::{Gate, Operation, QId};
+use crate::ops::reset::ResetChannel;
+use crate::ops::three_qubit_gates::{FredkinGate, ToffoliGate};
+
+impl Circuit {
+    /**
+     * Converts the circuit to an OpenQASM 2.0 program on a single register `q`, with qubits
+     * in the default qubit order, and a classical register for each measurement key. Gates
+     * without a `qelib1.inc` equivalent are decomposed, and gates that can't be decomposed
+     * either produce an error.
+     */
+    pub fn to_qasm(&self) -> Result<String, Error> {
+        let qubits = self.all_qubits();
+        if let Some(q) = qubits.iter().find(|q| q.dimension() != 2) {
+            return Err(anyhow!(format!("QASM only supports qubits, but {} has dimension {}.", q, q.dimension())));
+        }
+
+        let mut lines = vec![
+            "OPENQASM 2.0;".to_string(),
+            "include \"qelib1.inc\";".to_string(),
+            String::new(),
+            format!("qreg q[{}];", qubits.len()),
+        ];
+
+        // Every measurement key gets its own classical register.
+        let registers = classical_registers(self)?;
+        for register in &registers {
+            lines.push(format!("creg {}[{}];", register.name, register.size));
+        }
+        lines.push(String::new());
+
+        for op in self.all_operations() {
+            operation_to_qasm(op, &qubits, &registers, &mut lines)?;
+        }
+
+        Ok(lines.join("\n") + "\n")
+    }
+}
+
+fn operation_to_qasm(
+    op: &dyn Operation,
+    qubits: &[Box<dyn QId>],
+    registers: &[ClassicalRegister],
+    lines: &mut Vec<String>,
+) -> Result<(), Error> {
+    let gate = op.gate().ok_or_else(|| anyhow!("Only gate operations can be converted to QASM."))?;
+
+    let arguments: Vec<String> = op.qubits()
+        .iter()
+        .map(|q| qubits.iter().position(|other| other.eq_qid(q.as_ref())).map(|i| format!("q[{}]", i)))
+        .collect::<Option<_>>()
+        .ok_or_else(|| anyhow!("Operation acts on a qubit outside of the circuit."))?;
+
+    if let Some(measurement) = gate.as_any().downcast_ref::<MeasurementGate>() {
+        for (i, argument) in arguments.iter().enumerate() {
+            lines.push(format!("measure {} -> {}[{}];", argument, register_for(registers, &measurement.key), i));
+        }
+        return Ok(());
+    }
+
+    if let Some(instruction) = gate_to_qasm(gate.as_ref()) {
+        lines.push(format!("{} {};", instruction, arguments.join(",")));
+        return Ok(());
+    }
+
+    let decomposition = gate
+        .as_decompose()
+        .and_then(|decompose| decompose.decompose_once(op.qubits()))
+        .ok_or_else(|| anyhow!(format!("Gate {} has no QASM equivalent and can't be decomposed.", gate)))?;
+    for sub_operation in decomposition {
+        operation_to_qasm(sub_operation.as_ref(), qubits, registers, lines)?;
+    }
+    Ok(())
+}
+
+/// The classical register that holds the results of a measurement, in an exported program.
+pub(crate) struct ClassicalRegister {
+    pub key: String,
+    pub name: String,
+    pub size: usize,
+}
+
+/**
+ * Returns a register for each measurement key of the circuit, in the order the keys are first measured.
+ * Register names are the keys with invalid characters replaced, and a numeric suffix when two keys
+ * would otherwise share a name. Fails if a key is measured more than once.
+ */
+pub(crate) fn classical_registers(circuit: &Circuit) -> Result<Vec<ClassicalRegister>, Error> {
+    let mut registers: Vec<ClassicalRegister> = vec![];
+    for op in circuit.all_operations() {
+        if let Some(measurement) = op.gate().and_then(|gate| gate.as_any().downcast_ref::<MeasurementGate>().cloned()) {
+            if registers.iter().any(|register| register.key == measurement.key) {
+                return Err(anyhow!(format!("Measurement key {} is used more than once.", measurement.key)));
+            }
+
+            let sanitized: String = measurement.key.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+            let base = format!("m_{}", sanitized);
+            let mut name = base.clone();
+            let mut suffix = 2;
+            while registers.iter().any(|register| register.name == name) {
+                name = format!("{}_{}", base, suffix);
+                suffix += 1;
+            }
+            registers.push(ClassicalRegister {
+                key: measurement.key,
+                name,
+                size: measurement.qubits,
+            });
+        }
+    }
+    Ok(registers)
+}
+
+/// Returns the name of the register for the measurement key, which `classical_registers` has assigned.
+pub(crate) fn register_for<'a>(registers: &'a [ClassicalRegister], key: &str) -> &'a str {
+    registers.iter().find(|register| register.key == key).map(|register| register.name.as_str()).unwrap_or_default()
+}
+
+/// Returns the `qelib1.inc` instruction for the gate, or None if there isn't one.
+fn gate_to_qasm(gate: &dyn Gate) -> Option<String> {
+    let any = gate.as_any();
+    let instruction = if any.is::<HGate>() {
+        "h".to_string()
+    } else if any.is::<XGate>() {
+        "x".to_string()
+    } else if any.is::<YGate>() {
+        "y".to_string()
+    } else if any.is::<ZGate>() {
+        "z".to_string()
+    } else if any.is::<SGate>() {
+        "s".to_string()
+    } else if any.is::<SGateDag>() {
+        "sdg".to_string()
+    } else if any.is::<TGate>() {
+        "t".to_string()
+    } else if any.is::<TGateDag>() {
+        "tdg".to_string()
+    } else if any.is::<CNotGate>() {
+        "cx".to_string()
+    } else if any.is::<CZGate>() {
+        "cz".to_string()
+    } else if any.is::<SwapGate>() {
+        "swap".to_string()
+    } else if any.is::<ToffoliGate>() {
+        "ccx".to_string()
+    } else if any.is::<FredkinGate>() {
+        "cswap".to_string()
+    } else if any.is::<ResetChannel>() {
+        "reset".to_string()
+    } else if let Some(gate) = any.downcast_ref::<IdentityGate>() {
+        if gate.num_qubits != 1 {
+            return None;
+        }
+        "id".to_string()
+    } else if let Some(gate) = any.downcast_ref::<Rx>() {
+        format!("rx({})", gate.theta)
+    } else if let Some(gate) = any.downcast_ref::<Ry>() {
+        format!("ry({})", gate.theta)
+    } else if let Some(gate) = any.downcast_ref::<Rz>() {
+        format!("rz({})", gate.theta)
+    } else if let Some(gate) = any.downcast_ref::<ZPowGate>() {
+        format!("u1({})", PI * gate.exponent)
+    } else {
+        return None;
+    };
+
+    Some(instruction)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gate_on;
+    use crate::ops::common_gates::{CNOT, H, ISWAP};
+    use crate::ops::measure::M;
+    use crate::ops::gate_operation::GateOperation;
+    use crate::ops::qubits::LineQubit;
+
+    use super::*;
+
+    fn op(result: Result<GateOperation, Error>) -> Box<dyn Operation> {
+        Box::new(result.unwrap())
+    }
+
+    #[test]
+    fn qasm_for_bell_circuit() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            op(gate_on!(H, q[0].clone())),
+            op(gate_on!(CNOT, q[0].clone(), q[1].clone())),
+            op(gate_on!(Rz::new(0.5), q[1].clone())),
+        ]).unwrap();
+
+        let expected = "OPENQASM 2.0;\ninclude \"qelib1.inc\";\n\nqreg q[2];\n\nh q[0];\ncx q[0],q[1];\nrz(0.5) q[1];\n";
+        assert_eq!(circuit.to_qasm().unwrap(), expected);
+    }
+
+    #[test]
+    fn gates_without_qasm_equivalent_are_rejected() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![op(gate_on!(ISWAP, q[0].clone(), q[1].clone()))]).unwrap();
+        assert!(circuit.to_qasm().is_err());
+    }
+
+    #[test]
+    fn measurements_write_to_classical_registers() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![op(gate_on!(H, q[0].clone())), Box::new(M("q0,q1", &[&q[0], &q[1]]))]).unwrap();
+
+        let expected = "OPENQASM 2.0;\ninclude \"qelib1.inc\";\n\nqreg q[2];\ncreg m_q0_q1[2];\n\n\
+            h q[0];\nmeasure q[0] -> m_q0_q1[0];\nmeasure q[1] -> m_q0_q1[1];\n";
+        assert_eq!(circuit.to_qasm().unwrap(), expected);
+    }
+
+    #[test]
+    fn keys_with_the_same_sanitized_name_get_distinct_registers() {
+        let q = LineQubit::range(3);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            Box::new(M("a,b", &[&q[0]])),
+            Box::new(M("a_b", &[&q[1]])),
+            Box::new(M("a b", &[&q[2]])),
+        ]).unwrap();
+
+        let names: Vec<String> = classical_registers(&circuit).unwrap().into_iter().map(|register| register.name).collect();
+        assert_eq!(names, ["m_a_b", "m_a_b_2", "m_a_b_3"]);
+        let qasm = circuit.to_qasm().unwrap();
+        assert!(qasm.contains("creg m_a_b_2[1];\n") && qasm.contains("measure q[1] -> m_a_b_2[0];\n"));
+    }
+}
@@ -0,0 +1,215 @@
+use std::f64::consts::PI;
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::circuits::circuit::Circuit;
+use crate::circuits::qasm_output::{classical_registers, register_for};
+use crate::ops::common_gates::{
+    CNotGate, CZGate, HGate, ISwapGate, IdentityGate, Rx, Ry, Rz, SGate, SGateDag, SwapGate, TGate, TGateDag, ZPowGate,
+};
+use crate::ops::controlled_gate::ControlledGate;
+use crate::ops::measure::MeasurementGate;
+use crate::ops::pauli_gates::{XGate, YGate, ZGate};
+use crate::ops::raw_types::{Gate, Operation};
+use crate::ops::reset::ResetChannel;
+use crate::ops::three_qubit_gates::{FredkinGate, ToffoliGate};
+
+impl Circuit {
+    /**
+     * Converts the circuit to a Quil program, with qubits numbered in the default qubit order and a
+     * `BIT` register declared for each measurement key. Unitary gates without a Quil equivalent are
+     * defined with `DEFGATE` from their matrix, and only non-unitary operations produce an error.
+     */
+    pub fn to_quil(&self) -> Result<String, Error> {
+        let qubits = self.all_qubits();
+        if let Some(q) = qubits.iter().find(|q| q.dimension() != 2) {
+            return Err(anyhow!(format!("Quil only supports qubits, but {} has dimension {}.", q, q.dimension())));
+        }
+
+        let registers = classical_registers(self)?;
+        let declarations: Vec<String> = registers
+            .iter()
+            .map(|register| format!("DECLARE {} BIT[{}]", register.name, register.size))
+            .collect();
+        let mut definitions: Vec<(String, Vec<String>)> = vec![];
+        let mut instructions: Vec<String> = vec![];
+
+        for op in self.all_operations() {
+            let gate = op.gate().ok_or_else(|| anyhow!("Only gate operations can be converted to Quil."))?;
+            let arguments: Vec<String> = op.qubits()
+                .iter()
+                .map(|q| qubits.iter().position(|other| other.eq_qid(q.as_ref())).map(|i| i.to_string()))
+                .collect::<Option<_>>()
+                .ok_or_else(|| anyhow!("Operation acts on a qubit outside of the circuit."))?;
+
+            if let Some(measurement) = gate.as_any().downcast_ref::<MeasurementGate>() {
+                let register = register_for(&registers, &measurement.key);
+                for (i, argument) in arguments.iter().enumerate() {
+                    instructions.push(format!("MEASURE {} {}[{}]", argument, register, i));
+                }
+                continue;
+            }
+
+            let instruction = match gate_to_quil(gate.as_ref()) {
+                Some(instruction) => instruction,
+                None => define_gate(op, &mut definitions)?,
+            };
+            instructions.push(format!("{} {}", instruction, arguments.join(" ")));
+        }
+
+        let mut lines = declarations;
+        for (name, rows) in definitions {
+            lines.push(format!("DEFGATE {}:", name));
+            lines.extend(rows.into_iter().map(|row| format!("    {}", row)));
+        }
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.extend(instructions);
+        Ok(lines.join("\n") + "\n")
+    }
+}
+
+/// Returns the name of the `DEFGATE` for the operation's unitary, defining it if it is new.
+fn define_gate(op: &dyn Operation, definitions: &mut Vec<(String, Vec<String>)>) -> Result<String, Error> {
+    let gate = op.gate().unwrap();
+    let unitary = op.unitary().ok_or_else(|| anyhow!(format!("Gate {} has no matrix, so it can't be converted to Quil.", gate)))?;
+    let rows: Vec<String> = unitary
+        .iter()
+        .map(|row| row.iter().map(complex_to_quil).collect::<Vec<_>>().join(", "))
+        .collect();
+
+    if let Some((name, _)) = definitions.iter().find(|(_, other)| *other == rows) {
+        return Ok(name.clone());
+    }
+    let name = format!("GATE_{}", definitions.len());
+    definitions.push((name.clone(), rows));
+    Ok(name)
+}
+
+fn complex_to_quil(value: &Complex<f64>) -> String {
+    if value.im == 0.0 {
+        format!("{}", value.re)
+    } else if value.re == 0.0 {
+        format!("{}i", value.im)
+    } else if value.im < 0.0 {
+        format!("{}-{}i", value.re, -value.im)
+    } else {
+        format!("{}+{}i", value.re, value.im)
+    }
+}
+
+/// Returns the Quil instruction for the gate, or None if there isn't one.
+fn gate_to_quil(gate: &dyn Gate) -> Option<String> {
+    let any = gate.as_any();
+    let instruction = if any.is::<HGate>() {
+        "H".to_string()
+    } else if any.is::<XGate>() {
+        "X".to_string()
+    } else if any.is::<YGate>() {
+        "Y".to_string()
+    } else if any.is::<ZGate>() {
+        "Z".to_string()
+    } else if any.is::<SGate>() {
+        "S".to_string()
+    } else if any.is::<SGateDag>() {
+        "DAGGER S".to_string()
+    } else if any.is::<TGate>() {
+        "T".to_string()
+    } else if any.is::<TGateDag>() {
+        "DAGGER T".to_string()
+    } else if any.is::<CNotGate>() {
+        "CNOT".to_string()
+    } else if any.is::<CZGate>() {
+        "CZ".to_string()
+    } else if any.is::<SwapGate>() {
+        "SWAP".to_string()
+    } else if any.is::<ISwapGate>() {
+        "ISWAP".to_string()
+    } else if any.is::<ToffoliGate>() {
+        "CCNOT".to_string()
+    } else if any.is::<FredkinGate>() {
+        "CSWAP".to_string()
+    } else if any.is::<ResetChannel>() {
+        "RESET".to_string()
+    } else if let Some(gate) = any.downcast_ref::<IdentityGate>() {
+        if gate.num_qubits != 1 {
+            return None;
+        }
+        "I".to_string()
+    } else if let Some(gate) = any.downcast_ref::<Rx>() {
+        format!("RX({})", gate.theta)
+    } else if let Some(gate) = any.downcast_ref::<Ry>() {
+        format!("RY({})", gate.theta)
+    } else if let Some(gate) = any.downcast_ref::<Rz>() {
+        format!("RZ({})", gate.theta)
+    } else if let Some(gate) = any.downcast_ref::<ZPowGate>() {
+        format!("PHASE({})", PI * gate.exponent)
+    } else if let Some(gate) = any.downcast_ref::<ControlledGate>() {
+        let phase = gate.sub_gate.as_any().downcast_ref::<ZPowGate>().filter(|_| gate.num_controls == 1)?;
+        format!("CPHASE({})", PI * phase.exponent)
+    } else {
+        return None;
+    };
+
+    Some(instruction)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gate_on;
+    use crate::ops::channels::DepolarizingChannel;
+    use crate::ops::common_gates::{CNOT, H, ISWAP};
+    use crate::ops::gate_operation::GateOperation;
+    use crate::ops::measure::M;
+    use crate::ops::qubits::LineQubit;
+
+    use super::*;
+
+    fn op(result: Result<GateOperation, Error>) -> Box<dyn Operation> {
+        Box::new(result.unwrap())
+    }
+
+    #[test]
+    fn quil_for_measured_bell_circuit() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            op(gate_on!(H, q[0].clone())),
+            op(gate_on!(CNOT, q[0].clone(), q[1].clone())),
+            op(gate_on!(ControlledGate::new(Box::new(ZPowGate::new(0.5)), 1), q[0].clone(), q[1].clone())),
+            Box::new(M("m", &[&q[0], &q[1]])),
+        ]).unwrap();
+
+        let expected = format!(
+            "DECLARE m_m BIT[2]\n\nH 0\nCNOT 0 1\nCPHASE({}) 0 1\nMEASURE 0 m_m[0]\nMEASURE 1 m_m[1]\n",
+            PI / 2.0,
+        );
+        assert_eq!(circuit.to_quil().unwrap(), expected);
+
+        let mut colliding = Circuit::new();
+        colliding.append(vec![Box::new(M("a,b", &[&q[0]])), Box::new(M("a_b", &[&q[1]]))]).unwrap();
+        assert_eq!(colliding.to_quil().unwrap(), "DECLARE m_a_b BIT[1]\nDECLARE m_a_b_2 BIT[1]\n\nMEASURE 0 m_a_b[0]\nMEASURE 1 m_a_b_2[0]\n");
+    }
+
+    #[test]
+    fn unknown_gates_are_defined_from_their_matrix() {
+        let q = LineQubit::range(2);
+        let root = ISWAP.pow(0.5).unwrap();
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            op(root.on(vec![Box::new(q[0].clone()), Box::new(q[1].clone())])),
+            op(root.on(vec![Box::new(q[1].clone()), Box::new(q[0].clone())])),
+        ]).unwrap();
+
+        let quil = circuit.to_quil().unwrap();
+        assert!(quil.starts_with("DEFGATE GATE_0:\n    1, 0, 0, 0\n"));
+        assert_eq!(quil.matches("DEFGATE").count(), 1);
+        assert!(quil.ends_with("GATE_0 0 1\nGATE_0 1 0\n"));
+
+        let mut noisy = Circuit::new();
+        noisy.append(vec![op(gate_on!(DepolarizingChannel::new(0.1).unwrap(), q[0].clone()))]).unwrap();
+        assert!(noisy.to_quil().is_err());
+    }
+}
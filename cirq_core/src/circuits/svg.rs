@@ -0,0 +1,277 @@
+use crate::circuits::circuit::Circuit;
+use crate::ops::measure::MeasurementGate;
+use crate::ops::raw_types::{Operation, QId};
+
+/// Options for rendering a circuit as an SVG image. All sizes are in pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SvgConfig {
+    /// The vertical distance between two qubit wires.
+    pub wire_spacing: f32,
+    /// The smallest width of a gate box. Boxes are widened to fit their labels.
+    pub gate_width: f32,
+    pub font_size: f32,
+}
+
+impl Default for SvgConfig {
+    fn default() -> Self {
+        Self {
+            wire_spacing: 40.0,
+            gate_width: 30.0,
+            font_size: 14.0,
+        }
+    }
+}
+
+/// An operation placed in a column of the image, with the wire index of each of its qubits.
+struct Placement {
+    indices: Vec<usize>,
+    symbols: Vec<String>,
+    is_measurement: bool,
+}
+
+impl Circuit {
+    /// Renders the circuit as an SVG image with the default options.
+    pub fn to_svg(&self) -> String {
+        self.to_svg_with_config(&SvgConfig::default())
+    }
+
+    /**
+     * Renders the circuit as an SVG image, with a horizontal wire for each qubit in the default qubit order.
+     * Gates are boxes labelled with their wire symbols, controls are dots, the qubits of a multi-qubit gate
+     * are joined by a vertical line, and measurements are drawn as meters.
+     */
+    pub fn to_svg_with_config(&self, config: &SvgConfig) -> String {
+        let qubits = self.all_qubits();
+        let columns = self.svg_columns(&qubits);
+
+        // Text is measured with an average character width, since the font isn't known.
+        let text_width = |text: &str| text.chars().count() as f32 * config.font_size * 0.6;
+        let label_width = qubits.iter().map(|q| text_width(&q.to_string())).fold(0.0, f32::max) + config.font_size;
+        let column_widths: Vec<f32> = columns
+            .iter()
+            .map(|column| {
+                let widest = column.iter().flat_map(|p| &p.symbols).map(|s| text_width(s)).fold(0.0, f32::max);
+                config.gate_width.max(widest + config.font_size / 2.0)
+            })
+            .collect();
+        let gap = config.gate_width / 2.0;
+
+        let width = label_width + gap + column_widths.iter().map(|w| w + gap).sum::<f32>();
+        let height = config.wire_spacing * (qubits.len() + 1) as f32;
+        let wire_y = |i: usize| config.wire_spacing * (i + 1) as f32;
+        let box_height = config.wire_spacing * 0.6;
+
+        let mut elements = vec![format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"sans-serif\" font-size=\"{}\">",
+            width, height, config.font_size
+        )];
+        for (i, q) in qubits.iter().enumerate() {
+            elements.push(format!(
+                "<text x=\"{}\" y=\"{}\" text-anchor=\"end\" dominant-baseline=\"middle\">{}</text>",
+                label_width - config.font_size / 2.0,
+                wire_y(i),
+                escape(&q.to_string())
+            ));
+            elements.push(format!(
+                "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\"/>",
+                label_width,
+                wire_y(i),
+                width,
+                wire_y(i)
+            ));
+        }
+
+        let mut x = label_width + gap;
+        for (column, column_width) in columns.iter().zip(&column_widths) {
+            let center = x + column_width / 2.0;
+            for placement in column {
+                let low = placement.indices.iter().copied().min().unwrap_or(0);
+                let high = placement.indices.iter().copied().max().unwrap_or(0);
+                if low < high {
+                    elements.push(format!(
+                        "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\"/>",
+                        center,
+                        wire_y(low),
+                        center,
+                        wire_y(high)
+                    ));
+                }
+
+                for (i, symbol) in placement.indices.iter().zip(&placement.symbols) {
+                    let y = wire_y(*i);
+                    if symbol == "@" {
+                        elements.push(format!("<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"black\"/>", center, y, config.font_size / 3.0));
+                        continue;
+                    }
+
+                    elements.push(format!(
+                        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"white\" stroke=\"black\"/>",
+                        x,
+                        y - box_height / 2.0,
+                        column_width,
+                        box_height
+                    ));
+                    if placement.is_measurement {
+                        elements.extend(meter(center, y, box_height));
+                    } else {
+                        elements.push(format!(
+                            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" dominant-baseline=\"middle\">{}</text>",
+                            center,
+                            y,
+                            escape(symbol)
+                        ));
+                    }
+                }
+            }
+            x += column_width + gap;
+        }
+
+        elements.push("</svg>".to_string());
+        elements.join("\n") + "\n"
+    }
+
+    /// Splits each moment into columns, so that operations drawn in the same column don't overlap.
+    fn svg_columns(&self, qubits: &[Box<dyn QId>]) -> Vec<Vec<Placement>> {
+        let mut columns = vec![];
+
+        for moment in self.moments() {
+            let mut moment_columns: Vec<Vec<Placement>> = vec![];
+
+            for op in moment {
+                let placement = placement(op.as_ref(), qubits);
+                let (low, high) = match (placement.indices.iter().min(), placement.indices.iter().max()) {
+                    (Some(&low), Some(&high)) => (low, high),
+                    _ => continue,
+                };
+
+                let overlaps = |other: &Placement| {
+                    let other_low = other.indices.iter().copied().min().unwrap_or(0);
+                    let other_high = other.indices.iter().copied().max().unwrap_or(0);
+                    other_low <= high && low <= other_high
+                };
+                match moment_columns.iter().position(|column| !column.iter().any(overlaps)) {
+                    Some(i) => moment_columns[i].push(placement),
+                    None => moment_columns.push(vec![placement]),
+                }
+            }
+
+            if moment_columns.is_empty() {
+                moment_columns.push(vec![]);
+            }
+            columns.extend(moment_columns);
+        }
+
+        columns
+    }
+}
+
+fn placement(op: &dyn Operation, qubits: &[Box<dyn QId>]) -> Placement {
+    let gate = op.gate();
+    let symbols = match &gate {
+        Some(gate) => gate.wire_symbols(),
+        None => vec!["?".to_string(); op.qubits().len()],
+    };
+
+    Placement {
+        indices: op.qubits()
+            .iter()
+            .filter_map(|q| qubits.iter().position(|other| other.eq_qid(q.as_ref())))
+            .collect(),
+        symbols,
+        is_measurement: gate.is_some_and(|gate| gate.as_any().is::<MeasurementGate>()),
+    }
+}
+
+/// Returns the elements of a meter symbol centered on the given point: an arc with a needle.
+fn meter(x: f32, y: f32, box_height: f32) -> Vec<String> {
+    let radius = box_height * 0.35;
+    let base = y + radius / 2.0;
+    vec![
+        format!(
+            "<path d=\"M {} {} A {} {} 0 0 1 {} {}\" fill=\"none\" stroke=\"black\"/>",
+            x - radius,
+            base,
+            radius,
+            radius,
+            x + radius,
+            base
+        ),
+        format!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\"/>",
+            x,
+            base,
+            x + radius * 0.7,
+            base - radius
+        ),
+    ]
+}
+
+/// Escapes the characters that have a special meaning in XML text.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Error;
+
+    use crate::gate_on;
+    use crate::ops::common_gates::{CNOT, H};
+    use crate::ops::gate_operation::GateOperation;
+    use crate::ops::measure::M;
+    use crate::ops::pauli_gates::X;
+    use crate::ops::qubits::LineQubit;
+
+    use super::*;
+
+    fn op(result: Result<GateOperation, Error>) -> Box<dyn Operation> {
+        Box::new(result.unwrap())
+    }
+
+    #[test]
+    fn svg_has_wires_gates_and_meters() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            op(gate_on!(H, q[0].clone())),
+            op(gate_on!(CNOT, q[0].clone(), q[1].clone())),
+            Box::new(M("m", &[&q[1]])),
+        ]).unwrap();
+
+        let svg = circuit.to_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>\n"));
+        // Two wires and the line joining the control to the target.
+        assert_eq!(svg.matches("<line").count(), 4);
+        // A box for H, X and the measurement, and a dot for the control.
+        assert_eq!(svg.matches("<rect").count(), 3);
+        assert_eq!(svg.matches("<circle").count(), 1);
+        assert_eq!(svg.matches("<path").count(), 1);
+        assert!(svg.contains(">H</text>"));
+        assert!(svg.contains(">q0</text>"));
+    }
+
+    #[test]
+    fn overlapping_operations_in_a_moment_get_their_own_columns() {
+        let q = LineQubit::range(3);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![op(gate_on!(CNOT, q[0].clone(), q[2].clone())), op(gate_on!(X, q[1].clone()))]).unwrap();
+        assert_eq!(circuit.num_moments(), 1);
+
+        let config = SvgConfig { wire_spacing: 50.0, gate_width: 40.0, font_size: 10.0 };
+        let svg = circuit.to_svg_with_config(&config);
+        let xs: Vec<&str> = svg
+            .lines()
+            .filter(|line| line.starts_with("<rect"))
+            .map(|line| line.split('"').nth(1).unwrap())
+            .collect();
+        assert_eq!(xs.len(), 2);
+        assert_ne!(xs[0], xs[1]);
+        assert!(svg.contains("height=\"200\""));
+    }
+
+    #[test]
+    fn labels_are_escaped() {
+        assert_eq!(escape("a<b & \"c\">"), "a&lt;b &amp; &quot;c&quot;&gt;");
+    }
+}
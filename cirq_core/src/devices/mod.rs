@@ -0,0 +1,181 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::Error;
+
+use crate::circuits::circuit::Circuit;
+use crate::ops::raw_types::{Gate, QId};
+
+/// The hardware that a circuit runs on, with its qubits, their connectivity and its gate durations.
+pub trait Device {
+    /// Returns the qubits of the device.
+    fn qubit_set(&self) -> Vec<Box<dyn QId>>;
+
+    /// Returns the pairs of qubits that two-qubit gates can act on. The pairs are unordered.
+    fn coupling_graph(&self) -> Vec<(Box<dyn QId>, Box<dyn QId>)>;
+
+    /// Returns how long the gate takes on the given qubits, or None if the device doesn't say.
+    fn gate_duration(&self, gate: &dyn Gate, qubits: &[&dyn QId]) -> Option<Duration>;
+
+    /**
+     * Checks that the circuit can run on the device. By default, every qubit of the circuit must be a
+     * device qubit, and every two-qubit operation must act on a coupled pair. Operations on more
+     * qubits are rejected, since they have to be decomposed first.
+     */
+    fn validate_circuit(&self, circuit: &Circuit) -> Result<(), Error> {
+        let qubits = self.qubit_set();
+        if let Some(q) = circuit.all_qubits().iter().find(|q| !qubits.iter().any(|other| other.eq_qid(q.as_ref()))) {
+            return Err(anyhow!(format!("Qubit {} is not on the device.", q)));
+        }
+
+        let coupling_graph = self.coupling_graph();
+        for op in circuit.all_operations() {
+            let op_qubits = op.qubits();
+            match op_qubits.len() {
+                0 | 1 => {}
+                2 => {
+                    let coupled = coupling_graph.iter().any(|(a, b)| {
+                        (a.eq_qid(op_qubits[0].as_ref()) && b.eq_qid(op_qubits[1].as_ref()))
+                            || (a.eq_qid(op_qubits[1].as_ref()) && b.eq_qid(op_qubits[0].as_ref()))
+                    });
+                    if !coupled {
+                        return Err(anyhow!(format!("Qubits {} and {} are not coupled on the device.", op_qubits[0], op_qubits[1])));
+                    }
+                }
+                n => return Err(anyhow!(format!("The device doesn't support operations on {} qubits.", n))),
+            }
+        }
+        Ok(())
+    }
+}
+
+/**
+ * A device without constraints, which accepts every circuit. Its qubit set and coupling graph
+ * are empty, since any qubits can be used, and gate durations come from `durations`.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct UnconstrainedDevice {
+    pub durations: DeviceDurationMap,
+}
+
+impl UnconstrainedDevice {
+    pub fn new(durations: DeviceDurationMap) -> Self {
+        Self {
+            durations
+        }
+    }
+}
+
+impl Device for UnconstrainedDevice {
+    fn qubit_set(&self) -> Vec<Box<dyn QId>> {
+        vec![]
+    }
+
+    fn coupling_graph(&self) -> Vec<(Box<dyn QId>, Box<dyn QId>)> {
+        vec![]
+    }
+
+    fn gate_duration(&self, gate: &dyn Gate, _qubits: &[&dyn QId]) -> Option<Duration> {
+        self.durations.duration(gate)
+    }
+
+    fn validate_circuit(&self, _circuit: &Circuit) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// The durations of gates by their type, with an optional default for the gates that aren't listed.
+#[derive(Clone, Debug, Default)]
+pub struct DeviceDurationMap {
+    durations: HashMap<TypeId, Duration>,
+    default_duration: Option<Duration>,
+}
+
+impl DeviceDurationMap {
+    pub fn new(default_duration: Option<Duration>) -> Self {
+        Self {
+            durations: HashMap::new(),
+            default_duration,
+        }
+    }
+
+    /// Returns a copy of the map where gates of type `G` take `duration`.
+    pub fn with_duration<G: Gate + 'static>(mut self, duration: Duration) -> Self {
+        self.durations.insert(TypeId::of::<G>(), duration);
+        self
+    }
+
+    /// Returns the duration of the gate, falling back to the default duration.
+    pub fn duration(&self, gate: &dyn Gate) -> Option<Duration> {
+        self.durations.get(&Any::type_id(gate.as_any())).copied().or(self.default_duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gate_on;
+    use crate::ops::common_gates::{CZGate, CZ, H};
+    use crate::ops::qubits::LineQubit;
+    use crate::ops::raw_types::Operation;
+    use crate::ops::three_qubit_gates::CCX;
+
+    use super::*;
+
+    #[test]
+    fn durations_are_looked_up_by_gate_type() {
+        let q = LineQubit::range(2);
+        let durations = DeviceDurationMap::new(Some(Duration::from_nanos(25))).with_duration::<CZGate>(Duration::from_nanos(40));
+        let device = UnconstrainedDevice::new(durations);
+
+        assert_eq!(device.gate_duration(&CZ, &[&q[0], &q[1]]), Some(Duration::from_nanos(40)));
+        assert_eq!(device.gate_duration(&H, &[&q[0]]), Some(Duration::from_nanos(25)));
+        assert_eq!(DeviceDurationMap::default().duration(&H), None);
+    }
+
+    /// Three qubits on a line, where only neighbors are coupled.
+    struct LineDevice;
+
+    impl Device for LineDevice {
+        fn qubit_set(&self) -> Vec<Box<dyn QId>> {
+            LineQubit::range(3).into_iter().map(|q| Box::new(q) as Box<dyn QId>).collect()
+        }
+
+        fn coupling_graph(&self) -> Vec<(Box<dyn QId>, Box<dyn QId>)> {
+            vec![
+                (Box::new(LineQubit::new(0)), Box::new(LineQubit::new(1))),
+                (Box::new(LineQubit::new(1)), Box::new(LineQubit::new(2))),
+            ]
+        }
+
+        fn gate_duration(&self, _gate: &dyn Gate, _qubits: &[&dyn QId]) -> Option<Duration> {
+            None
+        }
+    }
+
+    #[test]
+    fn circuits_are_validated_against_the_device() {
+        let q = LineQubit::range(4);
+        let circuit = |ops: Vec<Box<dyn Operation>>| {
+            let mut circuit = Circuit::new();
+            circuit.append(ops).unwrap();
+            circuit
+        };
+
+        let neighbors = circuit(vec![Box::new(gate_on!(CZ, q[2].clone(), q[1].clone()).unwrap())]);
+        assert!(LineDevice.validate_circuit(&neighbors).is_ok());
+
+        let distant = circuit(vec![Box::new(gate_on!(CZ, q[0].clone(), q[2].clone()).unwrap())]);
+        assert!(LineDevice.validate_circuit(&distant).is_err());
+
+        let off_device = circuit(vec![Box::new(gate_on!(H, q[3].clone()).unwrap())]);
+        assert!(LineDevice.validate_circuit(&off_device).is_err());
+
+        let toffoli = circuit(vec![Box::new(gate_on!(CCX, q[0].clone(), q[1].clone(), q[2].clone()).unwrap())]);
+        assert!(LineDevice.validate_circuit(&toffoli).is_err());
+
+        for circuit in [neighbors, distant, off_device, toffoli] {
+            assert!(UnconstrainedDevice::default().validate_circuit(&circuit).is_ok());
+        }
+    }
+}
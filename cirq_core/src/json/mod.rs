@@ -0,0 +1,349 @@
+use anyhow::Error;
+use serde_json::{json, Map, Value};
+
+use crate::circuits::circuit::Circuit;
+use crate::circuits::moment::Moment;
+use crate::ops::common_gates::{CNOT, CZ, H};
+use crate::ops::gate_operation::GateOperation;
+use crate::ops::measure::MeasurementGate;
+use crate::ops::pauli_gates::{X, Y, Z};
+use crate::ops::qubits::{GridQubit, LineQid, LineQubit, NamedQubit};
+use crate::ops::raw_types::{Gate, Operation, QId};
+
+/**
+ * Converts one gate type to and from its Cirq JSON representation.
+ * The `cirq_type` of a serializer must match the `cirq_type` field of the objects it reads.
+ */
+pub trait GateSerializer {
+    /// Returns the Cirq type name of the gates handled by this serializer.
+    fn cirq_type(&self) -> &str;
+
+    /// Returns the JSON object for the gate, or None if the gate isn't handled by this serializer.
+    fn serialize(&self, gate: &dyn Gate) -> Option<Value>;
+
+    /// Reads a gate from its JSON object.
+    fn deserialize(&self, value: &Value) -> Result<Box<dyn Gate>, Error>;
+}
+
+/// Serializes a single gate constant that Cirq represents as an `EigenGate` with an exponent of 1.
+struct EigenGateSerializer<G> {
+    cirq_type: &'static str,
+    gate: G,
+}
+
+impl<G: 'static + Gate + Clone> GateSerializer for EigenGateSerializer<G> {
+    fn cirq_type(&self) -> &str {
+        self.cirq_type
+    }
+
+    fn serialize(&self, gate: &dyn Gate) -> Option<Value> {
+        gate.as_any().downcast_ref::<G>()?;
+        Some(json!({
+            "cirq_type": self.cirq_type,
+            "exponent": 1.0,
+            "global_shift": 0.0,
+        }))
+    }
+
+    fn deserialize(&self, value: &Value) -> Result<Box<dyn Gate>, Error> {
+        let exponent = value.get("exponent").and_then(Value::as_f64).unwrap_or(1.0);
+        let global_shift = value.get("global_shift").and_then(Value::as_f64).unwrap_or(0.0);
+        if exponent != 1.0 || global_shift != 0.0 {
+            return Err(anyhow!(format!(
+                "Unsupported {} with exponent {} and global shift {}.",
+                self.cirq_type, exponent, global_shift
+            )));
+        }
+        Ok(Box::new(self.gate.clone()))
+    }
+}
+
+/// Serializes measurement gates, which Cirq stores with their key and qid shape.
+struct MeasurementGateSerializer;
+
+impl GateSerializer for MeasurementGateSerializer {
+    fn cirq_type(&self) -> &str {
+        "MeasurementGate"
+    }
+
+    fn serialize(&self, gate: &dyn Gate) -> Option<Value> {
+        let gate = gate.as_any().downcast_ref::<MeasurementGate>()?;
+        Some(json!({
+            "cirq_type": "MeasurementGate",
+            "num_qubits": gate.qubits,
+            "key": gate.key,
+            "invert_mask": [],
+            "qid_shape": vec![2; gate.qubits],
+        }))
+    }
+
+    fn deserialize(&self, value: &Value) -> Result<Box<dyn Gate>, Error> {
+        let key = value.get("key").and_then(Value::as_str).ok_or_else(|| anyhow!("Missing string field key."))?;
+        let qubits = int_field(value, "num_qubits")? as usize;
+        if value.get("invert_mask").and_then(Value::as_array).is_some_and(|mask| !mask.is_empty()) {
+            return Err(anyhow!("Inverted measurements are not supported."));
+        }
+        Ok(Box::new(MeasurementGate::new(key, qubits)))
+    }
+}
+
+/// The set of gate serializers used to read and write circuits.
+pub struct GateSerializerRegistry {
+    serializers: Vec<Box<dyn GateSerializer>>,
+}
+
+impl Default for GateSerializerRegistry {
+    /// Returns a registry with serializers for the standard gates.
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(Box::new(EigenGateSerializer { cirq_type: "_PauliX", gate: X }));
+        registry.register(Box::new(EigenGateSerializer { cirq_type: "_PauliY", gate: Y }));
+        registry.register(Box::new(EigenGateSerializer { cirq_type: "_PauliZ", gate: Z }));
+        registry.register(Box::new(EigenGateSerializer { cirq_type: "HPowGate", gate: H }));
+        registry.register(Box::new(EigenGateSerializer { cirq_type: "CZPowGate", gate: CZ }));
+        registry.register(Box::new(EigenGateSerializer { cirq_type: "CXPowGate", gate: CNOT }));
+        registry.register(Box::new(MeasurementGateSerializer));
+        registry
+    }
+}
+
+impl GateSerializerRegistry {
+    /// Returns a registry without any serializers.
+    pub fn empty() -> Self {
+        Self {
+            serializers: vec![],
+        }
+    }
+
+    /// Adds a serializer to the registry. Serializers registered later take precedence.
+    pub fn register(&mut self, serializer: Box<dyn GateSerializer>) {
+        self.serializers.insert(0, serializer);
+    }
+
+    pub fn gate_to_json(&self, gate: &dyn Gate) -> Result<Value, Error> {
+        self.serializers
+            .iter()
+            .find_map(|serializer| serializer.serialize(gate))
+            .ok_or_else(|| anyhow!(format!("No serializer registered for gate {}.", gate)))
+    }
+
+    pub fn gate_from_json(&self, value: &Value) -> Result<Box<dyn Gate>, Error> {
+        let cirq_type = cirq_type(value)?;
+        self.serializers
+            .iter()
+            .find(|serializer| serializer.cirq_type() == cirq_type)
+            .ok_or_else(|| anyhow!(format!("No serializer registered for cirq_type {}.", cirq_type)))?
+            .deserialize(value)
+    }
+
+    /// Serializes the circuit to Cirq's JSON format.
+    pub fn circuit_to_json(&self, circuit: &Circuit) -> Result<String, Error> {
+        let mut moments = vec![];
+        for moment in circuit.moments() {
+            let mut operations = vec![];
+            for op in moment {
+                operations.push(self.operation_to_json(op.as_ref())?);
+            }
+            moments.push(json!({
+                "cirq_type": "Moment",
+                "operations": operations,
+            }));
+        }
+
+        let value = json!({
+            "cirq_type": "Circuit",
+            "moments": moments,
+        });
+        Ok(serde_json::to_string_pretty(&value)?)
+    }
+
+    /// Reads a circuit from Cirq's JSON format.
+    pub fn circuit_from_json(&self, s: &str) -> Result<Circuit, Error> {
+        let value: Value = serde_json::from_str(s)?;
+        expect_cirq_type(&value, "Circuit")?;
+
+        let mut moments = vec![];
+        for moment in array_field(&value, "moments")? {
+            expect_cirq_type(moment, "Moment")?;
+            let mut operations = vec![];
+            for op in array_field(moment, "operations")? {
+                operations.push(self.operation_from_json(op)?);
+            }
+            moments.push(Moment::new(operations)?);
+        }
+
+        Ok(Circuit::from_moments(moments))
+    }
+
+    fn operation_to_json(&self, op: &dyn Operation) -> Result<Value, Error> {
+        let gate = op.gate().ok_or_else(|| anyhow!("Only gate operations can be serialized."))?;
+        let qubits = op.qubits().iter().map(|q| qubit_to_json(q.as_ref())).collect::<Result<Vec<_>, _>>()?;
+
+        Ok(json!({
+            "cirq_type": "GateOperation",
+            "gate": self.gate_to_json(gate.as_ref())?,
+            "qubits": qubits,
+        }))
+    }
+
+    fn operation_from_json(&self, value: &Value) -> Result<Box<dyn Operation>, Error> {
+        expect_cirq_type(value, "GateOperation")?;
+        let gate = self.gate_from_json(value.get("gate").ok_or_else(|| anyhow!("Missing field gate."))?)?;
+        let qubits = array_field(value, "qubits")?.iter().map(qubit_from_json).collect::<Result<Vec<_>, _>>()?;
+
+        gate.validate_args(qubits.clone())?;
+        Ok(Box::new(GateOperation::new(gate, qubits)))
+    }
+}
+
+/// Serializes the circuit to Cirq's JSON format, using the standard gate serializers.
+pub fn to_json(circuit: &Circuit) -> Result<String, Error> {
+    GateSerializerRegistry::default().circuit_to_json(circuit)
+}
+
+/// Reads a circuit from Cirq's JSON format, using the standard gate serializers.
+pub fn from_json(s: &str) -> Result<Circuit, Error> {
+    GateSerializerRegistry::default().circuit_from_json(s)
+}
+
+fn cirq_type(value: &Value) -> Result<&str, Error> {
+    value.get("cirq_type").and_then(Value::as_str).ok_or_else(|| anyhow!("Missing field cirq_type."))
+}
+
+fn expect_cirq_type(value: &Value, expected: &str) -> Result<(), Error> {
+    let actual = cirq_type(value)?;
+    if actual != expected {
+        return Err(anyhow!(format!("Expected cirq_type {} but got {}.", expected, actual)));
+    }
+    Ok(())
+}
+
+fn array_field<'a>(value: &'a Value, field: &str) -> Result<&'a Vec<Value>, Error> {
+    value.get(field).and_then(Value::as_array).ok_or_else(|| anyhow!(format!("Missing array field {}.", field)))
+}
+
+fn int_field(value: &Value, field: &str) -> Result<i64, Error> {
+    value.get(field).and_then(Value::as_i64).ok_or_else(|| anyhow!(format!("Missing integer field {}.", field)))
+}
+
+fn qubit_to_json(qubit: &dyn QId) -> Result<Value, Error> {
+    let any = qubit.as_any();
+    let mut object = Map::new();
+
+    if let Some(q) = any.downcast_ref::<LineQubit>() {
+        object.insert("cirq_type".to_string(), json!("LineQubit"));
+        object.insert("x".to_string(), json!(q.x));
+    } else if let Some(q) = any.downcast_ref::<LineQid>() {
+        object.insert("cirq_type".to_string(), json!("LineQid"));
+        object.insert("x".to_string(), json!(q.x));
+        object.insert("dimension".to_string(), json!(q.dimension));
+    } else if let Some(q) = any.downcast_ref::<GridQubit>() {
+        object.insert("cirq_type".to_string(), json!("GridQubit"));
+        object.insert("row".to_string(), json!(q.row));
+        object.insert("col".to_string(), json!(q.col));
+    } else if let Some(q) = any.downcast_ref::<NamedQubit>() {
+        object.insert("cirq_type".to_string(), json!("NamedQubit"));
+        object.insert("name".to_string(), json!(q.name()));
+    } else {
+        return Err(anyhow!(format!("Qid {} can't be serialized.", qubit)));
+    }
+
+    Ok(Value::Object(object))
+}
+
+fn qubit_from_json(value: &Value) -> Result<Box<dyn QId>, Error> {
+    match cirq_type(value)? {
+        "LineQubit" => Ok(Box::new(LineQubit::new(int_field(value, "x")?))),
+        "LineQid" => Ok(Box::new(LineQid::new(int_field(value, "x")?, int_field(value, "dimension")? as u64)?)),
+        "GridQubit" => Ok(Box::new(GridQubit::new(int_field(value, "row")?, int_field(value, "col")?))),
+        "NamedQubit" => {
+            let name = value.get("name").and_then(Value::as_str).ok_or_else(|| anyhow!("Missing string field name."))?;
+            Ok(Box::new(NamedQubit::new(name)))
+        }
+        other => Err(anyhow!(format!("Unsupported qid type {}.", other))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gate_on;
+    use crate::ops::raw_traits::gates_equal;
+
+    use super::*;
+
+    fn bell_circuit() -> Circuit {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            Box::new(gate_on!(H, q[0].clone()).unwrap()),
+            Box::new(gate_on!(CNOT, q[0].clone(), q[1].clone()).unwrap()),
+        ]).unwrap();
+        circuit
+    }
+
+    #[test]
+    fn json_matches_cirq_format() {
+        let value: Value = serde_json::from_str(&to_json(&bell_circuit()).unwrap()).unwrap();
+        let expected = json!({
+            "cirq_type": "Circuit",
+            "moments": [
+                {
+                    "cirq_type": "Moment",
+                    "operations": [{
+                        "cirq_type": "GateOperation",
+                        "gate": {"cirq_type": "HPowGate", "exponent": 1.0, "global_shift": 0.0},
+                        "qubits": [{"cirq_type": "LineQubit", "x": 0}],
+                    }],
+                },
+                {
+                    "cirq_type": "Moment",
+                    "operations": [{
+                        "cirq_type": "GateOperation",
+                        "gate": {"cirq_type": "CXPowGate", "exponent": 1.0, "global_shift": 0.0},
+                        "qubits": [{"cirq_type": "LineQubit", "x": 0}, {"cirq_type": "LineQubit", "x": 1}],
+                    }],
+                },
+            ],
+        });
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let circuit = bell_circuit();
+        let restored = from_json(&to_json(&circuit).unwrap()).unwrap();
+
+        assert_eq!(restored.num_moments(), circuit.num_moments());
+        for (a, b) in restored.all_operations().zip(circuit.all_operations()) {
+            assert!(gates_equal(a, b));
+        }
+    }
+
+    #[test]
+    fn unknown_types_are_rejected() {
+        let mut circuit = Circuit::new();
+        circuit.append(vec![Box::new(gate_on!(crate::ops::common_gates::T, GridQubit::new(0, 0)).unwrap())]).unwrap();
+        assert!(to_json(&circuit).is_err());
+        assert!(from_json(r#"{"cirq_type": "Circuit", "moments": [{"cirq_type": "Moment", "operations": [
+            {"cirq_type": "GateOperation", "gate": {"cirq_type": "FooGate"}, "qubits": []}]}]}"#).is_err());
+    }
+
+    #[test]
+    fn measurements_round_trip() {
+        let q = LineQubit::range(2);
+        let gate = MeasurementGate::new("result", 2);
+        let value = GateSerializerRegistry::default().gate_to_json(&gate).unwrap();
+        assert_eq!(value, json!({
+            "cirq_type": "MeasurementGate",
+            "num_qubits": 2,
+            "key": "result",
+            "invert_mask": [],
+            "qid_shape": [2, 2],
+        }));
+
+        let mut circuit = Circuit::new();
+        circuit.append(vec![Box::new(crate::ops::measure::M("result", &[&q[0], &q[1]]))]).unwrap();
+        let restored = from_json(&to_json(&circuit).unwrap()).unwrap();
+        assert!(gates_equal(restored.all_operations().next().unwrap(), circuit.all_operations().next().unwrap()));
+    }
+}
@@ -1,9 +1,15 @@
 #[macro_use]
 extern crate anyhow;
 
-#[macro_use]
-extern crate dyn_clonable;
-
+pub mod circuits;
+pub mod devices;
+pub mod json;
+pub mod linalg;
+pub mod noise;
 pub mod ops;
+pub mod routing;
+pub mod sim;
+pub mod study;
+pub mod transformers;
 pub mod utils;
 
@@ -0,0 +1,462 @@
+use std::f64::consts::{FRAC_1_SQRT_2, FRAC_PI_2, FRAC_PI_4};
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::linalg::{self, dagger, dot, hermitian_eig, kron};
+use crate::ops::common_gates::{rz_decomposition_of_unitary, Rx, Ry, Rz, CNOT, H, S, S_DAG};
+use crate::ops::gate_operation::GateOperation;
+use crate::ops::matrix_gates::MatrixGate;
+use crate::ops::raw_traits::HasUnitary;
+use crate::ops::raw_types::{Gate, Operation, QId};
+
+type Matrix = Vec<Vec<Complex<f64>>>;
+
+fn c(re: f64, im: f64) -> Complex<f64> {
+    Complex::new(re, im)
+}
+
+/// Returns the determinant of a square matrix, by Gaussian elimination with partial pivoting.
+fn determinant(matrix: &[Vec<Complex<f64>>]) -> Complex<f64> {
+    let mut a = matrix.to_vec();
+    let n = a.len();
+    let mut det = c(1.0, 0.0);
+    for col in 0..n {
+        let pivot = (col..n).max_by(|i, j| a[*i][col].norm().total_cmp(&a[*j][col].norm())).unwrap();
+        if a[pivot][col].norm() < 1e-300 {
+            return c(0.0, 0.0);
+        }
+        if pivot != col {
+            a.swap(pivot, col);
+            det = -det;
+        }
+        det *= a[col][col];
+        let (upper, lower) = a.split_at_mut(col + 1);
+        for row in lower {
+            let factor = row[col] / upper[col][col];
+            for (entry, pivot_entry) in row.iter_mut().zip(&upper[col]) {
+                *entry -= factor * pivot_entry;
+            }
+        }
+    }
+    det
+}
+
+/// The single-qubit Paulis X, Y and Z.
+fn paulis() -> [Matrix; 3] {
+    let (zero, one, i) = (c(0.0, 0.0), c(1.0, 0.0), c(0.0, 1.0));
+    [
+        vec![vec![zero, one], vec![one, zero]],
+        vec![vec![zero, -i], vec![i, zero]],
+        vec![vec![one, zero], vec![zero, -one]],
+    ]
+}
+
+/**
+ * The magic basis, whose columns are Bell states with real phases chosen so that two-qubit gates
+ * made of single-qubit gates become real orthogonal matrices.
+ */
+fn magic_basis() -> Matrix {
+    let (zero, r, i) = (c(0.0, 0.0), c(FRAC_1_SQRT_2, 0.0), c(0.0, FRAC_1_SQRT_2));
+    vec![
+        vec![r, zero, zero, i],
+        vec![zero, i, r, zero],
+        vec![zero, i, -r, zero],
+        vec![r, zero, zero, -i],
+    ]
+}
+
+/**
+ * Factors a 4x4 matrix `M = g · (a ⊗ b)` into `(g, a, b)`, where `a` and `b` have determinant 1.
+ * The matrix must be a tensor product up to numerical error.
+ */
+fn kron_factor(matrix: &[Vec<Complex<f64>>]) -> (Complex<f64>, Matrix, Matrix) {
+    // The largest entry M[2i + p][2j + q] = g a[i][j] b[p][q] gives well-conditioned factors.
+    let (row, col) = (0..16).map(|k| (k / 4, k % 4)).max_by(|x, y| matrix[x.0][x.1].norm().total_cmp(&matrix[y.0][y.1].norm())).unwrap();
+    let mut a: Matrix = (0..2).map(|i| (0..2).map(|j| matrix[2 * i + row % 2][2 * j + col % 2]).collect()).collect();
+    let mut b: Matrix = (0..2).map(|p| (0..2).map(|q| matrix[2 * (row / 2) + p][2 * (col / 2) + q]).collect()).collect();
+
+    for factor in [&mut a, &mut b] {
+        let scale = determinant(factor).sqrt();
+        for entry in factor.iter_mut().flatten() {
+            *entry /= scale;
+        }
+    }
+    let g = matrix[row][col] / kron(&a, &b)[row][col];
+    (g, a, b)
+}
+
+/**
+ * A two-qubit unitary written as `global_phase · (a1 ⊗ b1) · exp(i(x XX + y YY + z ZZ)) · (a0 ⊗ b0)`,
+ * where `(a0, b0)` are the single-qubit gates before the interaction and `(a1, b1)` the ones after it.
+ * The interaction coefficients are canonical: π/4 ≥ x ≥ y ≥ |z|, with z ≥ 0 when x = π/4.
+ */
+#[derive(Clone, Debug)]
+pub struct KakDecomposition {
+    pub global_phase: Complex<f64>,
+    pub single_qubit_operations_before: (Matrix, Matrix),
+    pub interaction_coefficients: (f64, f64, f64),
+    pub single_qubit_operations_after: (Matrix, Matrix),
+}
+
+impl KakDecomposition {
+    /// Returns the matrix exp(i(x XX + y YY + z ZZ)) of the interaction.
+    pub fn interaction_matrix(&self) -> Matrix {
+        let (x, y, z) = self.interaction_coefficients;
+        // The magic basis states are eigenvectors of XX, YY and ZZ with these signs.
+        let signs = [(1.0, -1.0, 1.0), (1.0, 1.0, -1.0), (-1.0, -1.0, -1.0), (-1.0, 1.0, 1.0)];
+        let basis = magic_basis();
+        let mut diagonal = vec![vec![c(0.0, 0.0); 4]; 4];
+        for (k, (sx, sy, sz)) in signs.iter().enumerate() {
+            diagonal[k][k] = Complex::from_polar(1.0, sx * x + sy * y + sz * z);
+        }
+        dot(&dot(&basis, &diagonal), &dagger(&basis))
+    }
+
+    /// Returns the unitary that the decomposition describes.
+    pub fn unitary(&self) -> Matrix {
+        let (a0, b0) = &self.single_qubit_operations_before;
+        let (a1, b1) = &self.single_qubit_operations_after;
+        let product = dot(&dot(&kron(a1, b1), &self.interaction_matrix()), &kron(a0, b0));
+        product.iter().map(|row| row.iter().map(|entry| entry * self.global_phase).collect()).collect()
+    }
+
+    /// Replaces the interaction by exp(i(v' · (XX, YY, ZZ))) = C† exp(i(v · (XX, YY, ZZ))) C for the gate `c ⊗ c = C`.
+    fn conjugate(&mut self, gate: &[Vec<Complex<f64>>], swapped: (usize, usize)) {
+        let (a0, b0) = &mut self.single_qubit_operations_before;
+        *a0 = dot(&dagger(gate), a0);
+        *b0 = dot(&dagger(gate), b0);
+        let (a1, b1) = &mut self.single_qubit_operations_after;
+        *a1 = dot(a1, gate);
+        *b1 = dot(b1, gate);
+
+        let mut v = [self.interaction_coefficients.0, self.interaction_coefficients.1, self.interaction_coefficients.2];
+        v.swap(swapped.0, swapped.1);
+        self.interaction_coefficients = (v[0], v[1], v[2]);
+    }
+
+    /// Subtracts `n` quarter turns from coefficient `k`, using exp(iπ/2 PP) = i P ⊗ P.
+    fn shift(&mut self, k: usize, n: i32) {
+        let pauli = &paulis()[k];
+        let (a0, b0) = &mut self.single_qubit_operations_before;
+        for _ in 0..n.rem_euclid(2) {
+            *a0 = dot(pauli, a0);
+            *b0 = dot(pauli, b0);
+        }
+        self.global_phase *= c(0.0, 1.0).powi(n);
+
+        let mut v = [self.interaction_coefficients.0, self.interaction_coefficients.1, self.interaction_coefficients.2];
+        v[k] -= n as f64 * FRAC_PI_2;
+        self.interaction_coefficients = (v[0], v[1], v[2]);
+    }
+
+    /// Negates the two coefficients other than `k`, by conjugating with the Pauli `k` on the first qubit.
+    fn negate_others(&mut self, k: usize) {
+        let pauli = &paulis()[k];
+        self.single_qubit_operations_before.0 = dot(pauli, &self.single_qubit_operations_before.0);
+        self.single_qubit_operations_after.0 = dot(&self.single_qubit_operations_after.0, pauli);
+
+        let mut v = [self.interaction_coefficients.0, self.interaction_coefficients.1, self.interaction_coefficients.2];
+        for (i, coefficient) in v.iter_mut().enumerate() {
+            if i != k {
+                *coefficient = -*coefficient;
+            }
+        }
+        self.interaction_coefficients = (v[0], v[1], v[2]);
+    }
+
+    /// Moves the interaction coefficients into the Weyl chamber π/4 ≥ x ≥ y ≥ |z|, keeping the unitary the same.
+    fn canonicalize(&mut self) {
+        let coefficients = |kak: &Self| [kak.interaction_coefficients.0, kak.interaction_coefficients.1, kak.interaction_coefficients.2];
+        for k in 0..3 {
+            let n = (coefficients(self)[k] / FRAC_PI_2).round() as i32;
+            self.shift(k, n);
+        }
+
+        // Gates whose conjugation swaps XX with YY, YY with ZZ and XX with ZZ.
+        let (zero, one, half) = (c(0.0, 0.0), c(1.0, 0.0), c(FRAC_1_SQRT_2, 0.0));
+        let s = vec![vec![one, zero], vec![zero, c(0.0, 1.0)]];
+        let sqrt_x = vec![vec![half, c(0.0, -FRAC_1_SQRT_2)], vec![c(0.0, -FRAC_1_SQRT_2), half]];
+        let h = vec![vec![half, half], vec![half, -half]];
+        let swap_gate = |i: usize, j: usize| match (i.min(j), i.max(j)) {
+            (0, 1) => &s,
+            (1, 2) => &sqrt_x,
+            _ => &h,
+        };
+        for (i, j) in [(0, 1), (1, 2), (0, 1)] {
+            let v = coefficients(self);
+            if v[i].abs() < v[j].abs() {
+                self.conjugate(swap_gate(i, j), (i, j));
+            }
+        }
+
+        if coefficients(self)[0] < 0.0 {
+            self.negate_others(1);
+        }
+        if coefficients(self)[1] < 0.0 {
+            self.negate_others(0);
+        }
+        if (coefficients(self)[0] - FRAC_PI_4).abs() < 1e-9 && coefficients(self)[2] < 0.0 {
+            self.shift(0, 1);
+            self.negate_others(1);
+        }
+    }
+}
+
+/**
+ * Decomposes a two-qubit unitary into single-qubit gates around the canonical interaction
+ * exp(i(x XX + y YY + z ZZ)). Fails unless the gate has a 4x4 unitary.
+ */
+pub fn kak_decomposition(gate: &dyn HasUnitary) -> Result<KakDecomposition, Error> {
+    let unitary = gate.unitary().ok_or_else(|| anyhow!("Only unitary gates have a KAK decomposition."))?;
+    if unitary.len() != 4 {
+        return Err(anyhow!(format!("The KAK decomposition is for two-qubit gates, but the unitary is {0} x {0}.", unitary.len())));
+    }
+
+    // In the magic basis, single-qubit gates are real orthogonal: U = O_L · D · O_R.
+    let basis = magic_basis();
+    let magic = dot(&dot(&dagger(&basis), &unitary), &basis);
+    let squared = dot(&linalg::transpose(&magic), &magic);
+
+    // The real and imaginary parts of the symmetric unitary U^T U commute, so a generic real
+    // combination of them has the same real orthogonal eigenvectors.
+    let combined: Matrix = squared.iter().map(|row| row.iter().map(|x| c(x.re + 0.5683 * x.im, 0.0)).collect()).collect();
+    let (_, mut right) = hermitian_eig(&combined);
+    if determinant(&right).re < 0.0 {
+        for row in right.iter_mut() {
+            row[0] = -row[0];
+        }
+    }
+
+    let diagonal_squared = dot(&dot(&linalg::transpose(&right), &squared), &right);
+    let mut phases: Vec<Complex<f64>> = (0..4).map(|k| diagonal_squared[k][k].sqrt()).collect();
+    let left_unscaled = dot(&magic, &right);
+    let mut left: Matrix = left_unscaled.iter().map(|row| row.iter().zip(&phases).map(|(x, d)| x / d).collect()).collect();
+    if determinant(&left).re < 0.0 {
+        phases[0] = -phases[0];
+        for row in left.iter_mut() {
+            row[0] = -row[0];
+        }
+    }
+
+    // Solves arg d_k = g + s_x x + s_y y + s_z z, for the signs of XX, YY and ZZ on the magic basis.
+    let angles: Vec<f64> = phases.iter().map(|d| d.arg()).collect();
+    let solve = |signs: [f64; 4]| signs.iter().zip(&angles).map(|(s, a)| s * a).sum::<f64>() / 4.0;
+    let g = solve([1.0, 1.0, 1.0, 1.0]);
+    let x = solve([1.0, 1.0, -1.0, -1.0]);
+    let y = solve([-1.0, 1.0, -1.0, 1.0]);
+    let z = solve([1.0, -1.0, -1.0, 1.0]);
+
+    let (phase_after, a1, b1) = kron_factor(&dot(&dot(&basis, &left), &dagger(&basis)));
+    let (phase_before, a0, b0) = kron_factor(&dot(&dot(&basis, &linalg::transpose(&right)), &dagger(&basis)));
+
+    let mut kak = KakDecomposition {
+        global_phase: Complex::from_polar(1.0, g) * phase_after * phase_before,
+        single_qubit_operations_before: (a0, b0),
+        interaction_coefficients: (x, y, z),
+        single_qubit_operations_after: (a1, b1),
+    };
+    kak.canonicalize();
+    Ok(kak)
+}
+
+/// Returns a single-qubit operation for the 2x2 unitary.
+fn matrix_operation(unitary: &[Vec<Complex<f64>>], qubit: &dyn QId) -> Box<dyn Operation> {
+    // The matrices of a decomposition are unitary up to rounding, well within MatrixGate's tolerance.
+    let gate = MatrixGate::new(unitary.to_vec(), vec![2]).unwrap();
+    Box::new(GateOperation::new(Box::new(gate), vec![qubit.dyn_clone()]))
+}
+
+/**
+ * Returns operations that apply the decomposed unitary to `q0` and `q1`, up to its global phase.
+ * Each of the XX, YY and ZZ interactions becomes a CNOT, an Rz and a CNOT in the matching basis.
+ */
+pub fn kak_to_operations(decomp: &KakDecomposition, q0: Box<dyn QId>, q1: Box<dyn QId>) -> Vec<Box<dyn Operation>> {
+    let op = |gate: &dyn Gate, qubits: Vec<Box<dyn QId>>| -> Box<dyn Operation> {
+        Box::new(GateOperation::new(gate.dyn_clone(), qubits))
+    };
+    let on_both = |gate: &dyn Gate| vec![op(gate, vec![q0.clone()]), op(gate, vec![q1.clone()])];
+    // exp(iθ ZZ) = CNOT · (I ⊗ Rz(-2θ)) · CNOT.
+    let zz = |theta: f64| {
+        vec![
+            op(&CNOT, vec![q0.clone(), q1.clone()]),
+            op(&Rz::new(-2.0 * theta), vec![q1.clone()]),
+            op(&CNOT, vec![q0.clone(), q1.clone()]),
+        ]
+    };
+
+    let (a0, b0) = &decomp.single_qubit_operations_before;
+    let (a1, b1) = &decomp.single_qubit_operations_after;
+    let (x, y, z) = decomp.interaction_coefficients;
+
+    let mut ops = vec![matrix_operation(a0, q0.as_ref()), matrix_operation(b0, q1.as_ref())];
+    if z.abs() > 1e-12 {
+        ops.extend(zz(z));
+    }
+    if x.abs() > 1e-12 {
+        // H maps XX to ZZ.
+        ops.extend(on_both(&H));
+        ops.extend(zz(x));
+        ops.extend(on_both(&H));
+    }
+    if y.abs() > 1e-12 {
+        // H S† maps YY to ZZ.
+        ops.extend(on_both(&S_DAG));
+        ops.extend(on_both(&H));
+        ops.extend(zz(y));
+        ops.extend(on_both(&H));
+        ops.extend(on_both(&S));
+    }
+    ops.extend([matrix_operation(a1, q0.as_ref()), matrix_operation(b1, q1.as_ref())]);
+    ops
+}
+
+/// Returns the phase φ such that `unitary = e^{iφ} · product`, from their largest entry.
+fn relative_phase(unitary: &[Vec<Complex<f64>>], product: &[Vec<Complex<f64>>]) -> f64 {
+    let (row, col) = (0..4).map(|k| (k / 2, k % 2)).max_by(|x, y| product[x.0][x.1].norm().total_cmp(&product[y.0][y.1].norm())).unwrap();
+    (unitary[row][col] / product[row][col]).arg()
+}
+
+/**
+ * Decomposes a single-qubit gate into `(global_phase, z0, y, z1)`, such that the gate is
+ * `e^{i * global_phase} * Rz(z0) * Ry(y) * Rz(z1)`. Fails unless the gate has a 2x2 unitary.
+ */
+pub fn zyz_decomposition(gate: &dyn HasUnitary) -> Result<(f64, f64, f64, f64), Error> {
+    let unitary = gate.unitary().ok_or_else(|| anyhow!("Only unitary gates have a ZYZ decomposition."))?;
+    let (z0, y, z1) = rz_decomposition_of_unitary(&unitary).ok_or_else(|| anyhow!("The ZYZ decomposition is for single-qubit unitaries."))?;
+    let product = dot(&dot(&Rz::new(z0).unitary().unwrap(), &Ry::new(y).unitary().unwrap()), &Rz::new(z1).unitary().unwrap());
+    Ok((relative_phase(&unitary, &product), z0, y, z1))
+}
+
+/**
+ * Returns the operations Rz(z1), Ry(y) and Rz(z0) on the qubit, in that order, which apply
+ * `Rz(z0) * Ry(y) * Rz(z1)`. The global phase has no operation and is dropped.
+ */
+pub fn zyz_to_operations(q: Box<dyn QId>, _phase: f64, z0: f64, y: f64, z1: f64) -> Vec<Box<dyn Operation>> {
+    vec![
+        Box::new(GateOperation::new(Box::new(Rz::new(z1)), vec![q.clone()])),
+        Box::new(GateOperation::new(Box::new(Ry::new(y)), vec![q.clone()])),
+        Box::new(GateOperation::new(Box::new(Rz::new(z0)), vec![q])),
+    ]
+}
+
+/**
+ * Decomposes a single-qubit gate into `(global_phase, x0, y, x1)`, such that the gate is
+ * `e^{i * global_phase} * Rx(x0) * Ry(y) * Rx(x1)`. Fails unless the gate has a 2x2 unitary.
+ */
+pub fn xyx_decomposition(gate: &dyn HasUnitary) -> Result<(f64, f64, f64, f64), Error> {
+    let unitary = gate.unitary().ok_or_else(|| anyhow!("Only unitary gates have an XYX decomposition."))?;
+    if unitary.len() != 2 {
+        return Err(anyhow!("The XYX decomposition is for single-qubit unitaries."));
+    }
+
+    // H Rx(θ) H = Rz(θ) and H Ry(θ) H = Ry(-θ).
+    let hadamard = H.unitary().unwrap();
+    let conjugated = MatrixGate::new(dot(&dot(&hadamard, &unitary), &hadamard), vec![2])?;
+    let (phase, x0, y, x1) = zyz_decomposition(&conjugated)?;
+    Ok((phase, x0, -y, x1))
+}
+
+/// Returns the operations Rx(x1), Ry(y) and Rx(x0) on the qubit, in that order. The global phase is dropped.
+pub fn xyx_to_operations(q: Box<dyn QId>, _phase: f64, x0: f64, y: f64, x1: f64) -> Vec<Box<dyn Operation>> {
+    vec![
+        Box::new(GateOperation::new(Box::new(Rx::new(x1)), vec![q.clone()])),
+        Box::new(GateOperation::new(Box::new(Ry::new(y)), vec![q.clone()])),
+        Box::new(GateOperation::new(Box::new(Rx::new(x0)), vec![q])),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::circuits::circuit::Circuit;
+    use crate::ops::common_gates::{CZ, ISWAP, SWAP, T};
+    use crate::ops::controlled_gate::ControlledGate;
+    use crate::ops::qubits::LineQubit;
+
+    use super::*;
+
+    /// Determines if two unitaries are equal up to a global phase.
+    fn equal_up_to_phase(a: &[Vec<Complex<f64>>], b: &[Vec<Complex<f64>>]) -> bool {
+        let phase = a.iter().flatten().zip(b.iter().flatten()).find(|(x, _)| x.norm() > 1e-6).map(|(x, y)| y / x).unwrap();
+        let scaled: Vec<Vec<_>> = a.iter().map(|row| row.iter().map(|x| x * phase).collect()).collect();
+        linalg::allclose(&scaled, b, 1e-8)
+    }
+
+    fn assert_coefficients(gate: &dyn HasUnitary, expected: (f64, f64, f64)) {
+        let kak = kak_decomposition(gate).unwrap();
+        let (x, y, z) = kak.interaction_coefficients;
+        assert!(
+            (x - expected.0).abs() < 1e-8 && (y - expected.1).abs() < 1e-8 && (z - expected.2).abs() < 1e-8,
+            "{:?} != {:?}",
+            kak.interaction_coefficients,
+            expected,
+        );
+        assert!(linalg::allclose(&kak.unitary(), &gate.unitary().unwrap(), 1e-8));
+    }
+
+    #[test]
+    fn known_gates_have_known_kak_vectors() {
+        assert_coefficients(&CNOT, (FRAC_PI_4, 0.0, 0.0));
+        assert_coefficients(&CZ, (FRAC_PI_4, 0.0, 0.0));
+        assert_coefficients(&ISWAP, (FRAC_PI_4, FRAC_PI_4, 0.0));
+        assert_coefficients(&SWAP, (FRAC_PI_4, FRAC_PI_4, FRAC_PI_4));
+        assert_coefficients(&MatrixGate::new(linalg::eye(4), vec![2, 2]).unwrap(), (0.0, 0.0, 0.0));
+        assert!(kak_decomposition(&H).is_err());
+    }
+
+    /// Returns a generic two-qubit unitary built from rotations and entangling gates.
+    fn generic_unitary() -> Matrix {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            Box::new(Rx::new(0.3).on(vec![Box::new(q[0].clone())]).unwrap()) as Box<dyn Operation>,
+            Box::new(Ry::new(1.1).on(vec![Box::new(q[1].clone())]).unwrap()),
+            Box::new(CNOT.on(vec![Box::new(q[0].clone()), Box::new(q[1].clone())]).unwrap()),
+            Box::new(Rz::new(0.7).on(vec![Box::new(q[1].clone())]).unwrap()),
+            Box::new(ControlledGate::new(Box::new(Ry::new(2.1)), 1).on(vec![Box::new(q[1].clone()), Box::new(q[0].clone())]).unwrap()),
+            Box::new(Rx::new(-0.4).on(vec![Box::new(q[0].clone())]).unwrap()),
+            Box::new(ISWAP.pow(0.37).unwrap().on(vec![Box::new(q[0].clone()), Box::new(q[1].clone())]).unwrap()),
+        ]).unwrap();
+        circuit.unitary().unwrap()
+    }
+
+    #[test]
+    fn generic_unitaries_are_recomposed() {
+        let unitary = generic_unitary();
+        let kak = kak_decomposition(&MatrixGate::new(unitary.clone(), vec![2, 2]).unwrap()).unwrap();
+        assert!(linalg::allclose(&kak.unitary(), &unitary, 1e-8));
+
+        let (x, y, z) = kak.interaction_coefficients;
+        assert!(FRAC_PI_4 + 1e-9 >= x && x >= y && y >= z.abs());
+
+        let q = LineQubit::range(2);
+        let ops = kak_to_operations(&kak, Box::new(q[0].clone()), Box::new(q[1].clone()));
+        let mut circuit = Circuit::new();
+        circuit.append(ops).unwrap();
+        assert!(equal_up_to_phase(&circuit.unitary().unwrap(), &unitary));
+    }
+
+    /// Returns the matrix of the operations, applied in order to a single qubit, times the phase.
+    fn recompose(ops: &[Box<dyn Operation>], phase: f64) -> Matrix {
+        let product = ops.iter().fold(linalg::eye(2), |product, op| dot(&op.unitary().unwrap(), &product));
+        product.iter().map(|row| row.iter().map(|x| x * Complex::from_polar(1.0, phase)).collect()).collect()
+    }
+
+    #[test]
+    fn single_qubit_decompositions_recompose() {
+        let q = LineQubit::new(0);
+        let hadamard = H.unitary().unwrap();
+
+        let (phase, z0, y, z1) = zyz_decomposition(&H).unwrap();
+        assert!(linalg::allclose(&recompose(&zyz_to_operations(Box::new(q.clone()), phase, z0, y, z1), phase), &hadamard, 1e-10));
+        let (phase, x0, y, x1) = xyx_decomposition(&H).unwrap();
+        assert!(linalg::allclose(&recompose(&xyx_to_operations(Box::new(q.clone()), phase, x0, y, x1), phase), &hadamard, 1e-10));
+
+        let gate = MatrixGate::new(dot(&T.unitary().unwrap(), &Rx::new(0.8).unitary().unwrap()), vec![2]).unwrap();
+        let (phase, x0, y, x1) = xyx_decomposition(&gate).unwrap();
+        assert!(linalg::allclose(&recompose(&xyx_to_operations(Box::new(q), phase, x0, y, x1), phase), &gate.unitary().unwrap(), 1e-10));
+
+        assert!(zyz_decomposition(&CZ).is_err());
+        assert!(xyx_decomposition(&CZ).is_err());
+    }
+}
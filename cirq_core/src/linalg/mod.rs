@@ -0,0 +1,316 @@
+pub mod decompositions;
+pub mod operator_spaces;
+pub mod random;
+pub mod states;
+
+pub use self::operator_spaces::{expand_in_n_qubit_pauli_basis, expand_in_pauli_basis};
+pub use self::states::{
+    bloch_vector, bloch_vector_from_density_matrix, bloch_vector_of_qubit, density_matrix_fidelity, entanglement_entropy,
+    partial_trace, partial_trace_of_density_matrix, process_fidelity, schmidt_coefficients, schmidt_rank, state_fidelity,
+};
+
+use anyhow::Error;
+use num_complex::Complex;
+
+type Matrix = Vec<Vec<Complex<f64>>>;
+
+/// Returns the matrix product `a · b`.
+pub fn dot(a: &[Vec<Complex<f64>>], b: &[Vec<Complex<f64>>]) -> Vec<Vec<Complex<f64>>> {
+    a.iter()
+        .map(|row| {
+            (0..b[0].len())
+                .map(|j| row.iter().zip(b.iter()).map(|(x, b_row)| x * b_row[j]).sum())
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns the conjugate transpose of a matrix.
+pub fn dagger(matrix: &[Vec<Complex<f64>>]) -> Vec<Vec<Complex<f64>>> {
+    (0..matrix[0].len())
+        .map(|j| matrix.iter().map(|row| row[j].conj()).collect())
+        .collect()
+}
+
+/// Returns the `n` x `n` identity matrix.
+pub fn eye(n: usize) -> Vec<Vec<Complex<f64>>> {
+    (0..n)
+        .map(|i| (0..n).map(|j| Complex::new(if i == j { 1.0 } else { 0.0 }, 0.0)).collect())
+        .collect()
+}
+
+/// Returns the Kronecker product `a ⊗ b` of two square matrices.
+pub fn kron(a: &[Vec<Complex<f64>>], b: &[Vec<Complex<f64>>]) -> Vec<Vec<Complex<f64>>> {
+    let (n, m) = (a.len(), b.len());
+    (0..n * m).map(|i| (0..n * m).map(|j| a[i / m][j / m] * b[i % m][j % m]).collect()).collect()
+}
+
+/// Determines if two matrices have the same shape and all entries within `atol` of each other.
+pub fn allclose(a: &[Vec<Complex<f64>>], b: &[Vec<Complex<f64>>], atol: f64) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b.iter()).all(|(a_row, b_row)| {
+            a_row.len() == b_row.len() && a_row.iter().zip(b_row.iter()).all(|(x, y)| (x - y).norm() <= atol)
+        })
+}
+
+/// Determines if the matrix is square and satisfies U†U ≈ I, with every entry within `tolerance`.
+pub fn is_unitary(matrix: &[Vec<Complex<f64>>], tolerance: f64) -> bool {
+    matrix.iter().all(|row| row.len() == matrix.len()) && allclose(&dot(&dagger(matrix), matrix), &eye(matrix.len()), tolerance)
+}
+
+/// Determines if the matrix is square and equal to its conjugate transpose, with every entry within `tolerance`.
+pub fn is_hermitian(matrix: &[Vec<Complex<f64>>], tolerance: f64) -> bool {
+    matrix.iter().all(|row| row.len() == matrix.len()) && allclose(matrix, &dagger(matrix), tolerance)
+}
+
+/// Determines if two square matrices of the same dimension satisfy AB ≈ BA, with every entry within `tolerance`.
+pub fn commutes(a: &[Vec<Complex<f64>>], b: &[Vec<Complex<f64>>], tolerance: f64) -> bool {
+    let square = |m: &[Vec<Complex<f64>>]| m.iter().all(|row| row.len() == m.len());
+    a.len() == b.len() && square(a) && square(b) && allclose(&dot(a, b), &dot(b, a), tolerance)
+}
+
+/**
+ * Left-multiplies the target qubits of a state vector by a matrix, leaving the other qubits untouched.
+ * Qubits are numbered from the most significant bit of the state index, and `targets[0]`
+ * corresponds to the most significant bit of the matrix index.
+ */
+pub fn targeted_left_multiply(
+    matrix: &[Vec<Complex<f64>>],
+    targets: &[usize],
+    state: &[Complex<f64>],
+    num_qubits: usize,
+) -> Vec<Complex<f64>> {
+    let masks: Vec<usize> = targets.iter().map(|q| 1 << (num_qubits - 1 - q)).collect();
+    let sub_index = |i: usize| masks.iter().fold(0, |acc, mask| (acc << 1) | usize::from(i & mask != 0));
+    let with_sub_index = |i: usize, k: usize| {
+        masks.iter().rev().enumerate().fold(i, |acc, (bit, mask)| {
+            if k >> bit & 1 == 1 { acc | mask } else { acc & !mask }
+        })
+    };
+
+    (0..state.len())
+        .map(|i| {
+            matrix[sub_index(i)]
+                .iter()
+                .enumerate()
+                .map(|(k, entry)| entry * state[with_sub_index(i, k)])
+                .sum()
+        })
+        .collect()
+}
+
+/// Returns the transpose of a matrix.
+pub fn transpose(matrix: &[Vec<Complex<f64>>]) -> Vec<Vec<Complex<f64>>> {
+    (0..matrix[0].len())
+        .map(|j| matrix.iter().map(|row| row[j]).collect())
+        .collect()
+}
+
+/// Computes `U ρ U†`, where `U` acts on the target qubits of the density matrix `ρ`.
+pub fn targeted_conjugate(
+    matrix: &[Vec<Complex<f64>>],
+    targets: &[usize],
+    density_matrix: &[Vec<Complex<f64>>],
+    num_qubits: usize,
+) -> Vec<Vec<Complex<f64>>> {
+    let left_multiply = |m: &[Vec<Complex<f64>>]| {
+        let columns: Vec<Vec<Complex<f64>>> = transpose(m)
+            .iter()
+            .map(|column| targeted_left_multiply(matrix, targets, column, num_qubits))
+            .collect();
+        transpose(&columns)
+    };
+
+    // U ρ U† = (U (U ρ)†)†
+    dagger(&left_multiply(&dagger(&left_multiply(density_matrix))))
+}
+
+/**
+ * Diagonalizes a Hermitian matrix with cyclic Jacobi rotations.
+ * Returns the real eigenvalues and a unitary matrix whose columns are the matching eigenvectors.
+ */
+pub(crate) fn hermitian_eig(matrix: &[Vec<Complex<f64>>]) -> (Vec<f64>, Vec<Vec<Complex<f64>>>) {
+    let n = matrix.len();
+    let mut a = matrix.to_vec();
+    let mut vectors = eye(n);
+
+    for _ in 0..100 {
+        let off_diagonal: f64 = (0..n).flat_map(|i| (0..n).filter(move |j| *j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j].norm_sqr())
+            .sum();
+        if off_diagonal < 1e-28 {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                let magnitude = a[p][q].norm();
+                if magnitude < 1e-300 {
+                    continue;
+                }
+
+                // Rotate the phase of a[p][q] away, then apply a real Jacobi rotation that zeroes it.
+                let phase = a[p][q] / magnitude;
+                let tau = (a[q][q].re - a[p][p].re) / (2.0 * magnitude);
+                let t = if tau >= 0.0 { 1.0 } else { -1.0 } / (tau.abs() + (1.0 + tau * tau).sqrt());
+                let c = 1.0 / (1.0 + t * t).sqrt();
+                let s = t * c;
+                let j_pp = Complex::new(c, 0.0);
+                let j_pq = Complex::new(s, 0.0);
+                let j_qp = -s * phase.conj();
+                let j_qq = c * phase.conj();
+
+                for row in a.iter_mut().chain(vectors.iter_mut()) {
+                    let (x, y) = (row[p], row[q]);
+                    row[p] = x * j_pp + y * j_qp;
+                    row[q] = x * j_pq + y * j_qq;
+                }
+                let (upper, lower) = a.split_at_mut(q);
+                for (x, y) in upper[p].iter_mut().zip(lower[0].iter_mut()) {
+                    (*x, *y) = (j_pp.conj() * *x + j_qp.conj() * *y, j_pq.conj() * *x + j_qq.conj() * *y);
+                }
+            }
+        }
+    }
+
+    ((0..n).map(|i| a[i][i].re).collect(), vectors)
+}
+
+/**
+ * Diagonalizes a unitary matrix, returning its eigenvalues and a unitary matrix whose columns are the matching eigenvectors.
+ * The Hermitian and anti-Hermitian parts of a unitary commute, so the eigenvectors of the Hermitian part
+ * are refined by diagonalizing the anti-Hermitian part within each of its degenerate eigenspaces.
+ */
+fn unitary_eigendecomposition(unitary: &[Vec<Complex<f64>>]) -> (Vec<Complex<f64>>, Vec<Vec<Complex<f64>>>) {
+    let n = unitary.len();
+    let adjoint = dagger(unitary);
+    let part = |sign: f64, scale: Complex<f64>| -> Vec<Vec<Complex<f64>>> {
+        (0..n).map(|i| (0..n).map(|j| (unitary[i][j] + sign * adjoint[i][j]) * scale).collect()).collect()
+    };
+    let real_part = part(1.0, Complex::new(0.5, 0.0));
+    let imaginary_part = part(-1.0, Complex::new(0.0, -0.5));
+
+    let (values, mut vectors) = hermitian_eig(&real_part);
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|i, j| values[*i].total_cmp(&values[*j]));
+
+    let mut start = 0;
+    while start < n {
+        let mut end = start + 1;
+        while end < n && values[order[end]] - values[order[start]] < 1e-8 {
+            end += 1;
+        }
+
+        if end - start > 1 {
+            let cluster = &order[start..end];
+            let basis: Vec<Vec<Complex<f64>>> = vectors.iter().map(|row| cluster.iter().map(|c| row[*c]).collect()).collect();
+            let restricted = dot(&dagger(&basis), &dot(&imaginary_part, &basis));
+            let (_, rotation) = hermitian_eig(&restricted);
+            let refined = dot(&basis, &rotation);
+            for (row, refined_row) in vectors.iter_mut().zip(refined) {
+                for (c, entry) in cluster.iter().zip(refined_row) {
+                    row[*c] = entry;
+                }
+            }
+        }
+        start = end;
+    }
+
+    let eigenvalues = (0..n)
+        .map(|k| {
+            let column: Vec<Complex<f64>> = vectors.iter().map(|row| row[k]).collect();
+            let image: Vec<Complex<f64>> = unitary.iter().map(|row| row.iter().zip(&column).map(|(u, v)| u * v).sum()).collect();
+            column.iter().zip(image).map(|(v, w)| v.conj() * w).sum()
+        })
+        .collect();
+    (eigenvalues, vectors)
+}
+
+/**
+ * Diagonalizes a unitary matrix, returning its eigenvalues, which lie on the unit circle, and a unitary matrix
+ * whose columns are the matching eigenvectors. Fails if the matrix isn't unitary.
+ */
+pub fn unitary_eig(matrix: &[Vec<Complex<f64>>]) -> Result<(Vec<Complex<f64>>, Matrix), Error> {
+    if !is_unitary(matrix, 1e-8) {
+        return Err(anyhow!("Can't diagonalize a matrix that isn't unitary."));
+    }
+
+    let (eigenvalues, vectors) = unitary_eigendecomposition(matrix);
+    Ok((eigenvalues.iter().map(|value| value / value.norm()).collect(), vectors))
+}
+
+/// Returns the matrix V diag(λ) V† with the given eigenvalues λ and the matching eigenvectors as the columns of V.
+pub fn matrix_from_eig(eigenvalues: &[Complex<f64>], eigenvectors: &[Vec<Complex<f64>>]) -> Vec<Vec<Complex<f64>>> {
+    let scaled: Vec<Vec<Complex<f64>>> = eigenvectors
+        .iter()
+        .map(|row| row.iter().zip(eigenvalues).map(|(v, value)| v * value).collect())
+        .collect();
+    dot(&scaled, &dagger(eigenvectors))
+}
+
+/**
+ * Raises a unitary matrix to a real power by raising its eigenvalues to that power.
+ * Eigenvalue phases are taken in (-π, π], so for example the square root of -1 is i.
+ */
+pub fn unitary_power(unitary: &[Vec<Complex<f64>>], exponent: f64) -> Vec<Vec<Complex<f64>>> {
+    let (eigenvalues, vectors) = unitary_eigendecomposition(unitary);
+    let powers: Vec<Complex<f64>> = eigenvalues
+        .iter()
+        .map(|value| {
+            let mut phase = value.arg();
+            if phase <= -std::f64::consts::PI + 1e-12 {
+                phase = std::f64::consts::PI;
+            }
+            Complex::from_polar(1.0, phase * exponent)
+        })
+        .collect();
+    matrix_from_eig(&powers, &vectors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matrix_predicates() {
+        let (zero, one, i) = (Complex::new(0.0, 0.0), Complex::new(1.0, 0.0), Complex::new(0.0, 1.0));
+        let x = vec![vec![zero, one], vec![one, zero]];
+        let y = vec![vec![zero, -i], vec![i, zero]];
+        let z = vec![vec![one, zero], vec![zero, -one]];
+        let s = vec![vec![one, zero], vec![zero, i]];
+        let upper = vec![vec![one, one], vec![zero, one]];
+
+        assert!(is_unitary(&x, 1e-8) && is_unitary(&s, 1e-8));
+        assert!(!is_unitary(&upper, 1e-8));
+        assert!(!is_unitary(&[vec![one, zero]], 1e-8));
+
+        assert!(is_hermitian(&y, 1e-8));
+        assert!(!is_hermitian(&s, 1e-8));
+
+        assert!(commutes(&z, &s, 1e-8));
+        assert!(!commutes(&x, &z, 1e-8));
+        assert!(!commutes(&x, &eye(4), 1e-8));
+        assert!(commutes(&kron(&z, &z), &kron(&x, &x), 1e-8));
+    }
+
+    #[test]
+    fn unitary_eig_reconstructs_the_matrix() {
+        let (zero, one, i) = (Complex::new(0.0, 0.0), Complex::new(1.0, 0.0), Complex::new(0.0, 1.0));
+        let s = 0.5_f64.sqrt();
+        // The product of H and the phase gate S.
+        let unitary = vec![vec![Complex::new(s, 0.0), Complex::new(s, 0.0)], vec![i * s, -i * s]];
+
+        let (eigenvalues, eigenvectors) = unitary_eig(&unitary).unwrap();
+        assert!(eigenvalues.iter().all(|value| (value.norm() - 1.0).abs() < 1e-10));
+        assert!(allclose(&dot(&dagger(&eigenvectors), &eigenvectors), &eye(2), 1e-8));
+        assert!(allclose(&matrix_from_eig(&eigenvalues, &eigenvectors), &unitary, 1e-8));
+
+        // A degenerate spectrum still has orthonormal eigenvectors.
+        let diagonal = vec![vec![one, zero, zero], vec![zero, one, zero], vec![zero, zero, i]];
+        let (eigenvalues, eigenvectors) = unitary_eig(&diagonal).unwrap();
+        assert!(allclose(&matrix_from_eig(&eigenvalues, &eigenvectors), &diagonal, 1e-8));
+
+        assert!(unitary_eig(&[vec![one, one], vec![zero, one]]).is_err());
+        assert!(unitary_eig(&[vec![one, zero]]).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use num_complex::Complex;
+
+use crate::ops::pauli_gates::Pauli;
+use crate::ops::pauli_string::PauliString;
+use crate::ops::qubits::LineQubit;
+use crate::ops::raw_types::QId;
+
+/// Returns the coefficients (cI, cX, cY, cZ) with `matrix = cI*I + cX*X + cY*Y + cZ*Z`.
+pub fn expand_in_pauli_basis(matrix: &[[Complex<f64>; 2]; 2]) -> [Complex<f64>; 4] {
+    let [[a, b], [c, d]] = *matrix;
+    let half = Complex::new(0.5, 0.0);
+    [(a + d) * half, (b + c) * half, (b - c) * Complex::new(0.0, 0.5), (a - d) * half]
+}
+
+/**
+ * Expands a `2^n x 2^n` matrix in the basis of Pauli strings on `LineQubit`s 0 to n - 1, with qubit 0 as the
+ * most significant bit. Each key is a Pauli string with coefficient 1, and the matrix is the sum of the keys
+ * scaled by their values. Pauli strings whose coefficient is within 1e-12 of zero are left out.
+ */
+pub fn expand_in_n_qubit_pauli_basis(matrix: &[Vec<Complex<f64>>], n: usize) -> HashMap<PauliString, Complex<f64>> {
+    let qubits = LineQubit::range(n as i64);
+    let qubit_refs: Vec<&dyn QId> = qubits.iter().map(|q| q as &dyn QId).collect();
+    let paulis = [Pauli::I, Pauli::X, Pauli::Y, Pauli::Z];
+    let scale = 1.0 / (1u64 << n) as f64;
+
+    let mut expansion = HashMap::new();
+    for k in 0..1usize << (2 * n) {
+        // The base 4 digits of k pick the Pauli on each qubit.
+        let string = qubits.iter().enumerate().fold(PauliString::new(Complex::new(1.0, 0.0)), |string, (i, q)| {
+            &string * &PauliString::from_pauli(paulis[k >> (2 * (n - 1 - i)) & 3], q)
+        });
+
+        // The coefficient is Tr(P M) / 2^n, since the Pauli strings are Hermitian and orthogonal.
+        // Every qubit is in the list, so the action can be computed.
+        let action = string.basis_action(&qubit_refs).unwrap();
+        let trace: Complex<f64> = action.iter().enumerate().map(|(j, (target, amplitude))| amplitude * matrix[j][*target]).sum();
+        let coefficient = trace * scale;
+        if coefficient.norm() > 1e-12 {
+            expansion.insert(string, coefficient);
+        }
+    }
+    expansion
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ops::pauli_sum::PauliSum;
+
+    use super::*;
+
+    #[test]
+    fn single_qubit_expansion() {
+        let (zero, one, i) = (Complex::new(0.0, 0.0), Complex::new(1.0, 0.0), Complex::new(0.0, 1.0));
+        // |0⟩⟨1| = (X + iY) / 2.
+        let raising = [[zero, one], [zero, zero]];
+        let expected = [zero, Complex::new(0.5, 0.0), i * 0.5, zero];
+        assert!(expand_in_pauli_basis(&raising).iter().zip(expected).all(|(a, b)| (a - b).norm() < 1e-12));
+
+        let projector = [[one, zero], [zero, zero]];
+        assert_eq!(expand_in_pauli_basis(&projector), [Complex::new(0.5, 0.0), zero, zero, Complex::new(0.5, 0.0)]);
+    }
+
+    #[test]
+    fn n_qubit_expansion_rebuilds_the_matrix() {
+        let q = LineQubit::range(2);
+        let xz = PauliString::from_pauli(Pauli::X, &q[0]) * PauliString::from_pauli(Pauli::Z, &q[1]);
+        let y1 = PauliString::from_pauli(Pauli::Y, &q[1]);
+        let hamiltonian = PauliSum::from_pauli_strings(vec![xz.clone() * Complex::new(0.5, 0.0), y1.clone() * Complex::new(-2.0, 0.0)]);
+        let matrix = hamiltonian.matrix(&[&q[0], &q[1]]).unwrap();
+
+        let expansion = expand_in_n_qubit_pauli_basis(&matrix, 2);
+        assert_eq!(expansion.len(), 2);
+        assert!((expansion[&xz] - Complex::new(0.5, 0.0)).norm() < 1e-12);
+        assert!((expansion[&y1] - Complex::new(-2.0, 0.0)).norm() < 1e-12);
+
+        let rebuilt = PauliSum::from_pauli_strings(expansion.iter().map(|(string, c)| string.clone() * *c).collect());
+        assert!(crate::linalg::allclose(&rebuilt.matrix(&[&q[0], &q[1]]).unwrap(), &matrix, 1e-12));
+    }
+}
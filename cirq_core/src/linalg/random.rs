@@ -0,0 +1,86 @@
+use std::f64::consts::PI;
+
+use num_complex::Complex;
+use rand::Rng;
+
+/// Samples a complex number whose real and imaginary parts are independent standard normal variables.
+fn complex_gaussian<R: Rng>(rng: &mut R) -> Complex<f64> {
+    // The Box-Muller transform, with 1 - u in (0, 1] to keep the logarithm finite.
+    let radius = (-2.0 * (1.0 - rng.gen::<f64>()).ln()).sqrt();
+    Complex::from_polar(radius, 2.0 * PI * rng.gen::<f64>())
+}
+
+/**
+ * Samples an `n x n` unitary matrix from the Haar measure. The columns of a matrix of complex Gaussian entries
+ * are orthonormalized by Gram-Schmidt, which is its QR decomposition with a positive diagonal in R.
+ */
+pub fn random_unitary<R: Rng>(n: usize, rng: &mut R) -> Vec<Vec<Complex<f64>>> {
+    let mut columns: Vec<Vec<Complex<f64>>> = vec![];
+    while columns.len() < n {
+        let mut column: Vec<Complex<f64>> = (0..n).map(|_| complex_gaussian(rng)).collect();
+        for basis in &columns {
+            let overlap: Complex<f64> = basis.iter().zip(&column).map(|(b, c)| b.conj() * c).sum();
+            for (c, b) in column.iter_mut().zip(basis) {
+                *c -= overlap * b;
+            }
+        }
+
+        let norm = column.iter().map(|c| c.norm_sqr()).sum::<f64>().sqrt();
+        // A column in the span of the previous ones has probability zero, but is resampled if it happens.
+        if norm > 1e-10 {
+            columns.push(column.iter().map(|c| c / norm).collect());
+        }
+    }
+
+    (0..n).map(|i| columns.iter().map(|column| column[i]).collect()).collect()
+}
+
+/// Samples a state vector of `n_qubits` qubits uniformly from the unit sphere.
+pub fn random_state_vector<R: Rng>(n_qubits: usize, rng: &mut R) -> Vec<Complex<f64>> {
+    loop {
+        let state: Vec<Complex<f64>> = (0..1usize << n_qubits).map(|_| complex_gaussian(rng)).collect();
+        let norm = state.iter().map(|amplitude| amplitude.norm_sqr()).sum::<f64>().sqrt();
+        if norm > 1e-10 {
+            return state.iter().map(|amplitude| amplitude / norm).collect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::linalg;
+
+    use super::*;
+
+    #[test]
+    fn random_unitaries_are_unitary() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for n in [1, 2, 4, 8] {
+            let unitary = random_unitary(n, &mut rng);
+            assert_eq!(unitary.len(), n);
+            assert!(linalg::is_unitary(&unitary, 1e-8));
+        }
+        assert_ne!(random_unitary(2, &mut rng), random_unitary(2, &mut rng));
+    }
+
+    #[test]
+    fn haar_unitaries_have_no_preferred_phase() {
+        // The average of a Haar random matrix is zero, including along its diagonal.
+        let mut rng = StdRng::seed_from_u64(11);
+        let samples = 2000;
+        let mean: Complex<f64> = (0..samples).map(|_| random_unitary(2, &mut rng)[0][0]).sum::<Complex<f64>>() / samples as f64;
+        assert!(mean.norm() < 0.05);
+    }
+
+    #[test]
+    fn random_state_vectors_are_normalized() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let state = random_state_vector(3, &mut rng);
+        assert_eq!(state.len(), 8);
+        assert!((state.iter().map(|amplitude| amplitude.norm_sqr()).sum::<f64>() - 1.0).abs() < 1e-10);
+        assert_eq!(random_state_vector(0, &mut rng).len(), 1);
+    }
+}
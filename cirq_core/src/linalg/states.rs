@@ -0,0 +1,349 @@
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::linalg::{dagger, dot, hermitian_eig};
+
+/// Checks that the state has a power of two amplitudes and unit norm, and returns its number of qubits.
+fn validate_qubit_state(state: &[Complex<f64>]) -> Result<usize, Error> {
+    if state.is_empty() || !state.len().is_power_of_two() {
+        return Err(anyhow!(format!("A state of qubits needs a power of two amplitudes, but got {}.", state.len())));
+    }
+    let norm: f64 = state.iter().map(|amplitude| amplitude.norm_sqr()).sum();
+    if (norm - 1.0).abs() > 1e-8 {
+        return Err(anyhow!(format!("The state is not normalized, its norm is {}.", norm.sqrt())));
+    }
+    Ok(state.len().trailing_zeros() as usize)
+}
+
+/// Returns the Bloch vector (⟨X⟩, ⟨Y⟩, ⟨Z⟩) of a normalized single qubit state.
+pub fn bloch_vector(state: &[Complex<f64>]) -> Result<[f64; 3], Error> {
+    if state.len() != 2 {
+        return Err(anyhow!(format!("Expected a single qubit state but got {} amplitudes.", state.len())));
+    }
+    bloch_vector_of_qubit(state, 0)
+}
+
+/**
+ * Returns the Bloch vector of one qubit of a normalized multi-qubit state, from the qubit's reduced density matrix.
+ * Qubits are numbered from the most significant bit of the state index. The vector is shorter than 1 when the
+ * qubit is entangled with the others.
+ */
+pub fn bloch_vector_of_qubit(state: &[Complex<f64>], index: usize) -> Result<[f64; 3], Error> {
+    let num_qubits = validate_qubit_state(state)?;
+    if index >= num_qubits {
+        return Err(anyhow!(format!("Qubit {} is out of range for a state of {} qubits.", index, num_qubits)));
+    }
+
+    let mask = 1 << (num_qubits - 1 - index);
+    let mut density_matrix = [[Complex::new(0.0, 0.0); 2]; 2];
+    for (i, amplitude) in state.iter().enumerate().filter(|(i, _)| i & mask == 0) {
+        let flipped = state[i | mask];
+        density_matrix[0][0] += amplitude * amplitude.conj();
+        density_matrix[0][1] += amplitude * flipped.conj();
+        density_matrix[1][0] += flipped * amplitude.conj();
+        density_matrix[1][1] += flipped * flipped.conj();
+    }
+
+    let vector = bloch_vector_from_density_matrix(&density_matrix);
+    let length = vector.iter().map(|x| x * x).sum::<f64>().sqrt();
+    if length > 1.0 + 1e-8 {
+        return Err(anyhow!(format!("The Bloch vector has length {}, which is more than 1.", length)));
+    }
+    Ok(vector)
+}
+
+/// Returns the Bloch vector (⟨X⟩, ⟨Y⟩, ⟨Z⟩) = (Tr(ρX), Tr(ρY), Tr(ρZ)) of a single qubit density matrix.
+pub fn bloch_vector_from_density_matrix(dm: &[[Complex<f64>; 2]; 2]) -> [f64; 3] {
+    [2.0 * dm[0][1].re, 2.0 * dm[1][0].im, (dm[0][0] - dm[1][1]).re]
+}
+
+/// Returns the fidelity |⟨ψ|φ⟩|² of two pure states, which must have the same number of amplitudes.
+pub fn state_fidelity(state1: &[Complex<f64>], state2: &[Complex<f64>]) -> Result<f64, Error> {
+    if state1.len() != state2.len() {
+        return Err(anyhow!(format!("Can't compare states with {} and {} amplitudes.", state1.len(), state2.len())));
+    }
+    Ok(state1.iter().zip(state2).map(|(a, b)| a.conj() * b).sum::<Complex<f64>>().norm_sqr())
+}
+
+/// Returns the square root of a positive semidefinite Hermitian matrix, with small negative eigenvalues clipped to zero.
+fn positive_sqrt(matrix: &[Vec<Complex<f64>>]) -> Vec<Vec<Complex<f64>>> {
+    let (eigenvalues, vectors) = hermitian_eig(matrix);
+    let scaled: Vec<Vec<Complex<f64>>> = vectors
+        .iter()
+        .map(|row| row.iter().zip(&eigenvalues).map(|(v, value)| v * value.max(0.0).sqrt()).collect())
+        .collect();
+    dot(&scaled, &dagger(&vectors))
+}
+
+/**
+ * Returns the fidelity (Tr √(√ρ σ √ρ))² of two density matrices of the same dimension.
+ * For pure states this agrees with `state_fidelity`.
+ */
+pub fn density_matrix_fidelity(rho: &[Vec<Complex<f64>>], sigma: &[Vec<Complex<f64>>]) -> f64 {
+    let sqrt_rho = positive_sqrt(rho);
+    let product = dot(&dot(&sqrt_rho, sigma), &sqrt_rho);
+    let (eigenvalues, _) = hermitian_eig(&product);
+    eigenvalues.iter().map(|value| value.max(0.0).sqrt()).sum::<f64>().powi(2)
+}
+
+/**
+ * Returns the process fidelity |Tr(V†U)|² / d² of the unitary U of an implemented gate against the ideal unitary V,
+ * both of dimension d. It is 1 exactly when the unitaries are equal up to a global phase.
+ */
+pub fn process_fidelity(actual: &[Vec<Complex<f64>>], ideal: &[Vec<Complex<f64>>]) -> Result<f64, Error> {
+    let is_square = |matrix: &[Vec<Complex<f64>>], size: usize| matrix.len() == size && matrix.iter().all(|row| row.len() == size);
+    if actual.is_empty() || !is_square(actual, actual.len()) || !is_square(ideal, actual.len()) {
+        return Err(anyhow!(format!(
+            "Process fidelity needs two square matrices of the same dimension, but got {} and {} rows.",
+            actual.len(),
+            ideal.len(),
+        )));
+    }
+
+    let dimension = actual.len() as f64;
+    let overlap: Complex<f64> = ideal
+        .iter()
+        .zip(actual)
+        .flat_map(|(ideal_row, actual_row)| ideal_row.iter().zip(actual_row).map(|(v, u)| v.conj() * u))
+        .sum();
+    Ok(overlap.norm_sqr() / (dimension * dimension))
+}
+
+/**
+ * Returns the index into a state of `qid_shape` for the qids at `indices` taking the digits of `value`,
+ * read in mixed radix with `indices[0]` as the most significant digit, while the other qids are 0.
+ */
+fn place_digits(indices: &[usize], value: usize, qid_shape: &[u64]) -> usize {
+    let mut value = value;
+    let mut index = 0;
+    for q in indices.iter().rev() {
+        let stride: usize = qid_shape[q + 1..].iter().map(|d| *d as usize).product();
+        index += (value % qid_shape[*q] as usize) * stride;
+        value /= qid_shape[*q] as usize;
+    }
+    index
+}
+
+/// Returns the qids that aren't kept, and the dimensions of the kept and traced out subsystems.
+fn split_subsystems(keep_indices: &[usize], qid_shape: &[u64]) -> (Vec<usize>, usize, usize) {
+    let traced: Vec<usize> = (0..qid_shape.len()).filter(|q| !keep_indices.contains(q)).collect();
+    let dimension = |indices: &[usize]| indices.iter().map(|q| qid_shape[*q] as usize).product();
+    let (kept_dimension, traced_dimension) = (dimension(keep_indices), dimension(&traced));
+    (traced, kept_dimension, traced_dimension)
+}
+
+/**
+ * Returns the reduced density matrix of the qids at `keep_indices` in a pure state of qids with the
+ * given dimensions, tracing out the others. The reduced state has the kept qids in the order given,
+ * which must not repeat a qid.
+ */
+pub fn partial_trace(state: &[Complex<f64>], keep_indices: &[usize], qid_shape: &[u64]) -> Vec<Vec<Complex<f64>>> {
+    let (traced, kept_dimension, traced_dimension) = split_subsystems(keep_indices, qid_shape);
+    let index = |kept: usize, rest: usize| place_digits(keep_indices, kept, qid_shape) + place_digits(&traced, rest, qid_shape);
+
+    (0..kept_dimension)
+        .map(|a| {
+            (0..kept_dimension)
+                .map(|b| (0..traced_dimension).map(|t| state[index(a, t)] * state[index(b, t)].conj()).sum())
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns the reduced density matrix of the qids at `keep_indices`, as `partial_trace` does for a density matrix.
+pub fn partial_trace_of_density_matrix(
+    dm: &[Vec<Complex<f64>>],
+    keep_indices: &[usize],
+    qid_shape: &[u64],
+) -> Vec<Vec<Complex<f64>>> {
+    let (traced, kept_dimension, traced_dimension) = split_subsystems(keep_indices, qid_shape);
+    let index = |kept: usize, rest: usize| place_digits(keep_indices, kept, qid_shape) + place_digits(&traced, rest, qid_shape);
+
+    (0..kept_dimension)
+        .map(|a| (0..kept_dimension).map(|b| (0..traced_dimension).map(|t| dm[index(a, t)][index(b, t)]).sum()).collect())
+        .collect()
+}
+
+/**
+ * Returns the von Neumann entropy S = -Tr(ρ log₂ ρ), in bits, of the qids in `partition`, where ρ is their reduced
+ * density matrix in the pure state. It is 0 when the partition isn't entangled with the rest of the state.
+ */
+pub fn entanglement_entropy(state: &[Complex<f64>], partition: &[usize], qid_shape: &[u64]) -> f64 {
+    let (eigenvalues, _) = hermitian_eig(&partial_trace(state, partition, qid_shape));
+    -eigenvalues.iter().filter(|value| **value > 1e-12).map(|value| value * value.log2()).sum::<f64>()
+}
+
+/**
+ * Returns the Schmidt coefficients of a state of qubits, split between the qubits in `partition` and the others,
+ * in decreasing order. There is one coefficient for each basis state of the smaller side, and their squares sum to 1.
+ * Fails unless the state has 2^n amplitudes and the partition holds distinct qubit indices below n.
+ */
+pub fn schmidt_coefficients(state: &[Complex<f64>], partition: &[usize]) -> Result<Vec<f64>, Error> {
+    if !state.len().is_power_of_two() {
+        return Err(anyhow!(format!("A state of qubits has 2^n amplitudes, but this one has {}.", state.len())));
+    }
+    let num_qubits = state.len().trailing_zeros() as usize;
+    for (i, index) in partition.iter().enumerate() {
+        if *index >= num_qubits || partition[..i].contains(index) {
+            return Err(anyhow!(format!("The partition {:?} isn't a set of qubits of a {} qubit state.", partition, num_qubits)));
+        }
+    }
+
+    let qid_shape = vec![2; num_qubits];
+    let size = 1 << partition.len().min(num_qubits - partition.len());
+
+    let (eigenvalues, _) = hermitian_eig(&partial_trace(state, partition, &qid_shape));
+    let mut coefficients: Vec<f64> = eigenvalues.iter().map(|value| value.max(0.0).sqrt()).collect();
+    coefficients.sort_by(|a, b| b.total_cmp(a));
+    coefficients.truncate(size);
+    Ok(coefficients)
+}
+
+/// Returns the number of nonzero Schmidt coefficients of the state, which is 1 exactly for product states.
+pub fn schmidt_rank(state: &[Complex<f64>], partition: &[usize]) -> Result<usize, Error> {
+    Ok(schmidt_coefficients(state, partition)?.iter().filter(|coefficient| **coefficient > 1e-8).count())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    use crate::linalg::allclose;
+
+    use super::*;
+
+    fn close(a: [f64; 3], b: [f64; 3]) -> bool {
+        a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-10)
+    }
+
+    fn density_matrix(state: &[Complex<f64>]) -> Vec<Vec<Complex<f64>>> {
+        state.iter().map(|a| state.iter().map(|b| a * b.conj()).collect()).collect()
+    }
+
+    #[test]
+    fn fidelities_of_states() {
+        let h = FRAC_1_SQRT_2;
+        let zero = [Complex::new(1.0, 0.0), Complex::new(0.0, 0.0)];
+        let plus = [Complex::new(h, 0.0), Complex::new(h, 0.0)];
+        let minus_i = [Complex::new(0.0, -h), Complex::new(0.0, -h)];
+
+        assert!((state_fidelity(&zero, &plus).unwrap() - 0.5).abs() < 1e-10);
+        assert!((state_fidelity(&plus, &minus_i).unwrap() - 1.0).abs() < 1e-10);
+        assert!(state_fidelity(&zero, &[Complex::new(1.0, 0.0)]).is_err());
+
+        let mixed = vec![vec![Complex::new(0.5, 0.0), Complex::new(0.0, 0.0)], vec![Complex::new(0.0, 0.0), Complex::new(0.5, 0.0)]];
+        assert!((density_matrix_fidelity(&density_matrix(&zero), &density_matrix(&plus)) - 0.5).abs() < 1e-8);
+        assert!((density_matrix_fidelity(&density_matrix(&plus), &density_matrix(&plus)) - 1.0).abs() < 1e-8);
+        assert!((density_matrix_fidelity(&mixed, &density_matrix(&zero)) - 0.5).abs() < 1e-8);
+        assert!((density_matrix_fidelity(&mixed, &mixed) - 1.0).abs() < 1e-8);
+    }
+
+    #[test]
+    fn process_fidelity_ignores_global_phase() {
+        let one = Complex::new(1.0, 0.0);
+        let zero = Complex::new(0.0, 0.0);
+        let i = Complex::new(0.0, 1.0);
+        let identity = vec![vec![one, zero], vec![zero, one]];
+        let phased = vec![vec![i, zero], vec![zero, i]];
+        let z = vec![vec![one, zero], vec![zero, -one]];
+        let x = vec![vec![zero, one], vec![one, zero]];
+
+        assert!((process_fidelity(&phased, &identity).unwrap() - 1.0).abs() < 1e-10);
+        assert!(process_fidelity(&z, &identity).unwrap().abs() < 1e-10);
+        assert!(process_fidelity(&x, &z).unwrap().abs() < 1e-10);
+
+        let mut larger = vec![vec![zero; 4]; 4];
+        larger[0][0] = one;
+        assert!(process_fidelity(&identity, &larger).is_err());
+        assert!(process_fidelity(&larger, &identity).is_err());
+        assert!(process_fidelity(&[vec![one, zero]], &[vec![one, zero]]).is_err());
+    }
+
+    #[test]
+    fn partial_traces_of_qubits_and_qutrits() {
+        let h = FRAC_1_SQRT_2;
+        let zero = Complex::new(0.0, 0.0);
+        // |1⟩ ⊗ |+⟩.
+        let product = [zero, zero, Complex::new(h, 0.0), Complex::new(h, 0.0)];
+        let first = partial_trace(&product, &[0], &[2, 2]);
+        assert!(allclose(&first, &[vec![zero, zero], vec![zero, Complex::new(1.0, 0.0)]], 1e-10));
+        let second = partial_trace(&product, &[1], &[2, 2]);
+        assert!(allclose(&second, &density_matrix(&[Complex::new(h, 0.0), Complex::new(h, 0.0)]), 1e-10));
+
+        // Keeping every qid in a new order permutes the density matrix.
+        let swapped = partial_trace(&product, &[1, 0], &[2, 2]);
+        assert!(allclose(&swapped, &density_matrix(&[zero, Complex::new(h, 0.0), zero, Complex::new(h, 0.0)]), 1e-10));
+        assert!(allclose(&partial_trace_of_density_matrix(&density_matrix(&product), &[1], &[2, 2]), &second, 1e-10));
+
+        // A qubit and a qutrit in (|0, 2⟩ + |1, 0⟩) / √2.
+        let mut entangled = vec![zero; 6];
+        entangled[2] = Complex::new(h, 0.0);
+        entangled[3] = Complex::new(h, 0.0);
+        let qutrit = partial_trace(&entangled, &[1], &[2, 3]);
+        assert_eq!(qutrit.len(), 3);
+        assert!((qutrit[0][0] - Complex::new(0.5, 0.0)).norm() < 1e-10);
+        assert!((qutrit[2][2] - Complex::new(0.5, 0.0)).norm() < 1e-10);
+        assert!(qutrit[0][2].norm() < 1e-10);
+        assert!(allclose(&partial_trace_of_density_matrix(&density_matrix(&entangled), &[1], &[2, 3]), &qutrit, 1e-10));
+    }
+
+    #[test]
+    fn entanglement_of_bipartitions() {
+        let h = FRAC_1_SQRT_2;
+        let zero = Complex::new(0.0, 0.0);
+        let bell = [Complex::new(h, 0.0), zero, zero, Complex::new(h, 0.0)];
+        let product = [Complex::new(h, 0.0), Complex::new(h, 0.0), zero, zero];
+
+        assert!((entanglement_entropy(&bell, &[0], &[2, 2]) - 1.0).abs() < 1e-8);
+        assert!(entanglement_entropy(&product, &[1], &[2, 2]).abs() < 1e-8);
+        assert_eq!(schmidt_rank(&bell, &[0]).unwrap(), 2);
+        assert_eq!(schmidt_rank(&product, &[0]).unwrap(), 1);
+
+        let coefficients = schmidt_coefficients(&bell, &[1]).unwrap();
+        assert_eq!(coefficients.len(), 2);
+        assert!(coefficients.iter().all(|c| (c - h).abs() < 1e-8));
+
+        // A GHZ state on three qubits, split into one qubit and two.
+        let mut ghz = vec![zero; 8];
+        ghz[0] = Complex::new(h, 0.0);
+        ghz[7] = Complex::new(h, 0.0);
+        assert_eq!(schmidt_coefficients(&ghz, &[0, 2]).unwrap().len(), 2);
+        assert_eq!(schmidt_rank(&ghz, &[0, 2]).unwrap(), 2);
+
+        // Partitions that don't fit the state are rejected instead of panicking.
+        assert!(schmidt_coefficients(&bell, &[0, 1, 2]).is_err());
+        assert!(schmidt_coefficients(&bell, &[2]).is_err());
+        assert!(schmidt_coefficients(&bell, &[0, 0]).is_err());
+        assert!(schmidt_rank(&[zero; 3], &[0]).is_err());
+        assert!((entanglement_entropy(&ghz, &[1, 2], &[2, 2, 2]) - 1.0).abs() < 1e-8);
+    }
+
+    #[test]
+    fn bloch_vectors_of_poles() {
+        let h = FRAC_1_SQRT_2;
+        let zero = [Complex::new(1.0, 0.0), Complex::new(0.0, 0.0)];
+        let plus = [Complex::new(h, 0.0), Complex::new(h, 0.0)];
+        let plus_i = [Complex::new(h, 0.0), Complex::new(0.0, h)];
+
+        assert!(close(bloch_vector(&zero).unwrap(), [0.0, 0.0, 1.0]));
+        assert!(close(bloch_vector(&plus).unwrap(), [1.0, 0.0, 0.0]));
+        assert!(close(bloch_vector(&plus_i).unwrap(), [0.0, 1.0, 0.0]));
+
+        assert!(bloch_vector(&[Complex::new(1.0, 0.0), Complex::new(1.0, 0.0)]).is_err());
+        assert!(bloch_vector(&[Complex::new(1.0, 0.0); 4]).is_err());
+    }
+
+    #[test]
+    fn bloch_vectors_of_qubits_in_larger_states() {
+        let h = FRAC_1_SQRT_2;
+        let zero = Complex::new(0.0, 0.0);
+        // |0⟩ ⊗ |+⟩, and the Bell state (|00⟩ + |11⟩) / √2.
+        let product = [Complex::new(h, 0.0), Complex::new(h, 0.0), zero, zero];
+        let bell = [Complex::new(h, 0.0), zero, zero, Complex::new(h, 0.0)];
+
+        assert!(close(bloch_vector_of_qubit(&product, 0).unwrap(), [0.0, 0.0, 1.0]));
+        assert!(close(bloch_vector_of_qubit(&product, 1).unwrap(), [1.0, 0.0, 0.0]));
+        assert!(close(bloch_vector_of_qubit(&bell, 1).unwrap(), [0.0, 0.0, 0.0]));
+        assert!(bloch_vector_of_qubit(&bell, 2).is_err());
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::Error;
+
+use crate::circuits::moment::Moment;
+use crate::ops::channels::DepolarizingChannel;
+use crate::ops::measure::MeasurementGate;
+use crate::ops::raw_types::{Gate, Operation, QId};
+
+/// Replaces the moments of a circuit with noisy versions of them during simulation.
+pub trait NoiseModel {
+    /**
+     * Returns the moments to simulate in place of `moment`.
+     * `system_qubits` are all the qubits of the simulated circuit, including ones the moment doesn't act on.
+     */
+    fn noisy_moment(&self, moment: &Moment, system_qubits: &[Box<dyn QId>]) -> Vec<Moment>;
+}
+
+/// A noise model that leaves every moment unchanged.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoNoise;
+
+pub const NO_NOISE: NoNoise = NoNoise;
+
+impl NoiseModel for NoNoise {
+    fn noisy_moment(&self, moment: &Moment, _system_qubits: &[Box<dyn QId>]) -> Vec<Moment> {
+        vec![moment.clone()]
+    }
+}
+
+/**
+ * Depolarizes every qubit a gate acts on, with probability `p`, in a moment after the gate.
+ * Measurements are left noiseless.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct DepolarizingNoise {
+    pub p: f64,
+}
+
+impl DepolarizingNoise {
+    pub fn new(p: f64) -> Result<Self, Error> {
+        DepolarizingChannel::new(p)?;
+        Ok(Self { p })
+    }
+}
+
+impl NoiseModel for DepolarizingNoise {
+    fn noisy_moment(&self, moment: &Moment, _system_qubits: &[Box<dyn QId>]) -> Vec<Moment> {
+        let channel = DepolarizingChannel { p: self.p };
+        let noise: Vec<Box<dyn Operation>> = moment
+            .operations()
+            .iter()
+            .filter(|op| !op.gate().is_some_and(|gate| gate.as_any().is::<MeasurementGate>()))
+            .flat_map(|op| op.qubits())
+            .filter_map(|q| channel.on(vec![q]).ok())
+            .map(|op| Box::new(op) as Box<dyn Operation>)
+            .collect();
+
+        if noise.is_empty() {
+            return vec![moment.clone()];
+        }
+        // The operations of a moment act on distinct qubits, so the noise does too.
+        vec![moment.clone(), Moment::new(noise).unwrap()]
+    }
+}
+
+/// Applies the same single-qubit noise gate to every qubit of the system after each moment.
+#[derive(Clone)]
+pub struct ConstantQubitNoiseModel {
+    pub qubit_noise_gate: Box<dyn Gate>,
+}
+
+impl ConstantQubitNoiseModel {
+    pub fn new(qubit_noise_gate: Box<dyn Gate>) -> Result<Self, Error> {
+        if qubit_noise_gate.num_qubits() != 1 {
+            return Err(anyhow!(format!("The noise gate {} must act on a single qubit.", qubit_noise_gate)));
+        }
+        Ok(Self { qubit_noise_gate })
+    }
+}
+
+impl NoiseModel for ConstantQubitNoiseModel {
+    fn noisy_moment(&self, moment: &Moment, system_qubits: &[Box<dyn QId>]) -> Vec<Moment> {
+        let noise = system_qubits
+            .iter()
+            .filter_map(|q| self.qubit_noise_gate.on(vec![q.clone()]).ok())
+            .map(|op| Box::new(op) as Box<dyn Operation>)
+            .collect();
+        vec![moment.clone(), Moment::new(noise).unwrap()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_complex::Complex;
+
+    use crate::circuits::circuit::Circuit;
+    use crate::gate_on;
+    use crate::ops::measure::M;
+    use crate::ops::pauli_gates::X;
+    use crate::ops::qubits::LineQubit;
+    use crate::sim::density_matrix_simulator::DensityMatrixSimulator;
+
+    use super::*;
+
+    #[test]
+    fn depolarizing_noise_mixes_gate_qubits() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![Box::new(gate_on!(X, q[0].clone()).unwrap()), Box::new(M("m", &[&q[1]]))]).unwrap();
+
+        let simulator = DensityMatrixSimulator::new(Box::new(DepolarizingNoise::new(0.75).unwrap()));
+        let result = simulator.simulate(&circuit).unwrap();
+        let first = result.partial_trace(&[&q[0]]);
+        assert!((first.purity() - 0.5).abs() < 1e-10);
+        let second = result.partial_trace(&[&q[1]]);
+        assert!((second.density_matrix()[0][0] - Complex::new(1.0, 0.0)).norm() < 1e-10);
+
+        assert!(DepolarizingNoise::new(1.5).is_err());
+    }
+
+    #[test]
+    fn constant_noise_acts_on_every_qubit() {
+        let q = LineQubit::range(2);
+        let circuit = Circuit::from_moments(vec![
+            Moment::new(vec![Box::new(gate_on!(X, q[0].clone()).unwrap())]).unwrap(),
+            Moment::new(vec![Box::new(gate_on!(X, q[1].clone()).unwrap())]).unwrap(),
+        ]);
+
+        // Both moments are followed by a flip of both qubits, which cancel out: only the gates remain.
+        let noise = ConstantQubitNoiseModel::new(Box::new(X)).unwrap();
+        let result = DensityMatrixSimulator::new(Box::new(noise)).simulate(&circuit).unwrap();
+        assert!((result.density_matrix()[3][3] - Complex::new(1.0, 0.0)).norm() < 1e-10);
+
+        assert!(ConstantQubitNoiseModel::new(Box::new(crate::ops::common_gates::CNOT)).is_err());
+    }
+}
@@ -0,0 +1,545 @@
+use std::fmt;
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::linalg;
+use crate::ops::pauli_gates::Pauli;
+use crate::ops::raw_traits::{matrix_equality_value, EqValue, HasUnitary};
+use crate::ops::raw_types::{Gate, QIdShape};
+use crate::ops::wait::WaitGate;
+
+/**
+ * A channel on qubits given by its Kraus operators, ρ ↦ Σ K ρ K†.
+ * This is the most general representation of a quantum channel.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct KrausChannel {
+    operators: Vec<Vec<Vec<Complex<f64>>>>,
+}
+
+impl KrausChannel {
+    /**
+     * Creates a channel from its Kraus operators, which must be `2^n x 2^n` matrices for the same `n`.
+     * Fails unless the operators satisfy Σ K†K ≈ I, so that the channel preserves the trace.
+     */
+    pub fn new(operators: Vec<Vec<Vec<Complex<f64>>>>) -> Result<Self, Error> {
+        let dimension = operators.first().map_or(0, |operator| operator.len());
+        if !dimension.is_power_of_two() || dimension < 2 {
+            return Err(anyhow!(format!("Kraus operators must be 2^n x 2^n matrices with n > 0, but have {} rows.", dimension)));
+        }
+        if operators.iter().any(|operator| operator.len() != dimension || operator.iter().any(|row| row.len() != dimension)) {
+            return Err(anyhow!(format!("All Kraus operators must be {0} x {0} matrices.", dimension)));
+        }
+
+        let mut total = vec![vec![Complex::new(0.0, 0.0); dimension]; dimension];
+        for operator in &operators {
+            let product = linalg::dot(&linalg::dagger(operator), operator);
+            for (row, product_row) in total.iter_mut().zip(product) {
+                for (entry, product_entry) in row.iter_mut().zip(product_row) {
+                    *entry += product_entry;
+                }
+            }
+        }
+        if !linalg::allclose(&total, &linalg::eye(dimension), 1e-8) {
+            return Err(anyhow!("The Kraus operators of a channel must satisfy Σ K†K = I."));
+        }
+
+        Ok(Self {
+            operators,
+        })
+    }
+
+    /// Returns the amplitude damping channel, which decays |1⟩ to |0⟩ with probability `gamma`. Fails unless `gamma` is in [0, 1].
+    pub fn amplitude_damping(gamma: f64) -> Result<Self, Error> {
+        validate_probability("amplitude damping probability", gamma)?;
+        Self::new(amplitude_damping_operators(gamma))
+    }
+
+    /// Returns the single-qubit depolarizing channel with probability `p`. Fails unless `p` is in [0, 1].
+    pub fn depolarizing(p: f64) -> Result<Self, Error> {
+        Self::new(DepolarizingChannel::new(p)?.kraus().unwrap())
+    }
+
+    pub fn operators(&self) -> &[Vec<Vec<Complex<f64>>>] {
+        &self.operators
+    }
+
+    /**
+     * Returns the superoperator Σ K ⊗ K̄ of the channel, which acts on the density matrix flattened in
+     * row-major order: vec(Σ K ρ K†) = S vec(ρ).
+     */
+    pub fn superoperator(&self) -> Vec<Vec<Complex<f64>>> {
+        let dimension = self.operators[0].len();
+        let mut superoperator = vec![vec![Complex::new(0.0, 0.0); dimension * dimension]; dimension * dimension];
+        for operator in &self.operators {
+            for (row, superoperator_row) in superoperator.iter_mut().enumerate() {
+                let (i, k) = (row / dimension, row % dimension);
+                for (column, entry) in superoperator_row.iter_mut().enumerate() {
+                    let (j, l) = (column / dimension, column % dimension);
+                    *entry += operator[i][j] * operator[k][l].conj();
+                }
+            }
+        }
+        superoperator
+    }
+}
+
+/// Returns the Kraus operators [[1, 0], [0, √(1 - γ)]] and [[0, √γ], [0, 0]] of amplitude damping.
+fn amplitude_damping_operators(gamma: f64) -> Vec<Vec<Vec<Complex<f64>>>> {
+    let zero = Complex::new(0.0, 0.0);
+    let one = Complex::new(1.0, 0.0);
+    vec![
+        vec![vec![one, zero], vec![zero, Complex::new((1.0 - gamma).sqrt(), 0.0)]],
+        vec![vec![zero, Complex::new(gamma.sqrt(), 0.0)], vec![zero, zero]],
+    ]
+}
+
+impl fmt::Display for KrausChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operators: Vec<String> = self.operators
+            .iter()
+            .map(|operator| {
+                let rows: Vec<String> = operator
+                    .iter()
+                    .map(|row| format!("[{}]", row.iter().map(|entry| entry.to_string()).collect::<Vec<_>>().join(", ")))
+                    .collect();
+                format!("[{}]", rows.join(", "))
+            })
+            .collect();
+        write!(f, "KrausChannel([{}])", operators.join(", "))
+    }
+}
+
+impl QIdShape for KrausChannel {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2; self.operators[0].len().trailing_zeros() as usize]
+    }
+}
+
+impl HasUnitary for KrausChannel {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        None
+    }
+}
+
+impl Gate for KrausChannel {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![
+            EqValue::String("KrausChannel".to_string()),
+            EqValue::Tuple(self.operators.iter().map(|operator| matrix_equality_value(operator)).collect()),
+        ])
+    }
+
+    fn pow(&self, _exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Err(anyhow!("A Kraus channel can't be raised to a power."))
+    }
+
+    fn wire_symbols(&self) -> Vec<String> {
+        let mut symbols = vec!["Kraus".to_string()];
+        symbols.extend((2..=self.num_qubits()).map(|i| format!("#{}", i)));
+        symbols
+    }
+
+    fn kraus(&self) -> Option<Vec<Vec<Vec<Complex<f64>>>>> {
+        Some(self.operators.clone())
+    }
+}
+
+/**
+ * A single-qubit channel that applies each of X, Y and Z with probability `p / 3`,
+ * and leaves the qubit unchanged with probability `1 - p`.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DepolarizingChannel {
+    pub p: f64,
+}
+
+impl DepolarizingChannel {
+    pub fn new(p: f64) -> Result<Self, Error> {
+        if !(0.0..=1.0).contains(&p) {
+            return Err(anyhow!(format!("The depolarizing probability must be in [0, 1], but is {}.", p)));
+        }
+        Ok(Self { p })
+    }
+}
+
+/// Returns the depolarizing channel with probability `p`.
+pub fn depolarize(p: f64) -> Result<DepolarizingChannel, Error> {
+    DepolarizingChannel::new(p)
+}
+
+impl fmt::Display for DepolarizingChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "depolarize(p={})", self.p)
+    }
+}
+
+impl QIdShape for DepolarizingChannel {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2]
+    }
+}
+
+impl HasUnitary for DepolarizingChannel {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        None
+    }
+}
+
+impl Gate for DepolarizingChannel {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![EqValue::String("DepolarizingChannel".to_string()), EqValue::Float(self.p)])
+    }
+
+    fn pow(&self, _exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Err(anyhow!("A depolarizing channel can't be raised to a power."))
+    }
+
+    fn wire_symbols(&self) -> Vec<String> {
+        vec![format!("D({})", self.p)]
+    }
+
+    fn kraus(&self) -> Option<Vec<Vec<Vec<Complex<f64>>>>> {
+        let scaled = |pauli: Pauli, weight: f64| {
+            pauli.unitary().iter().map(|row| row.iter().map(|x| x * weight.sqrt()).collect()).collect()
+        };
+        Some(vec![
+            scaled(Pauli::I, 1.0 - self.p),
+            scaled(Pauli::X, self.p / 3.0),
+            scaled(Pauli::Y, self.p / 3.0),
+            scaled(Pauli::Z, self.p / 3.0),
+        ])
+    }
+}
+
+/// Checks that a channel parameter is a probability.
+fn validate_probability(name: &str, value: f64) -> Result<(), Error> {
+    if !(0.0..=1.0).contains(&value) {
+        return Err(anyhow!(format!("The {} must be in [0, 1], but is {}.", name, value)));
+    }
+    Ok(())
+}
+
+fn real(x: f64) -> Complex<f64> {
+    Complex::new(x, 0.0)
+}
+
+/**
+ * The T1 decay of a qubit, which relaxes |1⟩ to |0⟩ with probability `gamma`.
+ * The Kraus operators are [[1, 0], [0, √(1 - γ)]] and [[0, √γ], [0, 0]].
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AmplitudeDampingChannel {
+    pub gamma: f64,
+}
+
+impl AmplitudeDampingChannel {
+    pub fn new(gamma: f64) -> Result<Self, Error> {
+        validate_probability("amplitude damping probability", gamma)?;
+        Ok(Self { gamma })
+    }
+
+    /// Returns the decay of a qubit with relaxation time `t1_nanos` over `duration_nanos`, γ = 1 - e^(-t / T1).
+    pub fn from_t1(t1_nanos: f64, duration_nanos: f64) -> Result<Self, Error> {
+        if t1_nanos <= 0.0 || duration_nanos < 0.0 {
+            return Err(anyhow!(format!("Expected T1 > 0 and a non-negative duration, but got {} and {}.", t1_nanos, duration_nanos)));
+        }
+        Self::new(1.0 - (-duration_nanos / t1_nanos).exp())
+    }
+}
+
+/// Returns the amplitude damping channel with probability `gamma`.
+pub fn amplitude_damp(gamma: f64) -> Result<AmplitudeDampingChannel, Error> {
+    AmplitudeDampingChannel::new(gamma)
+}
+
+impl fmt::Display for AmplitudeDampingChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "amplitude_damp(gamma={})", self.gamma)
+    }
+}
+
+impl QIdShape for AmplitudeDampingChannel {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2]
+    }
+}
+
+impl HasUnitary for AmplitudeDampingChannel {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        None
+    }
+}
+
+impl Gate for AmplitudeDampingChannel {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![EqValue::String("AmplitudeDampingChannel".to_string()), EqValue::Float(self.gamma)])
+    }
+
+    fn pow(&self, _exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Err(anyhow!("An amplitude damping channel can't be raised to a power."))
+    }
+
+    fn wire_symbols(&self) -> Vec<String> {
+        vec![format!("AD({})", self.gamma)]
+    }
+
+    fn kraus(&self) -> Option<Vec<Vec<Vec<Complex<f64>>>>> {
+        Some(amplitude_damping_operators(self.gamma))
+    }
+}
+
+/**
+ * The pure dephasing of a qubit, which shrinks its coherences by √(1 - γ) and leaves the populations unchanged.
+ * The Kraus operators are [[1, 0], [0, √(1 - γ)]] and [[0, 0], [0, √γ]].
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhaseDampingChannel {
+    pub gamma: f64,
+}
+
+impl PhaseDampingChannel {
+    pub fn new(gamma: f64) -> Result<Self, Error> {
+        validate_probability("phase damping probability", gamma)?;
+        Ok(Self { gamma })
+    }
+
+    /// Returns the dephasing of a qubit with pure dephasing time `t_phi_nanos` over `duration_nanos`.
+    pub fn from_t_phi(t_phi_nanos: f64, duration_nanos: f64) -> Result<Self, Error> {
+        if t_phi_nanos <= 0.0 || duration_nanos < 0.0 {
+            return Err(anyhow!(format!("Expected Tφ > 0 and a non-negative duration, but got {} and {}.", t_phi_nanos, duration_nanos)));
+        }
+        Self::new(1.0 - (-2.0 * duration_nanos / t_phi_nanos).exp())
+    }
+}
+
+/// Returns the phase damping channel with probability `gamma`.
+pub fn phase_damp(gamma: f64) -> Result<PhaseDampingChannel, Error> {
+    PhaseDampingChannel::new(gamma)
+}
+
+impl fmt::Display for PhaseDampingChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "phase_damp(gamma={})", self.gamma)
+    }
+}
+
+impl QIdShape for PhaseDampingChannel {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2]
+    }
+}
+
+impl HasUnitary for PhaseDampingChannel {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        None
+    }
+}
+
+impl Gate for PhaseDampingChannel {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![EqValue::String("PhaseDampingChannel".to_string()), EqValue::Float(self.gamma)])
+    }
+
+    fn pow(&self, _exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Err(anyhow!("A phase damping channel can't be raised to a power."))
+    }
+
+    fn wire_symbols(&self) -> Vec<String> {
+        vec![format!("PD({})", self.gamma)]
+    }
+
+    fn kraus(&self) -> Option<Vec<Vec<Vec<Complex<f64>>>>> {
+        let zero = real(0.0);
+        Some(vec![
+            vec![vec![real(1.0), zero], vec![zero, real((1.0 - self.gamma).sqrt())]],
+            vec![vec![zero, zero], vec![zero, real(self.gamma.sqrt())]],
+        ])
+    }
+}
+
+/**
+ * The decoherence of an idle qubit over `duration_nanos`, with relaxation time T1 and dephasing time T2.
+ * It is amplitude damping with γ = 1 - e^(-t / T1), followed by the pure dephasing that brings the
+ * coherences down to e^(-t / T2). This requires T2 ≤ 2 T1.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ThermalRelaxationChannel {
+    pub t1_nanos: f64,
+    pub t2_nanos: f64,
+    pub duration_nanos: f64,
+}
+
+impl ThermalRelaxationChannel {
+    pub fn new(t1_nanos: f64, t2_nanos: f64, duration_nanos: f64) -> Result<Self, Error> {
+        if t1_nanos <= 0.0 || t2_nanos <= 0.0 || duration_nanos < 0.0 {
+            return Err(anyhow!(format!(
+                "Expected T1 > 0, T2 > 0 and a non-negative duration, but got {}, {} and {}.",
+                t1_nanos, t2_nanos, duration_nanos,
+            )));
+        }
+        if t2_nanos > 2.0 * t1_nanos {
+            return Err(anyhow!(format!("T2 = {} can't be longer than 2 T1 = {}.", t2_nanos, 2.0 * t1_nanos)));
+        }
+        Ok(Self { t1_nanos, t2_nanos, duration_nanos })
+    }
+
+    /// Returns the decoherence of a qubit over the duration of the wait gate.
+    pub fn during(wait: &WaitGate, t1_nanos: f64, t2_nanos: f64) -> Result<Self, Error> {
+        Self::new(t1_nanos, t2_nanos, wait.duration().as_secs_f64() * 1e9)
+    }
+
+    fn amplitude_damping(&self) -> AmplitudeDampingChannel {
+        AmplitudeDampingChannel { gamma: 1.0 - (-self.duration_nanos / self.t1_nanos).exp() }
+    }
+
+    fn phase_damping(&self) -> PhaseDampingChannel {
+        // Amplitude damping already shrinks the coherences by e^(-t / 2 T1).
+        let rate = 1.0 / self.t2_nanos - 0.5 / self.t1_nanos;
+        PhaseDampingChannel { gamma: 1.0 - (-2.0 * self.duration_nanos * rate).exp() }
+    }
+}
+
+/// Returns the thermal relaxation of a qubit with the given T1 and T2 over `duration_nanos`.
+pub fn thermal_relaxation(t1_nanos: f64, t2_nanos: f64, duration_nanos: f64) -> Result<ThermalRelaxationChannel, Error> {
+    ThermalRelaxationChannel::new(t1_nanos, t2_nanos, duration_nanos)
+}
+
+impl fmt::Display for ThermalRelaxationChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "thermal_relaxation(t1={}ns, t2={}ns, duration={}ns)", self.t1_nanos, self.t2_nanos, self.duration_nanos)
+    }
+}
+
+impl QIdShape for ThermalRelaxationChannel {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2]
+    }
+}
+
+impl HasUnitary for ThermalRelaxationChannel {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        None
+    }
+}
+
+impl Gate for ThermalRelaxationChannel {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![
+            EqValue::String("ThermalRelaxationChannel".to_string()),
+            EqValue::Float(self.t1_nanos),
+            EqValue::Float(self.t2_nanos),
+            EqValue::Float(self.duration_nanos),
+        ])
+    }
+
+    fn pow(&self, _exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Err(anyhow!("A thermal relaxation channel can't be raised to a power."))
+    }
+
+    fn wire_symbols(&self) -> Vec<String> {
+        vec![format!("TR({}ns)", self.duration_nanos)]
+    }
+
+    fn kraus(&self) -> Option<Vec<Vec<Vec<Complex<f64>>>>> {
+        let damping = self.amplitude_damping().kraus()?;
+        let dephasing = self.phase_damping().kraus()?;
+        Some(
+            dephasing
+                .iter()
+                .flat_map(|p| damping.iter().map(move |a| linalg::dot(p, a)))
+                .collect()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kraus_channels_must_preserve_the_trace() {
+        let one = Complex::new(1.0, 0.0);
+        let zero = Complex::new(0.0, 0.0);
+        assert!(KrausChannel::new(vec![linalg::eye(2)]).is_ok());
+        assert!(KrausChannel::new(vec![vec![vec![one, zero], vec![zero, zero]]]).is_err());
+        assert!(KrausChannel::new(vec![linalg::eye(3)]).is_err());
+        assert!(KrausChannel::new(vec![]).is_err());
+
+        let damping = KrausChannel::amplitude_damping(0.3).unwrap();
+        assert!(KrausChannel::new(damping.operators().to_vec()).is_ok());
+        assert!(KrausChannel::new(KrausChannel::depolarizing(0.2).unwrap().operators().to_vec()).is_ok());
+        assert!(KrausChannel::amplitude_damping(1.5).is_err());
+        assert!(KrausChannel::amplitude_damping(f64::NAN).is_err());
+        assert!(KrausChannel::depolarizing(-0.1).is_err());
+        assert_eq!(KrausChannel::new(vec![linalg::eye(4)]).unwrap().num_qubits(), 2);
+        assert!(damping.unitary().is_none());
+    }
+
+    #[test]
+    fn superoperator_acts_on_flattened_density_matrices() {
+        let gamma = 0.25;
+        let superoperator = KrausChannel::amplitude_damping(gamma).unwrap().superoperator();
+        assert_eq!(superoperator.len(), 4);
+
+        // |1⟩⟨1| flattens to [0, 0, 0, 1] and decays to (1 - γ)|1⟩⟨1| + γ|0⟩⟨0|.
+        let decayed: Vec<Complex<f64>> = superoperator.iter().map(|row| row[3]).collect();
+        let expected = [gamma, 0.0, 0.0, 1.0 - gamma];
+        assert!(decayed.iter().zip(expected).all(|(a, b)| (a - b).norm() < 1e-10));
+
+        // Coherences shrink by sqrt(1 - γ).
+        assert!((superoperator[1][1] - Complex::new((1.0 - gamma).sqrt(), 0.0)).norm() < 1e-10);
+    }
+
+    /// Applies the channel to the single-qubit density matrix.
+    fn apply(channel: &dyn Gate, rho: &[Vec<Complex<f64>>]) -> Vec<Vec<Complex<f64>>> {
+        let mut result = vec![vec![real(0.0); 2]; 2];
+        for k in channel.kraus().unwrap() {
+            let term = linalg::dot(&linalg::dot(&k, rho), &linalg::dagger(&k));
+            for i in 0..2 {
+                for j in 0..2 {
+                    result[i][j] += term[i][j];
+                }
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn damping_channels_are_valid() {
+        for channel in [
+            Box::new(amplitude_damp(0.3).unwrap()) as Box<dyn Gate>,
+            Box::new(phase_damp(0.6).unwrap()),
+            Box::new(thermal_relaxation(100.0, 150.0, 40.0).unwrap()),
+        ] {
+            assert!(KrausChannel::new(channel.kraus().unwrap()).is_ok(), "{}", channel);
+            assert!(channel.unitary().is_none());
+        }
+
+        assert!(amplitude_damp(1.2).is_err());
+        assert!(phase_damp(-0.1).is_err());
+        assert!(thermal_relaxation(100.0, 250.0, 10.0).is_err());
+        assert!(thermal_relaxation(0.0, 10.0, 10.0).is_err());
+    }
+
+    #[test]
+    fn thermal_relaxation_matches_t1_and_t2() {
+        let (t1, t2, t) = (100.0, 80.0, 30.0);
+        let half = real(0.5);
+        let plus = vec![vec![half, half], vec![half, half]];
+        let rho = apply(&thermal_relaxation(t1, t2, t).unwrap(), &plus);
+
+        assert!((rho[1][1].re - 0.5 * (-t / t1).exp()).abs() < 1e-10);
+        assert!((rho[0][1].re - 0.5 * (-t / t2).exp()).abs() < 1e-10);
+
+        let damping = AmplitudeDampingChannel::from_t1(t1, t).unwrap();
+        assert!((damping.gamma - (1.0 - (-t / t1).exp())).abs() < 1e-12);
+        let dephased = apply(&PhaseDampingChannel::from_t_phi(50.0, t).unwrap(), &plus);
+        assert!((dephased[0][1].re - 0.5 * (-t / 50.0).exp()).abs() < 1e-10);
+        assert!((dephased[1][1].re - 0.5).abs() < 1e-10);
+
+        let wait = WaitGate::new(t, 1).unwrap();
+        assert!((ThermalRelaxationChannel::during(&wait, t1, t2).unwrap().duration_nanos - t).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,307 @@
+use std::fmt;
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::ops::pauli_gates::Pauli;
+use crate::ops::raw_traits::{EqValue, HasUnitary};
+use crate::ops::raw_types::{Gate, MatrixPowGate, QIdShape};
+use crate::sim::clifford::{row_pauli, CliffordTableau};
+
+/// The image of a Pauli operator under conjugation by a Clifford gate: `to`, negated if `flip` is set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PauliTransform {
+    pub to: Pauli,
+    pub flip: bool,
+}
+
+impl PauliTransform {
+    pub const fn new(to: Pauli, flip: bool) -> Self {
+        Self {
+            to,
+            flip,
+        }
+    }
+}
+
+/**
+ * A single qubit Clifford gate, identified by where it maps X and Z under conjugation: U X U† and U Z U†.
+ * Every choice of two different non-identity Paulis and two signs is a Clifford gate, which gives the 24 gates of the group.
+ * The images of X and Z determine the gate up to a global phase.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SingleQubitCliffordGate {
+    pub x_to: PauliTransform,
+    pub z_to: PauliTransform,
+}
+
+const fn clifford(x_to: Pauli, x_flip: bool, z_to: Pauli, z_flip: bool) -> SingleQubitCliffordGate {
+    SingleQubitCliffordGate {
+        x_to: PauliTransform::new(x_to, x_flip),
+        z_to: PauliTransform::new(z_to, z_flip),
+    }
+}
+
+impl SingleQubitCliffordGate {
+    pub const I: Self = clifford(Pauli::X, false, Pauli::Z, false);
+    pub const X: Self = clifford(Pauli::X, false, Pauli::Z, true);
+    pub const Y: Self = clifford(Pauli::X, true, Pauli::Z, true);
+    pub const Z: Self = clifford(Pauli::X, true, Pauli::Z, false);
+
+    pub const H: Self = clifford(Pauli::Z, false, Pauli::X, false);
+    pub const H_NXZ: Self = clifford(Pauli::Z, true, Pauli::X, true);
+    pub const H_XY: Self = clifford(Pauli::Y, false, Pauli::Z, true);
+    pub const H_NXY: Self = clifford(Pauli::Y, true, Pauli::Z, true);
+    pub const H_YZ: Self = clifford(Pauli::X, true, Pauli::Y, false);
+    pub const H_NYZ: Self = clifford(Pauli::X, true, Pauli::Y, true);
+
+    pub const S: Self = clifford(Pauli::Y, false, Pauli::Z, false);
+    pub const S_DAG: Self = clifford(Pauli::Y, true, Pauli::Z, false);
+    pub const SQRT_X: Self = clifford(Pauli::X, false, Pauli::Y, true);
+    pub const SQRT_X_DAG: Self = clifford(Pauli::X, false, Pauli::Y, false);
+    pub const SQRT_Y: Self = clifford(Pauli::Z, true, Pauli::X, false);
+    pub const SQRT_Y_DAG: Self = clifford(Pauli::Z, false, Pauli::X, true);
+
+    pub const C_XYZ: Self = clifford(Pauli::Y, false, Pauli::X, false);
+    pub const C_NXYZ: Self = clifford(Pauli::Y, true, Pauli::X, true);
+    pub const C_XNYZ: Self = clifford(Pauli::Y, true, Pauli::X, false);
+    pub const C_XYNZ: Self = clifford(Pauli::Y, false, Pauli::X, true);
+    pub const C_ZYX: Self = clifford(Pauli::Z, false, Pauli::Y, false);
+    pub const C_NZYX: Self = clifford(Pauli::Z, true, Pauli::Y, true);
+    pub const C_ZNYX: Self = clifford(Pauli::Z, false, Pauli::Y, true);
+    pub const C_ZYNX: Self = clifford(Pauli::Z, true, Pauli::Y, false);
+
+    /// Returns the names and gates of the whole single qubit Clifford group.
+    pub fn all() -> [(&'static str, Self); 24] {
+        [
+            ("I", Self::I), ("X", Self::X), ("Y", Self::Y), ("Z", Self::Z),
+            ("H", Self::H), ("H_NXZ", Self::H_NXZ), ("H_XY", Self::H_XY),
+            ("H_NXY", Self::H_NXY), ("H_YZ", Self::H_YZ), ("H_NYZ", Self::H_NYZ),
+            ("S", Self::S), ("S_DAG", Self::S_DAG), ("SQRT_X", Self::SQRT_X),
+            ("SQRT_X_DAG", Self::SQRT_X_DAG), ("SQRT_Y", Self::SQRT_Y), ("SQRT_Y_DAG", Self::SQRT_Y_DAG),
+            ("C_XYZ", Self::C_XYZ), ("C_NXYZ", Self::C_NXYZ), ("C_XNYZ", Self::C_XNYZ),
+            ("C_XYNZ", Self::C_XYNZ), ("C_ZYX", Self::C_ZYX), ("C_NZYX", Self::C_NZYX),
+            ("C_ZNYX", Self::C_ZNYX), ("C_ZYNX", Self::C_ZYNX),
+        ]
+    }
+
+    /// Returns the image of `pauli` under conjugation by the gate.
+    pub fn transform(&self, pauli: Pauli) -> PauliTransform {
+        match pauli {
+            Pauli::I => PauliTransform::new(Pauli::I, false),
+            Pauli::X => self.x_to,
+            Pauli::Z => self.z_to,
+            Pauli::Y => {
+                // Y = iXZ, so U Y U† = i (U X U†)(U Z U†).
+                let (to, phase) = Pauli::product(self.x_to.to, self.z_to.to);
+                let flip = (Complex::new(0.0, 1.0) * phase).re < 0.0;
+                PauliTransform::new(to, flip ^ self.x_to.flip ^ self.z_to.flip)
+            }
+        }
+    }
+
+    /**
+     * Reads the gate from a single qubit tableau, whose destabilizer is the image of X
+     * and whose stabilizer is the image of Z. Fails unless the tableau has exactly 1 qubit.
+     */
+    pub fn from_clifford_tableau(tableau: &CliffordTableau) -> Result<Self, Error> {
+        let rows_fit = |rows: &[Vec<bool>]| rows.len() == 2 && rows.iter().all(|row| row.len() == 1);
+        if tableau.n != 1 || !rows_fit(&tableau.xs) || !rows_fit(&tableau.zs) || tableau.rs.len() != 2 {
+            return Err(anyhow!(format!(
+                "A single qubit Clifford gate needs a 1 qubit tableau, but got a tableau of {} qubits.",
+                tableau.n
+            )));
+        }
+
+        let image = |row: usize| PauliTransform::new(row_pauli(tableau.xs[row][0], tableau.zs[row][0]), tableau.rs[row]);
+        Ok(Self {
+            x_to: image(0),
+            z_to: image(1),
+        })
+    }
+
+    /// Returns the Clifford gate with the given 2 x 2 unitary up to a global phase, or None if it isn't a Clifford.
+    pub fn from_unitary(unitary: &[Vec<Complex<f64>>]) -> Option<Self> {
+        if unitary.len() != 2 || unitary.iter().any(|row| row.len() != 2) {
+            return None;
+        }
+        Self::all().iter().map(|(_, gate)| *gate).find(|gate| {
+            let candidate = gate.unitary().unwrap_or_default();
+            let overlap: Complex<f64> = candidate.iter().flatten().zip(unitary.iter().flatten()).map(|(a, b)| a.conj() * b).sum();
+            (overlap.norm() - 2.0).abs() < 1e-8
+        })
+    }
+
+    /// Returns the gate that applies `self` and then `other`.
+    pub fn compose(self, other: &Self) -> Self {
+        let then = |image: PauliTransform| {
+            let transformed = other.transform(image.to);
+            PauliTransform::new(transformed.to, transformed.flip ^ image.flip)
+        };
+        Self {
+            x_to: then(self.x_to),
+            z_to: then(self.z_to),
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self::all()
+            .iter()
+            .map(|(_, gate)| *gate)
+            .find(|gate| self.compose(gate) == Self::I)
+            .unwrap_or(Self::I)
+    }
+
+    /// Returns the name of the gate among the 24 named constants.
+    pub fn name(&self) -> &'static str {
+        Self::all().iter().find(|(_, gate)| gate == self).map(|(name, _)| *name).unwrap_or("?")
+    }
+}
+
+impl fmt::Display for SingleQubitCliffordGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl QIdShape for SingleQubitCliffordGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2]
+    }
+}
+
+impl HasUnitary for SingleQubitCliffordGate {
+    /**
+     * Returns a unitary implementing the gate. U|0⟩ is the +1 eigenvector of the image of Z,
+     * and U|1⟩ = U X|0⟩ is the image of X applied to U|0⟩.
+     */
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        let signed = |image: PauliTransform| {
+            let sign = if image.flip { -1.0 } else { 1.0 };
+            image.to.unitary().map(|row| row.map(|entry| entry * sign))
+        };
+        let (x_image, z_image) = (signed(self.x_to), signed(self.z_to));
+
+        // The columns of (I + Z') / 2 are multiples of the +1 eigenvector of Z'.
+        let projector = |column: usize| -> [Complex<f64>; 2] {
+            let identity = |row: usize| if row == column { 1.0 } else { 0.0 };
+            [(z_image[0][column] + identity(0)) / 2.0, (z_image[1][column] + identity(1)) / 2.0]
+        };
+        let (a, b) = (projector(0), projector(1));
+        let zero = if a[0].norm_sqr() + a[1].norm_sqr() > b[0].norm_sqr() + b[1].norm_sqr() { a } else { b };
+        let norm = (zero[0].norm_sqr() + zero[1].norm_sqr()).sqrt();
+        let zero = [zero[0] / norm, zero[1] / norm];
+        let one = [
+            x_image[0][0] * zero[0] + x_image[0][1] * zero[1],
+            x_image[1][0] * zero[0] + x_image[1][1] * zero[1],
+        ];
+
+        Some(vec![vec![zero[0], one[0]], vec![zero[1], one[1]]])
+    }
+}
+
+impl Gate for SingleQubitCliffordGate {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![
+            EqValue::String("SingleQubitCliffordGate".to_string()),
+            EqValue::String(format!("{:?}", self.x_to.to)),
+            EqValue::Int(self.x_to.flip as i64),
+            EqValue::String(format!("{:?}", self.z_to.to)),
+            EqValue::Int(self.z_to.flip as i64),
+        ])
+    }
+
+    /// Integer powers stay in the Clifford group; other powers fall back to a `MatrixPowGate`.
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        if exponent.fract() != 0.0 {
+            return Ok(Box::new(MatrixPowGate::new(Box::new(*self), exponent)));
+        }
+
+        let base = if exponent < 0.0 { SingleQubitCliffordGate::inverse(self) } else { *self };
+        // Every single qubit Clifford has an order dividing 24.
+        let count = (exponent.abs() as u64) % 24;
+        Ok(Box::new((0..count).fold(Self::I, |power, _| power.compose(&base))))
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(SingleQubitCliffordGate::inverse(self)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::linalg;
+    use crate::ops::common_gates::{H, S};
+    use crate::ops::pauli_gates::{X, Y};
+
+    use super::*;
+
+    /// Determines if two matrices are equal up to a global phase.
+    fn equal_up_to_phase(a: &[Vec<Complex<f64>>], b: &[Vec<Complex<f64>>]) -> bool {
+        let overlap: Complex<f64> = a.iter().flatten().zip(b.iter().flatten()).map(|(x, y)| x.conj() * y).sum();
+        (overlap.norm() - a.len() as f64).abs() < 1e-10
+    }
+
+    #[test]
+    fn constants_form_the_clifford_group() {
+        let all = SingleQubitCliffordGate::all();
+        for (i, (name, gate)) in all.iter().enumerate() {
+            assert_eq!(gate.to_string(), *name);
+            assert!(all[..i].iter().all(|(_, other)| other != gate));
+
+            // The unitary conjugates X and Z to their images.
+            let u = gate.unitary().unwrap();
+            for pauli in [Pauli::X, Pauli::Y, Pauli::Z] {
+                let image = gate.transform(pauli);
+                let sign = if image.flip { -1.0 } else { 1.0 };
+                let expected: Vec<Vec<Complex<f64>>> =
+                    image.to.unitary().iter().map(|row| row.iter().map(|x| x * sign).collect()).collect();
+                let p: Vec<Vec<Complex<f64>>> = pauli.unitary().iter().map(|row| row.to_vec()).collect();
+                let actual = linalg::dot(&linalg::dot(&u, &p), &linalg::dagger(&u));
+                assert!(linalg::allclose(&actual, &expected, 1e-10), "{} doesn't map {} correctly", name, pauli);
+            }
+        }
+
+        assert!(equal_up_to_phase(&SingleQubitCliffordGate::H.unitary().unwrap(), &H.unitary().unwrap()));
+        assert!(equal_up_to_phase(&SingleQubitCliffordGate::S.unitary().unwrap(), &S.unitary().unwrap()));
+        assert!(equal_up_to_phase(&SingleQubitCliffordGate::X.unitary().unwrap(), &X.unitary().unwrap()));
+        assert!(equal_up_to_phase(&SingleQubitCliffordGate::Y.unitary().unwrap(), &Y.unitary().unwrap()));
+        assert_eq!(SingleQubitCliffordGate::from_unitary(&H.unitary().unwrap()), Some(SingleQubitCliffordGate::H));
+        assert_eq!(SingleQubitCliffordGate::from_unitary(&crate::ops::common_gates::T.unitary().unwrap()), None);
+    }
+
+    #[test]
+    fn multiplication_table_matches_unitaries() {
+        let all = SingleQubitCliffordGate::all();
+        for (_, a) in &all {
+            for (_, b) in &all {
+                let product = a.compose(b);
+                assert!(all.iter().any(|(_, gate)| *gate == product));
+                let expected = linalg::dot(&b.unitary().unwrap(), &a.unitary().unwrap());
+                assert!(equal_up_to_phase(&product.unitary().unwrap(), &expected));
+            }
+            assert_eq!(a.compose(&SingleQubitCliffordGate::inverse(a)), SingleQubitCliffordGate::I);
+        }
+
+        assert_eq!(SingleQubitCliffordGate::S.compose(&SingleQubitCliffordGate::S), SingleQubitCliffordGate::Z);
+        assert_eq!(SingleQubitCliffordGate::SQRT_X.compose(&SingleQubitCliffordGate::SQRT_X), SingleQubitCliffordGate::X);
+        let cube = SingleQubitCliffordGate::C_XYZ.pow(3.0).unwrap();
+        assert_eq!(cube.to_string(), "I");
+    }
+
+    #[test]
+    fn reads_gates_from_tableaus() {
+        // Conjugating by S then H, as a tableau, is the gate S followed by H.
+        let mut tableau = CliffordTableau::new(1);
+        tableau.apply_s(0);
+        tableau.apply_h(0);
+        let expected = SingleQubitCliffordGate::S.compose(&SingleQubitCliffordGate::H);
+        assert_eq!(SingleQubitCliffordGate::from_clifford_tableau(&tableau).unwrap(), expected);
+
+        assert!(SingleQubitCliffordGate::from_clifford_tableau(&CliffordTableau::new(0)).is_err());
+        assert!(SingleQubitCliffordGate::from_clifford_tableau(&CliffordTableau::new(2)).is_err());
+        let mut truncated = CliffordTableau::new(1);
+        truncated.xs[1].clear();
+        assert!(SingleQubitCliffordGate::from_clifford_tableau(&truncated).is_err());
+    }
+}
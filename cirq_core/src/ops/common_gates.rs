@@ -0,0 +1,1025 @@
+use std::f64::consts::{FRAC_1_SQRT_2, FRAC_PI_4, PI};
+use std::fmt;
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::linalg;
+use crate::ops::gate_operation::GateOperation;
+use crate::ops::raw_traits::{EqValue, HasUnitary};
+use crate::ops::raw_types::{Gate, MatrixPowGate, QId, QIdShape};
+
+/// A gate that leaves every qubit it is applied to unchanged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdentityGate {
+    pub num_qubits: usize,
+}
+
+/// The single qubit identity gate.
+pub const I: IdentityGate = IdentityGate { num_qubits: 1 };
+
+impl IdentityGate {
+    pub fn new(num_qubits: usize) -> Self {
+        Self {
+            num_qubits
+        }
+    }
+
+    /// Returns the real 2^n x 2^n identity matrix. `HasUnitary::unitary` gives the same matrix with complex entries.
+    pub fn unitary_matrix(&self) -> Vec<Vec<f64>> {
+        self.unitary().unwrap().iter().map(|row| row.iter().map(|entry| entry.re).collect()).collect()
+    }
+}
+
+impl fmt::Display for IdentityGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.num_qubits == 1 {
+            write!(f, "I")
+        } else {
+            write!(f, "I({})", self.num_qubits)
+        }
+    }
+}
+
+impl QIdShape for IdentityGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2; self.num_qubits]
+    }
+}
+
+impl HasUnitary for IdentityGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        Some(linalg::eye(1 << self.num_qubits))
+    }
+}
+
+impl Gate for IdentityGate {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![EqValue::String("IdentityGate".to_string()), EqValue::Int(self.num_qubits as i64)])
+    }
+
+    fn pow(&self, _exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(self.clone()))
+    }
+}
+
+/**
+ * Raises a self-inverse gate to a power: the gate itself for odd exponents, the identity
+ * for even ones, and a `MatrixPowGate` for fractional ones.
+ */
+pub(crate) fn self_inverse_pow(gate: Box<dyn Gate>, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+    if exponent.fract() != 0.0 {
+        Ok(Box::new(MatrixPowGate::new(gate, exponent)))
+    } else if exponent % 2.0 == 0.0 {
+        Ok(Box::new(IdentityGate::new(gate.num_qubits())))
+    } else {
+        Ok(gate)
+    }
+}
+
+/// The Hadamard gate, which maps |0⟩ to |+⟩ and |1⟩ to |-⟩.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HGate;
+
+pub const H: HGate = HGate;
+
+impl fmt::Display for HGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "H")
+    }
+}
+
+impl QIdShape for HGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2]
+    }
+}
+
+impl HasUnitary for HGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        Some(vec![
+            vec![Complex::new(FRAC_1_SQRT_2, 0.0), Complex::new(FRAC_1_SQRT_2, 0.0)],
+            vec![Complex::new(FRAC_1_SQRT_2, 0.0), Complex::new(-FRAC_1_SQRT_2, 0.0)],
+        ])
+    }
+}
+
+impl Gate for HGate {
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        self_inverse_pow(Box::new(*self), exponent)
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(*self))
+    }
+}
+
+/// The S gate, a quarter turn around the Z axis: diag(1, i).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SGate;
+
+/// The inverse of the S gate: diag(1, -i).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SGateDag;
+
+/// The T gate, an eighth turn around the Z axis: diag(1, e^{iπ/4}).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TGate;
+
+/// The inverse of the T gate: diag(1, e^{-iπ/4}).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TGateDag;
+
+pub const S: SGate = SGate;
+pub const S_DAG: SGateDag = SGateDag;
+pub const T: TGate = TGate;
+pub const T_DAG: TGateDag = TGateDag;
+
+impl fmt::Display for SGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "S")
+    }
+}
+
+impl fmt::Display for SGateDag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "S**-1")
+    }
+}
+
+impl fmt::Display for TGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "T")
+    }
+}
+
+impl fmt::Display for TGateDag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "T**-1")
+    }
+}
+
+impl QIdShape for SGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2]
+    }
+}
+
+impl QIdShape for SGateDag {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2]
+    }
+}
+
+impl QIdShape for TGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2]
+    }
+}
+
+impl QIdShape for TGateDag {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2]
+    }
+}
+
+impl HasUnitary for SGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        Some(vec![
+            vec![Complex::new(1.0, 0.0), Complex::new(0.0, 0.0)],
+            vec![Complex::new(0.0, 0.0), Complex::new(0.0, 1.0)],
+        ])
+    }
+}
+
+impl HasUnitary for SGateDag {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        Some(vec![
+            vec![Complex::new(1.0, 0.0), Complex::new(0.0, 0.0)],
+            vec![Complex::new(0.0, 0.0), Complex::new(0.0, -1.0)],
+        ])
+    }
+}
+
+impl HasUnitary for TGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        Some(vec![
+            vec![Complex::new(1.0, 0.0), Complex::new(0.0, 0.0)],
+            vec![Complex::new(0.0, 0.0), Complex::from_polar(1.0, FRAC_PI_4)],
+        ])
+    }
+}
+
+impl HasUnitary for TGateDag {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        Some(vec![
+            vec![Complex::new(1.0, 0.0), Complex::new(0.0, 0.0)],
+            vec![Complex::new(0.0, 0.0), Complex::from_polar(1.0, -FRAC_PI_4)],
+        ])
+    }
+}
+
+impl Gate for SGate {
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(ZPowGate::new(0.5 * exponent)))
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(S_DAG))
+    }
+}
+
+impl Gate for SGateDag {
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(ZPowGate::new(-0.5 * exponent)))
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(S))
+    }
+}
+
+impl Gate for TGate {
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(ZPowGate::new(0.25 * exponent)))
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(T_DAG))
+    }
+}
+
+impl Gate for TGateDag {
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(ZPowGate::new(-0.25 * exponent)))
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(T))
+    }
+}
+
+/// A rotation around the X axis of the Bloch sphere by `theta` radians: exp(-iθX/2).
+/// Note that `Rx(π)` is X up to a global phase of -i.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rx {
+    pub theta: f64,
+}
+
+/// A rotation around the Y axis of the Bloch sphere by `theta` radians: exp(-iθY/2).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ry {
+    pub theta: f64,
+}
+
+/// A rotation around the Z axis of the Bloch sphere by `theta` radians: exp(-iθZ/2).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rz {
+    pub theta: f64,
+}
+
+impl Rx {
+    pub fn new(theta: f64) -> Self {
+        Self {
+            theta
+        }
+    }
+}
+
+impl Ry {
+    pub fn new(theta: f64) -> Self {
+        Self {
+            theta
+        }
+    }
+}
+
+impl Rz {
+    pub fn new(theta: f64) -> Self {
+        Self {
+            theta
+        }
+    }
+}
+
+impl fmt::Display for Rx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Rx({}π)", self.theta / PI)
+    }
+}
+
+impl fmt::Display for Ry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Ry({}π)", self.theta / PI)
+    }
+}
+
+impl fmt::Display for Rz {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Rz({}π)", self.theta / PI)
+    }
+}
+
+impl QIdShape for Rx {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2]
+    }
+}
+
+impl QIdShape for Ry {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2]
+    }
+}
+
+impl QIdShape for Rz {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2]
+    }
+}
+
+impl HasUnitary for Rx {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        let (c, s) = ((self.theta / 2.0).cos(), (self.theta / 2.0).sin());
+        Some(vec![
+            vec![Complex::new(c, 0.0), Complex::new(0.0, -s)],
+            vec![Complex::new(0.0, -s), Complex::new(c, 0.0)],
+        ])
+    }
+}
+
+impl HasUnitary for Ry {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        let (c, s) = ((self.theta / 2.0).cos(), (self.theta / 2.0).sin());
+        Some(vec![
+            vec![Complex::new(c, 0.0), Complex::new(-s, 0.0)],
+            vec![Complex::new(s, 0.0), Complex::new(c, 0.0)],
+        ])
+    }
+}
+
+impl HasUnitary for Rz {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        Some(vec![
+            vec![Complex::from_polar(1.0, -self.theta / 2.0), Complex::new(0.0, 0.0)],
+            vec![Complex::new(0.0, 0.0), Complex::from_polar(1.0, self.theta / 2.0)],
+        ])
+    }
+}
+
+impl Gate for Rx {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![EqValue::String("Rx".to_string()), EqValue::Float(self.theta)])
+    }
+
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(Rx::new(self.theta * exponent)))
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(Rx::new(-self.theta)))
+    }
+}
+
+impl Gate for Ry {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![EqValue::String("Ry".to_string()), EqValue::Float(self.theta)])
+    }
+
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(Ry::new(self.theta * exponent)))
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(Ry::new(-self.theta)))
+    }
+}
+
+impl Gate for Rz {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![EqValue::String("Rz".to_string()), EqValue::Float(self.theta)])
+    }
+
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(Rz::new(self.theta * exponent)))
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(Rz::new(-self.theta)))
+    }
+}
+
+/**
+ * Extracts ZYZ angles `(z0, y, z1)` from a single qubit gate, such that its unitary is
+ * `Rz(z0) * Ry(y) * Rz(z1)` up to a global phase.
+ * Returns None if the gate doesn't act on a single qubit or has no unitary.
+ */
+pub fn rz_decomposition(gate: &dyn Gate) -> Option<(f64, f64, f64)> {
+    if gate.qid_shape() != vec![2] {
+        return None;
+    }
+    rz_decomposition_of_unitary(&gate.unitary()?)
+}
+
+/// Extracts ZYZ angles like `rz_decomposition`, from a matrix. Returns None if the matrix is not a 2 x 2 unitary.
+pub fn rz_decomposition_of_unitary(unitary: &[Vec<Complex<f64>>]) -> Option<(f64, f64, f64)> {
+    if unitary.len() != 2 || unitary.iter().any(|row| row.len() != 2) {
+        return None;
+    }
+    let (a, b, c, d) = (unitary[0][0], unitary[0][1], unitary[1][0], unitary[1][1]);
+
+    let is_unitary = (a.norm_sqr() + c.norm_sqr() - 1.0).abs() < 1e-8
+        && (b.norm_sqr() + d.norm_sqr() - 1.0).abs() < 1e-8
+        && (a.conj() * b + c.conj() * d).norm() < 1e-8;
+    if !is_unitary {
+        return None;
+    }
+
+    // Remove the global phase, leaving a special unitary
+    // [[e^{-i(z0+z1)/2} cos(y/2), -e^{-i(z0-z1)/2} sin(y/2)], [e^{i(z0-z1)/2} sin(y/2), e^{i(z0+z1)/2} cos(y/2)]].
+    let phase = (a * d - b * c).sqrt();
+    let (a, c, d) = (a / phase, c / phase, d / phase);
+
+    let y = 2.0 * c.norm().atan2(a.norm());
+    let half_sum = if d.norm() < 1e-8 { 0.0 } else { d.arg() };
+    let half_difference = if c.norm() < 1e-8 { 0.0 } else { c.arg() };
+
+    Some((half_sum + half_difference, y, half_sum - half_difference))
+}
+
+/**
+ * A phase rotation around the Z axis, parameterized as a fraction of a half turn:
+ * diag(1, e^{iπ * exponent}).
+ * An exponent of 1 is Z, 0.5 is S and 0.25 is T.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ZPowGate {
+    pub exponent: f64,
+}
+
+impl ZPowGate {
+    pub fn new(exponent: f64) -> Self {
+        Self {
+            exponent
+        }
+    }
+}
+
+/// Returns the Z phase gate diag(1, e^{iπ * exponent}).
+#[allow(non_snake_case)]
+pub fn Z_POW_GATE(exponent: f64) -> ZPowGate {
+    ZPowGate::new(exponent)
+}
+
+impl fmt::Display for ZPowGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Z**{}", self.exponent)
+    }
+}
+
+impl QIdShape for ZPowGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2]
+    }
+}
+
+impl HasUnitary for ZPowGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        Some(vec![
+            vec![Complex::new(1.0, 0.0), Complex::new(0.0, 0.0)],
+            vec![Complex::new(0.0, 0.0), Complex::from_polar(1.0, PI * self.exponent)],
+        ])
+    }
+}
+
+impl Gate for ZPowGate {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![EqValue::String("ZPowGate".to_string()), EqValue::Float(self.exponent)])
+    }
+
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(ZPowGate::new(self.exponent * exponent)))
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(ZPowGate::new(-self.exponent)))
+    }
+}
+
+/// The controlled Z gate, which flips the phase of |11⟩: diag(1, 1, 1, -1).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CZGate;
+
+pub const CZ: CZGate = CZGate;
+
+impl fmt::Display for CZGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CZ")
+    }
+}
+
+impl QIdShape for CZGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2, 2]
+    }
+}
+
+impl HasUnitary for CZGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        let mut unitary = vec![vec![Complex::new(0.0, 0.0); 4]; 4];
+        for (i, row) in unitary.iter_mut().enumerate() {
+            row[i] = Complex::new(if i == 3 { -1.0 } else { 1.0 }, 0.0);
+        }
+        Some(unitary)
+    }
+}
+
+impl Gate for CZGate {
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        if exponent.fract() != 0.0 {
+            return Ok(Box::new(CZPowGate::new(exponent)));
+        }
+        self_inverse_pow(Box::new(*self), exponent)
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(*self))
+    }
+
+    fn wire_symbols(&self) -> Vec<String> {
+        vec!["@".to_string(), "@".to_string()]
+    }
+}
+
+/// The controlled Z gate raised to a power, which phases |11⟩ by e^{iπ * exponent}: diag(1, 1, 1, e^{iπ * exponent}).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CZPowGate {
+    pub exponent: f64,
+}
+
+impl CZPowGate {
+    pub fn new(exponent: f64) -> Self {
+        Self {
+            exponent
+        }
+    }
+}
+
+/// Returns the controlled phase gate diag(1, 1, 1, e^{iπ * exponent}).
+#[allow(non_snake_case)]
+pub fn CZ_POW_GATE(exponent: f64) -> CZPowGate {
+    CZPowGate::new(exponent)
+}
+
+impl fmt::Display for CZPowGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CZ**{}", self.exponent)
+    }
+}
+
+impl QIdShape for CZPowGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2, 2]
+    }
+}
+
+impl HasUnitary for CZPowGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        let mut unitary = vec![vec![Complex::new(0.0, 0.0); 4]; 4];
+        for (i, row) in unitary.iter_mut().enumerate() {
+            row[i] = if i == 3 { Complex::from_polar(1.0, PI * self.exponent) } else { Complex::new(1.0, 0.0) };
+        }
+        Some(unitary)
+    }
+}
+
+impl Gate for CZPowGate {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![EqValue::String("CZPowGate".to_string()), EqValue::Float(self.exponent)])
+    }
+
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(CZPowGate::new(self.exponent * exponent)))
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(CZPowGate::new(-self.exponent)))
+    }
+}
+
+/// The controlled NOT gate, which flips the second (target) qubit when the first (control) qubit is |1⟩.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CNotGate;
+
+pub type CXGate = CNotGate;
+
+pub const CNOT: CNotGate = CNotGate;
+pub const CX: CXGate = CNotGate;
+
+/// Returns a CNOT operation with the given control and target qubits.
+pub fn controlled_x(control: Box<dyn QId>, target: Box<dyn QId>) -> GateOperation {
+    GateOperation::new(Box::new(CNOT), vec![control, target])
+}
+
+impl fmt::Display for CNotGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CNOT")
+    }
+}
+
+impl QIdShape for CNotGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2, 2]
+    }
+}
+
+impl HasUnitary for CNotGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        let mut unitary = vec![vec![Complex::new(0.0, 0.0); 4]; 4];
+        for (i, j) in [(0, 0), (1, 1), (2, 3), (3, 2)] {
+            unitary[i][j] = Complex::new(1.0, 0.0);
+        }
+        Some(unitary)
+    }
+}
+
+impl Gate for CNotGate {
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        self_inverse_pow(Box::new(*self), exponent)
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(*self))
+    }
+
+    fn wire_symbols(&self) -> Vec<String> {
+        vec!["@".to_string(), "X".to_string()]
+    }
+}
+
+/// The SWAP gate, which exchanges the states of two qubits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapGate;
+
+pub const SWAP: SwapGate = SwapGate;
+
+/**
+ * The SWAP gate raised to a power. The symmetric subspace is left unchanged, while the
+ * antisymmetric state (|01⟩ - |10⟩)/√2 picks up a phase of e^{iπ * exponent}.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SwapPowGate {
+    pub exponent: f64,
+}
+
+/// The iSWAP gate, which exchanges the states of two qubits and phases |01⟩ and |10⟩ by i.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ISwapGate;
+
+pub const ISWAP: ISwapGate = ISwapGate;
+
+/// The iSWAP gate raised to a power, rotating within the |01⟩, |10⟩ subspace by π/2 * exponent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ISwapPowGate {
+    pub exponent: f64,
+}
+
+impl SwapPowGate {
+    pub fn new(exponent: f64) -> Self {
+        Self {
+            exponent
+        }
+    }
+}
+
+impl ISwapPowGate {
+    pub fn new(exponent: f64) -> Self {
+        Self {
+            exponent
+        }
+    }
+}
+
+impl fmt::Display for SwapGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SWAP")
+    }
+}
+
+impl fmt::Display for SwapPowGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SWAP**{}", self.exponent)
+    }
+}
+
+impl fmt::Display for ISwapGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ISWAP")
+    }
+}
+
+impl fmt::Display for ISwapPowGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ISWAP**{}", self.exponent)
+    }
+}
+
+impl QIdShape for SwapGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2, 2]
+    }
+}
+
+impl QIdShape for SwapPowGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2, 2]
+    }
+}
+
+impl QIdShape for ISwapGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2, 2]
+    }
+}
+
+impl QIdShape for ISwapPowGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2, 2]
+    }
+}
+
+impl HasUnitary for SwapGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        SwapPowGate::new(1.0).unitary()
+    }
+}
+
+impl HasUnitary for SwapPowGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        let phase = Complex::from_polar(1.0, PI * self.exponent);
+        let mut unitary = vec![vec![Complex::new(0.0, 0.0); 4]; 4];
+        unitary[0][0] = Complex::new(1.0, 0.0);
+        unitary[1][1] = (1.0 + phase) / 2.0;
+        unitary[1][2] = (1.0 - phase) / 2.0;
+        unitary[2][1] = (1.0 - phase) / 2.0;
+        unitary[2][2] = (1.0 + phase) / 2.0;
+        unitary[3][3] = Complex::new(1.0, 0.0);
+        Some(unitary)
+    }
+}
+
+impl HasUnitary for ISwapGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        ISwapPowGate::new(1.0).unitary()
+    }
+}
+
+impl HasUnitary for ISwapPowGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        let (c, s) = ((PI * self.exponent / 2.0).cos(), (PI * self.exponent / 2.0).sin());
+        let mut unitary = vec![vec![Complex::new(0.0, 0.0); 4]; 4];
+        unitary[0][0] = Complex::new(1.0, 0.0);
+        unitary[1][1] = Complex::new(c, 0.0);
+        unitary[1][2] = Complex::new(0.0, s);
+        unitary[2][1] = Complex::new(0.0, s);
+        unitary[2][2] = Complex::new(c, 0.0);
+        unitary[3][3] = Complex::new(1.0, 0.0);
+        Some(unitary)
+    }
+}
+
+impl Gate for SwapGate {
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(SwapPowGate::new(exponent)))
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(*self))
+    }
+
+    fn wire_symbols(&self) -> Vec<String> {
+        vec!["×".to_string(), "×".to_string()]
+    }
+}
+
+impl Gate for SwapPowGate {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![EqValue::String("SwapPowGate".to_string()), EqValue::Float(self.exponent)])
+    }
+
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(SwapPowGate::new(self.exponent * exponent)))
+    }
+}
+
+impl Gate for ISwapGate {
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(ISwapPowGate::new(exponent)))
+    }
+
+    fn wire_symbols(&self) -> Vec<String> {
+        vec!["iSwap".to_string(), "iSwap".to_string()]
+    }
+}
+
+impl Gate for ISwapPowGate {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![EqValue::String("ISwapPowGate".to_string()), EqValue::Float(self.exponent)])
+    }
+
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(ISwapPowGate::new(self.exponent * exponent)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_complex::Complex;
+
+    use crate::gate_on;
+    use crate::ops::pauli_gates::{X, Z};
+    use crate::ops::qubits::LineQubit;
+    use crate::ops::raw_traits::{gates_equal, unitary_product};
+    use crate::ops::raw_types::Operation;
+
+    use super::*;
+
+    fn assert_close(a: Vec<Vec<Complex<f64>>>, b: Vec<Vec<Complex<f64>>>) {
+        assert!(linalg::allclose(&a, &b, 1e-10), "{:?} != {:?}", a, b);
+    }
+
+    fn product(ops: &[&dyn HasUnitary]) -> Vec<Vec<Complex<f64>>> {
+        unitary_product(ops).unwrap()
+    }
+
+    #[test]
+    fn identity_unitary_matrix() {
+        assert_eq!(IdentityGate::new(2).unitary_matrix(), vec![
+            vec![1.0, 0.0, 0.0, 0.0],
+            vec![0.0, 1.0, 0.0, 0.0],
+            vec![0.0, 0.0, 1.0, 0.0],
+            vec![0.0, 0.0, 0.0, 1.0],
+        ]);
+        assert_eq!(I.unitary_matrix().len(), 2);
+    }
+
+    #[test]
+    fn h_is_self_inverse() {
+        assert_close(product(&[&H, &H]), linalg::eye(2));
+    }
+
+    #[test]
+    fn s_squared_is_z() {
+        assert_close(product(&[&S, &S]), Z.unitary().unwrap());
+        assert_close(product(&[&S, &S_DAG]), linalg::eye(2));
+    }
+
+    #[test]
+    fn t_squared_is_s() {
+        assert_close(product(&[&T, &T]), S.unitary().unwrap());
+        assert_close(product(&[&T, &T_DAG]), linalg::eye(2));
+    }
+
+    #[test]
+    fn rx_pi_is_x_up_to_global_phase() {
+        let mut rx = Rx::new(PI).unitary().unwrap();
+        for row in rx.iter_mut() {
+            for entry in row.iter_mut() {
+                *entry *= Complex::new(0.0, 1.0);
+            }
+        }
+        assert_close(rx, X.unitary().unwrap());
+    }
+
+    #[test]
+    fn rotation_inverse() {
+        assert_close(product(&[&Ry::new(0.3), &Ry::new(-0.3)]), linalg::eye(2));
+    }
+
+    #[test]
+    fn rz_decomposition_recomposes() {
+        for gate in [&H as &dyn Gate, &X, &T, &Rx::new(0.7)] {
+            let unitary = gate.unitary().unwrap();
+            let (z0, y, z1) = rz_decomposition(gate).unwrap();
+            let recomposed = product(&[&Rz::new(z1), &Ry::new(y), &Rz::new(z0)]);
+
+            // Compare up to global phase by normalizing against the largest entry.
+            let (i, j) = if unitary[0][0].norm() > 0.5 { (0, 0) } else { (1, 0) };
+            let phase = unitary[i][j] / recomposed[i][j];
+            let mut rephased = recomposed;
+            for row in rephased.iter_mut() {
+                for entry in row.iter_mut() {
+                    *entry *= phase;
+                }
+            }
+            assert_close(rephased, unitary);
+        }
+    }
+
+    #[test]
+    fn rz_decomposition_rejects_non_single_qubit_unitaries() {
+        assert_eq!(rz_decomposition(&CZ), None);
+        assert_eq!(rz_decomposition(&crate::ops::reset::R), None);
+        assert_eq!(rz_decomposition_of_unitary(&CZ.unitary().unwrap()), None);
+        let not_unitary = vec![vec![Complex::new(1.0, 0.0), Complex::new(1.0, 0.0)], vec![Complex::new(0.0, 0.0), Complex::new(1.0, 0.0)]];
+        assert_eq!(rz_decomposition_of_unitary(&not_unitary), None);
+    }
+
+    #[test]
+    fn z_pow_gate_matches_named_phase_gates() {
+        assert_close(ZPowGate::new(1.0).unitary().unwrap(), Z.unitary().unwrap());
+        assert_close(ZPowGate::new(0.5).unitary().unwrap(), S.unitary().unwrap());
+        assert_close(ZPowGate::new(0.25).unitary().unwrap(), T.unitary().unwrap());
+        assert_close(product(&[&ZPowGate::new(0.3), &ZPowGate::new(-0.3)]), linalg::eye(2));
+    }
+
+    #[test]
+    fn cz_pow_gate_unitary() {
+        assert_close(CZ_POW_GATE(1.0).unitary().unwrap(), CZ.unitary().unwrap());
+        let unitary = CZ_POW_GATE(0.5).unitary().unwrap();
+        assert!((unitary[3][3] - Complex::new(0.0, 1.0)).norm() < 1e-10);
+        assert_close(product(&[&CZ_POW_GATE(0.3), &CZ_POW_GATE(-0.3)]), linalg::eye(4));
+
+        let inverse = CZ_POW_GATE(0.3).inverse().unwrap();
+        assert_close(product(&[&CZ_POW_GATE(0.3), inverse.as_ref()]), linalg::eye(4));
+        assert_close(CZ_POW_GATE(0.25).pow(2.0).unwrap().unitary().unwrap(), CZ_POW_GATE(0.5).unitary().unwrap());
+        assert_close(CZ.pow(0.5).unwrap().unitary().unwrap(), unitary);
+        assert_close(Z_POW_GATE(0.5).unitary().unwrap(), S.unitary().unwrap());
+    }
+
+    #[test]
+    fn cz_validates_qubit_count() {
+        assert!(gate_on!(CZ, LineQubit::new(0), LineQubit::new(1)).is_ok());
+        assert!(gate_on!(CZ, LineQubit::new(0)).is_err());
+    }
+
+    #[test]
+    fn cz_unitary() {
+        for (i, row) in CZ.unitary().unwrap().iter().enumerate() {
+            for (j, entry) in row.iter().enumerate() {
+                let expected = match (i, j) {
+                    (3, 3) => -1.0,
+                    _ if i == j => 1.0,
+                    _ => 0.0,
+                };
+                assert_eq!(*entry, Complex::new(expected, 0.0));
+            }
+        }
+    }
+
+    #[test]
+    fn cnot_unitary_targets_second_qubit() {
+        let basis = |i: usize| -> Vec<Vec<Complex<f64>>> {
+            (0..4).map(|j| vec![Complex::new(if i == j { 1.0 } else { 0.0 }, 0.0)]).collect()
+        };
+        let apply = |state: Vec<Vec<Complex<f64>>>| linalg::dot(&CNOT.unitary().unwrap(), &state);
+
+        // |00⟩ and |01⟩ are unchanged, |10⟩ and |11⟩ are swapped.
+        assert_eq!(apply(basis(0)), basis(0));
+        assert_eq!(apply(basis(1)), basis(1));
+        assert_eq!(apply(basis(2)), basis(3));
+        assert_eq!(apply(basis(3)), basis(2));
+        assert_eq!(CX.unitary(), CNOT.unitary());
+    }
+
+    #[test]
+    fn sqrt_swap_squared_is_swap() {
+        let sqrt_swap = SWAP.pow(0.5).unwrap();
+        assert_close(product(&[&*sqrt_swap, &*sqrt_swap]), SWAP.unitary().unwrap());
+        assert_close(product(&[&SWAP, &SWAP]), linalg::eye(4));
+    }
+
+    #[test]
+    fn iswap_unitary() {
+        let i = Complex::new(0.0, 1.0);
+        let unitary = ISWAP.unitary().unwrap();
+        assert_eq!(unitary[1][2], i);
+        assert_eq!(unitary[2][1], i);
+        assert_close(product(&[&ISWAP, &ISwapPowGate::new(-1.0)]), linalg::eye(4));
+    }
+
+    #[test]
+    fn gate_operation_delegates_unitary_to_gate() {
+        let operation = gate_on!(CNOT, LineQubit::new(0), LineQubit::new(1)).unwrap();
+        assert!(operation.has_unitary());
+        assert_eq!(operation.unitary(), CNOT.unitary());
+    }
+
+    #[test]
+    fn unitary_product_rejects_mismatched_dimensions() {
+        assert_eq!(unitary_product(&[&H, &CZ]), None);
+        assert_eq!(unitary_product(&[]), None);
+    }
+
+    #[test]
+    fn operations_equal_by_gate_and_qubits() {
+        let q = LineQubit::range(2);
+        let cnot = gate_on!(CNOT, q[0].clone(), q[1].clone()).unwrap();
+
+        assert!(gates_equal(&cnot, &gate_on!(CNOT, q[0].clone(), q[1].clone()).unwrap()));
+        assert!(!gates_equal(&cnot, &gate_on!(CNOT, q[1].clone(), q[0].clone()).unwrap()));
+        assert!(!gates_equal(&cnot, &gate_on!(CZ, q[0].clone(), q[1].clone()).unwrap()));
+        assert!(!gates_equal(&gate_on!(Rx::new(0.5), q[0].clone()).unwrap(), &gate_on!(Rx::new(0.25), q[0].clone()).unwrap()));
+        assert!(gates_equal(&cnot.with_tags(vec![]), &cnot.with_tags(vec![])));
+    }
+}
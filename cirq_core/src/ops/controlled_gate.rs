@@ -0,0 +1,110 @@
+use std::fmt;
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::ops::raw_traits::{EqValue, HasUnitary};
+use crate::ops::raw_types::{Gate, QIdShape};
+use crate::study::ParamResolver;
+
+/**
+ * Applies `sub_gate` only when all `num_controls` control qubits are |1⟩.
+ * The control qubits come before the sub-gate's qubits.
+ */
+#[derive(Clone)]
+pub struct ControlledGate {
+    pub sub_gate: Box<dyn Gate>,
+    pub num_controls: usize,
+}
+
+impl ControlledGate {
+    pub fn new(sub_gate: Box<dyn Gate>, num_controls: usize) -> Self {
+        Self {
+            sub_gate,
+            num_controls,
+        }
+    }
+}
+
+impl fmt::Display for ControlledGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", "C".repeat(self.num_controls), self.sub_gate)
+    }
+}
+
+impl QIdShape for ControlledGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        let mut shape = vec![2; self.num_controls];
+        shape.extend(self.sub_gate.qid_shape());
+        shape
+    }
+}
+
+impl HasUnitary for ControlledGate {
+    /// Returns the block diagonal matrix with the identity in the upper left and the sub-gate's unitary in the lower right.
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        let sub_unitary = self.sub_gate.unitary()?;
+        let sub_dimension = sub_unitary.len();
+        let dimension = sub_dimension << self.num_controls;
+        let offset = dimension - sub_dimension;
+
+        let mut unitary = vec![vec![Complex::new(0.0, 0.0); dimension]; dimension];
+        for (i, row) in unitary.iter_mut().enumerate().take(offset) {
+            row[i] = Complex::new(1.0, 0.0);
+        }
+        for (i, sub_row) in sub_unitary.iter().enumerate() {
+            unitary[offset + i][offset..].copy_from_slice(sub_row);
+        }
+        Some(unitary)
+    }
+}
+
+impl Gate for ControlledGate {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![
+            EqValue::String("ControlledGate".to_string()),
+            self.sub_gate.equality_value(),
+            EqValue::Int(self.num_controls as i64),
+        ])
+    }
+
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(ControlledGate::new(self.sub_gate.pow(exponent)?, self.num_controls)))
+    }
+
+    fn resolve_parameters(&self, resolver: &ParamResolver) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(ControlledGate::new(self.sub_gate.resolve_parameters(resolver)?, self.num_controls)))
+    }
+
+    fn wire_symbols(&self) -> Vec<String> {
+        let mut symbols = vec!["@".to_string(); self.num_controls];
+        symbols.extend(self.sub_gate.wire_symbols());
+        symbols
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::linalg;
+    use crate::ops::common_gates::{CNOT, CZ};
+    use crate::ops::pauli_gates::{X, Z};
+    use crate::ops::three_qubit_gates::TOFFOLI;
+
+    use super::*;
+
+    #[test]
+    fn controlled_x_is_cnot() {
+        let gate = ControlledGate { sub_gate: Box::new(X), num_controls: 1 };
+        assert_eq!(gate.qid_shape(), vec![2, 2]);
+        assert!(linalg::allclose(&gate.unitary().unwrap(), &CNOT.unitary().unwrap(), 1e-10));
+        assert_eq!(gate.wire_symbols(), vec!["@", "X"]);
+    }
+
+    #[test]
+    fn controlled_is_provided_by_gate() {
+        assert!(linalg::allclose(&Z.controlled(1).unitary().unwrap(), &CZ.unitary().unwrap(), 1e-10));
+        let toffoli = X.controlled(2);
+        assert_eq!(toffoli.to_string(), "CCX");
+        assert!(linalg::allclose(&toffoli.unitary().unwrap(), &TOFFOLI.unitary().unwrap(), 1e-10));
+    }
+}
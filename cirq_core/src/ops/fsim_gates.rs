@@ -0,0 +1,133 @@
+use std::f64::consts::{FRAC_PI_4, PI};
+use std::fmt;
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::ops::raw_traits::{EqValue, HasUnitary};
+use crate::ops::raw_types::{Gate, QIdShape};
+
+/**
+ * The fermionic simulation gate, native to Google's Sycamore hardware.
+ * `theta` is the iSWAP-like swap angle within the |01⟩, |10⟩ subspace and
+ * `phi` is the CZ-like phase applied to |11⟩:
+ * [[1, 0, 0, 0], [0, cos(θ), -i sin(θ), 0], [0, -i sin(θ), cos(θ), 0], [0, 0, 0, e^{-iφ}]]
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FSimGate {
+    pub theta: f64,
+    pub phi: f64,
+}
+
+/// FSim with a swap angle of π/4 and no conditional phase, i.e. iSWAP**-0.5 in the iSWAP convention.
+pub const SQRT_ISWAP: FSimGate = FSimGate { theta: FRAC_PI_4, phi: 0.0 };
+
+impl FSimGate {
+    pub fn new(theta: f64, phi: f64) -> Self {
+        Self {
+            theta,
+            phi,
+        }
+    }
+
+    /// Determines if both angles are within `tolerance` of the target's angles, modulo 2π.
+    pub fn approximate_to(&self, target: &FSimGate, tolerance: f64) -> bool {
+        let close = |a: f64, b: f64| {
+            let difference = (a - b).rem_euclid(2.0 * PI);
+            difference.min(2.0 * PI - difference) <= tolerance
+        };
+
+        close(self.theta, target.theta) && close(self.phi, target.phi)
+    }
+}
+
+impl fmt::Display for FSimGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FSim({}π, {}π)", self.theta / PI, self.phi / PI)
+    }
+}
+
+impl QIdShape for FSimGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2, 2]
+    }
+}
+
+impl HasUnitary for FSimGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        let (c, s) = (self.theta.cos(), self.theta.sin());
+        let mut unitary = vec![vec![Complex::new(0.0, 0.0); 4]; 4];
+        unitary[0][0] = Complex::new(1.0, 0.0);
+        unitary[1][1] = Complex::new(c, 0.0);
+        unitary[1][2] = Complex::new(0.0, -s);
+        unitary[2][1] = Complex::new(0.0, -s);
+        unitary[2][2] = Complex::new(c, 0.0);
+        unitary[3][3] = Complex::from_polar(1.0, -self.phi);
+        Some(unitary)
+    }
+}
+
+impl Gate for FSimGate {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![
+            EqValue::String("FSimGate".to_string()),
+            EqValue::Float(self.theta),
+            EqValue::Float(self.phi),
+        ])
+    }
+
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(FSimGate::new(self.theta * exponent, self.phi * exponent)))
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(FSimGate::new(-self.theta, -self.phi)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_3;
+
+    use super::*;
+
+    #[test]
+    fn fsim_unitary() {
+        let (theta, phi) = (FRAC_PI_3, PI / 5.0);
+        let unitary = FSimGate::new(theta, phi).unitary().unwrap();
+        let zero = Complex::new(0.0, 0.0);
+        let expected = [
+            [Complex::new(1.0, 0.0), zero, zero, zero],
+            [zero, Complex::new(0.5, 0.0), Complex::new(0.0, -(3.0f64.sqrt()) / 2.0), zero],
+            [zero, Complex::new(0.0, -(3.0f64.sqrt()) / 2.0), Complex::new(0.5, 0.0), zero],
+            [zero, zero, zero, Complex::new((PI / 5.0).cos(), -(PI / 5.0).sin())],
+        ];
+        for (i, row) in expected.iter().enumerate() {
+            for (j, entry) in row.iter().enumerate() {
+                assert!((unitary[i][j] - entry).norm() < 1e-10, "entry ({}, {})", i, j);
+            }
+        }
+    }
+
+    #[test]
+    fn approximate_to_respects_tolerance() {
+        let target = FSimGate::new(0.5, 1.0);
+        assert!(FSimGate::new(0.5, 1.0).approximate_to(&target, 0.0));
+        assert!(FSimGate::new(0.5 + 1e-4, 1.0 - 1e-4).approximate_to(&target, 1e-3));
+        assert!(!FSimGate::new(0.5 + 1e-2, 1.0).approximate_to(&target, 1e-3));
+        assert!(!FSimGate::new(0.5, 1.0 - 1e-2).approximate_to(&target, 1e-3));
+
+        // Angles are compared modulo 2π, in both directions.
+        assert!(FSimGate::new(0.5 + 2.0 * PI, 1.0 - 2.0 * PI).approximate_to(&target, 1e-9));
+        assert!(FSimGate::new(-1e-4, 2.0 * PI - 1e-4).approximate_to(&FSimGate::new(2.0 * PI, 0.0), 1e-3));
+        assert!(!FSimGate::new(0.5 + 2.0 * PI + 1e-2, 1.0).approximate_to(&target, 1e-3));
+    }
+
+    #[test]
+    fn sqrt_iswap_is_a_quarter_turn_fsim() {
+        assert_eq!(SQRT_ISWAP, FSimGate { theta: FRAC_PI_4, phi: 0.0 });
+        let unitary = SQRT_ISWAP.unitary().unwrap();
+        assert!((unitary[1][2] - Complex::new(0.0, -FRAC_PI_4.sin())).norm() < 1e-10);
+        assert!((unitary[3][3] - Complex::new(1.0, 0.0)).norm() < 1e-10);
+    }
+}
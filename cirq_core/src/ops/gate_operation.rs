@@ -1,3 +1,7 @@
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::ops::raw_traits::{EqValue, EqualityValue, HasUnitary};
 use crate::ops::raw_types::{Gate, Operation, QId, QIdShape, TaggedOperation};
 use crate::utils::extra_traits::Hashable;
 
@@ -21,6 +25,20 @@ impl GateOperation {
             qubits: self.qubits.clone()
         }
     }
+
+    /// Returns the operation with both its gate and its qubits replaced. Fails if the gate can't act on the qubits.
+    pub fn with_qubits_and_gate(&self, new_qubits: Vec<Box<dyn QId>>, new_gate: Box<dyn Gate>) -> Result<Self, Error> {
+        new_gate.validate_args(new_qubits.clone())?;
+        Ok(Self::new(new_gate, new_qubits))
+    }
+
+    /// Returns the decomposition of the gate on the operation's qubits. Fails if the gate can't be decomposed.
+    pub fn decompose(&self) -> Result<Vec<Box<dyn Operation>>, Error> {
+        self.gate
+            .as_decompose()
+            .and_then(|decompose| decompose.decompose_once(self.qubits.clone()))
+            .ok_or_else(|| anyhow!(format!("Gate {} can't be decomposed.", self.gate)))
+    }
 }
 
 impl QIdShape for GateOperation {
@@ -29,13 +47,31 @@ impl QIdShape for GateOperation {
     }
 }
 
+impl HasUnitary for GateOperation {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        self.gate.unitary()
+    }
+}
+
+impl EqualityValue for GateOperation {
+    fn equality_value(&self) -> EqValue {
+        let qubits = self.qubits.iter().map(|q| EqValue::String(q.comparison_key())).collect();
+        EqValue::Tuple(vec![self.gate.equality_value(), EqValue::Tuple(qubits)])
+    }
+}
+
 impl Operation for GateOperation {
+    fn gate(&self) -> Option<Box<dyn Gate>> {
+        Some(self.gate.clone())
+    }
+
     fn qubits(&self) -> Vec<Box<dyn QId>> {
         self.qubits.clone()
     }
 
-    fn with_qubits(&self, new_qubits: Vec<Box<dyn QId>>) -> Box<dyn Operation> {
-        Box::new(Self::new(self.gate.clone(), new_qubits))
+    fn with_qubits(&self, new_qubits: Vec<Box<dyn QId>>) -> Result<Box<dyn Operation>, Error> {
+        self.gate.validate_args(new_qubits.clone())?;
+        Ok(Box::new(Self::new(self.gate.clone(), new_qubits)))
     }
 
     fn tags(&self) -> Vec<Box<dyn Hashable>> {
@@ -49,4 +85,50 @@ impl Operation for GateOperation {
     fn with_tags(&self, new_tags: Vec<Box<dyn Hashable>>) -> TaggedOperation {
         TaggedOperation::new(Box::new(self.clone()), new_tags)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gate_on;
+    use crate::ops::common_gates::{Rx, Ry, ZPowGate};
+    use crate::ops::prelude::*;
+    use crate::ops::raw_traits::gates_equal;
+    use crate::ops::raw_types::MatrixPowGate;
+    use crate::ops::three_qubit_gates::FREDKIN;
+
+    #[test]
+    fn qubits_are_validated_when_replaced() {
+        let q = LineQubit::range(3);
+        let op = gate_on!(CNOT, q[0].clone(), q[1].clone()).unwrap();
+
+        let moved = op.with_qubits(vec![Box::new(q[2].clone()), Box::new(q[0].clone())]).unwrap();
+        assert!(gates_equal(moved.as_ref(), &gate_on!(CNOT, q[2].clone(), q[0].clone()).unwrap()));
+        assert!(op.with_qubits(vec![Box::new(q[2].clone())]).is_err());
+
+        let swapped = op.with_qubits_and_gate(vec![Box::new(q[1].clone()), Box::new(q[2].clone())], Box::new(SWAP)).unwrap();
+        assert!(gates_equal(&swapped, &gate_on!(SWAP, q[1].clone(), q[2].clone()).unwrap()));
+        assert!(op.with_qubits_and_gate(vec![Box::new(q[1].clone())], Box::new(SWAP)).is_err());
+    }
+
+    #[test]
+    fn equality_depends_on_gate_parameters_not_display() {
+        let q = LineQubit::new(0);
+        let z_pow = gate_on!(ZPowGate::new(0.5), q.clone()).unwrap();
+        let matrix_pow = gate_on!(MatrixPowGate::new(Box::new(Z), 0.5), q.clone()).unwrap();
+        assert_eq!(z_pow.gate.to_string(), matrix_pow.gate.to_string());
+        assert!(!gates_equal(&z_pow, &matrix_pow));
+
+        assert!(gates_equal(&gate_on!(Rx::new(0.1 + 0.2), q.clone()).unwrap(), &gate_on!(Rx::new(0.1 + 0.2), q.clone()).unwrap()));
+        assert!(!gates_equal(&gate_on!(Rx::new(0.3), q.clone()).unwrap(), &gate_on!(Ry::new(0.3), q.clone()).unwrap()));
+        assert!(gates_equal(&gate_on!(H, q.clone()).unwrap(), &gate_on!(H, q.clone()).unwrap()));
+        assert!(!gates_equal(&gate_on!(H, q.clone()).unwrap(), &gate_on!(X, q).unwrap()));
+    }
+
+    #[test]
+    fn decompose_delegates_to_the_gate() {
+        let q = LineQubit::range(3);
+        let fredkin = gate_on!(FREDKIN, q[0].clone(), q[1].clone(), q[2].clone()).unwrap();
+        assert!(!fredkin.decompose().unwrap().is_empty());
+        assert!(gate_on!(H, q[0].clone()).unwrap().decompose().is_err());
+    }
+}
@@ -0,0 +1,219 @@
+use std::ops::{Add, Mul, Neg, Sub};
+
+use num_complex::Complex;
+
+use crate::ops::common_gates::{CNotGate, CZGate, HGate, IdentityGate, SwapGate};
+use crate::ops::pauli_gates::{XGate, YGate, ZGate};
+use crate::ops::raw_traits::HasUnitary;
+use crate::ops::raw_types::Gate;
+
+/**
+ * A weighted sum of gates, such as `0.5 * X + 0.5 * Y`.
+ * Sums of different gate types are represented with `G = Box<dyn Gate>`.
+ * The combination is generally not unitary, but its `unitary()` is the weighted sum of the gates' matrices.
+ */
+#[derive(Clone)]
+pub struct LinearCombinationOfGates<G = Box<dyn Gate>>(pub Vec<(Complex<f64>, G)>);
+
+impl<G> LinearCombinationOfGates<G> {
+    pub fn new(terms: Vec<(Complex<f64>, G)>) -> Self {
+        Self(terms)
+    }
+
+    pub fn terms(&self) -> &[(Complex<f64>, G)] {
+        &self.0
+    }
+}
+
+impl<G: HasUnitary> HasUnitary for LinearCombinationOfGates<G> {
+    /// Returns the weighted sum of the gates' matrices, or None if the combination is empty or the gates don't match.
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        let mut terms = self.0.iter();
+        let (coefficient, gate) = terms.next()?;
+        let mut sum: Vec<Vec<Complex<f64>>> = scale(&gate.unitary()?, *coefficient);
+
+        for (coefficient, gate) in terms {
+            let unitary = gate.unitary()?;
+            if unitary.len() != sum.len() {
+                return None;
+            }
+            for (row, unitary_row) in sum.iter_mut().zip(unitary) {
+                for (entry, unitary_entry) in row.iter_mut().zip(unitary_row) {
+                    *entry += coefficient * unitary_entry;
+                }
+            }
+        }
+        Some(sum)
+    }
+}
+
+fn scale(matrix: &[Vec<Complex<f64>>], factor: Complex<f64>) -> Vec<Vec<Complex<f64>>> {
+    matrix.iter().map(|row| row.iter().map(|entry| entry * factor).collect()).collect()
+}
+
+/**
+ * Finds the linear combination of the basis gates closest to the gate's matrix by least squares.
+ * Returns None if the matrices don't match in size, the basis is linearly dependent, or
+ * the residual's Frobenius norm exceeds `tolerance`.
+ */
+pub fn approximate_decomposition(
+    gate: &dyn HasUnitary,
+    basis: &[Box<dyn Gate>],
+    tolerance: f64,
+) -> Option<LinearCombinationOfGates> {
+    let target = gate.unitary()?;
+    let matrices: Vec<Vec<Vec<Complex<f64>>>> = basis.iter().map(|b| b.unitary()).collect::<Option<_>>()?;
+    if matrices.iter().any(|m| m.len() != target.len()) {
+        return None;
+    }
+
+    // Solves the normal equations ⟨B_j, B_k⟩ c_k = ⟨B_j, U⟩ with the Frobenius inner product.
+    let inner = |a: &[Vec<Complex<f64>>], b: &[Vec<Complex<f64>>]| -> Complex<f64> {
+        a.iter().flatten().zip(b.iter().flatten()).map(|(x, y)| x.conj() * y).sum()
+    };
+    let mut system: Vec<Vec<Complex<f64>>> = matrices
+        .iter()
+        .map(|bj| {
+            let mut row: Vec<Complex<f64>> = matrices.iter().map(|bk| inner(bj, bk)).collect();
+            row.push(inner(bj, &target));
+            row
+        })
+        .collect();
+    let coefficients = solve(&mut system)?;
+
+    let combination = LinearCombinationOfGates::new(coefficients.into_iter().zip(basis.iter().cloned()).collect());
+    let approximation = combination.unitary()?;
+    let residual: f64 = approximation.iter().flatten().zip(target.iter().flatten()).map(|(a, b)| (a - b).norm_sqr()).sum();
+    if residual.sqrt() > tolerance {
+        return None;
+    }
+    Some(combination)
+}
+
+/// Solves the augmented linear system in place by Gaussian elimination, or returns None if it is singular.
+fn solve(system: &mut [Vec<Complex<f64>>]) -> Option<Vec<Complex<f64>>> {
+    let n = system.len();
+    for column in 0..n {
+        let pivot = (column..n).max_by(|a, b| system[*a][column].norm().total_cmp(&system[*b][column].norm()))?;
+        if system[pivot][column].norm() < 1e-12 {
+            return None;
+        }
+        system.swap(column, pivot);
+
+        let pivot_row = system[column].clone();
+        for (i, row) in system.iter_mut().enumerate() {
+            if i == column {
+                continue;
+            }
+            let factor = row[column] / pivot_row[column];
+            for (entry, pivot_entry) in row.iter_mut().zip(&pivot_row) {
+                *entry -= factor * pivot_entry;
+            }
+        }
+    }
+    Some(system.iter().enumerate().map(|(i, row)| row[n] / row[i]).collect())
+}
+
+impl<G> Add for LinearCombinationOfGates<G> {
+    type Output = LinearCombinationOfGates<G>;
+
+    fn add(mut self, rhs: LinearCombinationOfGates<G>) -> LinearCombinationOfGates<G> {
+        self.0.extend(rhs.0);
+        self
+    }
+}
+
+impl<G> Sub for LinearCombinationOfGates<G> {
+    type Output = LinearCombinationOfGates<G>;
+
+    fn sub(self, rhs: LinearCombinationOfGates<G>) -> LinearCombinationOfGates<G> {
+        self + -rhs
+    }
+}
+
+impl<G> Neg for LinearCombinationOfGates<G> {
+    type Output = LinearCombinationOfGates<G>;
+
+    fn neg(self) -> LinearCombinationOfGates<G> {
+        self * Complex::new(-1.0, 0.0)
+    }
+}
+
+impl<G> Mul<Complex<f64>> for LinearCombinationOfGates<G> {
+    type Output = LinearCombinationOfGates<G>;
+
+    fn mul(self, rhs: Complex<f64>) -> LinearCombinationOfGates<G> {
+        LinearCombinationOfGates(self.0.into_iter().map(|(coefficient, gate)| (coefficient * rhs, gate)).collect())
+    }
+}
+
+impl<G> Mul<f64> for LinearCombinationOfGates<G> {
+    type Output = LinearCombinationOfGates<G>;
+
+    fn mul(self, rhs: f64) -> LinearCombinationOfGates<G> {
+        self * Complex::new(rhs, 0.0)
+    }
+}
+
+/// Implements `scalar * gate` for gate types, producing a single term combination of boxed gates.
+macro_rules! impl_scalar_mul {
+    ($($gate:ty),*) => {
+        $(
+            impl Mul<$gate> for Complex<f64> {
+                type Output = LinearCombinationOfGates;
+
+                fn mul(self, rhs: $gate) -> LinearCombinationOfGates {
+                    LinearCombinationOfGates::new(vec![(self, Box::new(rhs))])
+                }
+            }
+
+            impl Mul<$gate> for f64 {
+                type Output = LinearCombinationOfGates;
+
+                fn mul(self, rhs: $gate) -> LinearCombinationOfGates {
+                    Complex::new(self, 0.0) * rhs
+                }
+            }
+        )*
+    };
+}
+
+impl_scalar_mul!(XGate, YGate, ZGate, HGate, IdentityGate, CNotGate, CZGate, SwapGate);
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    use crate::linalg;
+    use crate::ops::common_gates::{H, I};
+    use crate::ops::pauli_gates::{X, Y, Z};
+
+    use super::*;
+
+    #[test]
+    fn weighted_sum_of_unitaries() {
+        let combination = 0.5 * X + 0.5 * Y;
+        let half = Complex::new(0.5, 0.0);
+        let expected = vec![
+            vec![Complex::new(0.0, 0.0), half * Complex::new(1.0, -1.0)],
+            vec![half * Complex::new(1.0, 1.0), Complex::new(0.0, 0.0)],
+        ];
+        assert!(linalg::allclose(&combination.unitary().unwrap(), &expected, 1e-10));
+
+        let hadamard = (FRAC_1_SQRT_2 * X + FRAC_1_SQRT_2 * Z).unitary().unwrap();
+        assert!(linalg::allclose(&hadamard, &H.unitary().unwrap(), 1e-10));
+        assert!((0.5 * X - 0.5 * X).unitary().unwrap().iter().flatten().all(|entry| entry.norm() < 1e-10));
+    }
+
+    #[test]
+    fn decomposes_into_paulis() {
+        let basis: Vec<Box<dyn Gate>> = vec![Box::new(I), Box::new(X), Box::new(Y), Box::new(Z)];
+        let combination = approximate_decomposition(&H, &basis, 1e-8).unwrap();
+        let coefficients: Vec<Complex<f64>> = combination.terms().iter().map(|(c, _)| *c).collect();
+        let r = FRAC_1_SQRT_2;
+        let expected = [0.0, r, 0.0, r];
+        assert!(coefficients.iter().zip(expected).all(|(c, e)| (c - e).norm() < 1e-10));
+
+        assert!(approximate_decomposition(&H, &basis[..2], 1e-8).is_none());
+    }
+}
@@ -0,0 +1,117 @@
+use std::fmt;
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::linalg;
+use crate::ops::raw_traits::{matrix_equality_value, EqValue, HasUnitary};
+use crate::ops::raw_types::{Gate, QIdShape};
+
+/// A gate defined by an explicit unitary matrix.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatrixGate {
+    unitary: Vec<Vec<Complex<f64>>>,
+    qid_shape: Vec<u64>,
+}
+
+impl MatrixGate {
+    /**
+     * Creates a gate acting on qids of the given dimensions.
+     * Fails unless the matrix is square, matches the product of the dimensions and satisfies U†U ≈ I.
+     */
+    pub fn new(unitary: Vec<Vec<Complex<f64>>>, qid_shape: Vec<u64>) -> Result<Self, Error> {
+        let dimension: u64 = qid_shape.iter().product();
+        if unitary.len() as u64 != dimension || unitary.iter().any(|row| row.len() != unitary.len()) {
+            return Err(anyhow!(format!("Expected a {0} x {0} matrix for qid shape {1:?}.", dimension, qid_shape)));
+        }
+        if !linalg::is_unitary(&unitary, 1e-8) {
+            return Err(anyhow!("The matrix of a MatrixGate must be unitary."));
+        }
+
+        Ok(Self {
+            unitary,
+            qid_shape,
+        })
+    }
+
+    /// Creates a single qubit gate from the matrix [[a, b], [c, d]].
+    pub fn from_2x2(matrix: [[Complex<f64>; 2]; 2]) -> Result<Self, Error> {
+        Self::new(matrix.iter().map(|row| row.to_vec()).collect(), vec![2])
+    }
+}
+
+impl fmt::Display for MatrixGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: Vec<String> = self.unitary
+            .iter()
+            .map(|row| format!("[{}]", row.iter().map(|entry| entry.to_string()).collect::<Vec<_>>().join(", ")))
+            .collect();
+        write!(f, "MatrixGate([{}])", rows.join(", "))
+    }
+}
+
+impl QIdShape for MatrixGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        self.qid_shape.clone()
+    }
+}
+
+impl HasUnitary for MatrixGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        Some(self.unitary.clone())
+    }
+}
+
+impl Gate for MatrixGate {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![
+            EqValue::String("MatrixGate".to_string()),
+            matrix_equality_value(&self.unitary),
+            EqValue::Tuple(self.qid_shape.iter().map(|d| EqValue::Int(*d as i64)).collect()),
+        ])
+    }
+
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(MatrixGate {
+            unitary: linalg::unitary_power(&self.unitary, exponent),
+            qid_shape: self.qid_shape.clone(),
+        }))
+    }
+
+    fn wire_symbols(&self) -> Vec<String> {
+        let mut symbols = vec!["Matrix".to_string()];
+        symbols.extend((2..=self.num_qubits()).map(|i| format!("#{}", i)));
+        symbols
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    use crate::ops::common_gates::{CNOT, H};
+
+    use super::*;
+
+    #[test]
+    fn matrix_gates_match_their_matrix() {
+        let r = Complex::new(FRAC_1_SQRT_2, 0.0);
+        let gate = MatrixGate::from_2x2([[r, r], [r, -r]]).unwrap();
+        assert_eq!(gate.qid_shape(), vec![2]);
+        assert!(linalg::allclose(&gate.unitary().unwrap(), &H.unitary().unwrap(), 1e-10));
+        assert!(linalg::allclose(&gate.pow(2.0).unwrap().unitary().unwrap(), &linalg::eye(2), 1e-10));
+
+        let cnot = MatrixGate::new(CNOT.unitary().unwrap(), vec![2, 2]).unwrap();
+        assert_eq!(cnot.num_qubits(), 2);
+    }
+
+    #[test]
+    fn invalid_matrices_are_rejected() {
+        let one = Complex::new(1.0, 0.0);
+        let zero = Complex::new(0.0, 0.0);
+        assert!(MatrixGate::new(vec![vec![one, zero]], vec![2]).is_err());
+        assert!(MatrixGate::new(linalg::eye(2), vec![2, 2]).is_err());
+        assert!(MatrixGate::new(linalg::eye(3), vec![3]).is_ok());
+        assert!(MatrixGate::from_2x2([[one, one], [zero, one]]).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+use std::fmt;
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::ops::gate_operation::GateOperation;
+use crate::ops::raw_traits::{EqValue, HasUnitary};
+use crate::ops::raw_types::{Gate, QId, QIdShape};
+
+/**
+ * A measurement of qubits in the computational basis.
+ * Simulators collapse the state and record the outcome under `key`.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MeasurementGate {
+    pub key: String,
+    pub qubits: usize,
+}
+
+impl MeasurementGate {
+    pub fn new(key: &str, qubits: usize) -> Self {
+        Self {
+            key: key.to_string(),
+            qubits,
+        }
+    }
+
+    /// Returns a separate measurement of each qubit, keyed by `key_prefix` followed by the qubit.
+    pub fn measure_each(key_prefix: &str, qubits: &[&dyn QId]) -> Vec<GateOperation> {
+        qubits
+            .iter()
+            .map(|q| {
+                let gate = MeasurementGate::new(&format!("{}{}", key_prefix, q), 1);
+                GateOperation::new(Box::new(gate), vec![q.dyn_clone()])
+            })
+            .collect()
+    }
+}
+
+/// Returns a measurement of all the given qubits under a single key.
+pub fn measure(key: &str, qubits: &[&dyn QId]) -> GateOperation {
+    GateOperation::new(
+        Box::new(MeasurementGate::new(key, qubits.len())),
+        qubits.iter().map(|q| q.dyn_clone()).collect(),
+    )
+}
+
+pub use self::measure as M;
+
+impl fmt::Display for MeasurementGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "M('{}')", self.key)
+    }
+}
+
+impl QIdShape for MeasurementGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2; self.qubits]
+    }
+}
+
+impl HasUnitary for MeasurementGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        None
+    }
+}
+
+impl Gate for MeasurementGate {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![
+            EqValue::String("MeasurementGate".to_string()),
+            EqValue::String(self.key.clone()),
+            EqValue::Int(self.qubits as i64),
+        ])
+    }
+
+    fn pow(&self, _exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Err(anyhow!("A measurement can't be raised to a power."))
+    }
+
+    fn wire_symbols(&self) -> Vec<String> {
+        let mut symbols = vec![self.to_string()];
+        symbols.extend((1..self.qubits).map(|_| "M".to_string()));
+        symbols
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ops::qubits::LineQubit;
+    use crate::ops::raw_types::Operation;
+
+    use super::*;
+
+    #[test]
+    fn measurement_is_not_unitary() {
+        let gate = MeasurementGate::new("m", 2);
+        assert_eq!(gate.qid_shape(), vec![2, 2]);
+        assert!(!gate.has_unitary());
+        assert!(gate.inverse().is_err());
+    }
+
+    #[test]
+    fn measure_each_keys_by_qubit() {
+        let q = LineQubit::range(2);
+        let ops = MeasurementGate::measure_each("m_", &[&q[0], &q[1]]);
+        assert_eq!(ops.len(), 2);
+        assert_eq!(ops[1].qubits(), vec![Box::new(q[1].clone()) as Box<dyn QId>]);
+        assert_eq!(ops[1].gate().unwrap().to_string(), "M('m_q1')");
+        assert_eq!(M("m", &[&q[0], &q[1]]).qubits().len(), 2);
+    }
+}
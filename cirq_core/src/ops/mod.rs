@@ -1,3 +1,25 @@
 pub mod raw_types;
 pub mod raw_traits;
-pub mod gate_operation;
\ No newline at end of file
+pub mod gate_operation;
+pub mod operation_tree;
+pub mod qubits;
+pub mod qubit_order;
+pub mod common_gates;
+pub mod pauli_gates;
+pub mod pauli_string;
+pub mod pauli_sum;
+pub mod clifford_gates;
+pub mod three_qubit_gates;
+pub mod fsim_gates;
+pub mod controlled_gate;
+pub mod parallel_gate;
+pub mod matrix_gates;
+pub mod linear_combinations;
+pub mod measure;
+pub mod reset;
+pub mod channels;
+pub mod wait;
+pub mod prelude;
+
+pub use self::reset::R;
+pub use self::wait::TimeUnit;
\ No newline at end of file
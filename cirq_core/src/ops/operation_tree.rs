@@ -0,0 +1,75 @@
+use crate::circuits::circuit::Circuit;
+use crate::ops::gate_operation::GateOperation;
+use crate::ops::raw_types::Operation;
+
+/**
+ * A nested collection of operations, such as the operations of a gate's definition grouped by layer.
+ * Flattening the tree visits its leaves from left to right.
+ */
+#[derive(Clone)]
+pub enum OperationTree {
+    Leaf(Box<dyn Operation>),
+    Branch(Vec<OperationTree>),
+}
+
+impl OperationTree {
+    /// Returns the operations of the tree, in order.
+    pub fn flatten(&self) -> Vec<Box<dyn Operation>> {
+        match self {
+            OperationTree::Leaf(op) => vec![op.clone()],
+            OperationTree::Branch(children) => children.iter().flat_map(|child| child.flatten()).collect(),
+        }
+    }
+}
+
+impl From<Box<dyn Operation>> for OperationTree {
+    fn from(op: Box<dyn Operation>) -> Self {
+        OperationTree::Leaf(op)
+    }
+}
+
+impl From<GateOperation> for OperationTree {
+    fn from(op: GateOperation) -> Self {
+        OperationTree::Leaf(Box::new(op))
+    }
+}
+
+impl<T: Into<OperationTree>> From<Vec<T>> for OperationTree {
+    fn from(children: Vec<T>) -> Self {
+        OperationTree::Branch(children.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Returns a circuit of the tree's operations, packed into moments as `Circuit::append` does.
+pub fn into_circuit(tree: OperationTree) -> Circuit {
+    Circuit::from_ops(tree.flatten())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gate_on;
+    use crate::ops::common_gates::{CNOT, H};
+    use crate::ops::pauli_gates::{XGate, X};
+    use crate::ops::qubits::LineQubit;
+
+    use super::*;
+
+    #[test]
+    fn nested_trees_are_flattened_in_order() {
+        let q = LineQubit::range(2);
+        let layer = vec![gate_on!(H, q[0].clone()).unwrap(), gate_on!(X, q[1].clone()).unwrap()];
+        let tree = OperationTree::Branch(vec![
+            OperationTree::from(layer),
+            OperationTree::Branch(vec![]),
+            OperationTree::from(gate_on!(CNOT, q[0].clone(), q[1].clone()).unwrap()),
+        ]);
+
+        let ops = tree.flatten();
+        assert_eq!(ops.len(), 3);
+        assert!(ops[1].gate().unwrap().as_any().is::<XGate>());
+
+        let circuit = into_circuit(tree);
+        assert_eq!(circuit.num_moments(), 2);
+        assert_eq!(circuit.moments()[1].len(), 1);
+    }
+}
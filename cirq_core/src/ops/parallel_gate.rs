@@ -0,0 +1,145 @@
+use std::fmt;
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::linalg;
+use crate::ops::gate_operation::GateOperation;
+use crate::ops::raw_traits::{Decompose, EqValue, HasUnitary};
+use crate::ops::raw_types::{Gate, Operation, QId, QIdShape};
+
+/**
+ * Applies `num_copies` copies of `sub_gate` side by side, each to its own block of qubits.
+ * The first copy acts on the first `sub_gate.num_qubits()` qubits, the second on the next ones, and so on.
+ */
+#[derive(Clone)]
+pub struct ParallelGate {
+    pub sub_gate: Box<dyn Gate>,
+    pub num_copies: usize,
+}
+
+impl ParallelGate {
+    pub fn new(sub_gate: Box<dyn Gate>, num_copies: usize) -> Self {
+        Self {
+            sub_gate,
+            num_copies,
+        }
+    }
+}
+
+impl fmt::Display for ParallelGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}×{}", self.sub_gate, self.num_copies)
+    }
+}
+
+impl QIdShape for ParallelGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        self.sub_gate.qid_shape().repeat(self.num_copies)
+    }
+}
+
+impl HasUnitary for ParallelGate {
+    /// Returns the tensor product of `num_copies` copies of the sub-gate's unitary.
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        let sub_unitary = self.sub_gate.unitary()?;
+        Some((0..self.num_copies).fold(linalg::eye(1), |unitary, _| linalg::kron(&unitary, &sub_unitary)))
+    }
+}
+
+impl Decompose for ParallelGate {
+    /**
+     * Decomposes the gate into one application of the sub-gate per block of qubits.
+     * A sub-gate without qubits has no blocks to apply to, so it decomposes into nothing.
+     */
+    fn decompose(&self, qubits: Vec<Box<dyn QId>>) -> Vec<Box<dyn Operation>> {
+        if self.sub_gate.num_qubits() == 0 {
+            return vec![];
+        }
+        qubits
+            .chunks(self.sub_gate.num_qubits())
+            .map(|block| Box::new(GateOperation::new(self.sub_gate.clone(), block.to_vec())) as Box<dyn Operation>)
+            .collect()
+    }
+}
+
+impl Gate for ParallelGate {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![
+            EqValue::String("ParallelGate".to_string()),
+            self.sub_gate.equality_value(),
+            EqValue::Int(self.num_copies as i64),
+        ])
+    }
+
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(ParallelGate::new(self.sub_gate.pow(exponent)?, self.num_copies)))
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(ParallelGate::new(self.sub_gate.inverse()?, self.num_copies)))
+    }
+
+    fn wire_symbols(&self) -> Vec<String> {
+        let symbols = self.sub_gate.wire_symbols();
+        (0..self.num_copies).flat_map(|_| symbols.iter().cloned()).collect()
+    }
+
+    fn as_decompose(&self) -> Option<&dyn Decompose> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::circuits::circuit::Circuit;
+    use crate::ops::common_gates::{CNOT, H};
+    use crate::ops::matrix_gates::MatrixGate;
+    use crate::ops::qubits::LineQubit;
+
+    use super::*;
+
+    fn qubits(n: i64) -> Vec<Box<dyn QId>> {
+        LineQubit::range(n).into_iter().map(|q| Box::new(q) as Box<dyn QId>).collect()
+    }
+
+    #[test]
+    fn parallel_gate_is_a_tensor_product() {
+        let gate = ParallelGate::new(Box::new(H), 3);
+        assert_eq!(gate.qid_shape(), vec![2, 2, 2]);
+        assert_eq!(gate.wire_symbols(), vec!["H", "H", "H"]);
+        assert_eq!(gate.to_string(), "H×3");
+
+        let h = H.unitary().unwrap();
+        let expected = linalg::kron(&linalg::kron(&h, &h), &h);
+        assert!(linalg::allclose(&gate.unitary().unwrap(), &expected, 1e-10));
+        assert!(linalg::allclose(&gate.pow(2.0).unwrap().unitary().unwrap(), &linalg::eye(8), 1e-10));
+
+        let cnots = ParallelGate::new(Box::new(CNOT), 2);
+        assert_eq!(cnots.num_qubits(), 4);
+        let ops = cnots.decompose(qubits(4));
+        assert_eq!(ops.len(), 2);
+        assert!(ops[1].qubits()[0].eq_qid(&LineQubit::new(2)));
+    }
+
+    #[test]
+    fn parallel_gate_of_a_zero_qubit_gate_decomposes_into_nothing() {
+        let scalar = MatrixGate::new(vec![vec![Complex::new(1.0, 0.0)]], vec![]).unwrap();
+        let gate = ParallelGate::new(Box::new(scalar), 3);
+        assert_eq!(gate.num_qubits(), 0);
+        assert!(gate.decompose(vec![]).is_empty());
+    }
+
+    #[test]
+    fn on_each_applies_the_gate_to_every_qubit() {
+        let ops = H.on_each(qubits(3)).unwrap();
+        assert_eq!(ops.len(), 3);
+
+        let mut circuit = Circuit::new();
+        circuit.append(ops.into_iter().map(|op| Box::new(op) as Box<dyn Operation>).collect()).unwrap();
+        assert_eq!(circuit.num_moments(), 1);
+        assert!(linalg::allclose(&circuit.unitary().unwrap(), &ParallelGate::new(Box::new(H), 3).unitary().unwrap(), 1e-10));
+
+        assert!(CNOT.on_each(qubits(2)).is_err());
+    }
+}
@@ -0,0 +1,231 @@
+use std::fmt;
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::ops::common_gates::{self_inverse_pow, ZPowGate, I};
+use crate::ops::gate_operation::GateOperation;
+use crate::ops::raw_traits::HasUnitary;
+use crate::ops::raw_types::{Gate, QId, QIdShape};
+
+/// The Pauli X gate, which flips a qubit between |0⟩ and |1⟩.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct XGate;
+
+/// The Pauli Y gate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct YGate;
+
+/// The Pauli Z gate, which flips the phase of |1⟩.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZGate;
+
+pub const X: XGate = XGate;
+pub const Y: YGate = YGate;
+pub const Z: ZGate = ZGate;
+
+/// A single qubit Pauli operator. Together with the phases ±1 and ±i, these form the Pauli group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Pauli {
+    I,
+    X,
+    Y,
+    Z,
+}
+
+impl Pauli {
+    /// Returns the product `a · b` as a Pauli and a phase, e.g. X · Y = iZ.
+    pub fn product(a: Pauli, b: Pauli) -> (Pauli, Complex<f64>) {
+        let i = Complex::new(0.0, 1.0);
+        match (a, b) {
+            (Pauli::I, p) | (p, Pauli::I) => (p, Complex::new(1.0, 0.0)),
+            (Pauli::X, Pauli::Y) => (Pauli::Z, i),
+            (Pauli::Y, Pauli::X) => (Pauli::Z, -i),
+            (Pauli::Y, Pauli::Z) => (Pauli::X, i),
+            (Pauli::Z, Pauli::Y) => (Pauli::X, -i),
+            (Pauli::Z, Pauli::X) => (Pauli::Y, i),
+            (Pauli::X, Pauli::Z) => (Pauli::Y, -i),
+            (Pauli::X, Pauli::X) | (Pauli::Y, Pauli::Y) | (Pauli::Z, Pauli::Z) => (Pauli::I, Complex::new(1.0, 0.0)),
+        }
+    }
+
+    pub fn unitary(&self) -> [[Complex<f64>; 2]; 2] {
+        let zero = Complex::new(0.0, 0.0);
+        let one = Complex::new(1.0, 0.0);
+        let i = Complex::new(0.0, 1.0);
+        match self {
+            Pauli::I => [[one, zero], [zero, one]],
+            Pauli::X => [[zero, one], [one, zero]],
+            Pauli::Y => [[zero, -i], [i, zero]],
+            Pauli::Z => [[one, zero], [zero, -one]],
+        }
+    }
+
+    /// Determines if the two Paulis commute. Distinct non-identity Paulis anticommute.
+    pub fn commutes_with(&self, other: Pauli) -> bool {
+        *self == Pauli::I || other == Pauli::I || *self == other
+    }
+
+    /// Returns the matching gate applied to `qubit`.
+    pub fn on(&self, qubit: Box<dyn QId>) -> GateOperation {
+        let gate: Box<dyn Gate> = match self {
+            Pauli::I => Box::new(I),
+            Pauli::X => Box::new(X),
+            Pauli::Y => Box::new(Y),
+            Pauli::Z => Box::new(Z),
+        };
+        GateOperation::new(gate, vec![qubit])
+    }
+}
+
+impl fmt::Display for Pauli {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pauli::I => write!(f, "I"),
+            Pauli::X => write!(f, "X"),
+            Pauli::Y => write!(f, "Y"),
+            Pauli::Z => write!(f, "Z"),
+        }
+    }
+}
+
+impl fmt::Display for XGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "X")
+    }
+}
+
+impl fmt::Display for YGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Y")
+    }
+}
+
+impl fmt::Display for ZGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Z")
+    }
+}
+
+impl QIdShape for XGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2]
+    }
+}
+
+impl QIdShape for YGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2]
+    }
+}
+
+impl QIdShape for ZGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2]
+    }
+}
+
+impl HasUnitary for XGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        Some(vec![
+            vec![Complex::new(0.0, 0.0), Complex::new(1.0, 0.0)],
+            vec![Complex::new(1.0, 0.0), Complex::new(0.0, 0.0)],
+        ])
+    }
+}
+
+impl HasUnitary for YGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        Some(vec![
+            vec![Complex::new(0.0, 0.0), Complex::new(0.0, -1.0)],
+            vec![Complex::new(0.0, 1.0), Complex::new(0.0, 0.0)],
+        ])
+    }
+}
+
+impl HasUnitary for ZGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        Some(vec![
+            vec![Complex::new(1.0, 0.0), Complex::new(0.0, 0.0)],
+            vec![Complex::new(0.0, 0.0), Complex::new(-1.0, 0.0)],
+        ])
+    }
+}
+
+impl Gate for XGate {
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        self_inverse_pow(Box::new(*self), exponent)
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(*self))
+    }
+}
+
+impl Gate for YGate {
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        self_inverse_pow(Box::new(*self), exponent)
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(*self))
+    }
+}
+
+impl Gate for ZGate {
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(ZPowGate::new(exponent)))
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(*self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::linalg;
+    use crate::ops::qubits::LineQubit;
+    use crate::ops::raw_types::{Gate, Operation, QId};
+
+    use super::*;
+
+    #[test]
+    fn pauli_gates_on_single_qubit() {
+        let gates: Vec<Box<dyn Gate>> = vec![Box::new(X), Box::new(Y), Box::new(Z)];
+
+        for gate in gates {
+            let op = gate.on(vec![Box::new(LineQubit::new(0))]).unwrap();
+            assert!(gate.validate_args(op.qubits()).is_ok());
+        }
+    }
+
+    #[test]
+    fn pauli_gates_reject_two_qubits() {
+        let qubits: Vec<Box<dyn QId>> = vec![Box::new(LineQubit::new(0)), Box::new(LineQubit::new(1))];
+        assert!(X.on(qubits).is_err());
+    }
+
+    #[test]
+    fn pauli_group_products() {
+        let paulis = [Pauli::I, Pauli::X, Pauli::Y, Pauli::Z];
+        for a in paulis {
+            for b in paulis {
+                let (product, phase) = Pauli::product(a, b);
+                let expected: Vec<Vec<Complex<f64>>> = linalg::dot(&matrix(a), &matrix(b));
+                let actual: Vec<Vec<Complex<f64>>> = matrix(product).iter().map(|row| row.iter().map(|x| x * phase).collect()).collect();
+                assert!(linalg::allclose(&expected, &actual, 1e-10));
+
+                let reversed = linalg::dot(&matrix(b), &matrix(a));
+                assert_eq!(a.commutes_with(b), linalg::allclose(&expected, &reversed, 1e-10));
+            }
+        }
+
+        let op = Pauli::Y.on(Box::new(LineQubit::new(0)));
+        assert_eq!(op.unitary(), Some(matrix(Pauli::Y)));
+    }
+
+    fn matrix(pauli: Pauli) -> Vec<Vec<Complex<f64>>> {
+        pauli.unitary().iter().map(|row| row.to_vec()).collect()
+    }
+}
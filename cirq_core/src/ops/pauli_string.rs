@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Div, Mul, Neg};
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::ops::pauli_gates::Pauli;
+use crate::ops::qubit_order::{DefaultQubitOrder, QubitOrder};
+use crate::ops::raw_types::{QId, QIdHasher};
+
+/**
+ * A tensor product of Pauli operators on distinct qubits, scaled by a complex coefficient.
+ * Qubits that aren't in the map are acted on by the identity.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct PauliString {
+    pub qubit_pauli_map: HashMap<QIdHasher, Pauli>,
+    pub coefficient: Complex<f64>,
+}
+
+impl PauliString {
+    /// Returns the identity scaled by `coefficient`.
+    pub fn new(coefficient: Complex<f64>) -> Self {
+        Self {
+            qubit_pauli_map: HashMap::new(),
+            coefficient,
+        }
+    }
+
+    /// Returns the single Pauli operator `pauli` on `qubit`.
+    pub fn from_pauli(pauli: Pauli, qubit: &dyn QId) -> Self {
+        let mut string = Self::new(Complex::new(1.0, 0.0));
+        if pauli != Pauli::I {
+            string.qubit_pauli_map.insert(QIdHasher::new(qubit.dyn_clone()), pauli);
+        }
+        string
+    }
+
+    /// Returns the Pauli operator acting on `qubit`, or None if it is acted on by the identity.
+    pub fn get(&self, qubit: &dyn QId) -> Option<Pauli> {
+        self.qubit_pauli_map.get(&QIdHasher::new(qubit.dyn_clone())).copied()
+    }
+
+    /// Returns the qubits with a non-identity Pauli operator, in the default qubit order.
+    pub fn qubits(&self) -> Vec<Box<dyn QId>> {
+        let qubits = self.qubit_pauli_map.keys().map(|q| q.0.clone()).collect();
+        DefaultQubitOrder.sorted(qubits).unwrap_or_default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.qubit_pauli_map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.qubit_pauli_map.is_empty()
+    }
+
+    /**
+     * Returns the matrix of the Pauli string on the given qubits.
+     * `qubits[0]` corresponds to the most significant bit of the matrix index.
+     */
+    pub fn to_matrix(&self, qubits: &[&dyn QId]) -> Result<Vec<Vec<Complex<f64>>>, Error> {
+        let action = self.basis_action(qubits)?;
+        let mut matrix = vec![vec![Complex::new(0.0, 0.0); action.len()]; action.len()];
+        for (column, (row, amplitude)) in action.into_iter().enumerate() {
+            matrix[row][column] = amplitude;
+        }
+        Ok(matrix)
+    }
+
+    /// Returns the real part of ⟨ψ|P|ψ⟩ for the state vector ψ over the given qubits.
+    pub fn expectation_value(&self, state: &[Complex<f64>], qubits: &[&dyn QId]) -> Result<f64, Error> {
+        let action = self.basis_action(qubits)?;
+        if state.len() != action.len() {
+            return Err(anyhow!(format!("Expected a state vector of size {} but got {}.", action.len(), state.len())));
+        }
+        Ok(action.iter().zip(state).map(|((target, amplitude), x)| state[*target].conj() * amplitude * x).sum::<Complex<f64>>().re)
+    }
+
+    /**
+     * Returns, for each computational basis state |i⟩ of the given qubits, the basis state
+     * index and amplitude of P|i⟩, which is always a single scaled basis state.
+     */
+    pub(crate) fn basis_action(&self, qubits: &[&dyn QId]) -> Result<Vec<(usize, Complex<f64>)>, Error> {
+        let num_qubits = qubits.len();
+        let mut factors = vec![];
+        for (qubit, pauli) in &self.qubit_pauli_map {
+            let index = qubits
+                .iter()
+                .position(|q| q.eq_qid(qubit.0.as_ref()))
+                .ok_or_else(|| anyhow!(format!("Qubit {} of the Pauli string is not in the given qubits.", qubit.0)))?;
+            factors.push((1usize << (num_qubits - 1 - index), *pauli));
+        }
+
+        Ok((0..1usize << num_qubits)
+            .map(|i| {
+                factors.iter().fold((i, self.coefficient), |(target, amplitude), (mask, pauli)| {
+                    let one = i & mask != 0;
+                    match pauli {
+                        Pauli::I => (target, amplitude),
+                        Pauli::X => (target ^ mask, amplitude),
+                        Pauli::Y => (target ^ mask, amplitude * Complex::new(0.0, if one { -1.0 } else { 1.0 })),
+                        Pauli::Z => (target, if one { -amplitude } else { amplitude }),
+                    }
+                })
+            })
+            .collect())
+    }
+}
+
+impl fmt::Display for PauliString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let factors: Vec<String> = self.qubits()
+            .iter()
+            .filter_map(|q| self.get(q.as_ref()).map(|pauli| format!("{}({})", pauli, q)))
+            .collect();
+
+        let coefficient = if self.coefficient == Complex::new(1.0, 0.0) {
+            String::new()
+        } else if self.coefficient == Complex::new(-1.0, 0.0) {
+            "-".to_string()
+        } else {
+            format!("({})*", self.coefficient)
+        };
+
+        if factors.is_empty() {
+            write!(f, "{}I", coefficient)
+        } else {
+            write!(f, "{}{}", coefficient, factors.join("*"))
+        }
+    }
+}
+
+/// Pauli strings are compared exactly, including their coefficients, so they can be used as `HashMap` keys.
+impl Eq for PauliString {}
+
+impl Hash for PauliString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // The map has no fixed iteration order, so its entries are hashed sorted by qubit.
+        let mut entries: Vec<(String, Pauli)> = self.qubit_pauli_map.iter().map(|(q, pauli)| (q.0.comparison_key(), *pauli)).collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries.hash(state);
+        // Adding zero turns -0.0 into 0.0, which compares equal to it.
+        (self.coefficient.re + 0.0).to_bits().hash(state);
+        (self.coefficient.im + 0.0).to_bits().hash(state);
+    }
+}
+
+impl Mul for &PauliString {
+    type Output = PauliString;
+
+    fn mul(self, rhs: &PauliString) -> PauliString {
+        let mut product = self.clone();
+        product.coefficient *= rhs.coefficient;
+        for (qubit, pauli) in &rhs.qubit_pauli_map {
+            match product.qubit_pauli_map.remove(qubit) {
+                None => {
+                    if *pauli != Pauli::I {
+                        product.qubit_pauli_map.insert(qubit.clone(), *pauli);
+                    }
+                }
+                Some(existing) => {
+                    let (result, phase) = Pauli::product(existing, *pauli);
+                    product.coefficient *= phase;
+                    if result != Pauli::I {
+                        product.qubit_pauli_map.insert(qubit.clone(), result);
+                    }
+                }
+            }
+        }
+        product
+    }
+}
+
+impl Mul for PauliString {
+    type Output = PauliString;
+
+    fn mul(self, rhs: PauliString) -> PauliString {
+        &self * &rhs
+    }
+}
+
+impl Mul<Complex<f64>> for PauliString {
+    type Output = PauliString;
+
+    fn mul(mut self, rhs: Complex<f64>) -> PauliString {
+        self.coefficient *= rhs;
+        self
+    }
+}
+
+impl Mul<PauliString> for Complex<f64> {
+    type Output = PauliString;
+
+    fn mul(self, rhs: PauliString) -> PauliString {
+        rhs * self
+    }
+}
+
+impl Div<Complex<f64>> for PauliString {
+    type Output = PauliString;
+
+    fn div(mut self, rhs: Complex<f64>) -> PauliString {
+        self.coefficient /= rhs;
+        self
+    }
+}
+
+impl Neg for PauliString {
+    type Output = PauliString;
+
+    fn neg(mut self) -> PauliString {
+        self.coefficient = -self.coefficient;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    use crate::linalg;
+    use crate::ops::pauli_gates::{X, Z};
+    use crate::ops::qubits::LineQubit;
+    use crate::ops::raw_traits::HasUnitary;
+
+    use super::*;
+
+    #[test]
+    fn products_track_phases() {
+        let q = LineQubit::range(2);
+        let x0 = PauliString::from_pauli(Pauli::X, &q[0]);
+        let y0 = PauliString::from_pauli(Pauli::Y, &q[0]);
+        let z1 = PauliString::from_pauli(Pauli::Z, &q[1]);
+
+        let product = &x0 * &y0;
+        assert_eq!(product.get(&q[0]), Some(Pauli::Z));
+        assert_eq!(product.coefficient, Complex::new(0.0, 1.0));
+        assert_eq!(&x0 * &x0, PauliString::new(Complex::new(1.0, 0.0)));
+
+        let string = -(x0 * z1) / Complex::new(2.0, 0.0);
+        assert_eq!(string.len(), 2);
+        assert_eq!(string.to_string(), "(-0.5+0i)*X(q0)*Z(q1)");
+    }
+
+    #[test]
+    fn matrix_is_tensor_product() {
+        let q = LineQubit::range(2);
+        let string = PauliString::from_pauli(Pauli::X, &q[0]) * PauliString::from_pauli(Pauli::Z, &q[1]);
+        let (x, z) = (X.unitary().unwrap(), Z.unitary().unwrap());
+        let mut expected = vec![vec![Complex::new(0.0, 0.0); 4]; 4];
+        for (r, c) in (0..4).flat_map(|r| (0..4).map(move |c| (r, c))) {
+            expected[r][c] = x[r >> 1][c >> 1] * z[r & 1][c & 1];
+        }
+
+        assert!(linalg::allclose(&string.to_matrix(&[&q[0], &q[1]]).unwrap(), &expected, 1e-10));
+        assert!(string.to_matrix(&[&q[0]]).is_err());
+    }
+
+    #[test]
+    fn bell_state_expectation_values() {
+        let q = LineQubit::range(2);
+        let r = Complex::new(FRAC_1_SQRT_2, 0.0);
+        let zero = Complex::new(0.0, 0.0);
+        let bell = vec![r, zero, zero, r];
+        let qubits: [&dyn QId; 2] = [&q[0], &q[1]];
+
+        let zz = PauliString::from_pauli(Pauli::Z, &q[0]) * PauliString::from_pauli(Pauli::Z, &q[1]);
+        let yy = PauliString::from_pauli(Pauli::Y, &q[0]) * PauliString::from_pauli(Pauli::Y, &q[1]);
+        assert!((zz.expectation_value(&bell, &qubits).unwrap() - 1.0).abs() < 1e-10);
+        assert!((yy.expectation_value(&bell, &qubits).unwrap() + 1.0).abs() < 1e-10);
+        assert!(PauliString::from_pauli(Pauli::Z, &q[0]).expectation_value(&bell, &qubits).unwrap().abs() < 1e-10);
+    }
+}
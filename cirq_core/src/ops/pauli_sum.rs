@@ -0,0 +1,168 @@
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::ops::pauli_string::PauliString;
+use crate::ops::raw_types::QId;
+
+/// A linear combination of Pauli strings, such as a Hamiltonian.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PauliSum {
+    pub terms: Vec<PauliString>,
+}
+
+impl PauliSum {
+    pub fn from_pauli_strings(terms: Vec<PauliString>) -> Self {
+        Self {
+            terms
+        }
+    }
+
+    /// Returns the matrix of the sum on the given qubits, with `qubits[0]` as the most significant bit.
+    pub fn matrix(&self, qubits: &[&dyn QId]) -> Result<Vec<Vec<Complex<f64>>>, Error> {
+        let dimension = 1 << qubits.len();
+        let mut matrix = vec![vec![Complex::new(0.0, 0.0); dimension]; dimension];
+        for term in &self.terms {
+            for (column, (row, amplitude)) in term.basis_action(qubits)?.into_iter().enumerate() {
+                matrix[row][column] += amplitude;
+            }
+        }
+        Ok(matrix)
+    }
+
+    /// Returns the real part of ⟨ψ|H|ψ⟩ for the state vector ψ over the given qubits.
+    pub fn expectation_value(&self, state: &[Complex<f64>], qubits: &[&dyn QId]) -> Result<f64, Error> {
+        self.terms.iter().map(|term| term.expectation_value(state, qubits)).sum()
+    }
+
+    /// Combines terms with the same Paulis and drops terms whose coefficients are within 1e-12 of zero.
+    pub fn simplify(&self) -> PauliSum {
+        let mut terms: Vec<PauliString> = vec![];
+        for term in &self.terms {
+            match terms.iter_mut().find(|other| other.qubit_pauli_map == term.qubit_pauli_map) {
+                Some(other) => other.coefficient += term.coefficient,
+                None => terms.push(term.clone()),
+            }
+        }
+        terms.retain(|term| term.coefficient.norm() > 1e-12);
+        PauliSum::from_pauli_strings(terms)
+    }
+}
+
+impl From<PauliString> for PauliSum {
+    fn from(term: PauliString) -> Self {
+        PauliSum::from_pauli_strings(vec![term])
+    }
+}
+
+impl fmt::Display for PauliSum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.terms.is_empty() {
+            return write!(f, "0");
+        }
+        let terms: Vec<String> = self.terms.iter().map(|term| term.to_string()).collect();
+        write!(f, "{}", terms.join(" + "))
+    }
+}
+
+impl<T: Into<PauliSum>> Add<T> for PauliSum {
+    type Output = PauliSum;
+
+    fn add(mut self, rhs: T) -> PauliSum {
+        self.terms.extend(rhs.into().terms);
+        self
+    }
+}
+
+impl<T: Into<PauliSum>> Sub<T> for PauliSum {
+    type Output = PauliSum;
+
+    fn sub(self, rhs: T) -> PauliSum {
+        self + -rhs.into()
+    }
+}
+
+impl<T: Into<PauliSum>> Add<T> for PauliString {
+    type Output = PauliSum;
+
+    fn add(self, rhs: T) -> PauliSum {
+        PauliSum::from(self) + rhs
+    }
+}
+
+impl<T: Into<PauliSum>> Sub<T> for PauliString {
+    type Output = PauliSum;
+
+    fn sub(self, rhs: T) -> PauliSum {
+        PauliSum::from(self) - rhs
+    }
+}
+
+impl Mul<Complex<f64>> for PauliSum {
+    type Output = PauliSum;
+
+    fn mul(self, rhs: Complex<f64>) -> PauliSum {
+        PauliSum::from_pauli_strings(self.terms.into_iter().map(|term| term * rhs).collect())
+    }
+}
+
+impl Mul<PauliSum> for Complex<f64> {
+    type Output = PauliSum;
+
+    fn mul(self, rhs: PauliSum) -> PauliSum {
+        rhs * self
+    }
+}
+
+impl Neg for PauliSum {
+    type Output = PauliSum;
+
+    fn neg(self) -> PauliSum {
+        self * Complex::new(-1.0, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::linalg;
+    use crate::ops::pauli_gates::Pauli;
+    use crate::ops::qubits::LineQubit;
+
+    use super::*;
+
+    #[test]
+    fn simplify_combines_like_terms() {
+        let q = LineQubit::range(2);
+        let x0 = PauliString::from_pauli(Pauli::X, &q[0]);
+        let z1 = PauliString::from_pauli(Pauli::Z, &q[1]);
+
+        let sum = x0.clone() + z1.clone() + x0.clone() * Complex::new(2.0, 0.0) - z1;
+        assert_eq!(sum.terms.len(), 4);
+        let simplified = sum.simplify();
+        assert_eq!(simplified, PauliSum::from(x0 * Complex::new(3.0, 0.0)));
+        assert_eq!(simplified.to_string(), "(3+0i)*X(q0)");
+    }
+
+    #[test]
+    fn matrix_and_expectation_value() {
+        let q = LineQubit::range(2);
+        let qubits: [&dyn QId; 2] = [&q[0], &q[1]];
+        let zz = PauliString::from_pauli(Pauli::Z, &q[0]) * PauliString::from_pauli(Pauli::Z, &q[1]);
+        let xx = PauliString::from_pauli(Pauli::X, &q[0]) * PauliString::from_pauli(Pauli::X, &q[1]);
+        let hamiltonian = (zz.clone() + xx.clone()) * Complex::new(0.5, 0.0);
+
+        let expected: Vec<Vec<Complex<f64>>> = zz.to_matrix(&qubits).unwrap()
+            .iter()
+            .zip(xx.to_matrix(&qubits).unwrap())
+            .map(|(a, b)| a.iter().zip(b).map(|(x, y)| (x + y) * 0.5).collect())
+            .collect();
+        assert!(linalg::allclose(&hamiltonian.matrix(&qubits).unwrap(), &expected, 1e-10));
+
+        // |01⟩ has ⟨ZZ⟩ = -1 and ⟨XX⟩ = 0.
+        let zero = Complex::new(0.0, 0.0);
+        let state = vec![zero, Complex::new(1.0, 0.0), zero, zero];
+        assert!((hamiltonian.expectation_value(&state, &qubits).unwrap() + 0.5).abs() < 1e-10);
+    }
+}
@@ -0,0 +1,6 @@
+pub use crate::ops::common_gates::{CNOT, CZ, H, S, SWAP, T};
+pub use crate::ops::gate_operation::GateOperation;
+pub use crate::ops::pauli_gates::{X, Y, Z};
+pub use crate::ops::qubits::{LineQubit, NamedQubit};
+pub use crate::ops::raw_traits::{Decompose, HasUnitary};
+pub use crate::ops::raw_types::{Gate, Operation, QId, TaggedOperation};
@@ -0,0 +1,98 @@
+use anyhow::Error;
+
+use crate::ops::raw_types::QId;
+
+/// Determines the canonical ordering of a collection of qubits.
+pub trait QubitOrder {
+    /// Sorts the qubits in place.
+    fn sort_qubits(&self, qubits: &mut Vec<Box<dyn QId>>) -> Result<(), anyhow::Error>;
+
+    /// Returns the qubits in sorted order.
+    fn sorted(&self, qubits: Vec<Box<dyn QId>>) -> Result<Vec<Box<dyn QId>>, anyhow::Error> {
+        let mut qubits = qubits;
+        self.sort_qubits(&mut qubits)?;
+        Ok(qubits)
+    }
+}
+
+/**
+ * Orders qubits by their comparison keys, in lexicographic order.
+ * The keys of the built-in qubits sort like the qubits themselves, grouped by type.
+ */
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultQubitOrder;
+
+impl QubitOrder for DefaultQubitOrder {
+    fn sort_qubits(&self, qubits: &mut Vec<Box<dyn QId>>) -> Result<(), Error> {
+        qubits.sort_by_cached_key(|q| q.comparison_key());
+        Ok(())
+    }
+}
+
+/// Orders qubits by their position in a user-specified list.
+#[derive(Clone, Debug)]
+pub struct ExplicitQubitOrder(pub Vec<Box<dyn QId>>);
+
+impl ExplicitQubitOrder {
+    pub fn new(fixed_qubits: Vec<Box<dyn QId>>) -> Self {
+        Self(fixed_qubits)
+    }
+
+    fn index_of(&self, qubit: &dyn QId) -> Option<usize> {
+        self.0.iter().position(|q| q.eq_qid(qubit))
+    }
+}
+
+impl QubitOrder for ExplicitQubitOrder {
+    fn sort_qubits(&self, qubits: &mut Vec<Box<dyn QId>>) -> Result<(), Error> {
+        if let Some(missing) = qubits.iter().find(|q| self.index_of(q.as_ref()).is_none()) {
+            return Err(anyhow!(format!("Qubit {} is not in the explicit qubit order.", missing)));
+        }
+
+        qubits.sort_by_key(|q| self.index_of(q.as_ref()));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ops::qubits::{GridQubit, LineQubit, NamedQubit};
+
+    use super::*;
+
+    fn names(qubits: &[Box<dyn QId>]) -> Vec<String> {
+        qubits.iter().map(|q| q.to_string()).collect()
+    }
+
+    #[test]
+    fn default_order_sorts_negative_indices_numerically() {
+        let line: Vec<Box<dyn QId>> = [0, -1, -10, 2, -2].iter().map(|x| Box::new(LineQubit::new(*x)) as Box<dyn QId>).collect();
+        assert_eq!(names(&DefaultQubitOrder.sorted(line).unwrap()), ["q-10", "q-2", "q-1", "q0", "q2"]);
+
+        let grid: Vec<Box<dyn QId>> = [(0, -1), (-1, 0), (0, -2)]
+            .iter()
+            .map(|(row, col)| Box::new(GridQubit::new(*row, *col)) as Box<dyn QId>)
+            .collect();
+        assert_eq!(names(&DefaultQubitOrder.sorted(grid).unwrap()), ["q(-1,0)", "q(0,-2)", "q(0,-1)"]);
+    }
+
+    #[test]
+    fn default_order_groups_mixed_qubit_types() {
+        let qubits: Vec<Box<dyn QId>> = vec![
+            Box::new(NamedQubit::new("q10")),
+            Box::new(LineQubit::new(1)),
+            Box::new(GridQubit::new(0, 0)),
+            Box::new(NamedQubit::new("q2")),
+            Box::new(LineQubit::new(-1)),
+        ];
+        assert_eq!(names(&DefaultQubitOrder.sorted(qubits).unwrap()), ["q(0,0)", "q-1", "q1", "q2", "q10"]);
+    }
+
+    #[test]
+    fn explicit_order_rejects_missing_qubits() {
+        let order = ExplicitQubitOrder::new(vec![Box::new(LineQubit::new(1)), Box::new(LineQubit::new(0))]);
+        let qubits: Vec<Box<dyn QId>> = vec![Box::new(LineQubit::new(0)), Box::new(LineQubit::new(1))];
+        assert_eq!(names(&order.sorted(qubits).unwrap()), ["q1", "q0"]);
+        assert!(order.sorted(vec![Box::new(LineQubit::new(2))]).is_err());
+    }
+}
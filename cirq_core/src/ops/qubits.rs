@@ -0,0 +1,425 @@
+use std::any::Any;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use anyhow::Error;
+
+use crate::ops::raw_traits::{EqValue, EqualityValue};
+use crate::ops::raw_types::QId;
+
+/// A qubit on a 1d lattice with nearest-neighbor connectivity.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LineQubit {
+    pub x: i64,
+}
+
+impl LineQubit {
+    pub fn new(x: i64) -> Self {
+        Self {
+            x
+        }
+    }
+
+    /// Returns a vector of line qubits with indices `0..n`.
+    pub fn range(n: i64) -> Vec<LineQubit> {
+        (0..n).map(LineQubit::new).collect()
+    }
+}
+
+impl fmt::Display for LineQubit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "q{}", self.x)
+    }
+}
+
+impl PartialEq<dyn QId> for LineQubit {
+    fn eq(&self, other: &dyn QId) -> bool {
+        self.eq_qid(other)
+    }
+}
+
+impl Hash for LineQubit {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.comparison_key().hash(state);
+    }
+}
+
+impl QId for LineQubit {
+    fn comparison_key(&self) -> String {
+        format!("LineQubit({})", pad_index(self.x))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn fmt_display(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+
+    fn dimension(&self) -> u64 {
+        2
+    }
+
+    fn validate_dimension(&self, dimension: u64) -> Result<(), Error> {
+        if dimension < 1 {
+            Err(anyhow!(format!("Wrong qid dimension. Expected a positive integer but got {}.", dimension)))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl EqualityValue for LineQubit {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![EqValue::String("LineQubit".to_string()), EqValue::Int(self.x)])
+    }
+}
+
+/// A qid on a 1d lattice with nearest-neighbor connectivity and a configurable dimension.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LineQid {
+    pub x: i64,
+    pub dimension: u64,
+}
+
+impl LineQid {
+    pub fn new(x: i64, dimension: u64) -> Result<Self, anyhow::Error> {
+        let q = Self {
+            x,
+            dimension,
+        };
+
+        q.validate_dimension(dimension)?;
+        Ok(q)
+    }
+
+    /// Returns a vector of `n` line qids of dimension `dimension`, with indices `0..n`.
+    pub fn range(n: i64, dimension: u64) -> Result<Vec<LineQid>, anyhow::Error> {
+        (0..n).map(|x| LineQid::new(x, dimension)).collect()
+    }
+}
+
+impl fmt::Display for LineQid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "q{}(d={})", self.x, self.dimension)
+    }
+}
+
+impl PartialEq<dyn QId> for LineQid {
+    fn eq(&self, other: &dyn QId) -> bool {
+        self.eq_qid(other)
+    }
+}
+
+impl Hash for LineQid {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.comparison_key().hash(state);
+    }
+}
+
+impl QId for LineQid {
+    fn comparison_key(&self) -> String {
+        format!("LineQid({},d={:020})", pad_index(self.x), self.dimension)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn fmt_display(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+
+    fn dimension(&self) -> u64 {
+        self.dimension
+    }
+
+    fn validate_dimension(&self, dimension: u64) -> Result<(), Error> {
+        if dimension < 1 {
+            Err(anyhow!(format!("Wrong qid dimension. Expected a positive integer but got {}.", dimension)))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A qubit on a 2d square lattice. Qubits are ordered row-major.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GridQubit {
+    pub row: i64,
+    pub col: i64,
+}
+
+impl GridQubit {
+    pub fn new(row: i64, col: i64) -> Self {
+        Self {
+            row,
+            col,
+        }
+    }
+
+    /// Returns a `rows` x `cols` rectangle of qubits, starting at (0, 0).
+    pub fn rect(rows: i64, cols: i64) -> Vec<Vec<GridQubit>> {
+        (0..rows)
+            .map(|row| (0..cols).map(|col| GridQubit::new(row, col)).collect())
+            .collect()
+    }
+
+    /// Returns an `n` x `n` square of qubits, starting at (0, 0).
+    pub fn square(n: i64) -> Vec<Vec<GridQubit>> {
+        GridQubit::rect(n, n)
+    }
+
+    /// Returns the four qubits adjacent to `q` on the lattice.
+    pub fn neighbors_of(q: &GridQubit) -> Vec<GridQubit> {
+        vec![
+            GridQubit::new(q.row - 1, q.col),
+            GridQubit::new(q.row, q.col - 1),
+            GridQubit::new(q.row, q.col + 1),
+            GridQubit::new(q.row + 1, q.col),
+        ]
+    }
+
+    /// Determines if two qubits are adjacent on the lattice.
+    pub fn is_adjacent(&self, other: &GridQubit) -> bool {
+        (self.row - other.row).abs() + (self.col - other.col).abs() == 1
+    }
+}
+
+impl fmt::Display for GridQubit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "q({},{})", self.row, self.col)
+    }
+}
+
+impl PartialEq<dyn QId> for GridQubit {
+    fn eq(&self, other: &dyn QId) -> bool {
+        self.eq_qid(other)
+    }
+}
+
+impl Hash for GridQubit {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.comparison_key().hash(state);
+    }
+}
+
+impl QId for GridQubit {
+    fn comparison_key(&self) -> String {
+        format!("GridQubit({},{})", pad_index(self.row), pad_index(self.col))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn fmt_display(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+
+    fn dimension(&self) -> u64 {
+        2
+    }
+
+    fn validate_dimension(&self, dimension: u64) -> Result<(), Error> {
+        if dimension < 1 {
+            Err(anyhow!(format!("Wrong qid dimension. Expected a positive integer but got {}.", dimension)))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl EqualityValue for GridQubit {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![
+            EqValue::String("GridQubit".to_string()),
+            EqValue::Int(self.row),
+            EqValue::Int(self.col),
+        ])
+    }
+}
+
+/// A qubit identified by name.
+/// By default, NamedQubits are ordered by their name, with runs of digits compared
+/// numerically so that "q2" < "q10".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NamedQubit(pub String);
+
+impl NamedQubit {
+    pub fn new(name: &str) -> Self {
+        Self(name.to_string())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the given qubits sorted by their comparison keys, which puts the names in natural order.
+    pub fn sorted_by_key(mut qubits: Vec<NamedQubit>) -> Vec<NamedQubit> {
+        qubits.sort_by_cached_key(|q| q.comparison_key());
+        qubits
+    }
+}
+
+impl PartialOrd for NamedQubit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NamedQubit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.comparison_key().cmp(&other.comparison_key())
+    }
+}
+
+impl fmt::Display for NamedQubit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl PartialEq<dyn QId> for NamedQubit {
+    fn eq(&self, other: &dyn QId) -> bool {
+        self.eq_qid(other)
+    }
+}
+
+impl Hash for NamedQubit {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.comparison_key().hash(state);
+    }
+}
+
+impl QId for NamedQubit {
+    fn comparison_key(&self) -> String {
+        format!("NamedQubit({})", pad_digits(&self.0))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn fmt_display(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+
+    fn dimension(&self) -> u64 {
+        2
+    }
+
+    fn validate_dimension(&self, dimension: u64) -> Result<(), Error> {
+        if dimension < 1 {
+            Err(anyhow!(format!("Wrong qid dimension. Expected a positive integer but got {}.", dimension)))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/**
+ * Rewrites every run of digits in `text` so that comparing the results as plain strings compares the runs
+ * numerically. A run becomes its length without leading zeros, zero-padded to 20 digits so that any `usize` fits,
+ * then its digits without leading zeros, then its number of leading zeros, padded the same way.
+ * Runs with the same value but more leading zeros come later, which keeps e.g. "01" and "1" distinct.
+ */
+fn pad_digits(text: &str) -> String {
+    let mut padded = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if !c.is_ascii_digit() {
+            padded.push(c);
+            continue;
+        }
+
+        let mut run = c.to_string();
+        while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+            run.push(digit);
+        }
+        let value = run.trim_start_matches('0');
+        padded.push_str(&format!("{:020}{}{:020}", value.len(), value, run.len() - value.len()));
+    }
+
+    padded
+}
+
+/**
+ * Writes an index as 20 digits, offset so that negative indices come first, so that comparing
+ * the results as plain strings orders the indices numerically.
+ */
+fn pad_index(index: i64) -> String {
+    format!("{:020}", (index as u64) ^ (1 << 63))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ops::raw_traits::gates_equal;
+
+    use super::*;
+
+    #[test]
+    fn equality_values_distinguish_qubit_types() {
+        assert!(gates_equal(&LineQubit::new(3), &LineQubit::new(3)));
+        assert!(!gates_equal(&LineQubit::new(3), &LineQubit::new(4)));
+        assert!(gates_equal(&GridQubit::new(1, 2), &GridQubit::new(1, 2)));
+        assert!(!gates_equal(&GridQubit::new(1, 2), &GridQubit::new(2, 1)));
+        assert!(!gates_equal(&LineQubit::new(0), &GridQubit::new(0, 0)));
+    }
+
+    #[test]
+    fn named_qubits_sort_naturally() {
+        let names = ["q10", "q2", "q1", "a", "q010", "q123456789012", "q99999999999"];
+        let sorted = NamedQubit::sorted_by_key(names.iter().map(|name| NamedQubit::new(name)).collect());
+        let sorted: Vec<&str> = sorted.iter().map(|q| q.name()).collect();
+        assert_eq!(sorted, ["a", "q1", "q2", "q10", "q010", "q99999999999", "q123456789012"]);
+
+        assert_ne!(NamedQubit::new("q01").comparison_key(), NamedQubit::new("q1").comparison_key());
+    }
+
+    #[test]
+    fn line_and_grid_qubit_keys_sort_like_their_indices() {
+        let xs = [3, -10, 0, -2, 10, -1, 2, i64::MIN, i64::MAX];
+        let mut qubits: Vec<LineQubit> = xs.iter().map(|x| LineQubit::new(*x)).collect();
+        qubits.sort_by_cached_key(|q| q.comparison_key());
+        let mut expected: Vec<LineQubit> = xs.iter().map(|x| LineQubit::new(*x)).collect();
+        expected.sort();
+        assert_eq!(qubits, expected);
+
+        let mut grid: Vec<GridQubit> = [(0, -1), (-1, 0), (0, -2), (1, -5), (-1, 3), (0, 0)]
+            .iter()
+            .map(|(row, col)| GridQubit::new(*row, *col))
+            .collect();
+        grid.sort_by_cached_key(|q| q.comparison_key());
+        let grid: Vec<String> = grid.iter().map(|q| q.to_string()).collect();
+        assert_eq!(grid, ["q(-1,0)", "q(-1,3)", "q(0,-2)", "q(0,-1)", "q(0,0)", "q(1,-5)"]);
+
+        let mut qids = vec![LineQid::new(1, 3).unwrap(), LineQid::new(-1, 10).unwrap(), LineQid::new(1, 2).unwrap()];
+        qids.sort_by_cached_key(|q| q.comparison_key());
+        assert_eq!(qids, [LineQid::new(-1, 10).unwrap(), LineQid::new(1, 2).unwrap(), LineQid::new(1, 3).unwrap()]);
+    }
+
+    #[test]
+    fn qid_keys_display_and_equality() {
+        let line: Box<dyn QId> = Box::new(LineQubit::new(-3));
+        assert_eq!(line.to_string(), "q-3");
+        assert!(GridQubit::new(1, -2).eq_qid(&GridQubit::new(1, -2)));
+        assert_eq!((Box::new(LineQid::new(2, 3).unwrap()) as Box<dyn QId>).to_string(), "q2(d=3)");
+
+        // Qubits of different types never share a key, even when they display the same.
+        assert!(!line.eq_qid(&NamedQubit::new("q-3")));
+        assert!(!LineQubit::new(0).eq_qid(&LineQid::new(0, 2).unwrap()));
+    }
+
+    #[test]
+    fn named_qubit_keys_sort_naturally_as_strings() {
+        let names = ["q10", "q2", "q1", "a", "q010", "q1a", "x1y10", "x1y2", "q123456789012", "q99999999999"];
+        let mut keys: Vec<(String, &str)> = names.iter().map(|name| (NamedQubit::new(name).comparison_key(), *name)).collect();
+        keys.sort();
+        let sorted: Vec<&str> = keys.iter().map(|(_, name)| *name).collect();
+        assert_eq!(sorted, ["a", "q1", "q1a", "q2", "q10", "q010", "q99999999999", "q123456789012", "x1y2", "x1y10"]);
+    }
+}
@@ -1,14 +1,90 @@
-use crate::ops::raw_types::{QId, Gate};
+use num_complex::Complex;
+
+use crate::linalg;
+use crate::ops::raw_types::{Operation, QId};
 
 pub trait Decompose {
-    fn decompose<T>(&self, qubits: Vec<Box<dyn QId>>) -> T;
+    /**
+     * Decomposes the gate, applied to the given qubits, into a sequence of operations.
+     * Gates that are already native return an empty vector.
+     */
+    fn decompose(&self, qubits: Vec<Box<dyn QId>>) -> Vec<Box<dyn Operation>>;
+
+    /// Returns the decomposition of the gate, or None if the gate is already native.
+    fn decompose_once(&self, qubits: Vec<Box<dyn QId>>) -> Option<Vec<Box<dyn Operation>>> {
+        let operations = self.decompose(qubits);
+        if operations.is_empty() {
+            None
+        } else {
+            Some(operations)
+        }
+    }
 }
 
 pub trait HasUnitary {
-    fn has_unitary(&self) -> bool;
+    /**
+     * Returns the 2^n x 2^n unitary matrix of the effect, with the first qubit
+     * as the most significant bit, or None if the effect is not unitary.
+     */
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>>;
+
+    /// Determines if the effect has a unitary matrix.
+    fn has_unitary(&self) -> bool {
+        self.unitary().is_some()
+    }
+}
+
+impl<T: HasUnitary + ?Sized> HasUnitary for Box<T> {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        self.as_ref().unitary()
+    }
+}
+
+/**
+ * Returns the unitary of applying the given effects one after another, with `ops[0]` applied first.
+ * Returns None if the sequence is empty, any effect is not unitary, or the dimensions don't match.
+ */
+pub fn unitary_product(ops: &[&dyn HasUnitary]) -> Option<Vec<Vec<Complex<f64>>>> {
+    let (first, rest) = ops.split_first()?;
+    let mut product = first.unitary()?;
+
+    for op in rest {
+        let unitary = op.unitary()?;
+        if unitary.len() != product.len() {
+            return None;
+        }
+        product = linalg::dot(&unitary, &product);
+    }
+
+    Some(product)
+}
+
+/// A value that two objects can be compared by to determine if they are equal.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EqValue {
+    Int(i64),
+    Float(f64),
+    String(String),
+    Tuple(Vec<EqValue>),
 }
 
 pub trait EqualityValue {
-    fn equality_value<T>(&self) -> T;
+    /// Returns a value that is equal for two objects if and only if the objects are equal.
+    fn equality_value(&self) -> EqValue;
+}
+
+/// Returns the equality value of a matrix: its rows, with each entry as its real and imaginary parts.
+pub(crate) fn matrix_equality_value(matrix: &[Vec<Complex<f64>>]) -> EqValue {
+    EqValue::Tuple(
+        matrix
+            .iter()
+            .map(|row| EqValue::Tuple(row.iter().flat_map(|entry| [EqValue::Float(entry.re), EqValue::Float(entry.im)]).collect()))
+            .collect(),
+    )
+}
+
+/// Determines if two objects are equal by comparing their equality values.
+pub fn gates_equal(a: &dyn EqualityValue, b: &dyn EqualityValue) -> bool {
+    a.equality_value() == b.equality_value()
 }
 
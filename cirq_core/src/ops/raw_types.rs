@@ -1,15 +1,23 @@
+use std::any::Any;
 use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 
 use anyhow::Error;
 use dyn_clonable::dyn_clone;
 use dyn_clonable::dyn_clone::DynClone;
 
-use crate::utils::extra_traits::Hashable;
+use num_complex::Complex;
+
+use crate::linalg;
+use crate::ops::controlled_gate::ControlledGate;
 use crate::ops::gate_operation::GateOperation;
-use std::ops::Deref;
+use crate::ops::raw_traits::{Decompose, EqValue, EqualityValue, HasUnitary};
+use crate::study::ParamResolver;
+use crate::utils::extra_traits::Hashable;
 
 /// Identifies a quantum object such as a qubit, qudit, resonator, etc.
-pub trait QId: DynClone {
+pub trait QId: DynClone + QIdClone + Send + Sync {
     fn comparison_key(&self) -> String;
     /**
      * Returns the dimension or the number of quantum levels this qid has.
@@ -19,11 +27,91 @@ pub trait QId: DynClone {
 
     /// Validates dimension
     fn validate_dimension(&self, dimension: u64) -> Result<(), anyhow::Error>;
+
+    /// Returns the qid as `Any`, so that it can be downcast to its concrete type.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Formats the qid for display. Defaults to the comparison key.
+    fn fmt_display(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.comparison_key())
+    }
+
+    /// Determines if two qids are equal, based on their comparison keys.
+    fn eq_qid(&self, other: &dyn QId) -> bool {
+        self.comparison_key() == other.comparison_key()
+    }
+
+    /// Feeds the qid into a `std::hash::Hasher`, based on its comparison key.
+    fn std_hash(&self, state: &mut dyn Hasher) {
+        let mut state = state;
+        self.comparison_key().hash(&mut state);
+    }
 }
 
 dyn_clone::clone_trait_object!(QId);
 
-#[derive(Clone)]
+/// Returns a boxed copy of a qid. Implemented for every qid that is `Clone`.
+pub trait QIdClone {
+    fn dyn_clone(&self) -> Box<dyn QId>;
+}
+
+impl<T: 'static + QId + Clone> QIdClone for T {
+    fn dyn_clone(&self) -> Box<dyn QId> {
+        Box::new(self.clone())
+    }
+}
+
+impl fmt::Display for dyn QId + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_display(f)
+    }
+}
+
+impl fmt::Debug for dyn QId + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_display(f)
+    }
+}
+
+impl PartialEq for dyn QId {
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_qid(other)
+    }
+}
+
+impl Eq for dyn QId {}
+
+/// Determines if two qids are equal.
+pub fn qid_eq(a: &dyn QId, b: &dyn QId) -> bool {
+    a.eq_qid(b)
+}
+
+/// Wraps a `Box<dyn QId>` so that it can be used as a key in `HashMap`s and `HashSet`s.
+#[derive(Clone, Debug)]
+pub struct QIdHasher(pub Box<dyn QId>);
+
+impl QIdHasher {
+    pub fn new(qid: Box<dyn QId>) -> Self {
+        Self(qid)
+    }
+}
+
+impl Hash for QIdHasher {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.std_hash(state);
+    }
+}
+
+impl PartialEq for QIdHasher {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_qid(other.0.as_ref())
+    }
+}
+
+impl Eq for QIdHasher {}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug)]
 struct QubitAsQId {
     comparison_key: String,
     pub qubit: Box<dyn QId>,
@@ -38,9 +126,15 @@ impl PartialEq for QubitAsQId {
 
 impl Eq for QubitAsQId {}
 
+impl Hash for QubitAsQId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.comparison_key.hash(state);
+    }
+}
+
 impl PartialOrd for QubitAsQId {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.comparison_key.partial_cmp(&other.comparison_key)
+        Some(self.cmp(other))
     }
 }
 
@@ -55,8 +149,12 @@ impl QId for QubitAsQId {
         self.comparison_key.clone()
     }
 
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
     fn dimension(&self) -> u64 {
-        self.dimension.clone()
+        self.dimension
     }
 
     fn validate_dimension(&self, dimension: u64) -> Result<(), Error> {
@@ -68,6 +166,7 @@ impl QId for QubitAsQId {
     }
 }
 
+#[allow(dead_code)]
 impl QubitAsQId {
     pub fn new(qubit: Box<dyn QId>, dimension: u64) -> Result<Self, anyhow::Error> {
         let q = Self {
@@ -110,8 +209,17 @@ dyn_clone::clone_trait_object!(QIdShape);
  *   Linear combinations of gates can be created by adding gates together and
  *   multiplying them by scalars.
 */
+pub trait Gate: QIdShape + HasUnitary + fmt::Display + DynClone + GateClone + Send + Sync {
+    /// Returns the number of qubits the gate acts on.
+    fn num_qubits(&self) -> usize {
+        self.qid_shape().len()
+    }
+
+    /// Determines if the gate can be applied to `n` qubits.
+    fn is_valid_qubit_count(&self, n: usize) -> bool {
+        n == self.num_qubits()
+    }
 
-pub trait Gate: QIdShape + DynClone {
     /**
      * Checks if this gate can be applied to the given qubits.
      * By default checks that:
@@ -120,13 +228,13 @@ pub trait Gate: QIdShape + DynClone {
      * qubit_i.dimension == qid_shape[i] for all qubits
      */
     fn validate_args(&self, qubits: Vec<Box<dyn QId>>) -> Result<(), anyhow::Error> {
-        if qubits.len() != self.qid_shape().len() {
+        if !self.is_valid_qubit_count(qubits.len()) {
             return Err(anyhow!(format!("The gate can't be applied to qubits")));
         }
 
         let qid_shape = self.qid_shape();
-        for i in 0..qubits.len() {
-            if qid_shape[i] != qubits[i].dimension() {
+        for (dimension, qubit) in qid_shape.iter().zip(qubits.iter()) {
+            if *dimension != qubit.dimension() {
                 return Err(anyhow!(format!("The gate can't be applied to qubits")));
             }
         }
@@ -134,18 +242,122 @@ pub trait Gate: QIdShape + DynClone {
         Ok(())
     }
 
-    fn pow(&self, power: i64) -> Result<Box<dyn Gate>, anyhow::Error>;
+    /**
+     * Returns this gate raised to the given power.
+     * By default the gate is wrapped in a `MatrixPowGate`, which raises its unitary to the power numerically.
+     */
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, anyhow::Error> {
+        Ok(Box::new(MatrixPowGate::new(self.dyn_clone(), exponent)))
+    }
+
+    /**
+     * Returns the inverse of this gate. By default, a gate with a decomposition is wrapped in an
+     * `InverseCompositeGate`, and any other gate is raised to the power of -1.
+     */
+    fn inverse(&self) -> Result<Box<dyn Gate>, anyhow::Error> {
+        if self.as_decompose().is_some() {
+            Ok(Box::new(InverseCompositeGate::new(self.dyn_clone())))
+        } else {
+            self.pow(-1.0)
+        }
+    }
+
+    /**
+     * Returns the symbol drawn on each qubit's wire in circuit diagrams.
+     * By default the first qubit is labelled with the gate and the rest with their position.
+     */
+    fn wire_symbols(&self) -> Vec<String> {
+        let mut symbols = vec![self.to_string()];
+        symbols.extend((2..=self.num_qubits()).map(|i| format!("#{}", i)));
+        symbols
+    }
+
+    /**
+     * Returns the Kraus operators of the gate as a quantum channel, which satisfy Σ K†K = I.
+     * Defaults to the gate's unitary as the only operator, or None if the gate has no unitary.
+     */
+    fn kraus(&self) -> Option<Vec<Vec<Vec<Complex<f64>>>>> {
+        self.unitary().map(|unitary| vec![unitary])
+    }
+
+    /**
+     * Returns the gate with its symbols replaced by the values the resolver gives them.
+     * Gates without symbols are returned unchanged.
+     */
+    fn resolve_parameters(&self, _resolver: &ParamResolver) -> Result<Box<dyn Gate>, anyhow::Error> {
+        Ok(self.dyn_clone())
+    }
+
+    /**
+     * Returns a value that is equal for two gates if and only if the gates are equal.
+     * Defaults to the gate's type, which is enough for gates without parameters; gates with parameters add them.
+     */
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![EqValue::String(std::any::type_name::<Self>().to_string())])
+    }
+
+    /// Returns the gate's decomposition protocol, or None if the gate can't be decomposed.
+    fn as_decompose(&self) -> Option<&dyn Decompose> {
+        None
+    }
+
+    /// Returns a version of this gate that only acts when `num_controls` extra qubits, placed first, are all |1⟩.
+    fn controlled(&self, num_controls: usize) -> ControlledGate {
+        ControlledGate::new(self.dyn_clone(), num_controls)
+    }
 
-    fn on(&self, qubits: Vec<Box<dyn QId>>) -> Box<dyn Operation>;
+    /// Returns an application of this gate to the given qubits.
+    fn on(&self, qubits: Vec<Box<dyn QId>>) -> Result<GateOperation, anyhow::Error> {
+        self.validate_args(qubits.clone())?;
+        Ok(GateOperation::new(self.dyn_clone(), qubits))
+    }
+
+    /// Returns an application of this single-qubit gate to each of the given qubits, such as `H.on_each(qubits)`.
+    fn on_each(&self, qubits: Vec<Box<dyn QId>>) -> Result<Vec<GateOperation>, anyhow::Error> {
+        qubits.into_iter().map(|q| self.on(vec![q])).collect()
+    }
 }
 
 dyn_clone::clone_trait_object!(Gate);
 
+/// Object-safe helpers implemented for every gate that is `Clone`.
+pub trait GateClone {
+    /// Returns a boxed copy of the gate.
+    fn dyn_clone(&self) -> Box<dyn Gate>;
+
+    /// Returns the gate as `Any`, so that it can be downcast to its concrete type.
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: 'static + Gate + Clone> GateClone for T {
+    fn dyn_clone(&self) -> Box<dyn Gate> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/**
+ * Applies a gate to the given qubits, e.g. `gate_on!(CZ, q0, q1)`.
+ * Expands to `gate.on(vec![...])` with each qubit boxed as a `Box<dyn QId>`.
+ */
+#[macro_export]
+macro_rules! gate_on {
+    ($gate:expr, $($qubit:expr),+ $(,)?) => {
+        $crate::ops::raw_types::Gate::on(
+            &$gate,
+            vec![$(Box::new($qubit) as Box<dyn $crate::ops::raw_types::QId>),+],
+        )
+    };
+}
+
 /** An effect applied to a collection of qubits.
  * The most common kind of Operation is a GateOperation, which separates its
  * effect into a qubit-independent Gate and the qubits it should be applied to.
  */
-pub trait Operation: QIdShape + DynClone {
+pub trait Operation: QIdShape + HasUnitary + EqualityValue + DynClone + Send + Sync {
     fn gate(&self) -> Option<Box<dyn Gate>> {
         None
     }
@@ -157,8 +369,9 @@ pub trait Operation: QIdShape + DynClone {
      *      new_qubits: The new qubits to apply the operation to. The order must
      *          exactly match the order of qubits returned from the operation's
      *          `qubits` property.
+     *    Fails if the operation can't act on the new qubits.
      */
-    fn with_qubits(&self, new_qubits: Vec<Box<dyn QId>>) -> Box<dyn Operation>;
+    fn with_qubits(&self, new_qubits: Vec<Box<dyn QId>>) -> Result<Box<dyn Operation>, Error>;
 
     /// Returns a vector of the operation's tags.
     fn tags(&self) -> Vec<Box<dyn Hashable>>;
@@ -182,12 +395,20 @@ pub trait Operation: QIdShape + DynClone {
     *    new_tags: The tags to wrap this operation in.
     */
     fn with_tags(&self, new_tags: Vec<Box<dyn Hashable>>) -> TaggedOperation;
+
+    /// Determines if the operation has a tag with the same hash as `tag`.
+    fn is_tagged_with(&self, tag: &dyn Hashable) -> bool {
+        self.tags().iter().any(|other| other.hash() == tag.hash())
+    }
 }
 
 dyn_clone::clone_trait_object!(Operation);
 
 
-/// Operation annotated with a set of Tags.
+/**
+ * Operation annotated with a set of Tags.
+ * A tagged operation can wrap another one, in which case it has the tags of both.
+ */
 #[derive(Clone)]
 pub struct TaggedOperation {
     pub sub_operation: Box<dyn Operation>,
@@ -202,27 +423,90 @@ impl TaggedOperation {
         }
     }
 
-    pub fn qubits(&self) -> Vec<Box<dyn QId>> {
-        self.sub_operation.qubits()
+    pub fn with_qubits(&self, new_qubits: Vec<Box<dyn QId>>) -> Result<Self, Error> {
+        Ok(Self::new(self.sub_operation.with_qubits(new_qubits)?, self.tags.clone()))
+    }
+
+    /// Returns the tagged operation with the sub-operation replaced and `new_tags` added to its tags.
+    pub fn with_tags_and_operation(&self, new_tags: Vec<Box<dyn Hashable>>, new_operation: Box<dyn Operation>) -> Self {
+        let mut tags = self.tags.clone();
+        tags.extend(new_tags);
+        Self::new(new_operation, tags)
     }
 
-    pub fn gate(&self) -> Option<Box<dyn Gate>> {
+    /// Determines if the operation, or an operation it wraps, has a tag with the same hash as `tag`.
+    pub fn has_tag(&self, tag: &dyn Hashable) -> bool {
+        self.is_tagged_with(tag)
+    }
+
+    /// Returns the untagged operation with every tag except those with the same hash as `tag`.
+    pub fn without_tag(&self, tag: &dyn Hashable) -> TaggedOperation {
+        let tags = self.tags().into_iter().filter(|other| other.hash() != tag.hash()).collect();
+        Self::new(self.untagged(), tags)
+    }
+}
+
+impl QIdShape for TaggedOperation {
+    fn qid_shape(&self) -> Vec<u64> {
+        self.sub_operation.qid_shape()
+    }
+}
+
+impl HasUnitary for TaggedOperation {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        self.sub_operation.unitary()
+    }
+}
+
+impl Operation for TaggedOperation {
+    fn gate(&self) -> Option<Box<dyn Gate>> {
         self.sub_operation.gate()
     }
 
-    pub fn with_qubits(&self, new_qubits: Vec<Box<dyn QId>>) -> Self {
-        Self::new(self.sub_operation.with_qubits(new_qubits), self.tags.clone())
+    fn qubits(&self) -> Vec<Box<dyn QId>> {
+        self.sub_operation.qubits()
+    }
+
+    fn with_qubits(&self, new_qubits: Vec<Box<dyn QId>>) -> Result<Box<dyn Operation>, Error> {
+        Ok(Box::new(TaggedOperation::with_qubits(self, new_qubits)?))
+    }
+
+    /// Returns the tags of this operation, followed by those of the operations it wraps.
+    fn tags(&self) -> Vec<Box<dyn Hashable>> {
+        let mut tags = self.tags.clone();
+        tags.extend(self.sub_operation.tags());
+        tags
+    }
+
+    /// Returns the innermost operation, unwrapping every level of tags.
+    fn untagged(&self) -> Box<dyn Operation> {
+        self.sub_operation.untagged()
+    }
+
+    fn with_tags(&self, new_tags: Vec<Box<dyn Hashable>>) -> TaggedOperation {
+        self.with_tags_and_operation(new_tags, self.sub_operation.clone())
     }
+}
 
-    pub fn tags(&self) -> Vec<Box<dyn Hashable>> {
-        self.tags.clone()
+impl EqualityValue for TaggedOperation {
+    /**
+     * Combines the hashes of this operation's tags with the full equality value of its sub-operation,
+     * so the tags of nested tagged operations are compared too.
+     */
+    fn equality_value(&self) -> EqValue {
+        let tags = self.tags.iter().map(|tag| EqValue::Int(tag.hash() as i64)).collect();
+        let sub_operation: &dyn Operation = self.sub_operation.as_ref();
+        EqValue::Tuple(vec![sub_operation.equality_value(), EqValue::Tuple(tags)])
     }
 }
 
-/// The inverse of a composite gate.
+/**
+ * The inverse of a composite gate. Its unitary is the conjugate transpose of the original's, and it
+ * decomposes into the inverses of the original's decomposition, in reverse order.
+ */
 #[derive(Clone)]
-struct InverseCompositeGate {
-    original: Box<dyn Gate>,
+pub struct InverseCompositeGate {
+    pub original: Box<dyn Gate>,
 }
 
 impl InverseCompositeGate {
@@ -239,19 +523,236 @@ impl QIdShape for InverseCompositeGate {
     }
 }
 
+impl fmt::Display for InverseCompositeGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}**-1", self.original)
+    }
+}
+
+impl HasUnitary for InverseCompositeGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        self.original.unitary().map(|unitary| linalg::dagger(&unitary))
+    }
+}
+
+impl Decompose for InverseCompositeGate {
+    /// Returns the inverted decomposition, or nothing if an operation of the original's decomposition can't be inverted.
+    fn decompose(&self, qubits: Vec<Box<dyn QId>>) -> Vec<Box<dyn Operation>> {
+        let decomposition = match self.original.as_decompose() {
+            Some(decompose) => decompose.decompose(qubits),
+            None => return vec![],
+        };
+        decomposition
+            .iter()
+            .rev()
+            .map(|op| {
+                let inverse = op.gate()?.inverse().ok()?;
+                Some(Box::new(GateOperation::new(inverse, op.qubits())) as Box<dyn Operation>)
+            })
+            .collect::<Option<_>>()
+            .unwrap_or_default()
+    }
+}
+
 impl Gate for InverseCompositeGate {
-    fn pow(&self, power: i64) -> Result<Box<dyn Gate>, Error> {
-        if power == 1 {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![EqValue::String("InverseCompositeGate".to_string()), self.original.equality_value()])
+    }
+
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        if exponent == 1.0 {
             Ok(Box::new(self.clone()))
-        } else if power == -1 {
+        } else if exponent == -1.0 {
             Ok(self.original.clone())
         } else {
-            Err(anyhow!("Not implemented"))
+            Ok(Box::new(MatrixPowGate::new(self.original.clone(), -exponent)))
+        }
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(self.original.clone())
+    }
+
+    fn as_decompose(&self) -> Option<&dyn Decompose> {
+        self.original.as_decompose().map(|_| self as &dyn Decompose)
+    }
+}
+
+/**
+ * A gate raised to a power by raising its unitary to that power through its eigendecomposition.
+ * This is the fallback for gates that don't have a closed form for their powers.
+ */
+#[derive(Clone)]
+pub struct MatrixPowGate {
+    pub original: Box<dyn Gate>,
+    pub exponent: f64,
+}
+
+impl MatrixPowGate {
+    pub fn new(original: Box<dyn Gate>, exponent: f64) -> Self {
+        Self {
+            original,
+            exponent,
         }
     }
+}
 
-    fn on(&self, qubits: Vec<Box<dyn QId>>) -> Box<dyn Operation> {
-        Box::new(GateOperation::new(Box::new(self.clone()), qubits))
+impl QIdShape for MatrixPowGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        self.original.qid_shape()
     }
 }
 
+impl fmt::Display for MatrixPowGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}**{}", self.original, self.exponent)
+    }
+}
+
+impl HasUnitary for MatrixPowGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        self.original.unitary().map(|unitary| linalg::unitary_power(&unitary, self.exponent))
+    }
+}
+
+impl Gate for MatrixPowGate {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![
+            EqValue::String("MatrixPowGate".to_string()),
+            self.original.equality_value(),
+            EqValue::Float(self.exponent),
+        ])
+    }
+
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(MatrixPowGate::new(self.original.clone(), self.exponent * exponent)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ops::common_gates::{HGate, CNOT, H, S};
+    use crate::ops::pauli_gates::{XGate, X};
+    use crate::ops::qubits::LineQubit;
+    use crate::ops::raw_traits::gates_equal;
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct Tag(&'static str);
+
+    impl Hashable for Tag {
+        fn hash(&self) -> u64 {
+            self.0.bytes().fold(17, |hash: u64, byte| hash.wrapping_mul(31).wrapping_add(byte as u64))
+        }
+    }
+
+    fn tag(name: &'static str) -> Box<dyn Hashable> {
+        Box::new(Tag(name))
+    }
+
+    #[test]
+    fn tags_are_filtered_and_unwrapped() {
+        let q = LineQubit::new(0);
+        let op = gate_on!(H, q.clone()).unwrap();
+        let tagged = op.with_tags(vec![tag("a"), tag("b")]);
+        assert!(tagged.has_tag(tag("a").as_ref()));
+        assert!(!op.is_tagged_with(tag("a").as_ref()));
+
+        let without = tagged.without_tag(tag("a").as_ref());
+        assert!(!without.has_tag(tag("a").as_ref()));
+        assert!(without.has_tag(tag("b").as_ref()));
+
+        // Nested tagged operations have every tag and unwrap to the innermost operation.
+        let nested = TaggedOperation::new(Box::new(tagged.clone()), vec![tag("c")]);
+        assert_eq!(nested.tags().len(), 3);
+        assert!(nested.is_tagged_with(tag("b").as_ref()));
+        assert!(gates_equal(nested.untagged().as_ref(), &op));
+        assert_eq!(nested.without_tag(tag("b").as_ref()).tags().len(), 2);
+
+        // Tags of nested operations take part in equality.
+        let differently_nested = TaggedOperation::new(Box::new(op.with_tags(vec![tag("a"), tag("e")])), vec![tag("c")]);
+        assert!(gates_equal(&nested, &TaggedOperation::new(Box::new(tagged.clone()), vec![tag("c")])));
+        assert!(!gates_equal(&nested, &differently_nested));
+        assert!(!gates_equal(&nested, &TaggedOperation::new(Box::new(op.clone()), vec![tag("c")])));
+
+        let replaced = tagged.with_tags_and_operation(vec![tag("d")], Box::new(gate_on!(X, q).unwrap()));
+        assert_eq!(replaced.tags().len(), 3);
+        assert!(replaced.gate().unwrap().as_any().is::<XGate>());
+    }
+
+    #[test]
+    fn matrix_powers_compose() {
+        let root = X.pow(0.5).unwrap();
+        let squared = root.pow(2.0).unwrap();
+        assert!(linalg::allclose(&squared.unitary().unwrap(), &X.unitary().unwrap(), 1e-10));
+
+        let root_unitary = root.unitary().unwrap();
+        let half = Complex::new(0.5, 0.0);
+        let expected = vec![vec![half * Complex::new(1.0, 1.0), half * Complex::new(1.0, -1.0)],
+                            vec![half * Complex::new(1.0, -1.0), half * Complex::new(1.0, 1.0)]];
+        assert!(linalg::allclose(&root_unitary, &expected, 1e-10));
+    }
+
+    #[test]
+    fn matrix_powers_of_degenerate_unitaries() {
+        for gate in [Box::new(H) as Box<dyn Gate>, Box::new(CNOT)] {
+            let unitary = gate.unitary().unwrap();
+            let third = gate.pow(1.0 / 3.0).unwrap().unitary().unwrap();
+            assert!(linalg::allclose(&linalg::dot(&third, &linalg::dot(&third, &third)), &unitary, 1e-10));
+            assert!(linalg::allclose(&gate.inverse().unwrap().unitary().unwrap(), &linalg::dagger(&unitary), 1e-10));
+        }
+    }
+
+    /// Applies H and then S, through its decomposition.
+    #[derive(Clone)]
+    struct HThenS;
+
+    impl fmt::Display for HThenS {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "HThenS")
+        }
+    }
+
+    impl QIdShape for HThenS {
+        fn qid_shape(&self) -> Vec<u64> {
+            vec![2]
+        }
+    }
+
+    impl HasUnitary for HThenS {
+        fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+            Some(linalg::dot(&S.unitary().unwrap(), &H.unitary().unwrap()))
+        }
+    }
+
+    impl Decompose for HThenS {
+        fn decompose(&self, qubits: Vec<Box<dyn QId>>) -> Vec<Box<dyn Operation>> {
+            vec![Box::new(H.on(qubits.clone()).unwrap()), Box::new(S.on(qubits).unwrap())]
+        }
+    }
+
+    impl Gate for HThenS {
+        fn as_decompose(&self) -> Option<&dyn Decompose> {
+            Some(self)
+        }
+    }
+
+    #[test]
+    fn composite_gates_are_inverted_through_their_decomposition() {
+        let inverse = HThenS.inverse().unwrap();
+        assert!(inverse.as_any().is::<InverseCompositeGate>());
+        assert_eq!(inverse.to_string(), "HThenS**-1");
+        assert!(linalg::allclose(&inverse.unitary().unwrap(), &linalg::dagger(&HThenS.unitary().unwrap()), 1e-10));
+
+        let q: Box<dyn QId> = Box::new(LineQubit::new(0));
+        let ops = inverse.as_decompose().unwrap().decompose(vec![q]);
+        assert_eq!(ops.len(), 2);
+        assert!(linalg::allclose(&ops[0].unitary().unwrap(), &linalg::dagger(&S.unitary().unwrap()), 1e-10));
+        assert!(ops[1].gate().unwrap().as_any().is::<HGate>());
+        assert!(inverse.inverse().unwrap().as_any().is::<HThenS>());
+
+        // Only the inverses of decomposable gates can be decomposed.
+        assert!(InverseCompositeGate::new(Box::new(H)).as_decompose().is_none());
+    }
+}
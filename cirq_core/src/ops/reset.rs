@@ -0,0 +1,49 @@
+use std::fmt;
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::ops::raw_traits::HasUnitary;
+use crate::ops::raw_types::{Gate, QIdShape};
+
+/**
+ * Resets a qubit to |0⟩, whatever its state.
+ * This is a non-unitary channel with the Kraus operators |0⟩⟨0| and |0⟩⟨1|.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResetChannel;
+
+pub const R: ResetChannel = ResetChannel;
+
+impl fmt::Display for ResetChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "R")
+    }
+}
+
+impl QIdShape for ResetChannel {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2]
+    }
+}
+
+impl HasUnitary for ResetChannel {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        None
+    }
+}
+
+impl Gate for ResetChannel {
+    fn pow(&self, _exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Err(anyhow!("A reset can't be raised to a power."))
+    }
+
+    fn kraus(&self) -> Option<Vec<Vec<Vec<Complex<f64>>>>> {
+        let one = Complex::new(1.0, 0.0);
+        let zero = Complex::new(0.0, 0.0);
+        Some(vec![
+            vec![vec![one, zero], vec![zero, zero]],
+            vec![vec![zero, one], vec![zero, zero]],
+        ])
+    }
+}
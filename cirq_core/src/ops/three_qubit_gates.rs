@@ -0,0 +1,323 @@
+use std::fmt;
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::ops::common_gates::{self_inverse_pow, CZGate, CNOT};
+use crate::ops::gate_operation::GateOperation;
+use crate::ops::raw_traits::{Decompose, HasUnitary};
+use crate::ops::raw_types::{Gate, Operation, QId, QIdShape};
+
+/// The Toffoli gate, which flips the third qubit when the first two qubits are both |1⟩.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ToffoliGate;
+
+pub type CCXGate = ToffoliGate;
+
+pub const TOFFOLI: ToffoliGate = ToffoliGate;
+pub const CCX: CCXGate = ToffoliGate;
+
+/// The doubly controlled Z gate, which flips the phase of |111⟩.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CCZGate;
+
+pub const CCZ: CCZGate = CCZGate;
+
+/// The triply controlled X gate, which flips the fourth qubit when the first three qubits are all |1⟩.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CCCXGate;
+
+impl ToffoliGate {
+    /**
+     * Returns the Toffoli gate with an additional control qubit, as a dedicated 4-qubit `CCCXGate`.
+     * This shadows `Gate::controlled` in method calls; use `Gate::controlled(&TOFFOLI, n)` for a `ControlledGate`.
+     */
+    pub fn controlled(&self) -> CCCXGate {
+        CCCXGate
+    }
+}
+
+impl CZGate {
+    /**
+     * Returns the CZ gate with an additional control qubit, as a dedicated `CCZGate`.
+     * This shadows `Gate::controlled` in method calls; use `Gate::controlled(&CZ, n)` for a `ControlledGate`.
+     */
+    pub fn controlled(&self) -> CCZGate {
+        CCZGate
+    }
+}
+
+impl fmt::Display for ToffoliGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TOFFOLI")
+    }
+}
+
+impl fmt::Display for CCZGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CCZ")
+    }
+}
+
+impl fmt::Display for CCCXGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CCCX")
+    }
+}
+
+impl QIdShape for ToffoliGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2, 2, 2]
+    }
+}
+
+impl QIdShape for CCZGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2, 2, 2]
+    }
+}
+
+impl QIdShape for CCCXGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2, 2, 2, 2]
+    }
+}
+
+impl HasUnitary for ToffoliGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        let mut unitary = vec![vec![Complex::new(0.0, 0.0); 8]; 8];
+        for (i, row) in unitary.iter_mut().enumerate() {
+            let j = if i >= 6 { i ^ 1 } else { i };
+            row[j] = Complex::new(1.0, 0.0);
+        }
+        Some(unitary)
+    }
+}
+
+impl HasUnitary for CCZGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        let mut unitary = vec![vec![Complex::new(0.0, 0.0); 8]; 8];
+        for (i, row) in unitary.iter_mut().enumerate() {
+            row[i] = Complex::new(if i == 7 { -1.0 } else { 1.0 }, 0.0);
+        }
+        Some(unitary)
+    }
+}
+
+impl HasUnitary for CCCXGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        let mut unitary = vec![vec![Complex::new(0.0, 0.0); 16]; 16];
+        for (i, row) in unitary.iter_mut().enumerate() {
+            let j = if i >= 14 { i ^ 1 } else { i };
+            row[j] = Complex::new(1.0, 0.0);
+        }
+        Some(unitary)
+    }
+}
+
+impl Gate for ToffoliGate {
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        self_inverse_pow(Box::new(*self), exponent)
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(*self))
+    }
+
+    fn wire_symbols(&self) -> Vec<String> {
+        vec!["@".to_string(), "@".to_string(), "X".to_string()]
+    }
+}
+
+impl Gate for CCZGate {
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        self_inverse_pow(Box::new(*self), exponent)
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(*self))
+    }
+
+    fn wire_symbols(&self) -> Vec<String> {
+        vec!["@".to_string(), "@".to_string(), "@".to_string()]
+    }
+}
+
+impl Gate for CCCXGate {
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        self_inverse_pow(Box::new(*self), exponent)
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(*self))
+    }
+
+    fn wire_symbols(&self) -> Vec<String> {
+        vec!["@".to_string(), "@".to_string(), "@".to_string(), "X".to_string()]
+    }
+}
+
+/// The Fredkin gate, which swaps the last two qubits when the first qubit is |1⟩.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FredkinGate;
+
+pub type CSwapGate = FredkinGate;
+
+pub const FREDKIN: FredkinGate = FredkinGate;
+pub const CSWAP: CSwapGate = FredkinGate;
+
+impl fmt::Display for FredkinGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FREDKIN")
+    }
+}
+
+impl QIdShape for FredkinGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2, 2, 2]
+    }
+}
+
+impl HasUnitary for FredkinGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        let mut unitary = vec![vec![Complex::new(0.0, 0.0); 8]; 8];
+        for (i, row) in unitary.iter_mut().enumerate() {
+            let j = match i {
+                5 => 6,
+                6 => 5,
+                _ => i,
+            };
+            row[j] = Complex::new(1.0, 0.0);
+        }
+        Some(unitary)
+    }
+}
+
+impl Decompose for FredkinGate {
+    /**
+     * Decomposes the gate, applied to `[control, a, b]`, into CNOT(b, a), TOFFOLI(control, a, b)
+     * and CNOT(b, a). Returns nothing for any other number of qubits.
+     */
+    fn decompose(&self, qubits: Vec<Box<dyn QId>>) -> Vec<Box<dyn Operation>> {
+        if qubits.len() != 3 {
+            return vec![];
+        }
+        let (control, a, b) = (qubits[0].clone(), qubits[1].clone(), qubits[2].clone());
+        vec![
+            Box::new(GateOperation::new(Box::new(CNOT), vec![b.clone(), a.clone()])),
+            Box::new(GateOperation::new(Box::new(TOFFOLI), vec![control, a.clone(), b.clone()])),
+            Box::new(GateOperation::new(Box::new(CNOT), vec![b, a])),
+        ]
+    }
+}
+
+impl Gate for FredkinGate {
+    fn pow(&self, exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        self_inverse_pow(Box::new(*self), exponent)
+    }
+
+    fn inverse(&self) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(*self))
+    }
+
+    fn wire_symbols(&self) -> Vec<String> {
+        vec!["@".to_string(), "×".to_string(), "×".to_string()]
+    }
+
+    fn as_decompose(&self) -> Option<&dyn Decompose> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gate_on;
+    use crate::linalg;
+    use crate::ops::common_gates::CZ;
+    use crate::ops::qubits::LineQubit;
+
+    use super::*;
+
+    #[test]
+    fn toffoli_unitary() {
+        let unitary = TOFFOLI.unitary().unwrap();
+        for (i, row) in unitary.iter().enumerate() {
+            for (j, entry) in row.iter().enumerate() {
+                let expected = match (i, j) {
+                    (6, 7) | (7, 6) => 1.0,
+                    (6, 6) | (7, 7) => 0.0,
+                    _ if i == j => 1.0,
+                    _ => 0.0,
+                };
+                assert_eq!(*entry, Complex::new(expected, 0.0), "entry ({}, {})", i, j);
+            }
+        }
+        assert_eq!(CCX.unitary(), Some(unitary));
+    }
+
+    #[test]
+    fn ccz_unitary() {
+        let unitary = CCZ.unitary().unwrap();
+        for (i, row) in unitary.iter().enumerate() {
+            for (j, entry) in row.iter().enumerate() {
+                let expected = match (i, j) {
+                    (7, 7) => -1.0,
+                    _ if i == j => 1.0,
+                    _ => 0.0,
+                };
+                assert_eq!(*entry, Complex::new(expected, 0.0), "entry ({}, {})", i, j);
+            }
+        }
+        assert_eq!(CZ.controlled(), CCZ);
+        assert_eq!(Gate::controlled(&CZ, 1).unitary(), Some(unitary));
+    }
+
+    #[test]
+    fn cccx_unitary() {
+        let unitary = TOFFOLI.controlled().unitary().unwrap();
+        assert_eq!(Gate::controlled(&TOFFOLI, 1).unitary(), Some(unitary.clone()));
+        assert_eq!(unitary[14][15], Complex::new(1.0, 0.0));
+        assert_eq!(unitary[15][14], Complex::new(1.0, 0.0));
+        assert_eq!(unitary[13][13], Complex::new(1.0, 0.0));
+        assert_eq!(unitary[15][15], Complex::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn ccz_validates_qubit_count() {
+        assert!(gate_on!(CCZ, LineQubit::new(0), LineQubit::new(1), LineQubit::new(2)).is_ok());
+        assert!(gate_on!(CCZ, LineQubit::new(0), LineQubit::new(1)).is_err());
+    }
+
+    #[test]
+    fn fredkin_swaps_targets_when_control_is_set() {
+        let unitary = CSWAP.unitary().unwrap();
+        // |101⟩ -> |110⟩ and |110⟩ -> |101⟩, while |001⟩ and |010⟩ are unchanged.
+        assert_eq!(unitary[6][5], Complex::new(1.0, 0.0));
+        assert_eq!(unitary[5][6], Complex::new(1.0, 0.0));
+        assert_eq!(unitary[1][1], Complex::new(1.0, 0.0));
+        assert_eq!(unitary[2][2], Complex::new(1.0, 0.0));
+    }
+
+    #[test]
+    fn fredkin_decomposition() {
+        let qubits: Vec<Box<dyn QId>> = LineQubit::range(3).into_iter().map(|q| Box::new(q) as Box<dyn QId>).collect();
+        let ops = FREDKIN.decompose(qubits.clone());
+
+        assert_eq!(ops.len(), 3);
+        assert_eq!(ops[0].qubits(), vec![qubits[2].clone(), qubits[1].clone()]);
+        assert_eq!(ops[1].qubits(), qubits);
+        assert_eq!(ops[2].qubits(), vec![qubits[2].clone(), qubits[1].clone()]);
+
+        // Compose the unitaries of the returned operations, each embedded on its own qubits.
+        let mut columns = linalg::eye(8);
+        for op in &ops {
+            let targets: Vec<usize> = op.qubits().iter().map(|q| qubits.iter().position(|p| p == q).unwrap()).collect();
+            let unitary = op.unitary().unwrap();
+            columns = columns.iter().map(|column| linalg::targeted_left_multiply(&unitary, &targets, column, 3)).collect();
+        }
+        assert!(linalg::allclose(&linalg::transpose(&columns), &FREDKIN.unitary().unwrap(), 1e-10));
+
+        assert!(FREDKIN.decompose(qubits[..2].to_vec()).is_empty());
+        assert!(FREDKIN.decompose_once(vec![]).is_none());
+    }
+}
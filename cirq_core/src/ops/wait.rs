@@ -0,0 +1,137 @@
+use std::fmt;
+use std::time::Duration;
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::linalg;
+use crate::ops::gate_operation::GateOperation;
+use crate::ops::raw_traits::{EqValue, HasUnitary};
+use crate::ops::raw_types::{Gate, QId, QIdShape};
+
+/**
+ * Leaves qubits idle for `duration_nanos` nanoseconds.
+ * It acts as the identity under unitary simulation, but noise models can use its duration
+ * to apply the decoherence that idle qubits suffer on hardware.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WaitGate {
+    pub duration_nanos: f64,
+    pub num_qubits: usize,
+}
+
+impl WaitGate {
+    /// Creates a wait. Fails unless the duration is finite and not negative.
+    pub fn new(duration_nanos: f64, num_qubits: usize) -> Result<Self, Error> {
+        if !duration_nanos.is_finite() || duration_nanos < 0.0 {
+            return Err(anyhow!(format!("The duration of a wait must be finite and not negative, but is {}ns.", duration_nanos)));
+        }
+        Ok(Self {
+            duration_nanos,
+            num_qubits,
+        })
+    }
+
+    /**
+     * Returns the idle time. Negative or NaN `duration_nanos` values, which `new` rejects but the public
+     * field allows, are treated as zero; values too large for a `Duration` saturate to `Duration::MAX`.
+     */
+    pub fn duration(&self) -> Duration {
+        Duration::try_from_secs_f64(self.duration_nanos * 1e-9)
+            .unwrap_or(if self.duration_nanos > 0.0 { Duration::MAX } else { Duration::ZERO })
+    }
+}
+
+/// The unit of a duration given as a number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeUnit {
+    Nanoseconds,
+    Microseconds,
+}
+
+impl TimeUnit {
+    /// Returns the duration as a number of this unit.
+    pub fn convert(&self, duration: Duration) -> f64 {
+        match self {
+            TimeUnit::Nanoseconds => duration.as_nanos() as f64,
+            TimeUnit::Microseconds => duration.as_nanos() as f64 / 1000.0,
+        }
+    }
+}
+
+/// Returns a wait of `duration` nanoseconds on the given qubits. Fails unless the duration is finite and not negative.
+pub fn wait(duration: f64, qubits: &[&dyn QId]) -> Result<GateOperation, Error> {
+    Ok(GateOperation::new(
+        Box::new(WaitGate::new(duration, qubits.len())?),
+        qubits.iter().map(|q| q.dyn_clone()).collect(),
+    ))
+}
+
+impl fmt::Display for WaitGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Wait({}ns)", self.duration_nanos)
+    }
+}
+
+impl QIdShape for WaitGate {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2; self.num_qubits]
+    }
+}
+
+impl HasUnitary for WaitGate {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        Some(linalg::eye(1 << self.num_qubits))
+    }
+}
+
+impl Gate for WaitGate {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![
+            EqValue::String("WaitGate".to_string()),
+            EqValue::Float(self.duration_nanos),
+            EqValue::Int(self.num_qubits as i64),
+        ])
+    }
+
+    /// A wait acts as the identity, so every power of it is the same wait.
+    fn pow(&self, _exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Ok(Box::new(*self))
+    }
+
+    fn wire_symbols(&self) -> Vec<String> {
+        vec![self.to_string(); self.num_qubits]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ops::qubits::LineQubit;
+
+    use super::*;
+
+    #[test]
+    fn wait_is_identity_with_a_duration() {
+        let q = LineQubit::range(2);
+        let op = wait(1500.0, &[&q[0], &q[1]]).unwrap();
+        assert_eq!(op.unitary(), Some(linalg::eye(4)));
+
+        let gate = WaitGate::new(1500.0, 2).unwrap();
+        assert_eq!(gate.duration(), Duration::from_nanos(1500));
+        assert_eq!(gate.to_string(), "Wait(1500ns)");
+        assert_eq!(gate.pow(0.5).unwrap().to_string(), gate.to_string());
+        assert_eq!(TimeUnit::Microseconds.convert(gate.duration()), 1.5);
+    }
+
+    #[test]
+    fn waits_reject_non_finite_durations() {
+        assert!(WaitGate::new(f64::INFINITY, 1).is_err());
+        assert!(WaitGate::new(f64::NAN, 1).is_err());
+        assert!(WaitGate::new(-1.0, 1).is_err());
+        assert!(wait(f64::INFINITY, &[&LineQubit::new(0)]).is_err());
+
+        let gate = WaitGate { duration_nanos: f64::INFINITY, num_qubits: 1 };
+        assert_eq!(gate.duration(), Duration::MAX);
+        assert_eq!(WaitGate { duration_nanos: -5.0, num_qubits: 1 }.duration(), Duration::ZERO);
+    }
+}
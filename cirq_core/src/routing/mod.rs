@@ -0,0 +1,182 @@
+use std::collections::VecDeque;
+
+use anyhow::Error;
+
+use crate::circuits::circuit::Circuit;
+use crate::ops::common_gates::SWAP;
+use crate::ops::measure::MeasurementGate;
+use crate::ops::qubits::LineQubit;
+use crate::ops::raw_types::{Gate, Operation, QId};
+
+/// A circuit routed onto physical qubits, with the physical qubit holding each logical qubit at the end.
+#[derive(Clone)]
+pub struct RoutedCircuit {
+    pub circuit: Circuit,
+    pub final_mapping: Vec<(Box<dyn QId>, LineQubit)>,
+}
+
+/**
+ * Routes circuits onto hardware whose physical qubits are `LineQubit(i)` for every index in the
+ * coupling map, and whose two-qubit gates can only act on the coupled pairs.
+ * The logical qubits start on the physical qubits 0, 1, 2, ... in the default qubit order. Before each
+ * two-qubit gate on uncoupled qubits, the first qubit is swapped along a shortest path until it is
+ * next to the second one.
+ */
+#[derive(Clone, Debug)]
+pub struct SWAPNetworkRouter {
+    pub coupling_map: Vec<(usize, usize)>,
+}
+
+impl SWAPNetworkRouter {
+    pub fn new(coupling_map: Vec<(usize, usize)>) -> Self {
+        Self {
+            coupling_map
+        }
+    }
+
+    /// Returns the number of physical qubits of the device.
+    pub fn num_physical_qubits(&self) -> usize {
+        self.coupling_map.iter().map(|(a, b)| a.max(b) + 1).max().unwrap_or(0)
+    }
+
+    fn are_coupled(&self, a: usize, b: usize) -> bool {
+        self.coupling_map.iter().any(|edge| *edge == (a, b) || *edge == (b, a))
+    }
+
+    /// Returns a shortest path of physical qubits from `start` to `end`, including both, if there is one.
+    fn shortest_path(&self, start: usize, end: usize) -> Option<Vec<usize>> {
+        let mut previous = vec![None; self.num_physical_qubits()];
+        let mut queue = VecDeque::from(vec![start]);
+        previous[start] = Some(start);
+
+        while let Some(node) = queue.pop_front() {
+            if node == end {
+                let mut path = vec![end];
+                while let Some(before) = previous[*path.last().unwrap()].filter(|before| *before != *path.last().unwrap()) {
+                    path.push(before);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            let neighbors = self.coupling_map.iter().filter_map(|(a, b)| {
+                if *a == node {
+                    Some(*b)
+                } else if *b == node {
+                    Some(*a)
+                } else {
+                    None
+                }
+            });
+            for next in neighbors {
+                if previous[next].is_none() {
+                    previous[next] = Some(node);
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    /// Routes the circuit, failing if it has too many qubits, gates on more than two qubits or uncoupled components.
+    pub fn route(&self, circuit: &Circuit) -> Result<RoutedCircuit, Error> {
+        let logical = circuit.all_qubits();
+        let num_physical = self.num_physical_qubits();
+        if logical.len() > num_physical {
+            return Err(anyhow!(format!("Can't route {} qubits onto a device with {} qubits.", logical.len(), num_physical)));
+        }
+
+        // The physical qubit of each logical qubit, by index into `logical`.
+        let mut placement: Vec<usize> = (0..logical.len()).collect();
+        let logical_index = |q: &dyn QId| logical.iter().position(|other| other.eq_qid(q)).unwrap();
+        let physical = |i: usize| Box::new(LineQubit::new(i as i64)) as Box<dyn QId>;
+
+        let mut ops: Vec<Box<dyn Operation>> = vec![];
+        for op in circuit.all_operations() {
+            let qubits: Vec<usize> = op.qubits().iter().map(|q| logical_index(q.as_ref())).collect();
+            let is_measurement = op.gate().is_some_and(|gate| gate.as_any().is::<MeasurementGate>());
+
+            if qubits.len() == 2 && !is_measurement {
+                let target = placement[qubits[1]];
+                while !self.are_coupled(placement[qubits[0]], target) {
+                    let path = self
+                        .shortest_path(placement[qubits[0]], target)
+                        .ok_or_else(|| anyhow!(format!("Physical qubits {} and {} aren't connected.", placement[qubits[0]], target)))?;
+                    let (from, to) = (path[0], path[1]);
+                    ops.push(Box::new(SWAP.on(vec![physical(from), physical(to)])?));
+                    for position in placement.iter_mut() {
+                        if *position == to {
+                            *position = from;
+                        } else if *position == from {
+                            *position = to;
+                        }
+                    }
+                }
+            } else if qubits.len() > 2 && !is_measurement {
+                return Err(anyhow!("Only one and two qubit gates can be routed."));
+            }
+
+            ops.push(op.with_qubits(qubits.iter().map(|i| physical(placement[*i])).collect())?);
+        }
+
+        let mut routed = Circuit::new();
+        routed.append(ops)?;
+        Ok(RoutedCircuit {
+            circuit: routed,
+            final_mapping: logical.into_iter().zip(placement).map(|(q, i)| (q, LineQubit::new(i as i64))).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gate_on;
+    use crate::ops::common_gates::{CNOT, H};
+    use crate::ops::pauli_gates::X;
+    use crate::sim::state_vector_simulator::StateVectorSimulator;
+
+    use super::*;
+
+    fn line_router(n: usize) -> SWAPNetworkRouter {
+        SWAPNetworkRouter::new((1..n).map(|i| (i - 1, i)).collect())
+    }
+
+    #[test]
+    fn distant_gates_are_routed_with_swaps() {
+        let q = LineQubit::range(4);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            Box::new(gate_on!(X, q[0].clone()).unwrap()),
+            Box::new(gate_on!(H, q[1].clone()).unwrap()),
+            Box::new(gate_on!(CNOT, q[0].clone(), q[3].clone()).unwrap()),
+        ]).unwrap();
+
+        let router = line_router(5);
+        let routed = router.route(&circuit).unwrap();
+        for op in routed.circuit.all_operations().filter(|op| op.qubits().len() == 2) {
+            let ends: Vec<i64> = op.qubits().iter().map(|q| q.as_any().downcast_ref::<LineQubit>().unwrap().x).collect();
+            assert!(router.are_coupled(ends[0] as usize, ends[1] as usize));
+        }
+        assert_eq!(routed.circuit.all_operations().count(), 4);
+
+        // q3 starts on the third physical qubit, so a single swap brings q0 next to it.
+        let result = StateVectorSimulator::with_seed(3).simulate(&routed.circuit, None).unwrap();
+        let physical = |logical: &LineQubit| routed.final_mapping.iter().find(|(q, _)| q.eq_qid(logical)).unwrap().1.clone();
+        assert_eq!(physical(&q[0]).x, 1);
+        assert_eq!(physical(&q[1]).x, 0);
+        assert_eq!(result.measure(&[&physical(&q[0]), &physical(&q[3])]).measurements, vec![true, true]);
+    }
+
+    #[test]
+    fn unroutable_circuits_are_rejected() {
+        let q = LineQubit::range(3);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            Box::new(gate_on!(H, q[1].clone()).unwrap()),
+            Box::new(gate_on!(CNOT, q[0].clone(), q[2].clone()).unwrap()),
+        ]).unwrap();
+
+        assert!(line_router(2).route(&circuit).is_err());
+        assert!(SWAPNetworkRouter::new(vec![(0, 1), (2, 3)]).route(&circuit).is_err());
+        assert!(line_router(3).route(&circuit).is_ok());
+    }
+}
@@ -0,0 +1,242 @@
+use num_complex::Complex;
+use rand::Rng;
+
+use crate::ops::pauli_gates::Pauli;
+use crate::ops::pauli_string::PauliString;
+use crate::ops::qubits::LineQubit;
+use crate::ops::raw_types::QId;
+
+/**
+ * The stabilizer tableau of an `n` qubit stabilizer state, following Aaronson and Gottesman.
+ * Rows `0..n` are the destabilizers and rows `n..2n` the stabilizers. Row `i` stands for the
+ * Pauli string `(-1)^rs[i]` times the product over qubits `j` of X if only `xs[i][j]` is set,
+ * Z if only `zs[i][j]` is set, and Y if both are.
+ * Gates and measurements take time polynomial in `n`, so hundreds of qubits are cheap.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CliffordTableau {
+    pub n: usize,
+    pub xs: Vec<Vec<bool>>,
+    pub zs: Vec<Vec<bool>>,
+    pub rs: Vec<bool>,
+}
+
+impl CliffordTableau {
+    /// Returns the tableau of |0...0⟩, which is stabilized by each Z and destabilized by each X.
+    pub fn new(n: usize) -> Self {
+        let row = |i: usize, offset: usize| (0..n).map(|j| i == j + offset).collect::<Vec<bool>>();
+        Self {
+            n,
+            xs: (0..2 * n).map(|i| row(i, 0)).collect(),
+            zs: (0..2 * n).map(|i| row(i, n)).collect(),
+            rs: vec![false; 2 * n],
+        }
+    }
+
+    pub fn apply_h(&mut self, qubit: usize) {
+        for i in 0..2 * self.n {
+            self.rs[i] ^= self.xs[i][qubit] && self.zs[i][qubit];
+            std::mem::swap(&mut self.xs[i][qubit], &mut self.zs[i][qubit]);
+        }
+    }
+
+    pub fn apply_s(&mut self, qubit: usize) {
+        for i in 0..2 * self.n {
+            self.rs[i] ^= self.xs[i][qubit] && self.zs[i][qubit];
+            self.zs[i][qubit] ^= self.xs[i][qubit];
+        }
+    }
+
+    pub fn apply_cnot(&mut self, control: usize, target: usize) {
+        for i in 0..2 * self.n {
+            let (xc, zc, xt, zt) = (self.xs[i][control], self.zs[i][control], self.xs[i][target], self.zs[i][target]);
+            self.rs[i] ^= xc && zt && (xt == zc);
+            self.xs[i][target] ^= xc;
+            self.zs[i][control] ^= zt;
+        }
+    }
+
+    /// Measures the qubit in the computational basis, sampling random outcomes with `rand::thread_rng`.
+    pub fn apply_measurement(&mut self, qubit: usize) -> bool {
+        self.apply_measurement_with_rng(qubit, &mut rand::thread_rng())
+    }
+
+    pub fn apply_measurement_with_rng<R: Rng>(&mut self, qubit: usize, rng: &mut R) -> bool {
+        self.measure(qubit, || rng.gen())
+    }
+
+    /// Measures the qubit, calling `random_outcome` only if the outcome isn't determined by the state.
+    fn measure(&mut self, qubit: usize, mut random_outcome: impl FnMut() -> bool) -> bool {
+        let n = self.n;
+        if let Some(p) = (n..2 * n).find(|p| self.xs[*p][qubit]) {
+            for i in (0..2 * n).filter(|i| *i != p) {
+                if self.xs[i][qubit] {
+                    self.rowsum(i, p);
+                }
+            }
+
+            self.xs[p - n] = self.xs[p].clone();
+            self.zs[p - n] = self.zs[p].clone();
+            self.rs[p - n] = self.rs[p];
+
+            let outcome = random_outcome();
+            self.xs[p] = vec![false; n];
+            self.zs[p] = (0..n).map(|j| j == qubit).collect();
+            self.rs[p] = outcome;
+            return outcome;
+        }
+
+        // The outcome is determined: it is the sign of the product of the stabilizers
+        // whose destabilizers anticommute with Z on the qubit.
+        let mut scratch = (vec![false; n], vec![false; n], false);
+        for i in (0..n).filter(|i| self.xs[*i][qubit]) {
+            let row = (self.xs[i + n].clone(), self.zs[i + n].clone(), self.rs[i + n]);
+            scratch = multiply_rows(&row, &scratch);
+        }
+        scratch.2
+    }
+
+    /// Left-multiplies row `h` by row `i`.
+    fn rowsum(&mut self, h: usize, i: usize) {
+        let product = multiply_rows(
+            &(self.xs[i].clone(), self.zs[i].clone(), self.rs[i]),
+            &(self.xs[h].clone(), self.zs[h].clone(), self.rs[h]),
+        );
+        (self.xs[h], self.zs[h], self.rs[h]) = product;
+    }
+
+    /// Returns the stabilizer generators of the state, acting on `LineQubit(j)` for each qubit `j`.
+    pub fn stabilizers(&self) -> Vec<PauliString> {
+        (self.n..2 * self.n).map(|i| self.row_to_pauli_string(i)).collect()
+    }
+
+    /// Returns the destabilizers of the state, acting on `LineQubit(j)` for each qubit `j`.
+    pub fn destabilizers(&self) -> Vec<PauliString> {
+        (0..self.n).map(|i| self.row_to_pauli_string(i)).collect()
+    }
+
+    fn row_to_pauli_string(&self, i: usize) -> PauliString {
+        let sign = if self.rs[i] { -1.0 } else { 1.0 };
+        (0..self.n).fold(PauliString::new(Complex::new(sign, 0.0)), |string, j| {
+            let pauli = row_pauli(self.xs[i][j], self.zs[i][j]);
+            string * PauliString::from_pauli(pauli, &LineQubit::new(j as i64))
+        })
+    }
+
+    /**
+     * Returns the state vector of the stabilizer state, with qubit 0 as the most significant bit.
+     * This takes exponential space, so it is only practical for small numbers of qubits.
+     */
+    pub fn to_state_vector(&self) -> Vec<Complex<f64>> {
+        // Find a basis state in the support of the state by measuring deterministically...
+        let mut copy = self.clone();
+        let support = (0..self.n).fold(0usize, |index, q| (index << 1) | usize::from(copy.measure(q, || false)));
+
+        // ...and project it onto the state with the stabilizer projectors (I + S) / 2.
+        let mut state = vec![Complex::new(0.0, 0.0); 1 << self.n];
+        state[support] = Complex::new(1.0, 0.0);
+        let qubits: Vec<LineQubit> = (0..self.n as i64).map(LineQubit::new).collect();
+        let qubit_refs: Vec<&dyn QId> = qubits.iter().map(|q| q as _).collect();
+        for stabilizer in self.stabilizers() {
+            let action = stabilizer.basis_action(&qubit_refs).unwrap_or_default();
+            let mut projected: Vec<Complex<f64>> = state.iter().map(|x| x / 2.0).collect();
+            for (i, (target, amplitude)) in action.into_iter().enumerate() {
+                projected[target] += amplitude * state[i] / 2.0;
+            }
+            state = projected;
+        }
+
+        let norm = state.iter().map(|x| x.norm_sqr()).sum::<f64>().sqrt();
+        state.iter().map(|x| x / norm).collect()
+    }
+}
+
+/// Returns the Pauli for a tableau entry.
+pub(crate) fn row_pauli(x: bool, z: bool) -> Pauli {
+    match (x, z) {
+        (false, false) => Pauli::I,
+        (true, false) => Pauli::X,
+        (true, true) => Pauli::Y,
+        (false, true) => Pauli::Z,
+    }
+}
+
+type Row = (Vec<bool>, Vec<bool>, bool);
+
+/// Returns the product `a · b` of two rows, which must commute so that the result is Hermitian.
+fn multiply_rows(a: &Row, b: &Row) -> Row {
+    // Sum the exponents of i picked up by each qubit's product.
+    let mut phase: i64 = 2 * i64::from(a.2) + 2 * i64::from(b.2);
+    for j in 0..a.0.len() {
+        let (x1, z1, x2, z2) = (i64::from(a.0[j]), i64::from(a.1[j]), i64::from(b.0[j]), i64::from(b.1[j]));
+        phase += match (x1, z1) {
+            (0, 0) => 0,
+            (1, 1) => z2 - x2,
+            (1, 0) => z2 * (2 * x2 - 1),
+            _ => x2 * (1 - 2 * z2),
+        };
+    }
+
+    let xs = a.0.iter().zip(&b.0).map(|(x, y)| x ^ y).collect();
+    let zs = a.1.iter().zip(&b.1).map(|(x, y)| x ^ y).collect();
+    (xs, zs, phase.rem_euclid(4) == 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn bell_state_tableau() {
+        let mut tableau = CliffordTableau::new(2);
+        tableau.apply_h(0);
+        tableau.apply_cnot(0, 1);
+
+        let stabilizers: Vec<String> = tableau.stabilizers().iter().map(|s| s.to_string()).collect();
+        assert_eq!(stabilizers, vec!["X(q0)*X(q1)", "Z(q0)*Z(q1)"]);
+
+        let state = tableau.to_state_vector();
+        let expected = [FRAC_1_SQRT_2, 0.0, 0.0, FRAC_1_SQRT_2];
+        assert!(state.iter().zip(expected).all(|(a, b)| (a - b).norm() < 1e-10));
+    }
+
+    #[test]
+    fn phases_are_tracked() {
+        // S H |0⟩ = |+i⟩, which is stabilized by Y.
+        let mut tableau = CliffordTableau::new(1);
+        tableau.apply_h(0);
+        tableau.apply_s(0);
+        assert_eq!(tableau.stabilizers()[0].to_string(), "Y(q0)");
+
+        // X = H S S H flips |0⟩ to |1⟩, which is stabilized by -Z.
+        let mut tableau = CliffordTableau::new(1);
+        tableau.apply_h(0);
+        tableau.apply_s(0);
+        tableau.apply_s(0);
+        tableau.apply_h(0);
+        assert_eq!(tableau.stabilizers()[0].to_string(), "-Z(q0)");
+        assert!(tableau.apply_measurement(0));
+        assert!((tableau.to_state_vector()[1] - Complex::new(1.0, 0.0)).norm() < 1e-10);
+    }
+
+    #[test]
+    fn large_ghz_state_measurements_agree() {
+        let n = 200;
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..5 {
+            let mut tableau = CliffordTableau::new(n);
+            tableau.apply_h(0);
+            for q in 1..n {
+                tableau.apply_cnot(q - 1, q);
+            }
+
+            let first = tableau.apply_measurement_with_rng(0, &mut rng);
+            assert!((1..n).all(|q| tableau.apply_measurement_with_rng(q, &mut rng) == first));
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+
+use anyhow::Error;
+use num_complex::Complex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::circuits::circuit::Circuit;
+use crate::ops::clifford_gates::SingleQubitCliffordGate;
+use crate::ops::common_gates::{CNotGate, CZGate, IdentityGate, SwapGate};
+use crate::ops::measure::MeasurementGate;
+use crate::ops::pauli_string::PauliString;
+use crate::ops::qubits::LineQubit;
+use crate::ops::raw_types::{Gate, Operation, QId};
+use crate::sim::clifford::CliffordTableau;
+use crate::sim::measurement::{measurements_by_key, MeasurementResult};
+use crate::sim::state_vector_simulator::qubit_indices;
+use crate::sim::{SimulationResult, Simulator};
+
+/**
+ * Simulates circuits of Clifford gates and measurements with a stabilizer tableau,
+ * which takes polynomial time and space in the number of qubits.
+ * Single qubit gates are supported if their unitary is a Clifford, along with CNOT, CZ and SWAP.
+ */
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CliffordSimulator {
+    /// Seeds the random number generator used for measurements, to make simulations reproducible.
+    pub seed: Option<u64>,
+}
+
+impl CliffordSimulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+        }
+    }
+
+    /// Simulates the circuit from |0...0⟩.
+    pub fn simulate(&self, circuit: &Circuit) -> Result<CliffordResult, Error> {
+        let qubits = circuit.all_qubits();
+        if let Some(q) = qubits.iter().find(|q| q.dimension() != 2) {
+            return Err(anyhow!(format!("The Clifford simulator only supports qubits, but {} has dimension {}.", q, q.dimension())));
+        }
+
+        let mut tableau = CliffordTableau::new(qubits.len());
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let mut measurements = vec![];
+        for op in circuit.all_operations() {
+            if let Some(measurement) = apply_operation(op, &qubits, &mut tableau, &mut rng)? {
+                measurements.push(measurement);
+            }
+        }
+
+        Ok(CliffordResult {
+            qubits,
+            tableau,
+            measurements_by_key: measurements_by_key(&measurements),
+            measurements,
+        })
+    }
+}
+
+impl Simulator for CliffordSimulator {
+    type Result = CliffordResult;
+
+    fn simulate(&self, circuit: &Circuit) -> Result<CliffordResult, Error> {
+        CliffordSimulator::simulate(self, circuit)
+    }
+}
+
+fn apply_operation<R: Rng>(
+    op: &dyn Operation,
+    qubits: &[Box<dyn QId>],
+    tableau: &mut CliffordTableau,
+    rng: &mut R,
+) -> Result<Option<MeasurementResult>, Error> {
+    let targets = qubit_indices(op, qubits)?;
+    let gate = op.gate().ok_or_else(|| anyhow!("The Clifford simulator can only simulate gate operations."))?;
+    let any = gate.as_any();
+
+    if let Some(measurement) = any.downcast_ref::<MeasurementGate>() {
+        let bits = targets.iter().map(|q| tableau.apply_measurement_with_rng(*q, rng)).collect();
+        return Ok(Some(MeasurementResult::new(&measurement.key, bits)));
+    }
+
+    if any.is::<IdentityGate>() {
+        // Nothing to do.
+    } else if any.is::<CNotGate>() {
+        tableau.apply_cnot(targets[0], targets[1]);
+    } else if any.is::<CZGate>() {
+        tableau.apply_h(targets[1]);
+        tableau.apply_cnot(targets[0], targets[1]);
+        tableau.apply_h(targets[1]);
+    } else if any.is::<SwapGate>() {
+        tableau.apply_cnot(targets[0], targets[1]);
+        tableau.apply_cnot(targets[1], targets[0]);
+        tableau.apply_cnot(targets[0], targets[1]);
+    } else if let Some(clifford) = single_qubit_clifford(gate.as_ref()) {
+        for h in generator_word(&clifford) {
+            if h {
+                tableau.apply_h(targets[0]);
+            } else {
+                tableau.apply_s(targets[0]);
+            }
+        }
+    } else {
+        return Err(anyhow!(format!("The Clifford simulator can't simulate the non-Clifford gate {}.", gate)));
+    }
+    Ok(None)
+}
+
+fn single_qubit_clifford(gate: &dyn Gate) -> Option<SingleQubitCliffordGate> {
+    if let Some(clifford) = gate.as_any().downcast_ref::<SingleQubitCliffordGate>() {
+        return Some(*clifford);
+    }
+    if gate.num_qubits() != 1 {
+        return None;
+    }
+    SingleQubitCliffordGate::from_unitary(&gate.unitary()?)
+}
+
+/// Returns a shortest sequence of H (true) and S (false) gates that makes up the Clifford, in order of application.
+fn generator_word(target: &SingleQubitCliffordGate) -> Vec<bool> {
+    let mut visited = vec![(SingleQubitCliffordGate::I, vec![])];
+    let mut next = 0;
+    while next < visited.len() {
+        let (gate, word) = visited[next].clone();
+        if gate == *target {
+            return word;
+        }
+        for h in [true, false] {
+            let generator = if h { SingleQubitCliffordGate::H } else { SingleQubitCliffordGate::S };
+            let product = gate.compose(&generator);
+            if visited.iter().all(|(other, _)| *other != product) {
+                let mut extended = word.clone();
+                extended.push(h);
+                visited.push((product, extended));
+            }
+        }
+        next += 1;
+    }
+    vec![]
+}
+
+/// The final stabilizer state of a Clifford simulation.
+#[derive(Clone, Debug)]
+pub struct CliffordResult {
+    qubits: Vec<Box<dyn QId>>,
+    tableau: CliffordTableau,
+    measurements: Vec<MeasurementResult>,
+    measurements_by_key: HashMap<String, Vec<bool>>,
+}
+
+impl CliffordResult {
+    /// Returns the simulated qubits, in the order used to index the state vector.
+    pub fn qubits(&self) -> &[Box<dyn QId>] {
+        &self.qubits
+    }
+
+    pub fn tableau(&self) -> &CliffordTableau {
+        &self.tableau
+    }
+
+    /// Returns the outcomes of the circuit's measurements, in the order they were applied.
+    pub fn measurements(&self) -> &[MeasurementResult] {
+        &self.measurements
+    }
+
+    /// Returns the stabilizer generators of the final state, on the circuit's qubits.
+    pub fn stabilizers(&self) -> Vec<PauliString> {
+        self.tableau.stabilizers().iter().map(|s| self.on_circuit_qubits(s)).collect()
+    }
+
+    /// Returns the destabilizers of the final state, on the circuit's qubits.
+    pub fn destabilizers(&self) -> Vec<PauliString> {
+        self.tableau.destabilizers().iter().map(|s| self.on_circuit_qubits(s)).collect()
+    }
+
+    /// Returns the state vector of the final state. This takes exponential space in the number of qubits.
+    pub fn to_state_vector(&self) -> Vec<Complex<f64>> {
+        self.tableau.to_state_vector()
+    }
+
+    /// Samples a measurement of every qubit, in the order of `qubits()`, without changing the result.
+    pub fn measure_all(&self) -> Vec<bool> {
+        self.measure_all_with_rng(&mut rand::thread_rng())
+    }
+
+    pub fn measure_all_with_rng<R: Rng>(&self, rng: &mut R) -> Vec<bool> {
+        let mut tableau = self.tableau.clone();
+        (0..self.qubits.len()).map(|q| tableau.apply_measurement_with_rng(q, rng)).collect()
+    }
+
+    /// Moves a Pauli string from the tableau's `LineQubit(j)` to the `j`th circuit qubit.
+    fn on_circuit_qubits(&self, string: &PauliString) -> PauliString {
+        self.qubits.iter().enumerate().fold(PauliString::new(string.coefficient), |result, (j, q)| {
+            match string.get(&LineQubit::new(j as i64)) {
+                Some(pauli) => result * PauliString::from_pauli(pauli, q.as_ref()),
+                None => result,
+            }
+        })
+    }
+}
+
+/// The tableau doesn't keep a state vector, but `CliffordResult::to_state_vector` can compute one.
+impl SimulationResult for CliffordResult {
+    fn final_state_vector(&self) -> Option<&[Complex<f64>]> {
+        None
+    }
+
+    fn measurements(&self) -> &HashMap<String, Vec<bool>> {
+        &self.measurements_by_key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gate_on;
+    use crate::ops::common_gates::{CNOT, CZ, H, S, S_DAG, T};
+    use crate::ops::measure::M;
+    use crate::ops::pauli_gates::{X, Y, Z};
+    use crate::ops::qubits::GridQubit;
+    use crate::sim::state_vector_simulator::StateVectorSimulator;
+
+    use super::*;
+
+    #[test]
+    fn matches_the_state_vector_simulator() {
+        let q = LineQubit::range(3);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            Box::new(gate_on!(H, q[0].clone()).unwrap()),
+            Box::new(gate_on!(S, q[1].clone()).unwrap()),
+            Box::new(gate_on!(CNOT, q[0].clone(), q[1].clone()).unwrap()),
+            Box::new(gate_on!(Y, q[2].clone()).unwrap()),
+            Box::new(gate_on!(CZ, q[1].clone(), q[2].clone()).unwrap()),
+            Box::new(gate_on!(S_DAG, q[0].clone()).unwrap()),
+            Box::new(gate_on!(X, q[1].clone()).unwrap()),
+            Box::new(gate_on!(Z, q[2].clone()).unwrap()),
+            Box::new(gate_on!(H, q[2].clone()).unwrap()),
+        ]).unwrap();
+
+        let clifford = CliffordSimulator::new().simulate(&circuit).unwrap().to_state_vector();
+        let expected = StateVectorSimulator::new().simulate(&circuit, None).unwrap().state_vector().to_vec();
+        let overlap: Complex<f64> = clifford.iter().zip(&expected).map(|(a, b)| a.conj() * b).sum();
+        assert!((overlap.norm() - 1.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn stabilizers_use_circuit_qubits() {
+        let q = [GridQubit::new(0, 0), GridQubit::new(0, 1)];
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            Box::new(gate_on!(H, q[0].clone()).unwrap()),
+            Box::new(gate_on!(CNOT, q[0].clone(), q[1].clone()).unwrap()),
+        ]).unwrap();
+
+        let result = CliffordSimulator::new().simulate(&circuit).unwrap();
+        let stabilizers: Vec<String> = result.stabilizers().iter().map(|s| s.to_string()).collect();
+        assert_eq!(stabilizers, vec!["X(q(0,0))*X(q(0,1))", "Z(q(0,0))*Z(q(0,1))"]);
+        assert_eq!(result.destabilizers().len(), 2);
+    }
+
+    #[test]
+    fn simulates_large_ghz_states() {
+        let n = 100;
+        let q = LineQubit::range(n);
+        let mut ops: Vec<Box<dyn Operation>> = vec![Box::new(gate_on!(H, q[0].clone()).unwrap())];
+        for i in 1..n as usize {
+            ops.push(Box::new(gate_on!(CNOT, q[i - 1].clone(), q[i].clone()).unwrap()));
+        }
+        ops.push(Box::new(M("first", &[&q[0]])));
+        let mut circuit = Circuit::new();
+        circuit.append(ops).unwrap();
+
+        let result = CliffordSimulator::with_seed(3).simulate(&circuit).unwrap();
+        let first = result.measurements()[0].measurements[0];
+        assert!(result.measure_all().iter().all(|bit| *bit == first));
+    }
+
+    #[test]
+    fn non_clifford_gates_are_rejected() {
+        let mut circuit = Circuit::new();
+        circuit.append(vec![Box::new(gate_on!(T, LineQubit::new(0)).unwrap())]).unwrap();
+        let error = CliffordSimulator::new().simulate(&circuit).unwrap_err();
+        assert_eq!(error.to_string(), "The Clifford simulator can't simulate the non-Clifford gate T.");
+    }
+}
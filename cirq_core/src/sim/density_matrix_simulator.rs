@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::circuits::circuit::Circuit;
+use crate::linalg;
+use crate::noise::{NoiseModel, NO_NOISE};
+use crate::ops::measure::MeasurementGate;
+use crate::ops::pauli_string::PauliString;
+use crate::ops::raw_types::{Operation, QId};
+use crate::sim::state_vector_simulator::{initial_state_vector, qubit_indices};
+use crate::sim::{SimulationResult, Simulator};
+
+/**
+ * A simulator of mixed states, which evolves the `2^n x 2^n` density matrix of the circuit's qubits.
+ * Every moment is passed through the noise model before it is simulated.
+ */
+pub struct DensityMatrixSimulator {
+    noise: Box<dyn NoiseModel>,
+}
+
+impl Default for DensityMatrixSimulator {
+    fn default() -> Self {
+        Self::new(Box::new(NO_NOISE))
+    }
+}
+
+impl DensityMatrixSimulator {
+    pub fn new(noise: Box<dyn NoiseModel>) -> Self {
+        Self {
+            noise
+        }
+    }
+
+    /// Simulates the circuit, starting from |0...0⟩⟨0...0|.
+    pub fn simulate(&self, circuit: &Circuit) -> Result<DensityMatrixResult, Error> {
+        let qubits = circuit.all_qubits();
+        if let Some(q) = qubits.iter().find(|q| q.dimension() != 2) {
+            return Err(anyhow!(format!("The density matrix simulator only supports qubits, but {} has dimension {}.", q, q.dimension())));
+        }
+
+        let state = initial_state_vector(qubits.len(), None)?;
+        let mut density_matrix: Vec<Vec<Complex<f64>>> = state
+            .iter()
+            .map(|a| state.iter().map(|b| a * b.conj()).collect())
+            .collect();
+
+        for moment in circuit.moments() {
+            for noisy_moment in self.noise.noisy_moment(moment, &qubits) {
+                for op in &noisy_moment {
+                    density_matrix = apply_operation(op.as_ref(), &qubits, &density_matrix)?;
+                }
+            }
+        }
+
+        Ok(DensityMatrixResult {
+            qubits,
+            density_matrix,
+            measurements: HashMap::new(),
+        })
+    }
+}
+
+impl Simulator for DensityMatrixSimulator {
+    type Result = DensityMatrixResult;
+
+    fn simulate(&self, circuit: &Circuit) -> Result<DensityMatrixResult, Error> {
+        DensityMatrixSimulator::simulate(self, circuit)
+    }
+}
+
+fn apply_operation(
+    op: &dyn Operation,
+    qubits: &[Box<dyn QId>],
+    density_matrix: &[Vec<Complex<f64>>],
+) -> Result<Vec<Vec<Complex<f64>>>, Error> {
+    let targets = qubit_indices(op, qubits)?;
+
+    if let Some(gate) = op.gate() {
+        if gate.as_any().is::<MeasurementGate>() {
+            return Ok(dephase(density_matrix, &targets, qubits.len()));
+        }
+        if !gate.has_unitary() {
+            let kraus = gate.kraus().ok_or_else(|| anyhow!(format!("The density matrix simulator can't simulate {}.", gate)))?;
+            return Ok(apply_kraus(&kraus, &targets, density_matrix, qubits.len()));
+        }
+    }
+
+    let unitary = op.unitary().ok_or_else(|| anyhow!("The density matrix simulator can't simulate a non-unitary operation."))?;
+    Ok(linalg::targeted_conjugate(&unitary, &targets, density_matrix, qubits.len()))
+}
+
+/// Applies the channel with the given Kraus operators to the target qubits: ρ ↦ Σ K ρ K†.
+fn apply_kraus(
+    kraus_operators: &[Vec<Vec<Complex<f64>>>],
+    targets: &[usize],
+    density_matrix: &[Vec<Complex<f64>>],
+    num_qubits: usize,
+) -> Vec<Vec<Complex<f64>>> {
+    let dimension = density_matrix.len();
+    let mut result = vec![vec![Complex::new(0.0, 0.0); dimension]; dimension];
+    for kraus in kraus_operators {
+        let term = linalg::targeted_conjugate(kraus, targets, density_matrix, num_qubits);
+        for (row, term_row) in result.iter_mut().zip(term) {
+            for (entry, term_entry) in row.iter_mut().zip(term_row) {
+                *entry += term_entry;
+            }
+        }
+    }
+    result
+}
+
+/// Applies an unrecorded measurement of the target qubits, which removes the coherences between their outcomes.
+fn dephase(density_matrix: &[Vec<Complex<f64>>], targets: &[usize], num_qubits: usize) -> Vec<Vec<Complex<f64>>> {
+    let mask = targets.iter().fold(0, |mask, q| mask | 1 << (num_qubits - 1 - q));
+    density_matrix
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            row.iter()
+                .enumerate()
+                .map(|(j, entry)| if i & mask == j & mask { *entry } else { Complex::new(0.0, 0.0) })
+                .collect()
+        })
+        .collect()
+}
+
+/// The final state of a density matrix simulation.
+#[derive(Clone, Debug)]
+pub struct DensityMatrixResult {
+    qubits: Vec<Box<dyn QId>>,
+    density_matrix: Vec<Vec<Complex<f64>>>,
+    measurements: HashMap<String, Vec<bool>>,
+}
+
+impl DensityMatrixResult {
+    /// Returns the qubits of the state, in the order used to index the density matrix.
+    pub fn qubits(&self) -> &[Box<dyn QId>] {
+        &self.qubits
+    }
+
+    pub fn density_matrix(&self) -> &[Vec<Complex<f64>>] {
+        &self.density_matrix
+    }
+
+    /// Returns the trace of the density matrix, which is 1 for a valid state.
+    pub fn trace(&self) -> f64 {
+        (0..self.density_matrix.len()).map(|i| self.density_matrix[i][i].re).sum()
+    }
+
+    /// Returns Tr(ρ²), which is 1 for pure states and 1 / 2^n for the maximally mixed state.
+    pub fn purity(&self) -> f64 {
+        linalg::dot(&self.density_matrix, &self.density_matrix)
+            .iter()
+            .enumerate()
+            .map(|(i, row)| row[i].re)
+            .sum()
+    }
+
+    /// Returns the real part of Tr(ρP) for the Pauli string P.
+    pub fn expectation_value(&self, observable: &PauliString) -> Result<f64, Error> {
+        let qubits: Vec<&dyn QId> = self.qubits.iter().map(|q| q.as_ref()).collect();
+        let action = observable.basis_action(&qubits)?;
+        Ok(action.iter().enumerate().map(|(i, (target, amplitude))| self.density_matrix[i][*target] * amplitude).sum::<Complex<f64>>().re)
+    }
+
+    /**
+     * Traces out every qubit except `keep_qubits`. The qubits of the reduced state are in the
+     * order given, and qubits that aren't part of the state are ignored.
+     */
+    pub fn partial_trace(&self, keep_qubits: &[&dyn QId]) -> DensityMatrixResult {
+        let mut kept = vec![];
+        for q in keep_qubits {
+            if let Some(i) = self.qubits.iter().position(|other| other.eq_qid(*q)) {
+                if !kept.contains(&i) {
+                    kept.push(i);
+                }
+            }
+        }
+
+        DensityMatrixResult {
+            qubits: kept.iter().map(|i| self.qubits[*i].clone()).collect(),
+            density_matrix: linalg::partial_trace_of_density_matrix(&self.density_matrix, &kept, &vec![2; self.qubits.len()]),
+            measurements: HashMap::new(),
+        }
+    }
+}
+
+/// Measurements only dephase the density matrix, so a density matrix simulation has no measured bits.
+impl SimulationResult for DensityMatrixResult {
+    fn final_state_vector(&self) -> Option<&[Complex<f64>]> {
+        None
+    }
+
+    fn measurements(&self) -> &HashMap<String, Vec<bool>> {
+        &self.measurements
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::circuits::moment::Moment;
+    use crate::gate_on;
+    use crate::ops::common_gates::{CNOT, H};
+    use crate::ops::measure::M;
+    use crate::ops::pauli_gates::{Pauli, X};
+    use crate::ops::qubits::LineQubit;
+    use crate::ops::raw_types::Gate;
+    use crate::ops::reset::R;
+
+    use super::*;
+
+    fn bell_circuit() -> Circuit {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            Box::new(gate_on!(H, q[0].clone()).unwrap()),
+            Box::new(gate_on!(CNOT, q[0].clone(), q[1].clone()).unwrap()),
+        ]).unwrap();
+        circuit
+    }
+
+    #[test]
+    fn bell_state_is_pure_with_mixed_marginals() {
+        let result = DensityMatrixSimulator::default().simulate(&bell_circuit()).unwrap();
+        assert!((result.trace() - 1.0).abs() < 1e-10);
+        assert!((result.purity() - 1.0).abs() < 1e-10);
+        assert!((result.density_matrix()[0][3] - Complex::new(0.5, 0.0)).norm() < 1e-10);
+
+        let q = LineQubit::range(2);
+        let reduced = result.partial_trace(&[&q[1]]);
+        assert_eq!(reduced.density_matrix().len(), 2);
+        assert!((reduced.trace() - 1.0).abs() < 1e-10);
+        assert!((reduced.purity() - 0.5).abs() < 1e-10);
+
+        let xx = PauliString::from_pauli(Pauli::X, &q[0]) * PauliString::from_pauli(Pauli::X, &q[1]);
+        assert!((result.expectation_value(&xx).unwrap() - 1.0).abs() < 1e-10);
+        assert!(result.expectation_value(&PauliString::from_pauli(Pauli::Z, &q[1])).unwrap().abs() < 1e-10);
+    }
+
+    #[test]
+    fn partial_trace_of_product_state() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            Box::new(gate_on!(X, q[0].clone()).unwrap()),
+            Box::new(gate_on!(H, q[1].clone()).unwrap()),
+        ]).unwrap();
+
+        let result = DensityMatrixSimulator::default().simulate(&circuit).unwrap();
+        let first = result.partial_trace(&[&q[0]]);
+        assert!((first.density_matrix()[1][1] - Complex::new(1.0, 0.0)).norm() < 1e-10);
+        let second = result.partial_trace(&[&q[1]]);
+        assert!((second.density_matrix()[0][1] - Complex::new(0.5, 0.0)).norm() < 1e-10);
+    }
+
+    /// Flips every qubit after each moment.
+    struct BitFlipEverything;
+
+    impl NoiseModel for BitFlipEverything {
+        fn noisy_moment(&self, moment: &Moment, system_qubits: &[Box<dyn QId>]) -> Vec<Moment> {
+            let flips = system_qubits
+                .iter()
+                .map(|q| Box::new(X.on(vec![q.clone()]).unwrap()) as Box<dyn Operation>)
+                .collect();
+            vec![moment.clone(), Moment::new(flips).unwrap()]
+        }
+    }
+
+    #[test]
+    fn noise_model_moments_are_simulated() {
+        let q = LineQubit::new(0);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![Box::new(gate_on!(X, q).unwrap())]).unwrap();
+
+        let result = DensityMatrixSimulator::new(Box::new(BitFlipEverything)).simulate(&circuit).unwrap();
+        assert!((result.density_matrix()[0][0] - Complex::new(1.0, 0.0)).norm() < 1e-10);
+    }
+
+    #[test]
+    fn measurement_dephases() {
+        let q = LineQubit::new(0);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![Box::new(gate_on!(H, q.clone()).unwrap()), Box::new(M("m", &[&q]))]).unwrap();
+
+        let result = DensityMatrixSimulator::default().simulate(&circuit).unwrap();
+        assert!((result.purity() - 0.5).abs() < 1e-10);
+        assert!(result.density_matrix()[0][1].norm() < 1e-10);
+    }
+
+    #[test]
+    fn reset_projects_onto_zero() {
+        let q = LineQubit::range(2);
+        let mut circuit = bell_circuit();
+        circuit.append(vec![Box::new(gate_on!(R, q[0].clone()).unwrap())]).unwrap();
+
+        let result = DensityMatrixSimulator::default().simulate(&circuit).unwrap();
+        assert!((result.trace() - 1.0).abs() < 1e-10);
+        let first = result.partial_trace(&[&q[0]]);
+        assert!((first.density_matrix()[0][0] - Complex::new(1.0, 0.0)).norm() < 1e-10);
+        let second = result.partial_trace(&[&q[1]]);
+        assert!((second.purity() - 0.5).abs() < 1e-10);
+    }
+}
@@ -0,0 +1,23 @@
+use std::collections::HashMap;
+
+/// The outcome of measuring a set of qubits in the computational basis.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MeasurementResult {
+    pub key: String,
+    /// The measured bit of each qubit, in the order the qubits were measured.
+    pub measurements: Vec<bool>,
+}
+
+impl MeasurementResult {
+    pub fn new(key: &str, measurements: Vec<bool>) -> Self {
+        Self {
+            key: key.to_string(),
+            measurements,
+        }
+    }
+}
+
+/// Returns the measured bits of each key. When a key appears more than once, the last outcome is kept.
+pub fn measurements_by_key(results: &[MeasurementResult]) -> HashMap<String, Vec<bool>> {
+    results.iter().map(|result| (result.key.clone(), result.measurements.clone())).collect()
+}
@@ -0,0 +1,95 @@
+pub mod clifford;
+pub mod clifford_simulator;
+pub mod density_matrix_simulator;
+pub mod measurement;
+pub mod product_state;
+pub mod state_vector_simulator;
+
+use std::collections::HashMap;
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::circuits::circuit::Circuit;
+use crate::ops::raw_types::QId;
+use crate::study::ParamResolver;
+
+/**
+ * The interface shared by the simulators, so that code running circuits can be written once for all of them.
+ * Simulators with more options, such as an initial state, also offer them as inherent methods.
+ */
+pub trait Simulator {
+    type Result: SimulationResult;
+
+    /// Simulates the circuit from |0...0⟩.
+    fn simulate(&self, circuit: &Circuit) -> Result<Self::Result, Error>;
+
+    /// Simulates the circuit from |0...0⟩, with its symbols replaced by the values the resolver gives them.
+    fn simulate_with_resolver(&self, circuit: &Circuit, resolver: &ParamResolver) -> Result<Self::Result, Error> {
+        self.simulate(&circuit.resolve_parameters(resolver)?)
+    }
+}
+
+/// The final state of a simulation.
+pub trait SimulationResult {
+    /// Returns the final state vector, or None if the simulator doesn't keep one.
+    fn final_state_vector(&self) -> Option<&[Complex<f64>]>;
+
+    /// Returns the measured bits by measurement key. A key measured more than once keeps its last outcome.
+    fn measurements(&self) -> &HashMap<String, Vec<bool>>;
+}
+
+/// A state that a simulation can start from.
+pub trait SimulationState {
+    /// Returns the state vector of the given qubits, with the first qubit as the most significant bit.
+    fn initial_state_for_qubits(&self, qubits: &[Box<dyn QId>]) -> Vec<Complex<f64>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::gate_on;
+    use crate::ops::measure::M;
+    use crate::ops::pauli_gates::{Pauli, X};
+    use crate::ops::qubits::LineQubit;
+    use crate::sim::clifford_simulator::CliffordSimulator;
+    use crate::sim::density_matrix_simulator::DensityMatrixSimulator;
+    use crate::sim::state_vector_simulator::StateVectorSimulator;
+    use crate::study::{Symbol, SymbolicRotation};
+
+    use super::*;
+
+    fn measured_bits<S: Simulator>(simulator: &S, circuit: &Circuit) -> HashMap<String, Vec<bool>> {
+        simulator.simulate(circuit).unwrap().measurements().clone()
+    }
+
+    #[test]
+    fn simulators_share_an_interface() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![Box::new(gate_on!(X, q[0].clone()).unwrap()), Box::new(M("m", &[&q[0], &q[1]]))]).unwrap();
+
+        let expected = HashMap::from([("m".to_string(), vec![true, false])]);
+        assert_eq!(measured_bits(&StateVectorSimulator::with_seed(1), &circuit), expected);
+        assert_eq!(measured_bits(&CliffordSimulator::with_seed(1), &circuit), expected);
+        assert!(measured_bits(&DensityMatrixSimulator::default(), &circuit).is_empty());
+
+        let result = Simulator::simulate(&StateVectorSimulator::new(), &circuit).unwrap();
+        assert_eq!(result.final_state_vector().unwrap().len(), 4);
+        assert!(Simulator::simulate(&CliffordSimulator::new(), &circuit).unwrap().final_state_vector().is_none());
+    }
+
+    #[test]
+    fn simulate_with_resolver_resolves_symbols() {
+        let q = LineQubit::new(0);
+        let mut circuit = Circuit::new();
+        let rotation = SymbolicRotation::new(Pauli::X, Symbol::new("theta")).unwrap();
+        circuit.append(vec![Box::new(gate_on!(rotation, q).unwrap())]).unwrap();
+
+        let resolver = ParamResolver::new(&[("theta", PI)]);
+        let result = StateVectorSimulator::new().simulate_with_resolver(&circuit, &resolver).unwrap();
+        assert!((result.final_state_vector().unwrap()[1].norm() - 1.0).abs() < 1e-10);
+        assert!(StateVectorSimulator::new().simulate_with_resolver(&circuit, &ParamResolver::default()).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use std::f64::consts::FRAC_1_SQRT_2;
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::ops::qubit_order::QubitOrder;
+use crate::ops::raw_types::QId;
+use crate::sim::SimulationState;
+
+/// One of the six poles of the Bloch sphere: the eigenstates of the Pauli Z, X and Y operators.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum QubitState {
+    Zero,
+    One,
+    Plus,
+    Minus,
+    PlusI,
+    MinusI,
+}
+
+impl QubitState {
+    /// Returns the amplitudes of |0⟩ and |1⟩ in the state.
+    pub fn amplitudes(&self) -> [Complex<f64>; 2] {
+        let h = FRAC_1_SQRT_2;
+        match self {
+            QubitState::Zero => [Complex::new(1.0, 0.0), Complex::new(0.0, 0.0)],
+            QubitState::One => [Complex::new(0.0, 0.0), Complex::new(1.0, 0.0)],
+            QubitState::Plus => [Complex::new(h, 0.0), Complex::new(h, 0.0)],
+            QubitState::Minus => [Complex::new(h, 0.0), Complex::new(-h, 0.0)],
+            QubitState::PlusI => [Complex::new(h, 0.0), Complex::new(0.0, h)],
+            QubitState::MinusI => [Complex::new(h, 0.0), Complex::new(0.0, -h)],
+        }
+    }
+}
+
+/// A product state, with each qubit in one of the poles of the Bloch sphere. If a qubit is listed more than once, its first state is used.
+#[derive(Clone, Debug, Default)]
+pub struct ProductState(pub Vec<(Box<dyn QId>, QubitState)>);
+
+impl ProductState {
+    /// Returns the state of the qubit, or None if the qubit isn't part of the product state.
+    pub fn state_of(&self, qubit: &dyn QId) -> Option<QubitState> {
+        self.0.iter().find(|(q, _)| q.eq_qid(qubit)).map(|(_, state)| *state)
+    }
+
+    /// Returns the state vector of the product state's qubits sorted by the order, with the first qubit as the most significant bit.
+    pub fn to_state_vector(&self, qubit_order: &dyn QubitOrder) -> Result<Vec<Complex<f64>>, Error> {
+        let mut qubits: Vec<Box<dyn QId>> = vec![];
+        for (q, _) in &self.0 {
+            if !qubits.iter().any(|other| other.eq_qid(q.as_ref())) {
+                qubits.push(q.clone());
+            }
+        }
+        Ok(self.initial_state_for_qubits(&qubit_order.sorted(qubits)?))
+    }
+}
+
+/// Qubits that aren't part of the product state start in |0⟩.
+impl SimulationState for ProductState {
+    fn initial_state_for_qubits(&self, qubits: &[Box<dyn QId>]) -> Vec<Complex<f64>> {
+        qubits.iter().fold(vec![Complex::new(1.0, 0.0)], |state, q| {
+            let amplitudes = self.state_of(q.as_ref()).unwrap_or(QubitState::Zero).amplitudes();
+            state.iter().flat_map(|a| amplitudes.iter().map(move |b| a * b)).collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::circuits::circuit::Circuit;
+    use crate::gate_on;
+    use crate::ops::common_gates::H;
+    use crate::ops::qubit_order::{DefaultQubitOrder, ExplicitQubitOrder};
+    use crate::ops::qubits::LineQubit;
+    use crate::sim::state_vector_simulator::StateVectorSimulator;
+
+    use super::*;
+
+    #[test]
+    fn product_state_vectors() {
+        let q = LineQubit::range(2);
+        let state = ProductState(vec![(Box::new(q[1].clone()), QubitState::One), (Box::new(q[0].clone()), QubitState::Plus)]);
+
+        let vector = state.to_state_vector(&DefaultQubitOrder).unwrap();
+        let h = FRAC_1_SQRT_2;
+        let expected = [0.0, h, 0.0, h];
+        assert!(vector.iter().zip(expected).all(|(a, b)| (a - Complex::new(b, 0.0)).norm() < 1e-10));
+
+        let reversed = state.to_state_vector(&ExplicitQubitOrder::new(vec![Box::new(q[1].clone()), Box::new(q[0].clone())])).unwrap();
+        assert!((reversed[2] - Complex::new(h, 0.0)).norm() < 1e-10);
+        assert!(state.to_state_vector(&ExplicitQubitOrder::new(vec![])).is_err());
+
+        let with_idle = state.initial_state_for_qubits(&[Box::new(q[0].clone()), Box::new(q[1].clone()), Box::new(LineQubit::new(2))]);
+        assert_eq!(with_idle.len(), 8);
+        assert!((with_idle[2] - Complex::new(h, 0.0)).norm() < 1e-10);
+    }
+
+    #[test]
+    fn bloch_poles_are_normalized() {
+        for state in [QubitState::Zero, QubitState::One, QubitState::Plus, QubitState::Minus, QubitState::PlusI, QubitState::MinusI] {
+            let norm: f64 = state.amplitudes().iter().map(|a| a.norm_sqr()).sum();
+            assert!((norm - 1.0).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn simulation_starts_from_product_state() {
+        let q = LineQubit::new(0);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![Box::new(gate_on!(H, q.clone()).unwrap())]).unwrap();
+
+        let initial = ProductState(vec![(Box::new(q), QubitState::Minus)]).initial_state_for_qubits(&circuit.all_qubits());
+        let result = StateVectorSimulator::new().simulate(&circuit, Some(initial)).unwrap();
+        assert!((result.probabilities()[1] - 1.0).abs() < 1e-10);
+    }
+}
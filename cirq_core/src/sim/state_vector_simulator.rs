@@ -0,0 +1,747 @@
+use std::collections::HashMap;
+
+use anyhow::Error;
+use num_complex::Complex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::circuits::circuit::Circuit;
+use crate::linalg;
+use crate::ops::measure::MeasurementGate;
+use crate::ops::pauli_sum::PauliSum;
+use crate::ops::qubit_order::{DefaultQubitOrder, QubitOrder};
+use crate::ops::raw_types::{Operation, QId};
+use crate::sim::measurement::{measurements_by_key, MeasurementResult};
+use crate::sim::{SimulationResult, Simulator};
+use crate::study::{ParamResolver, Sweep};
+
+/**
+ * A dense state vector simulator for circuits of unitary operations, measurements and channels on qubits.
+ * Channels are simulated as quantum trajectories, by sampling one of their Kraus operators.
+ * The state of `n` qubits is stored as `2^n` amplitudes, so it is practical up to about 20 qubits.
+ * Qubits are taken in the default qubit order, with the first qubit as the most significant bit.
+ */
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StateVectorSimulator {
+    /// Seeds the random number generator used for measurements, to make simulations reproducible.
+    pub seed: Option<u64>,
+}
+
+impl StateVectorSimulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+        }
+    }
+
+    pub(crate) fn rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        }
+    }
+
+    /// Simulates the circuit, starting from the given state or from |0...0⟩ if none is given.
+    pub fn simulate(&self, circuit: &Circuit, initial_state: Option<Vec<Complex<f64>>>) -> Result<StateVectorResult, Error> {
+        let qubits = circuit.all_qubits();
+        if let Some(q) = qubits.iter().find(|q| q.dimension() != 2) {
+            return Err(anyhow!(format!("The state vector simulator only supports qubits, but {} has dimension {}.", q, q.dimension())));
+        }
+
+        let mut state_vector = initial_state_vector(qubits.len(), initial_state)?;
+        let mut rng = self.rng();
+        let mut measurements = vec![];
+        for op in circuit.all_operations() {
+            if let Some(measurement) = apply_operation(op, &qubits, &mut state_vector, &mut rng)? {
+                measurements.push(measurement);
+            }
+        }
+
+        Ok(StateVectorResult {
+            qubits,
+            state_vector,
+            measurements_by_key: measurements_by_key(&measurements),
+            measurements,
+        })
+    }
+
+    /**
+     * Simulates the circuit from |0...0⟩ once for each point of the sweep, with the circuit's symbols
+     * resolved by that point. The results are in the order of the sweep.
+     */
+    pub fn simulate_sweep(&self, circuit: &Circuit, sweep: &Sweep) -> Result<Vec<SweepResult>, Error> {
+        sweep
+            .iter()
+            .map(|params| {
+                let result = self.simulate(&circuit.resolve_parameters(params)?, None)?;
+                Ok(SweepResult {
+                    params: params.clone(),
+                    result,
+                })
+            })
+            .collect()
+    }
+
+    /**
+     * Lazily simulates the circuit from |0...0⟩, one moment at a time, yielding the state after each moment.
+     * Each step is a `Result` since an operation may fail to simulate; the iterator ends after an error.
+     */
+    pub fn simulate_moment_steps<'a>(&'a self, circuit: &'a Circuit) -> impl Iterator<Item = Result<StepResult, Error>> + 'a {
+        let qubits = circuit.all_qubits();
+        let state_vector = initial_state_vector(qubits.len(), None).unwrap_or_default();
+
+        MomentSteps {
+            circuit,
+            qubits,
+            state_vector,
+            rng: self.rng(),
+            moment_index: 0,
+            failed: false,
+        }
+    }
+}
+
+impl Simulator for StateVectorSimulator {
+    type Result = StateVectorResult;
+
+    fn simulate(&self, circuit: &Circuit) -> Result<StateVectorResult, Error> {
+        StateVectorSimulator::simulate(self, circuit, None)
+    }
+}
+
+struct MomentSteps<'a> {
+    circuit: &'a Circuit,
+    qubits: Vec<Box<dyn QId>>,
+    state_vector: Vec<Complex<f64>>,
+    rng: StdRng,
+    moment_index: usize,
+    failed: bool,
+}
+
+impl MomentSteps<'_> {
+    fn step(&mut self) -> Result<StepResult, Error> {
+        if let Some(q) = self.qubits.iter().find(|q| q.dimension() != 2) {
+            return Err(anyhow!(format!("The state vector simulator only supports qubits, but {} has dimension {}.", q, q.dimension())));
+        }
+
+        let mut measurements = vec![];
+        for op in &self.circuit.moments()[self.moment_index] {
+            if let Some(measurement) = apply_operation(op.as_ref(), &self.qubits, &mut self.state_vector, &mut self.rng)? {
+                measurements.push(measurement);
+            }
+        }
+
+        Ok(StepResult {
+            moment_index: self.moment_index,
+            state_vector: self.state_vector.clone(),
+            measurements,
+        })
+    }
+}
+
+impl Iterator for MomentSteps<'_> {
+    type Item = Result<StepResult, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.moment_index >= self.circuit.num_moments() {
+            return None;
+        }
+
+        let step = self.step();
+        self.failed = step.is_err();
+        self.moment_index += 1;
+        Some(step)
+    }
+}
+
+/// The state of a simulation just after a moment.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StepResult {
+    pub moment_index: usize,
+    pub state_vector: Vec<Complex<f64>>,
+    /// The outcomes of the measurements in the moment.
+    pub measurements: Vec<MeasurementResult>,
+}
+
+impl StepResult {
+    /// Returns the density matrix |ψ⟩⟨ψ| of the state.
+    pub fn density_matrix(&self) -> Vec<Vec<Complex<f64>>> {
+        self.state_vector
+            .iter()
+            .map(|a| self.state_vector.iter().map(|b| a * b.conj()).collect())
+            .collect()
+    }
+}
+
+/// Returns the given initial state after checking it, or |0...0⟩.
+pub(crate) fn initial_state_vector(num_qubits: usize, initial_state: Option<Vec<Complex<f64>>>) -> Result<Vec<Complex<f64>>, Error> {
+    let size = 1 << num_qubits;
+    let state = match initial_state {
+        Some(state) => state,
+        None => {
+            let mut state = vec![Complex::new(0.0, 0.0); size];
+            state[0] = Complex::new(1.0, 0.0);
+            return Ok(state);
+        }
+    };
+
+    if state.len() != size {
+        return Err(anyhow!(format!("Expected an initial state with {} amplitudes but got {}.", size, state.len())));
+    }
+    let norm: f64 = state.iter().map(|amplitude| amplitude.norm_sqr()).sum();
+    if (norm - 1.0).abs() > 1e-8 {
+        return Err(anyhow!(format!("The initial state is not normalized, its norm is {}.", norm.sqrt())));
+    }
+    Ok(state)
+}
+
+/// Returns the indices of the operation's qubits within `qubits`.
+pub(crate) fn qubit_indices(op: &dyn Operation, qubits: &[Box<dyn QId>]) -> Result<Vec<usize>, Error> {
+    op.qubits()
+        .iter()
+        .map(|q| {
+            qubits
+                .iter()
+                .position(|other| other.eq_qid(q.as_ref()))
+                .ok_or_else(|| anyhow!(format!("Qubit {} is not simulated.", q)))
+        })
+        .collect()
+}
+
+/// Applies the operation to the state, collapsing it and returning the outcome if the operation is a measurement.
+fn apply_operation<R: Rng>(
+    op: &dyn Operation,
+    qubits: &[Box<dyn QId>],
+    state: &mut Vec<Complex<f64>>,
+    rng: &mut R,
+) -> Result<Option<MeasurementResult>, Error> {
+    let targets = qubit_indices(op, qubits)?;
+
+    if let Some(gate) = op.gate() {
+        if let Some(measurement) = gate.as_any().downcast_ref::<MeasurementGate>() {
+            let bits = collapse(state, &targets, qubits.len(), rng);
+            return Ok(Some(MeasurementResult::new(&measurement.key, bits)));
+        }
+        if !gate.has_unitary() {
+            if let Some(kraus) = gate.kraus() {
+                sample_kraus(state, &kraus, &targets, qubits.len(), rng);
+                return Ok(None);
+            }
+        }
+    }
+
+    let unitary = op.unitary().ok_or_else(|| anyhow!("The state vector simulator only supports unitary operations, measurements and channels."))?;
+    *state = linalg::targeted_left_multiply(&unitary, &targets, state, qubits.len());
+    Ok(None)
+}
+
+/// Measures the target qubits, projecting the state onto the sampled outcome and returning its bits.
+pub(crate) fn collapse<R: Rng>(state: &mut [Complex<f64>], targets: &[usize], num_qubits: usize, rng: &mut R) -> Vec<bool> {
+    let probabilities: Vec<f64> = state.iter().map(|amplitude| amplitude.norm_sqr()).collect();
+    let sampled = sample_state(&probabilities, rng);
+    let bit = |index: usize, q: usize| index >> (num_qubits - 1 - q) & 1 == 1;
+    let bits: Vec<bool> = targets.iter().map(|q| bit(sampled, *q)).collect();
+
+    let mut norm = 0.0;
+    for (i, amplitude) in state.iter_mut().enumerate() {
+        if targets.iter().zip(bits.iter()).all(|(q, b)| bit(i, *q) == *b) {
+            norm += amplitude.norm_sqr();
+        } else {
+            *amplitude = Complex::new(0.0, 0.0);
+        }
+    }
+    for amplitude in state.iter_mut() {
+        *amplitude /= norm.sqrt();
+    }
+
+    bits
+}
+
+/**
+ * Applies one of the channel's Kraus operators to the target qubits, choosing K with probability ‖Kψ‖²,
+ * and renormalizes the state. Averaged over runs, this reproduces the channel.
+ */
+fn sample_kraus<R: Rng>(
+    state: &mut Vec<Complex<f64>>,
+    kraus_operators: &[Vec<Vec<Complex<f64>>>],
+    targets: &[usize],
+    num_qubits: usize,
+    rng: &mut R,
+) {
+    let mut branches: Vec<Vec<Complex<f64>>> = kraus_operators
+        .iter()
+        .map(|kraus| linalg::targeted_left_multiply(kraus, targets, state, num_qubits))
+        .collect();
+    let probabilities: Vec<f64> = branches.iter().map(|branch| branch.iter().map(|x| x.norm_sqr()).sum()).collect();
+
+    let chosen = sample_state(&probabilities, rng);
+    let norm = probabilities[chosen].sqrt();
+    *state = branches.swap_remove(chosen).into_iter().map(|x| x / norm).collect();
+}
+
+/// The final state of a state vector simulation.
+#[derive(Clone, Debug)]
+pub struct StateVectorResult {
+    qubits: Vec<Box<dyn QId>>,
+    state_vector: Vec<Complex<f64>>,
+    measurements: Vec<MeasurementResult>,
+    measurements_by_key: HashMap<String, Vec<bool>>,
+}
+
+impl StateVectorResult {
+    /// Returns the simulated qubits, in the order used to index the state vector.
+    pub fn qubits(&self) -> &[Box<dyn QId>] {
+        &self.qubits
+    }
+
+    pub fn state_vector(&self) -> &[Complex<f64>] {
+        &self.state_vector
+    }
+
+    /// Returns the outcomes of the circuit's measurements, in the order they were applied.
+    pub fn measurements(&self) -> &[MeasurementResult] {
+        &self.measurements
+    }
+
+    /// Returns the amplitude of the computational basis state with the given index.
+    pub fn amplitude(&self, state: u64) -> Complex<f64> {
+        self.state_vector.get(state as usize).copied().unwrap_or_else(|| Complex::new(0.0, 0.0))
+    }
+
+    /**
+     * Returns the amplitudes of the basis states of the given qubits, sorted by `qubit_order` with the first
+     * qubit as the most significant bit, while the other qubits are in |0⟩. Qubits that the circuit doesn't
+     * act on are in |0⟩. Given all of the simulated qubits, this is the state vector in the order.
+     */
+    pub fn amplitudes_for_qubits(&self, qubits: &[&dyn QId], qubit_order: &dyn QubitOrder) -> Result<Vec<Complex<f64>>, Error> {
+        let mut sorted: Vec<Box<dyn QId>> = vec![];
+        for q in qubits {
+            if !sorted.iter().any(|other| other.eq_qid(*q)) {
+                sorted.push(q.dyn_clone());
+            }
+        }
+        let sorted = qubit_order.sorted(sorted)?;
+
+        let num_qubits = self.qubits.len();
+        let positions: Vec<Option<usize>> = sorted
+            .iter()
+            .map(|q| self.qubits.iter().position(|other| other.eq_qid(q.as_ref())))
+            .collect();
+
+        let amplitudes = (0..1usize << sorted.len())
+            .map(|j| {
+                let mut index = 0;
+                for (k, position) in positions.iter().enumerate() {
+                    if j >> (sorted.len() - 1 - k) & 1 == 1 {
+                        match position {
+                            Some(position) => index |= 1 << (num_qubits - 1 - position),
+                            None => return Complex::new(0.0, 0.0),
+                        }
+                    }
+                }
+                self.state_vector[index]
+            })
+            .collect();
+        Ok(amplitudes)
+    }
+
+    /**
+     * Formats the state as a sum of basis states, such as `0.71|00⟩ + 0.71|11⟩`, with the amplitudes
+     * rounded to `decimals` decimal places. Basis states whose amplitude rounds to zero are left out.
+     */
+    pub fn dirac_notation(&self, decimals: usize) -> String {
+        let num_qubits = self.qubits.len();
+        let mut terms: Vec<String> = vec![];
+        for (i, amplitude) in self.state_vector.iter().enumerate() {
+            let coefficient = match format_coefficient(*amplitude, decimals) {
+                Some(coefficient) => coefficient,
+                None => continue,
+            };
+            let ket: String = (0..num_qubits).map(|k| if i >> (num_qubits - 1 - k) & 1 == 1 { '1' } else { '0' }).collect();
+            terms.push(format!("{}|{}⟩", coefficient, ket));
+        }
+
+        if terms.is_empty() {
+            return "0".to_string();
+        }
+        terms.join(" + ").replace(" + -", " - ")
+    }
+
+    /// Returns the probability of each computational basis state.
+    pub fn probabilities(&self) -> Vec<f64> {
+        self.state_vector.iter().map(|amplitude| amplitude.norm_sqr()).collect()
+    }
+
+    /**
+     * Samples a measurement of the given qubits in the computational basis, without changing the state.
+     * The key of the result is the comma separated list of the qubits.
+     */
+    pub fn measure(&self, qubits: &[&dyn QId]) -> MeasurementResult {
+        self.measure_with_rng(qubits, &mut rand::thread_rng())
+    }
+
+    /// Samples a measurement of the given qubits using the given random number generator.
+    pub fn measure_with_rng<R: Rng>(&self, qubits: &[&dyn QId], rng: &mut R) -> MeasurementResult {
+        let key = qubits.iter().map(|q| q.to_string()).collect::<Vec<_>>().join(",");
+        let state = sample_state(&self.probabilities(), rng);
+        let num_qubits = self.qubits.len();
+
+        let measurements = qubits
+            .iter()
+            .map(|q| match self.qubits.iter().position(|other| other.eq_qid(*q)) {
+                Some(i) => state >> (num_qubits - 1 - i) & 1 == 1,
+                // Qubits that aren't acted upon by the circuit are still in |0⟩.
+                None => false,
+            })
+            .collect();
+
+        MeasurementResult::new(&key, measurements)
+    }
+
+    /**
+     * Returns the real part of ⟨ψ|H|ψ⟩ for the observable H. The state is taken over its qubits and the
+     * observable's qubits, arranged by `qubit_order`, where qubits that the circuit doesn't act on are in |0⟩.
+     */
+    pub fn expectation_value(&self, observable: &PauliSum, qubit_order: &dyn QubitOrder) -> Result<f64, Error> {
+        let extended = self.with_observable_qubits(observable, qubit_order)?;
+        let (qubits, state) = (extended.qubits(), extended.state_vector());
+        let qubits: Vec<&dyn QId> = qubits.iter().map(|q| q.as_ref()).collect();
+        let action = apply_observable(observable, state, &qubits)?;
+        Ok(state.iter().zip(&action).map(|(a, b)| a.conj() * b).sum::<Complex<f64>>().re)
+    }
+
+    /// Returns ⟨H²⟩ - ⟨H⟩² for the observable H, which must be Hermitian.
+    pub fn variance(&self, observable: &PauliSum) -> Result<f64, Error> {
+        let extended = self.with_observable_qubits(observable, &DefaultQubitOrder)?;
+        let (qubits, state) = (extended.qubits(), extended.state_vector());
+        let qubits: Vec<&dyn QId> = qubits.iter().map(|q| q.as_ref()).collect();
+        let action = apply_observable(observable, state, &qubits)?;
+
+        let mean = state.iter().zip(&action).map(|(a, b)| a.conj() * b).sum::<Complex<f64>>().re;
+        let mean_square: f64 = action.iter().map(|amplitude| amplitude.norm_sqr()).sum();
+        Ok(mean_square - mean * mean)
+    }
+
+    /// Returns the state extended by |0⟩ on the observable's other qubits, with the qubits sorted by `qubit_order`.
+    fn with_observable_qubits(&self, observable: &PauliSum, qubit_order: &dyn QubitOrder) -> Result<StateVectorResult, Error> {
+        let mut qubits = self.qubits.clone();
+        for q in observable.terms.iter().flat_map(|term| term.qubits()) {
+            if !qubits.iter().any(|other| other.eq_qid(q.as_ref())) {
+                qubits.push(q);
+            }
+        }
+        let qubits = qubit_order.sorted(qubits)?;
+
+        // The position of each of the result's qubits in the sorted list.
+        let num_qubits = qubits.len();
+        let positions: Vec<usize> = self.qubits
+            .iter()
+            .map(|q| qubits.iter().position(|other| other.eq_qid(q.as_ref())).unwrap())
+            .collect();
+        let mut state = vec![Complex::new(0.0, 0.0); 1 << num_qubits];
+        for (i, amplitude) in self.state_vector.iter().enumerate() {
+            let index = positions.iter().enumerate().fold(0, |index, (k, position)| {
+                index | ((i >> (positions.len() - 1 - k) & 1) << (num_qubits - 1 - position))
+            });
+            state[index] = *amplitude;
+        }
+
+        Ok(StateVectorResult {
+            qubits,
+            state_vector: state,
+            measurements: vec![],
+            measurements_by_key: HashMap::new(),
+        })
+    }
+}
+
+/**
+ * Formats an amplitude rounded to `decimals` decimal places, with `1` and `-1` written as an empty string and `-`.
+ * Returns None if the amplitude rounds to zero.
+ */
+fn format_coefficient(amplitude: Complex<f64>, decimals: usize) -> Option<String> {
+    let round = |x: f64| {
+        let text = format!("{:.*}", decimals, x);
+        let text = if text.contains('.') { text.trim_end_matches('0').trim_end_matches('.').to_string() } else { text };
+        if text == "-0" { "0".to_string() } else { text }
+    };
+    let (re, im) = (round(amplitude.re), round(amplitude.im));
+
+    let coefficient = match (re.as_str(), im.as_str()) {
+        ("0", "0") => return None,
+        ("1", "0") => String::new(),
+        ("-1", "0") => "-".to_string(),
+        (re, "0") => re.to_string(),
+        ("0", im) => format!("{}i", im),
+        (re, im) if im.starts_with('-') => format!("({}{}i)", re, im),
+        (re, im) => format!("({}+{}i)", re, im),
+    };
+    Some(coefficient)
+}
+
+/// Returns H|ψ⟩, applying each Pauli string of the observable to the state in turn.
+fn apply_observable(observable: &PauliSum, state: &[Complex<f64>], qubits: &[&dyn QId]) -> Result<Vec<Complex<f64>>, Error> {
+    let mut result = vec![Complex::new(0.0, 0.0); state.len()];
+    for term in &observable.terms {
+        for ((target, amplitude), x) in term.basis_action(qubits)?.into_iter().zip(state) {
+            result[target] += amplitude * x;
+        }
+    }
+    Ok(result)
+}
+
+impl SimulationResult for StateVectorResult {
+    fn final_state_vector(&self) -> Option<&[Complex<f64>]> {
+        Some(&self.state_vector)
+    }
+
+    fn measurements(&self) -> &HashMap<String, Vec<bool>> {
+        &self.measurements_by_key
+    }
+}
+
+/// The result of simulating one point of a sweep.
+#[derive(Clone, Debug)]
+pub struct SweepResult {
+    params: ParamResolver,
+    result: StateVectorResult,
+}
+
+impl SweepResult {
+    /// Returns the values the circuit's symbols were resolved to.
+    pub fn params(&self) -> &ParamResolver {
+        &self.params
+    }
+
+    pub fn result(&self) -> &StateVectorResult {
+        &self.result
+    }
+}
+
+/// Samples an index according to the given probabilities.
+pub(crate) fn sample_state<R: Rng>(probabilities: &[f64], rng: &mut R) -> usize {
+    let mut threshold = rng.gen::<f64>() * probabilities.iter().sum::<f64>();
+    for (i, probability) in probabilities.iter().enumerate() {
+        if threshold < *probability {
+            return i;
+        }
+        threshold -= probability;
+    }
+    probabilities.iter().rposition(|probability| *probability > 0.0).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    use crate::gate_on;
+    use crate::ops::common_gates::{Ry, CNOT, H};
+    use crate::ops::measure::M;
+    use crate::ops::pauli_gates::{Pauli, X};
+    use crate::ops::pauli_string::PauliString;
+    use crate::ops::qubit_order::ExplicitQubitOrder;
+    use crate::ops::qubits::LineQubit;
+    use crate::ops::reset::R;
+    use crate::study::{Symbol, SymbolicRotation};
+
+    use super::*;
+
+    fn bell_circuit() -> Circuit {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            Box::new(gate_on!(H, q[0].clone()).unwrap()),
+            Box::new(gate_on!(CNOT, q[0].clone(), q[1].clone()).unwrap()),
+        ]).unwrap();
+        circuit
+    }
+
+    #[test]
+    fn simulates_bell_state() {
+        let result = StateVectorSimulator::new().simulate(&bell_circuit(), None).unwrap();
+
+        assert!((result.amplitude(0) - Complex::new(FRAC_1_SQRT_2, 0.0)).norm() < 1e-10);
+        assert!((result.amplitude(3) - Complex::new(FRAC_1_SQRT_2, 0.0)).norm() < 1e-10);
+        let probabilities = result.probabilities();
+        assert!((probabilities[0] - 0.5).abs() < 1e-10);
+        assert!(probabilities[1].abs() < 1e-10);
+    }
+
+    #[test]
+    fn bell_state_measurements_are_correlated() {
+        let result = StateVectorSimulator::new().simulate(&bell_circuit(), None).unwrap();
+        let q = LineQubit::range(2);
+        let mut rng = StdRng::seed_from_u64(1234);
+
+        for _ in 0..20 {
+            let measurement = result.measure_with_rng(&[&q[0], &q[1]], &mut rng);
+            assert_eq!(measurement.key, "q0,q1");
+            assert_eq!(measurement.measurements[0], measurement.measurements[1]);
+        }
+    }
+
+    #[test]
+    fn initial_state_is_used_and_checked() {
+        let q = LineQubit::new(0);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![Box::new(gate_on!(X, q).unwrap())]).unwrap();
+
+        let one = vec![Complex::new(0.0, 0.0), Complex::new(1.0, 0.0)];
+        let result = StateVectorSimulator::new().simulate(&circuit, Some(one)).unwrap();
+        assert_eq!(result.amplitude(0), Complex::new(1.0, 0.0));
+
+        let simulator = StateVectorSimulator::new();
+        assert!(simulator.simulate(&circuit, Some(vec![Complex::new(1.0, 0.0)])).is_err());
+        assert!(simulator.simulate(&circuit, Some(vec![Complex::new(1.0, 0.0); 2])).is_err());
+    }
+
+    #[test]
+    fn moment_steps_yield_intermediate_states() {
+        let circuit = bell_circuit();
+        let simulator = StateVectorSimulator::new();
+        let steps: Vec<StepResult> = simulator.simulate_moment_steps(&circuit).collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].moment_index, 0);
+        // After H on q0 the state is (|00⟩ + |10⟩)/√2.
+        assert!((steps[0].state_vector[2] - Complex::new(FRAC_1_SQRT_2, 0.0)).norm() < 1e-10);
+        assert_eq!(steps[1].state_vector, simulator.simulate(&circuit, None).unwrap().state_vector());
+
+        let density_matrix = steps[1].density_matrix();
+        assert!((density_matrix[0][3] - Complex::new(0.5, 0.0)).norm() < 1e-10);
+        assert!(density_matrix[1][1].norm() < 1e-10);
+    }
+
+    #[test]
+    fn measurements_collapse_the_state() {
+        let q = LineQubit::range(2);
+        let mut circuit = bell_circuit();
+        circuit.append(vec![Box::new(M("m", &[&q[0]]))]).unwrap();
+
+        for seed in 0..10 {
+            let result = StateVectorSimulator::with_seed(seed).simulate(&circuit, None).unwrap();
+            let measurement = &result.measurements()[0];
+            assert_eq!(measurement.key, "m");
+
+            // Measuring q0 of a Bell pair leaves both qubits in the measured state.
+            let index = if measurement.measurements[0] { 3 } else { 0 };
+            assert!((result.amplitude(index).norm() - 1.0).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn reset_returns_one_to_zero() {
+        let q = LineQubit::new(0);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![Box::new(gate_on!(X, q.clone()).unwrap()), Box::new(gate_on!(R, q).unwrap())]).unwrap();
+
+        let result = StateVectorSimulator::new().simulate(&circuit, None).unwrap();
+        assert!((result.amplitude(0) - Complex::new(1.0, 0.0)).norm() < 1e-10);
+    }
+
+    #[test]
+    fn reset_collapses_superpositions() {
+        // q1 copies q0, so after resetting q0 it records which branch the reset collapsed to.
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            Box::new(gate_on!(Ry::new(std::f64::consts::FRAC_PI_3), q[0].clone()).unwrap()),
+            Box::new(gate_on!(CNOT, q[0].clone(), q[1].clone()).unwrap()),
+            Box::new(gate_on!(R, q[0].clone()).unwrap()),
+        ]).unwrap();
+
+        let runs = 1000;
+        let mut zeros = 0;
+        for seed in 0..runs {
+            let result = StateVectorSimulator::with_seed(seed).simulate(&circuit, None).unwrap();
+            if (result.amplitude(0).norm() - 1.0).abs() < 1e-10 {
+                zeros += 1;
+            } else {
+                assert!((result.amplitude(1).norm() - 1.0).abs() < 1e-10);
+            }
+        }
+
+        // Ry(π/3)|0⟩ has a |0⟩ component of cos(π/6), so the reset finds |0⟩ with probability 3/4.
+        assert!((zeros as f64 / runs as f64 - 0.75).abs() < 0.05);
+    }
+
+    #[test]
+    fn expectation_values_and_variances() {
+        let result = StateVectorSimulator::new().simulate(&bell_circuit(), None).unwrap();
+        let q = LineQubit::range(3);
+        let z = |i: usize| PauliString::from_pauli(Pauli::Z, &q[i]);
+        let x = |i: usize| PauliString::from_pauli(Pauli::X, &q[i]);
+
+        // The Bell state is a +1 eigenstate of ZZ and XX, but Z alone is completely uncertain.
+        let hamiltonian = PauliSum::from_pauli_strings(vec![z(0) * z(1), x(0) * x(1) * Complex::new(0.5, 0.0)]);
+        assert!((result.expectation_value(&hamiltonian, &DefaultQubitOrder).unwrap() - 1.5).abs() < 1e-10);
+        assert!(result.variance(&hamiltonian).unwrap().abs() < 1e-10);
+
+        let first = PauliSum::from(z(0));
+        assert!(result.expectation_value(&first, &DefaultQubitOrder).unwrap().abs() < 1e-10);
+        assert!((result.variance(&first).unwrap() - 1.0).abs() < 1e-10);
+
+        // q2 isn't acted upon, so it is in |0⟩, whichever position the qubit order puts it in.
+        let idle = PauliSum::from(z(2) * z(0) * z(1));
+        let order = ExplicitQubitOrder::new(vec![Box::new(q[2].clone()), Box::new(q[1].clone()), Box::new(q[0].clone())]);
+        assert!((result.expectation_value(&idle, &order).unwrap() - 1.0).abs() < 1e-10);
+        assert!((result.expectation_value(&PauliSum::from(x(2)), &DefaultQubitOrder).unwrap()).abs() < 1e-10);
+        assert!(result.expectation_value(&idle, &ExplicitQubitOrder::new(vec![])).is_err());
+    }
+
+    #[test]
+    fn amplitudes_and_dirac_notation() {
+        let q = LineQubit::range(3);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            Box::new(gate_on!(H, q[0].clone()).unwrap()),
+            Box::new(gate_on!(CNOT, q[0].clone(), q[1].clone()).unwrap()),
+        ]).unwrap();
+        let result = StateVectorSimulator::new().simulate(&circuit, None).unwrap();
+
+        assert_eq!(result.dirac_notation(2), "0.71|00⟩ + 0.71|11⟩");
+        assert!((result.amplitude(3) - Complex::new(FRAC_1_SQRT_2, 0.0)).norm() < 1e-10);
+
+        let order = ExplicitQubitOrder::new(vec![Box::new(q[2].clone()), Box::new(q[1].clone()), Box::new(q[0].clone())]);
+        let reordered = result.amplitudes_for_qubits(&[&q[0], &q[1], &q[2]], &order).unwrap();
+        assert_eq!(reordered.len(), 8);
+        assert!((reordered[3] - Complex::new(FRAC_1_SQRT_2, 0.0)).norm() < 1e-10);
+        assert!(reordered[1].norm() < 1e-10);
+
+        // With q1 in |0⟩, only q0 = 0 has any amplitude.
+        let marginal = result.amplitudes_for_qubits(&[&q[0]], &DefaultQubitOrder).unwrap();
+        assert!((marginal[0] - Complex::new(FRAC_1_SQRT_2, 0.0)).norm() < 1e-10);
+        assert!(marginal[1].norm() < 1e-10);
+    }
+
+    #[test]
+    fn coefficients_are_rounded() {
+        assert_eq!(format_coefficient(Complex::new(1.0, 0.0), 3), Some(String::new()));
+        assert_eq!(format_coefficient(Complex::new(-1.0, 1e-9), 3), Some("-".to_string()));
+        assert_eq!(format_coefficient(Complex::new(0.5, -0.5), 2), Some("(0.5-0.5i)".to_string()));
+        assert_eq!(format_coefficient(Complex::new(-0.0001, 0.25), 2), Some("0.25i".to_string()));
+        assert_eq!(format_coefficient(Complex::new(0.001, 0.0), 2), None);
+    }
+
+    #[test]
+    fn sweeps_are_simulated_point_by_point() {
+        let q = LineQubit::new(0);
+        let mut circuit = Circuit::new();
+        let rotation = SymbolicRotation::new(Pauli::X, Symbol::new("theta")).unwrap();
+        circuit.append(vec![Box::new(gate_on!(rotation, q).unwrap())]).unwrap();
+
+        let sweep = Sweep::linspace("theta", 0.0, std::f64::consts::PI, 3);
+        let results = StateVectorSimulator::new().simulate_sweep(&circuit, &sweep).unwrap();
+        assert_eq!(results.len(), 3);
+        for (expected, result) in [0.0, 0.5, 1.0].iter().zip(&results) {
+            assert_eq!(result.params(), &sweep.0[(expected * 2.0) as usize]);
+            assert!((result.result().probabilities()[1] - expected).abs() < 1e-10);
+        }
+
+        assert!(StateVectorSimulator::new().simulate_sweep(&circuit, &Sweep(vec![ParamResolver::default()])).is_err());
+    }
+}
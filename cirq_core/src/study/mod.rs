@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::ops::common_gates::{Rx, Ry, Rz};
+use crate::ops::pauli_gates::Pauli;
+use crate::ops::raw_traits::{EqValue, HasUnitary};
+use crate::ops::raw_types::{Gate, QIdShape};
+
+/// A named parameter of a circuit, whose value is given by a `ParamResolver`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Symbol(pub String);
+
+impl Symbol {
+    pub fn new(name: &str) -> Self {
+        Self(name.to_string())
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Assigns values to symbols by their name.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParamResolver(pub HashMap<String, f64>);
+
+impl ParamResolver {
+    pub fn new(values: &[(&str, f64)]) -> Self {
+        Self(values.iter().map(|(name, value)| (name.to_string(), *value)).collect())
+    }
+
+    /// Returns the value of the symbol. Fails if the resolver has no value for it.
+    pub fn value_of(&self, symbol: &Symbol) -> Result<f64, Error> {
+        self.0.get(&symbol.0).copied().ok_or_else(|| anyhow!(format!("No value is given for the symbol {}.", symbol)))
+    }
+}
+
+/// A sequence of assignments of values to symbols, each of which is one run of a parameterized circuit.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Sweep(pub Vec<ParamResolver>);
+
+impl Sweep {
+    /// Returns `num` evenly spaced values of the symbol from `start` to `stop`, inclusive.
+    pub fn linspace(symbol: &str, start: f64, stop: f64, num: usize) -> Sweep {
+        let step = if num > 1 { (stop - start) / (num - 1) as f64 } else { 0.0 };
+        Sweep((0..num).map(|i| ParamResolver::new(&[(symbol, start + step * i as f64)])).collect())
+    }
+
+    /**
+     * Returns the sweep that runs through both sweeps side by side, merging their assignments
+     * pairwise. It is as long as the shorter sweep, and values from `other` win on shared symbols.
+     */
+    pub fn zip(&self, other: &Sweep) -> Sweep {
+        Sweep(
+            self.0
+                .iter()
+                .zip(other.0.iter())
+                .map(|(a, b)| {
+                    let mut values = a.0.clone();
+                    values.extend(b.0.iter().map(|(name, value)| (name.clone(), *value)));
+                    ParamResolver(values)
+                })
+                .collect(),
+        )
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, ParamResolver> {
+        self.0.iter()
+    }
+}
+
+/**
+ * A rotation around a Pauli axis by a symbolic angle in radians. It has no unitary until the angle
+ * is resolved, which turns it into an `Rx`, `Ry` or `Rz`.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct SymbolicRotation {
+    pub axis: Pauli,
+    pub theta: Symbol,
+}
+
+impl SymbolicRotation {
+    pub fn new(axis: Pauli, theta: Symbol) -> Result<Self, Error> {
+        if axis == Pauli::I {
+            return Err(anyhow!("A rotation needs an X, Y or Z axis."));
+        }
+        Ok(Self {
+            axis,
+            theta,
+        })
+    }
+}
+
+impl fmt::Display for SymbolicRotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let axis = match self.axis {
+            Pauli::X => "x",
+            Pauli::Y => "y",
+            _ => "z",
+        };
+        write!(f, "R{}({})", axis, self.theta)
+    }
+}
+
+impl QIdShape for SymbolicRotation {
+    fn qid_shape(&self) -> Vec<u64> {
+        vec![2]
+    }
+}
+
+impl HasUnitary for SymbolicRotation {
+    fn unitary(&self) -> Option<Vec<Vec<Complex<f64>>>> {
+        None
+    }
+}
+
+impl Gate for SymbolicRotation {
+    fn equality_value(&self) -> EqValue {
+        EqValue::Tuple(vec![
+            EqValue::String("SymbolicRotation".to_string()),
+            EqValue::String(format!("{:?}", self.axis)),
+            EqValue::String(self.theta.0.clone()),
+        ])
+    }
+
+    fn pow(&self, _exponent: f64) -> Result<Box<dyn Gate>, Error> {
+        Err(anyhow!("A symbolic rotation has to be resolved before it can be raised to a power."))
+    }
+
+    fn resolve_parameters(&self, resolver: &ParamResolver) -> Result<Box<dyn Gate>, Error> {
+        let theta = resolver.value_of(&self.theta)?;
+        Ok(match self.axis {
+            Pauli::X => Box::new(Rx::new(theta)),
+            Pauli::Y => Box::new(Ry::new(theta)),
+            _ => Box::new(Rz::new(theta)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::circuits::circuit::Circuit;
+    use crate::gate_on;
+    use crate::linalg;
+    use crate::ops::common_gates::H;
+    use crate::ops::controlled_gate::ControlledGate;
+    use crate::ops::qubits::LineQubit;
+    use crate::ops::raw_types::Operation;
+
+    use super::*;
+
+    #[test]
+    fn sweeps_are_spaced_and_zipped() {
+        let sweep = Sweep::linspace("a", 0.0, 1.0, 5);
+        assert_eq!(sweep.len(), 5);
+        assert_eq!(sweep.0[1].value_of(&Symbol::new("a")).unwrap(), 0.25);
+        assert_eq!(sweep.0[4].value_of(&Symbol::new("a")).unwrap(), 1.0);
+        assert!(sweep.0[0].value_of(&Symbol::new("b")).is_err());
+
+        let zipped = sweep.zip(&Sweep::linspace("b", 2.0, 3.0, 3));
+        assert_eq!(zipped.len(), 3);
+        assert_eq!(zipped.0[2], ParamResolver::new(&[("a", 0.5), ("b", 3.0)]));
+        assert_eq!(Sweep::linspace("a", 1.0, 2.0, 1).0, vec![ParamResolver::new(&[("a", 1.0)])]);
+    }
+
+    #[test]
+    fn symbolic_gates_are_resolved() {
+        let rotation = SymbolicRotation::new(Pauli::Y, Symbol::new("theta")).unwrap();
+        assert!(!rotation.has_unitary());
+        assert_eq!(rotation.to_string(), "Ry(theta)");
+        assert!(SymbolicRotation::new(Pauli::I, Symbol::new("theta")).is_err());
+
+        let resolver = ParamResolver::new(&[("theta", PI / 2.0)]);
+        let resolved = rotation.resolve_parameters(&resolver).unwrap();
+        assert_eq!(resolved.as_any().downcast_ref::<Ry>(), Some(&Ry::new(PI / 2.0)));
+        assert!(rotation.resolve_parameters(&ParamResolver::default()).is_err());
+
+        let controlled = ControlledGate::new(Box::new(rotation), 1).resolve_parameters(&resolver).unwrap();
+        assert!(linalg::allclose(&controlled.unitary().unwrap(), &Ry::new(PI / 2.0).controlled(1).unitary().unwrap(), 1e-10));
+        assert!(H.resolve_parameters(&resolver).is_ok());
+    }
+
+    #[test]
+    fn circuits_are_resolved_for_each_point_of_a_sweep() {
+        let q = LineQubit::new(0);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            Box::new(gate_on!(H, q.clone()).unwrap()) as Box<dyn Operation>,
+            Box::new(gate_on!(SymbolicRotation::new(Pauli::Z, Symbol::new("phi")).unwrap(), q.clone()).unwrap()),
+        ]).unwrap();
+        assert!(circuit.unitary().is_err());
+
+        for resolver in Sweep::linspace("phi", 0.0, PI, 3).iter() {
+            let resolved = circuit.resolve_parameters(resolver).unwrap();
+            let phi = resolver.value_of(&Symbol::new("phi")).unwrap();
+            let expected = linalg::dot(&Rz::new(phi).unitary().unwrap(), &H.unitary().unwrap());
+            assert!(linalg::allclose(&resolved.unitary().unwrap(), &expected, 1e-10));
+        }
+    }
+}
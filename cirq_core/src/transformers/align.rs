@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use anyhow::Error;
+
+use crate::circuits::circuit::Circuit;
+use crate::circuits::moment::Moment;
+use crate::ops::raw_types::{Operation, QIdHasher};
+use crate::transformers::OptimizationPass;
+
+/// Moves every operation to the earliest moment allowed by the operations before it on its qubits.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AlignLeft;
+
+/// Moves every operation to the latest moment allowed by the operations after it on its qubits.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AlignRight;
+
+/**
+ * Places the operations, in order, each one moment after the last placed operation it shares a qubit with.
+ * Sharing a qubit is the only dependency between operations, so this keeps the circuit's meaning.
+ */
+fn align<'a>(ops: impl Iterator<Item = &'a Box<dyn Operation>>) -> Result<Vec<Moment>, Error> {
+    let mut moments: Vec<Moment> = vec![];
+    // The index of the last moment acting on each qubit.
+    let mut latest: HashMap<QIdHasher, usize> = HashMap::new();
+
+    for op in ops {
+        let qubits = op.qubits();
+        let index = qubits
+            .iter()
+            .filter_map(|q| latest.get(&QIdHasher::new(q.clone())))
+            .map(|index| index + 1)
+            .max()
+            .unwrap_or(0);
+
+        if index == moments.len() {
+            moments.push(Moment::default());
+        }
+        moments[index] = moments[index].with_operation(op.clone())?;
+        for q in qubits {
+            latest.insert(QIdHasher::new(q), index);
+        }
+    }
+    Ok(moments)
+}
+
+impl OptimizationPass for AlignLeft {
+    fn optimize_circuit(&self, circuit: Circuit) -> Result<Circuit, Error> {
+        let moments = align(circuit.moments().iter().flat_map(|moment| moment.iter()))?;
+        Ok(Circuit::from_moments(moments))
+    }
+}
+
+impl OptimizationPass for AlignRight {
+    fn optimize_circuit(&self, circuit: Circuit) -> Result<Circuit, Error> {
+        // Aligning the reversed circuit to the left aligns the circuit to the right.
+        let mut moments = align(circuit.moments().iter().rev().flat_map(|moment| moment.iter()))?;
+        moments.reverse();
+        Ok(Circuit::from_moments(moments))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gate_on;
+    use crate::linalg;
+    use crate::ops::common_gates::{CNOT, H, T};
+    use crate::ops::pauli_gates::X;
+    use crate::ops::qubits::LineQubit;
+
+    use super::*;
+
+    /// A circuit with one operation per moment, where H on q2 could run alongside the first CNOT.
+    fn under_packed_circuit() -> Circuit {
+        let q = LineQubit::range(3);
+        let ops: Vec<Box<dyn Operation>> = vec![
+            Box::new(gate_on!(H, q[0].clone()).unwrap()),
+            Box::new(gate_on!(CNOT, q[0].clone(), q[1].clone()).unwrap()),
+            Box::new(gate_on!(T, q[0].clone()).unwrap()),
+            Box::new(gate_on!(X, q[2].clone()).unwrap()),
+        ];
+        Circuit::from_moments(ops.into_iter().map(|op| Moment::new(vec![op]).unwrap()).collect())
+    }
+
+    #[test]
+    fn align_left_packs_operations_early() {
+        let circuit = under_packed_circuit();
+        let aligned = AlignLeft.optimize_circuit(circuit.clone()).unwrap();
+        assert!(aligned.depth() < circuit.depth());
+        assert_eq!(aligned.depth(), 3);
+        assert_eq!(aligned.moments()[0].len(), 2);
+        assert!(linalg::allclose(&aligned.unitary().unwrap(), &circuit.unitary().unwrap(), 1e-10));
+    }
+
+    #[test]
+    fn align_right_packs_operations_late() {
+        let circuit = under_packed_circuit();
+        let aligned = AlignRight.optimize_circuit(circuit.clone()).unwrap();
+        assert!(aligned.depth() < circuit.depth());
+        assert_eq!(aligned.depth(), 3);
+        assert_eq!(aligned.moments()[0].len(), 1);
+        assert_eq!(aligned.moments()[2].len(), 2);
+        assert!(linalg::allclose(&aligned.unitary().unwrap(), &circuit.unitary().unwrap(), 1e-10));
+    }
+}
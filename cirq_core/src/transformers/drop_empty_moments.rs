@@ -0,0 +1,95 @@
+use anyhow::Error;
+
+use crate::circuits::circuit::Circuit;
+use crate::circuits::moment::Moment;
+use crate::linalg;
+use crate::transformers::OptimizationPass;
+
+/**
+ * Removes the moments that have no operations. With a negligible tolerance, it first drops the
+ * operations whose unitary is within the tolerance of the identity, entry by entry.
+ */
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DropEmptyMoments {
+    pub negligible_tolerance: Option<f64>,
+}
+
+impl DropEmptyMoments {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the pass that also drops operations within `tolerance` of the identity.
+    pub fn drop_negligible_operations(tolerance: f64) -> Self {
+        Self {
+            negligible_tolerance: Some(tolerance),
+        }
+    }
+}
+
+impl OptimizationPass for DropEmptyMoments {
+    fn optimize_circuit(&self, circuit: Circuit) -> Result<Circuit, Error> {
+        let mut moments = vec![];
+        for moment in circuit.moments() {
+            let moment = match self.negligible_tolerance {
+                Some(tolerance) => Moment::new(
+                    moment
+                        .iter()
+                        .filter(|op| !op.unitary().is_some_and(|u| linalg::allclose(&u, &linalg::eye(u.len()), tolerance)))
+                        .cloned()
+                        .collect(),
+                )?,
+                None => moment.clone(),
+            };
+            if !moment.is_empty() {
+                moments.push(moment);
+            }
+        }
+        Ok(Circuit::from_moments(moments))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gate_on;
+    use crate::ops::common_gates::{IdentityGate, Rz, CNOT, H};
+    use crate::ops::measure::M;
+    use crate::ops::qubits::LineQubit;
+    use crate::ops::raw_types::{Operation, QId};
+
+    use super::*;
+
+    #[test]
+    fn cleared_moments_are_dropped() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            Box::new(gate_on!(H, q[0].clone()).unwrap()),
+            Box::new(gate_on!(CNOT, q[0].clone(), q[1].clone()).unwrap()),
+            Box::new(gate_on!(H, q[1].clone()).unwrap()),
+        ]).unwrap();
+        circuit.clear_operations_touching(&[&q[0] as &dyn QId], 0..2);
+        assert_eq!(circuit.num_moments(), 3);
+
+        let optimized = DropEmptyMoments::new().optimize_circuit(circuit).unwrap();
+        assert_eq!(optimized.num_moments(), 1);
+        assert_eq!(optimized.all_operations().count(), 1);
+    }
+
+    #[test]
+    fn negligible_operations_are_dropped() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            Box::new(gate_on!(Rz::new(1e-6), q[0].clone()).unwrap()) as Box<dyn Operation>,
+            Box::new(gate_on!(IdentityGate { num_qubits: 1 }, q[1].clone()).unwrap()),
+            Box::new(gate_on!(H, q[0].clone()).unwrap()),
+            Box::new(M("m", &[&q[1]])),
+        ]).unwrap();
+
+        assert_eq!(DropEmptyMoments::new().optimize_circuit(circuit.clone()).unwrap().all_operations().count(), 4);
+        let optimized = DropEmptyMoments::drop_negligible_operations(1e-4).optimize_circuit(circuit).unwrap();
+        assert_eq!(optimized.num_moments(), 1);
+        assert_eq!(optimized.all_operations().count(), 2);
+    }
+}
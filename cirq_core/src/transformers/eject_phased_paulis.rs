@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use anyhow::Error;
+
+use crate::circuits::circuit::Circuit;
+use crate::ops::common_gates::{CZGate, HGate, IdentityGate, Rx, Ry, Rz, ZPowGate};
+use crate::ops::gate_operation::GateOperation;
+use crate::ops::measure::MeasurementGate;
+use crate::ops::pauli_gates::{Pauli, XGate, YGate, ZGate};
+use crate::ops::raw_types::{Gate, Operation, QId, QIdHasher};
+use crate::transformers::eject_z::z_exponent;
+use crate::transformers::OptimizationPass;
+
+/**
+ * Moves Pauli gates towards the end of the circuit by tracking a Pauli frame on each qubit.
+ * X, Y and Z gates, and X, Y and Z rotations within `tolerance` of a half turn, are absorbed into the frame.
+ * An X or Y in the frame passes through a CZ by adding a Z on the other qubit. The frame passes any other
+ * X, Y or Z rotation, reversing the rotation when the frame anticommutes with its axis.
+ * The frame is conjugated by H gates.
+ * It is written out before a measurement, where a Z in the frame is dropped, before any other gate
+ * and at the end of the circuit. Global phases are ignored.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct EjectPhasedPaulis {
+    pub tolerance: f64,
+}
+
+impl EjectPhasedPaulis {
+    pub fn new(tolerance: f64) -> Self {
+        Self {
+            tolerance
+        }
+    }
+}
+
+impl Default for EjectPhasedPaulis {
+    fn default() -> Self {
+        Self::new(1e-8)
+    }
+}
+
+/// Determines if the Pauli has an X component, so that it anticommutes with Z.
+fn flips_bits(pauli: Pauli) -> bool {
+    pauli == Pauli::X || pauli == Pauli::Y
+}
+
+impl EjectPhasedPaulis {
+    /// Returns the Pauli that the gate is equal to up to a global phase, if there is one.
+    fn as_pauli(&self, gate: &dyn Gate) -> Option<Pauli> {
+        let any = gate.as_any();
+        let is_half_turn = |theta: f64| ((theta / PI).rem_euclid(2.0) - 1.0).abs() <= self.tolerance;
+        if any.is::<XGate>() || any.downcast_ref::<Rx>().is_some_and(|gate| is_half_turn(gate.theta)) {
+            Some(Pauli::X)
+        } else if any.is::<YGate>() || any.downcast_ref::<Ry>().is_some_and(|gate| is_half_turn(gate.theta)) {
+            Some(Pauli::Y)
+        } else if any.is::<ZGate>() || any.downcast_ref::<Rz>().is_some_and(|gate| is_half_turn(gate.theta)) {
+            Some(Pauli::Z)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the axis and angle of an X or Y rotation. Z rotations are handled with the other Z phases.
+fn rotation(gate: &dyn Gate) -> Option<(Pauli, f64)> {
+    let any = gate.as_any();
+    if let Some(gate) = any.downcast_ref::<Rx>() {
+        Some((Pauli::X, gate.theta))
+    } else {
+        any.downcast_ref::<Ry>().map(|gate| (Pauli::Y, gate.theta))
+    }
+}
+
+/// Returns the frame of the qubit.
+fn pauli_of(frame: &HashMap<QIdHasher, Pauli>, qubit: &dyn QId) -> Pauli {
+    frame.get(&QIdHasher::new(qubit.dyn_clone())).copied().unwrap_or(Pauli::I)
+}
+
+/// Multiplies the Pauli onto the frame of the qubit.
+fn absorb(frame: &mut HashMap<QIdHasher, Pauli>, qubit: Box<dyn QId>, pauli: Pauli) {
+    let entry = frame.entry(QIdHasher::new(qubit)).or_insert(Pauli::I);
+    *entry = Pauli::product(*entry, pauli).0;
+}
+
+/// Writes out the frame of the qubit, if it isn't the identity, and clears it.
+fn dump(frame: &mut HashMap<QIdHasher, Pauli>, qubit: &dyn QId, ops: &mut Vec<Box<dyn Operation>>) {
+    let pauli = frame.remove(&QIdHasher::new(qubit.dyn_clone()));
+    if let Some(pauli) = pauli.filter(|pauli| *pauli != Pauli::I) {
+        ops.push(Box::new(pauli.on(qubit.dyn_clone())));
+    }
+}
+
+impl OptimizationPass for EjectPhasedPaulis {
+    fn optimize_circuit(&self, circuit: Circuit) -> Result<Circuit, Error> {
+        let mut frame: HashMap<QIdHasher, Pauli> = HashMap::new();
+        let mut ops: Vec<Box<dyn Operation>> = vec![];
+
+        for op in circuit.moments().iter().flat_map(|moment| moment.iter()) {
+            let qubits = op.qubits();
+            let gate = match op.gate() {
+                Some(gate) => gate,
+                None => {
+                    for q in &qubits {
+                        dump(&mut frame, q.as_ref(), &mut ops);
+                    }
+                    ops.push(op.clone());
+                    continue;
+                }
+            };
+            let any = gate.as_any();
+
+            if let Some(pauli) = self.as_pauli(gate.as_ref()) {
+                absorb(&mut frame, qubits[0].clone(), pauli);
+            } else if any.is::<CZGate>() {
+                // X_a CZ = CZ X_a Z_b, up to a global phase.
+                let (first, second) = (pauli_of(&frame, qubits[0].as_ref()), pauli_of(&frame, qubits[1].as_ref()));
+                if flips_bits(first) {
+                    absorb(&mut frame, qubits[1].clone(), Pauli::Z);
+                }
+                if flips_bits(second) {
+                    absorb(&mut frame, qubits[0].clone(), Pauli::Z);
+                }
+                ops.push(op.clone());
+            } else if let Some(exponent) = z_exponent(gate.as_ref()) {
+                // X Z^t X = Z^-t up to a global phase.
+                if flips_bits(pauli_of(&frame, qubits[0].as_ref())) {
+                    ops.push(Box::new(GateOperation::new(Box::new(ZPowGate::new(-exponent)), qubits)));
+                } else {
+                    ops.push(op.clone());
+                }
+            } else if let Some((axis, theta)) = rotation(gate.as_ref()) {
+                // P R_A(θ) = R_A(-θ) P when the Pauli P anticommutes with A.
+                let pauli = pauli_of(&frame, qubits[0].as_ref());
+                if pauli != Pauli::I && pauli != axis {
+                    let reversed: Box<dyn Gate> = match axis {
+                        Pauli::X => Box::new(Rx::new(-theta)),
+                        _ => Box::new(Ry::new(-theta)),
+                    };
+                    ops.push(Box::new(GateOperation::new(reversed, qubits)));
+                } else {
+                    ops.push(op.clone());
+                }
+            } else if any.is::<HGate>() {
+                let conjugated = match pauli_of(&frame, qubits[0].as_ref()) {
+                    Pauli::X => Pauli::Z,
+                    Pauli::Z => Pauli::X,
+                    pauli => pauli,
+                };
+                frame.insert(QIdHasher::new(qubits[0].clone()), conjugated);
+                ops.push(op.clone());
+            } else if any.is::<IdentityGate>() {
+                ops.push(op.clone());
+            } else if any.is::<MeasurementGate>() {
+                // Z doesn't change the outcome of a measurement, so only the bit flip is kept.
+                for q in &qubits {
+                    if frame.remove(&QIdHasher::new(q.clone())).is_some_and(flips_bits) {
+                        ops.push(Box::new(Pauli::X.on(q.clone())));
+                    }
+                }
+                ops.push(op.clone());
+            } else {
+                for q in &qubits {
+                    dump(&mut frame, q.as_ref(), &mut ops);
+                }
+                ops.push(op.clone());
+            }
+        }
+
+        for q in circuit.all_qubits() {
+            dump(&mut frame, q.as_ref(), &mut ops);
+        }
+
+        let mut optimized = Circuit::new();
+        optimized.append(ops)?;
+        Ok(optimized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_complex::Complex;
+
+    use crate::gate_on;
+    use crate::linalg;
+    use crate::ops::common_gates::{CNotGate, CNOT, CZ, H, T};
+    use crate::ops::measure::M;
+    use crate::ops::pauli_gates::{X, Y};
+    use crate::ops::qubits::LineQubit;
+
+    use super::*;
+
+    fn op(result: Result<GateOperation, Error>) -> Box<dyn Operation> {
+        Box::new(result.unwrap())
+    }
+
+    /// Determines if two unitaries are equal up to a global phase.
+    fn equal_up_to_phase(a: &[Vec<Complex<f64>>], b: &[Vec<Complex<f64>>]) -> bool {
+        let phase = a.iter().flatten().zip(b.iter().flatten()).find(|(x, _)| x.norm() > 1e-6).map(|(x, y)| y / x).unwrap();
+        let scaled: Vec<Vec<_>> = a.iter().map(|row| row.iter().map(|x| x * phase).collect()).collect();
+        linalg::allclose(&scaled, b, 1e-8)
+    }
+
+    #[test]
+    fn paulis_move_past_cz_gates() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            op(gate_on!(X, q[0].clone())),
+            op(gate_on!(H, q[1].clone())),
+            op(gate_on!(CZ, q[0].clone(), q[1].clone())),
+            op(gate_on!(T, q[0].clone())),
+            op(gate_on!(Rx::new(PI), q[0].clone())),
+            op(gate_on!(CZ, q[0].clone(), q[1].clone())),
+            op(gate_on!(Y, q[1].clone())),
+        ]).unwrap();
+        let unitary = circuit.unitary().unwrap();
+
+        let optimized = EjectPhasedPaulis::default().optimize_circuit(circuit).unwrap();
+        assert!(equal_up_to_phase(&optimized.unitary().unwrap(), &unitary));
+        // The two X gates cancel, so only the Z and Y on q1 remain, merged into one Pauli at the end.
+        let paulis: Vec<&dyn Operation> = optimized
+            .all_operations()
+            .filter(|op| EjectPhasedPaulis::default().as_pauli(op.gate().unwrap().as_ref()).is_some())
+            .collect();
+        assert_eq!(paulis.len(), 1);
+        assert!(paulis[0].gate().unwrap().as_any().is::<XGate>());
+        assert_eq!(optimized.moments().last().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn paulis_move_past_arbitrary_rotations() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            op(gate_on!(Y, q[0].clone())),
+            op(gate_on!(Rx::new(0.3), q[0].clone())),
+            op(gate_on!(Ry::new(0.7), q[0].clone())),
+            op(gate_on!(CZ, q[0].clone(), q[1].clone())),
+            op(gate_on!(X, q[1].clone())),
+            op(gate_on!(Rx::new(1.1), q[1].clone())),
+        ]).unwrap();
+        let unitary = circuit.unitary().unwrap();
+
+        let optimized = EjectPhasedPaulis::default().optimize_circuit(circuit).unwrap();
+        assert!(equal_up_to_phase(&optimized.unitary().unwrap(), &unitary));
+
+        // Y anticommutes with X, so the X rotation is reversed, and commutes with the Y rotation.
+        let ops: Vec<&dyn Operation> = optimized.all_operations().collect();
+        assert!((ops[0].gate().unwrap().as_any().downcast_ref::<Rx>().unwrap().theta + 0.3).abs() < 1e-12);
+        assert!((ops[1].gate().unwrap().as_any().downcast_ref::<Ry>().unwrap().theta - 0.7).abs() < 1e-12);
+        assert!(ops[2].gate().unwrap().as_any().is::<CZGate>());
+        // The Z picked up from the CZ and the X on q1 reverse the last rotation, then are written out at the end.
+        assert!((ops[3].gate().unwrap().as_any().downcast_ref::<Rx>().unwrap().theta + 1.1).abs() < 1e-12);
+        assert_eq!(ops.len(), 6);
+    }
+
+    #[test]
+    fn frames_are_written_out_before_measurements_and_other_gates() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            op(gate_on!(Y, q[0].clone())),
+            op(gate_on!(ZGate, q[1].clone())),
+            Box::new(M("m", &[&q[0], &q[1]])),
+            op(gate_on!(X, q[0].clone())),
+            op(gate_on!(CNOT, q[0].clone(), q[1].clone())),
+        ]).unwrap();
+
+        let optimized = EjectPhasedPaulis::default().optimize_circuit(circuit).unwrap();
+        let ops: Vec<&dyn Operation> = optimized.all_operations().collect();
+        assert_eq!(ops.len(), 4);
+        assert!(ops[0].gate().unwrap().as_any().is::<XGate>());
+        assert!(ops[1].gate().unwrap().as_any().is::<MeasurementGate>());
+        assert!(ops[2].gate().unwrap().as_any().is::<XGate>());
+        assert!(ops[3].gate().unwrap().as_any().is::<CNotGate>());
+    }
+}
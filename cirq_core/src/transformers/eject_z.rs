@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use anyhow::Error;
+
+use crate::circuits::circuit::Circuit;
+use crate::ops::common_gates::{CZGate, HGate, IdentityGate, Rz, SGate, SGateDag, SwapGate, TGate, TGateDag, ZPowGate};
+use crate::ops::gate_operation::GateOperation;
+use crate::ops::measure::MeasurementGate;
+use crate::ops::pauli_gates::{XGate, YGate, ZGate, X};
+use crate::ops::raw_types::{Gate, Operation, QId, QIdHasher};
+use crate::transformers::OptimizationPass;
+
+/**
+ * Moves Z rotations towards the end of the circuit, merging them along the way.
+ * The phase on each qubit passes through CZ and identity gates, swaps qubits at a SWAP,
+ * flips sign at an X or Y, turns a Pauli Z into an X at an H, and is dropped at a measurement.
+ * It is written out as a `ZPowGate` before any other gate and at the end of the circuit,
+ * unless it is within `tolerance` of a multiple of a full turn.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct EjectZ {
+    pub tolerance: f64,
+}
+
+impl EjectZ {
+    pub fn new(tolerance: f64) -> Self {
+        Self {
+            tolerance
+        }
+    }
+}
+
+impl Default for EjectZ {
+    fn default() -> Self {
+        Self::new(1e-8)
+    }
+}
+
+/// Returns the exponent t of the gate as Z^t, up to a global phase, if it is a Z rotation.
+pub(crate) fn z_exponent(gate: &dyn Gate) -> Option<f64> {
+    let any = gate.as_any();
+    if any.is::<ZGate>() {
+        Some(1.0)
+    } else if any.is::<SGate>() {
+        Some(0.5)
+    } else if any.is::<SGateDag>() {
+        Some(-0.5)
+    } else if any.is::<TGate>() {
+        Some(0.25)
+    } else if any.is::<TGateDag>() {
+        Some(-0.25)
+    } else if let Some(gate) = any.downcast_ref::<ZPowGate>() {
+        Some(gate.exponent)
+    } else {
+        any.downcast_ref::<Rz>().map(|gate| gate.theta / PI)
+    }
+}
+
+/// Returns the exponent brought into (-1, 1].
+fn canonical_exponent(exponent: f64) -> f64 {
+    let reduced = exponent.rem_euclid(2.0);
+    if reduced > 1.0 {
+        reduced - 2.0
+    } else {
+        reduced
+    }
+}
+
+impl EjectZ {
+    fn is_zero(&self, exponent: f64) -> bool {
+        canonical_exponent(exponent).abs() <= self.tolerance
+    }
+
+    fn is_pauli_z(&self, exponent: f64) -> bool {
+        (canonical_exponent(exponent) - 1.0).abs() <= self.tolerance
+    }
+
+    /// Writes out the phase of the qubit, if there is one, and clears it.
+    fn dump(&self, phases: &mut HashMap<QIdHasher, f64>, qubit: &dyn QId, ops: &mut Vec<Box<dyn Operation>>) {
+        if let Some(exponent) = phases.remove(&QIdHasher::new(qubit.dyn_clone())) {
+            if !self.is_zero(exponent) {
+                let gate = ZPowGate::new(canonical_exponent(exponent));
+                ops.push(Box::new(GateOperation::new(Box::new(gate), vec![qubit.dyn_clone()])));
+            }
+        }
+    }
+}
+
+impl OptimizationPass for EjectZ {
+    fn optimize_circuit(&self, circuit: Circuit) -> Result<Circuit, Error> {
+        let mut phases: HashMap<QIdHasher, f64> = HashMap::new();
+        let mut ops: Vec<Box<dyn Operation>> = vec![];
+
+        for op in circuit.moments().iter().flat_map(|moment| moment.iter()) {
+            let qubits = op.qubits();
+            let gate = match op.gate() {
+                Some(gate) => gate,
+                None => {
+                    for q in &qubits {
+                        self.dump(&mut phases, q.as_ref(), &mut ops);
+                    }
+                    ops.push(op.clone());
+                    continue;
+                }
+            };
+            let any = gate.as_any();
+
+            if let Some(exponent) = z_exponent(gate.as_ref()) {
+                *phases.entry(QIdHasher::new(qubits[0].clone())).or_insert(0.0) += exponent;
+            } else if any.is::<XGate>() || any.is::<YGate>() {
+                // Z^t X = X Z^-t up to a global phase.
+                if let Some(exponent) = phases.get_mut(&QIdHasher::new(qubits[0].clone())) {
+                    *exponent = -*exponent;
+                }
+                ops.push(op.clone());
+            } else if any.is::<CZGate>() || any.is::<IdentityGate>() {
+                ops.push(op.clone());
+            } else if any.is::<SwapGate>() {
+                let first = phases.remove(&QIdHasher::new(qubits[0].clone()));
+                let second = phases.remove(&QIdHasher::new(qubits[1].clone()));
+                if let Some(exponent) = first {
+                    phases.insert(QIdHasher::new(qubits[1].clone()), exponent);
+                }
+                if let Some(exponent) = second {
+                    phases.insert(QIdHasher::new(qubits[0].clone()), exponent);
+                }
+                ops.push(op.clone());
+            } else if any.is::<MeasurementGate>() {
+                // Phases don't change the outcomes of a measurement in the computational basis.
+                for q in &qubits {
+                    phases.remove(&QIdHasher::new(q.clone()));
+                }
+                ops.push(op.clone());
+            } else if any.is::<HGate>() && phases.get(&QIdHasher::new(qubits[0].clone())).is_some_and(|t| self.is_pauli_z(*t)) {
+                // H Z = X H.
+                phases.remove(&QIdHasher::new(qubits[0].clone()));
+                ops.push(op.clone());
+                ops.push(Box::new(X.on(qubits)?));
+            } else {
+                for q in &qubits {
+                    self.dump(&mut phases, q.as_ref(), &mut ops);
+                }
+                ops.push(op.clone());
+            }
+        }
+
+        for q in circuit.all_qubits() {
+            self.dump(&mut phases, q.as_ref(), &mut ops);
+        }
+
+        let mut optimized = Circuit::new();
+        optimized.append(ops)?;
+        Ok(optimized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_complex::Complex;
+
+    use crate::gate_on;
+    use crate::linalg;
+    use crate::ops::common_gates::{CZ, H, S, SWAP, T};
+    use crate::ops::measure::M;
+    use crate::ops::pauli_gates::Z;
+    use crate::ops::qubits::LineQubit;
+    use crate::ops::raw_traits::{gates_equal, HasUnitary};
+    use crate::transformers::merge_single_qubit_gates::MergeSingleQubitGates;
+    use crate::transformers::OptimizationPipeline;
+
+    use super::*;
+
+    fn op(result: Result<GateOperation, Error>) -> Box<dyn Operation> {
+        Box::new(result.unwrap())
+    }
+
+    /// Determines if two unitaries are equal up to a global phase.
+    fn equal_up_to_phase(a: &[Vec<Complex<f64>>], b: &[Vec<Complex<f64>>]) -> bool {
+        let phase = a.iter().flatten().zip(b.iter().flatten()).find(|(x, _)| x.norm() > 1e-6).map(|(x, y)| y / x).unwrap();
+        let scaled: Vec<Vec<_>> = a.iter().map(|row| row.iter().map(|x| x * phase).collect()).collect();
+        linalg::allclose(&scaled, b, 1e-8)
+    }
+
+    #[test]
+    fn h_z_h_simplifies_to_x() {
+        let q = LineQubit::new(0);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![op(gate_on!(H, q.clone())), op(gate_on!(Z, q.clone())), op(gate_on!(H, q.clone()))]).unwrap();
+
+        let ejected = EjectZ::default().optimize_circuit(circuit).unwrap();
+        assert!(!ejected.all_operations().any(|op| z_exponent(op.gate().unwrap().as_ref()).is_some()));
+        assert!(gates_equal(ejected.all_operations().last().unwrap(), &gate_on!(X, q.clone()).unwrap()));
+
+        let pipeline = OptimizationPipeline::new(vec![Box::new(EjectZ::default()), Box::new(MergeSingleQubitGates::default())]);
+        let optimized = pipeline.optimize(ejected).unwrap();
+        let ops: Vec<&dyn Operation> = optimized.all_operations().collect();
+        assert_eq!(ops.len(), 1);
+        assert!(equal_up_to_phase(&ops[0].unitary().unwrap(), &X.unitary().unwrap()));
+    }
+
+    #[test]
+    fn phases_move_through_two_qubit_gates_and_merge() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            op(gate_on!(S, q[0].clone())),
+            op(gate_on!(Rz::new(PI / 4.0), q[1].clone())),
+            op(gate_on!(CZ, q[0].clone(), q[1].clone())),
+            op(gate_on!(X, q[0].clone())),
+            op(gate_on!(S, q[0].clone())),
+            op(gate_on!(SWAP, q[0].clone(), q[1].clone())),
+            op(gate_on!(H, q[1].clone())),
+        ]).unwrap();
+        let unitary = circuit.unitary().unwrap();
+
+        let optimized = EjectZ::default().optimize_circuit(circuit).unwrap();
+        assert!(equal_up_to_phase(&optimized.unitary().unwrap(), &unitary));
+        // The S gates cancel past the X, and the rotation on q1 is swapped to q0 and written out at the end.
+        let z_rotations: Vec<f64> = optimized.all_operations().filter_map(|op| z_exponent(op.gate().unwrap().as_ref())).collect();
+        assert_eq!(z_rotations.len(), 1);
+        assert!((z_rotations[0] - 0.25).abs() < 1e-10);
+    }
+
+    #[test]
+    fn phases_before_measurements_are_dropped() {
+        let q = LineQubit::new(0);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![op(gate_on!(H, q.clone())), op(gate_on!(T, q.clone())), Box::new(M("m", &[&q]))]).unwrap();
+
+        let optimized = EjectZ::default().optimize_circuit(circuit).unwrap();
+        assert_eq!(optimized.all_operations().count(), 2);
+    }
+}
@@ -0,0 +1,143 @@
+use anyhow::Error;
+use num_complex::Complex;
+
+use crate::circuits::circuit::Circuit;
+use crate::linalg;
+use crate::ops::gate_operation::GateOperation;
+use crate::ops::matrix_gates::MatrixGate;
+use crate::ops::raw_types::Operation;
+use crate::transformers::{PointOptimizationSummary, PointOptimizer};
+
+/**
+ * Replaces each run of single-qubit unitary operations on a qubit with one `MatrixGate` of their product.
+ * A run continues across moments until an operation that acts on more qubits or isn't unitary.
+ * Runs whose product is within `tolerance` of the identity, up to a global phase, are removed.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct MergeSingleQubitGates {
+    pub tolerance: f64,
+}
+
+impl MergeSingleQubitGates {
+    pub fn new(tolerance: f64) -> Self {
+        Self {
+            tolerance
+        }
+    }
+}
+
+impl Default for MergeSingleQubitGates {
+    fn default() -> Self {
+        Self::new(1e-8)
+    }
+}
+
+/// Returns the unitary of the operation if it is a single-qubit unitary operation.
+fn single_qubit_unitary(op: &dyn Operation) -> Option<Vec<Vec<Complex<f64>>>> {
+    if op.qubits().len() == 1 {
+        op.unitary()
+    } else {
+        None
+    }
+}
+
+impl PointOptimizer for MergeSingleQubitGates {
+    fn optimization_at(
+        &self,
+        circuit: &Circuit,
+        index: usize,
+        op: &dyn Operation,
+    ) -> Result<Option<PointOptimizationSummary>, Error> {
+        let mut product = match single_qubit_unitary(op) {
+            Some(unitary) => unitary,
+            None => return Ok(None),
+        };
+        let qubit = op.qubits()[0].clone();
+
+        let mut end = index + 1;
+        let mut run_length = 1;
+        for moment in &circuit.moments()[index + 1..] {
+            match moment.iter().find(|other| other.qubits().iter().any(|q| q.eq_qid(qubit.as_ref()))) {
+                None => end += 1,
+                Some(other) => match single_qubit_unitary(other.as_ref()) {
+                    Some(unitary) => {
+                        product = linalg::dot(&unitary, &product);
+                        run_length += 1;
+                        end += 1;
+                    }
+                    None => break,
+                },
+            }
+        }
+        if run_length == 1 {
+            return Ok(None);
+        }
+
+        let phase = product[0][0];
+        let identity: Vec<Vec<_>> = linalg::eye(product.len()).iter().map(|row| row.iter().map(|x| x * phase).collect()).collect();
+        let new_operations: Vec<Box<dyn Operation>> = if linalg::allclose(&product, &identity, self.tolerance) {
+            vec![]
+        } else {
+            let gate = MatrixGate::new(product, vec![qubit.dimension()])?;
+            vec![Box::new(GateOperation::new(Box::new(gate), vec![qubit.clone()]))]
+        };
+
+        Ok(Some(PointOptimizationSummary {
+            clear_span: end - index,
+            clear_qubits: vec![qubit],
+            new_operations,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gate_on;
+    use crate::ops::common_gates::{CNOT, H, S, T};
+    use crate::ops::measure::M;
+    use crate::ops::qubits::LineQubit;
+    use crate::ops::raw_traits::HasUnitary;
+    use crate::transformers::OptimizationPass;
+
+    use super::*;
+
+    #[test]
+    fn three_hadamards_collapse_to_one() {
+        let q = LineQubit::new(0);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            Box::new(gate_on!(H, q.clone()).unwrap()),
+            Box::new(gate_on!(H, q.clone()).unwrap()),
+            Box::new(gate_on!(H, q.clone()).unwrap()),
+        ]).unwrap();
+
+        let optimized = MergeSingleQubitGates::default().optimize_circuit(circuit).unwrap();
+        let ops: Vec<&dyn Operation> = optimized.all_operations().collect();
+        assert_eq!(ops.len(), 1);
+        assert!(linalg::allclose(&ops[0].unitary().unwrap(), &H.unitary().unwrap(), 1e-10));
+    }
+
+    #[test]
+    fn runs_stop_at_multi_qubit_and_non_unitary_operations() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![
+            Box::new(gate_on!(H, q[0].clone()).unwrap()) as Box<dyn Operation>,
+            Box::new(gate_on!(T, q[1].clone()).unwrap()),
+            Box::new(gate_on!(T, q[1].clone()).unwrap()),
+            Box::new(gate_on!(S, q[0].clone()).unwrap()),
+            Box::new(gate_on!(CNOT, q[0].clone(), q[1].clone()).unwrap()),
+            Box::new(gate_on!(H, q[0].clone()).unwrap()),
+            Box::new(gate_on!(H, q[0].clone()).unwrap()),
+            Box::new(M("m", &[&q[1]])),
+            Box::new(gate_on!(T, q[1].clone()).unwrap()),
+        ]).unwrap();
+        let unitary_before = Circuit::from_moments(circuit.moments()[..3].to_vec()).unitary().unwrap();
+
+        let optimized = MergeSingleQubitGates::default().optimize_circuit(circuit).unwrap();
+        // S·H and T·T are merged, H·H cancels, and the measurement and last T stay.
+        assert_eq!(optimized.all_operations().count(), 5);
+        let unitary_after = Circuit::from_moments(optimized.moments()[..3].to_vec()).unitary().unwrap();
+        assert!(linalg::allclose(&unitary_before, &unitary_after, 1e-10));
+    }
+}
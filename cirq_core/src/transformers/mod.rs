@@ -0,0 +1,191 @@
+pub mod align;
+pub mod drop_empty_moments;
+pub mod eject_phased_paulis;
+pub mod eject_z;
+pub mod merge_single_qubit_gates;
+
+use std::collections::HashMap;
+
+use anyhow::Error;
+
+use crate::circuits::circuit::Circuit;
+use crate::ops::raw_types::{Operation, QId, QIdHasher};
+
+/// A transformation of a circuit, such as merging gates or removing redundant operations.
+pub trait OptimizationPass {
+    fn optimize_circuit(&self, circuit: Circuit) -> Result<Circuit, Error>;
+}
+
+/// Runs a sequence of passes, each on the output of the previous one.
+#[derive(Default)]
+pub struct OptimizationPipeline(pub Vec<Box<dyn OptimizationPass>>);
+
+impl OptimizationPipeline {
+    pub fn new(passes: Vec<Box<dyn OptimizationPass>>) -> Self {
+        Self(passes)
+    }
+
+    /// Returns the circuit after running every pass of the pipeline in order.
+    pub fn optimize(&self, circuit: Circuit) -> Result<Circuit, Error> {
+        self.0.iter().try_fold(circuit, |circuit, pass| pass.optimize_circuit(circuit))
+    }
+}
+
+impl OptimizationPass for OptimizationPipeline {
+    fn optimize_circuit(&self, circuit: Circuit) -> Result<Circuit, Error> {
+        self.optimize(circuit)
+    }
+}
+
+/// A rewrite of part of a circuit, returned by a point optimizer.
+pub struct PointOptimizationSummary {
+    /// The number of moments to clear, starting at the moment of the optimized operation.
+    pub clear_span: usize,
+    /// The qubits whose operations are cleared in those moments.
+    pub clear_qubits: Vec<Box<dyn QId>>,
+    /// The operations that replace the cleared ones. They may only act on `clear_qubits`.
+    pub new_operations: Vec<Box<dyn Operation>>,
+}
+
+/**
+ * An optimization that looks at one operation at a time and may rewrite the part of the circuit
+ * that follows it. Every point optimizer is an optimization pass, which visits the operations
+ * moment by moment and skips the ones that an earlier rewrite has already replaced.
+ */
+pub trait PointOptimizer {
+    /// Returns the rewrite to apply at the operation in the given moment, or None to leave it unchanged.
+    fn optimization_at(
+        &self,
+        circuit: &Circuit,
+        index: usize,
+        op: &dyn Operation,
+    ) -> Result<Option<PointOptimizationSummary>, Error>;
+}
+
+impl<T: PointOptimizer> OptimizationPass for T {
+    fn optimize_circuit(&self, circuit: Circuit) -> Result<Circuit, Error> {
+        let mut circuit = circuit;
+        // The first moment on each qubit that hasn't been rewritten yet.
+        let mut frontier: HashMap<QIdHasher, usize> = HashMap::new();
+
+        let mut index = 0;
+        while index < circuit.num_moments() {
+            let ops = circuit.moments()[index].operations().to_vec();
+            for op in ops {
+                let behind_frontier = op.qubits()
+                    .into_iter()
+                    .any(|q| frontier.get(&QIdHasher::new(q)).is_some_and(|start| *start > index));
+                if behind_frontier {
+                    continue;
+                }
+
+                let summary = match self.optimization_at(&circuit, index, op.as_ref())? {
+                    Some(summary) => summary,
+                    None => continue,
+                };
+                let clear_qubits: Vec<&dyn QId> = summary.clear_qubits.iter().map(|q| q.as_ref()).collect();
+                for new_op in &summary.new_operations {
+                    if let Some(q) = new_op.qubits().iter().find(|q| !clear_qubits.iter().any(|other| other.eq_qid(q.as_ref()))) {
+                        return Err(anyhow!(format!("A point optimization added an operation on {}, which it didn't clear.", q)));
+                    }
+                }
+
+                let end = (index + summary.clear_span).min(circuit.num_moments());
+                circuit.clear_operations_touching(&clear_qubits, index..end);
+                let new_end = circuit.insert_into_range(summary.new_operations, index, end)?;
+
+                // Inserted moments shift everything after the range.
+                for start in frontier.values_mut() {
+                    if *start >= end {
+                        *start += new_end - end;
+                    }
+                }
+                for q in summary.clear_qubits {
+                    let start = frontier.entry(QIdHasher::new(q)).or_insert(new_end);
+                    *start = (*start).max(new_end);
+                }
+            }
+            index += 1;
+        }
+
+        Ok(circuit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gate_on;
+    use crate::ops::common_gates::{HGate, CNOT, H};
+    use crate::ops::gate_operation::GateOperation;
+    use crate::ops::pauli_gates::{X, Z};
+    use crate::ops::qubits::LineQubit;
+    use crate::ops::raw_traits::gates_equal;
+    use crate::ops::raw_types::Gate;
+
+    use super::*;
+
+    fn op(result: Result<GateOperation, Error>) -> Box<dyn Operation> {
+        Box::new(result.unwrap())
+    }
+
+    /// Replaces every H with X and Z.
+    struct ExpandH;
+
+    impl PointOptimizer for ExpandH {
+        fn optimization_at(
+            &self,
+            _circuit: &Circuit,
+            _index: usize,
+            operation: &dyn Operation,
+        ) -> Result<Option<PointOptimizationSummary>, Error> {
+            if !operation.gate().is_some_and(|gate| gate.as_any().is::<HGate>()) {
+                return Ok(None);
+            }
+            let q = operation.qubits()[0].clone();
+            Ok(Some(PointOptimizationSummary {
+                clear_span: 1,
+                clear_qubits: vec![q.clone()],
+                new_operations: vec![op(X.on(vec![q.clone()])), op(Z.on(vec![q]))],
+            }))
+        }
+    }
+
+    /// Removes the first moment of the circuit.
+    struct DropFirstMoment;
+
+    impl OptimizationPass for DropFirstMoment {
+        fn optimize_circuit(&self, circuit: Circuit) -> Result<Circuit, Error> {
+            Ok(Circuit::from_moments(circuit.moments().iter().skip(1).cloned().collect()))
+        }
+    }
+
+    #[test]
+    fn point_optimizers_rewrite_in_place() {
+        let q = LineQubit::range(2);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![op(gate_on!(H, q[0].clone())), op(gate_on!(CNOT, q[0].clone(), q[1].clone()))]).unwrap();
+
+        let optimized = ExpandH.optimize_circuit(circuit).unwrap();
+        assert_eq!(optimized.num_moments(), 3);
+        assert!(gates_equal(optimized.moments()[0].operations()[0].as_ref(), &gate_on!(X, q[0].clone()).unwrap()));
+        assert!(gates_equal(optimized.moments()[1].operations()[0].as_ref(), &gate_on!(Z, q[0].clone()).unwrap()));
+        assert!(gates_equal(
+            optimized.moments()[2].operations()[0].as_ref(),
+            &gate_on!(CNOT, q[0].clone(), q[1].clone()).unwrap(),
+        ));
+    }
+
+    #[test]
+    fn pipelines_run_passes_in_order() {
+        let q = LineQubit::new(0);
+        let mut circuit = Circuit::new();
+        circuit.append(vec![op(gate_on!(H, q.clone())), op(gate_on!(H, q.clone()))]).unwrap();
+
+        let pipeline = OptimizationPipeline::new(vec![Box::new(DropFirstMoment), Box::new(ExpandH)]);
+        let optimized = pipeline.optimize(circuit.clone()).unwrap();
+        assert_eq!(optimized.num_moments(), 2);
+        assert_eq!(optimized.all_operations().count(), 2);
+
+        assert_eq!(OptimizationPipeline::default().optimize(circuit).unwrap().num_moments(), 2);
+    }
+}
@@ -1,7 +1,7 @@
 use dyn_clonable::dyn_clone::DynClone;
 use dyn_clonable::dyn_clone;
 
-pub trait Hashable: DynClone {
+pub trait Hashable: DynClone + Send + Sync {
     fn hash(&self) -> u64;
 }
 